@@ -0,0 +1,457 @@
+// the programmatic interface of bartib, e.g. for GUIs or status bar widgets embedding it
+//
+// in contrast to the controllers, these functions do not print anything but return typed results.
+// Lines of the activity log that can not be parsed are skipped (see `bartib check`).
+//
+//     let filter = ActivityFilter::new(None, None, None, None, None, &[], Default::default());
+//     let report = bartib::api::get_report("activities.bartib", &filter, Vec::new(), None)?;
+//     println!("{} projects", report.projects.len());
+use anyhow::{Context, Result};
+use chrono::{Duration, Local, NaiveDate, NaiveDateTime};
+use std::collections::BTreeMap;
+
+use crate::conf;
+use crate::controller::hooks;
+use crate::data::archive;
+use crate::data::bartib_file;
+use crate::data::filter::Filters;
+use crate::data::getter;
+use crate::data::goal::Goal;
+use crate::data::hooks::Event;
+use crate::data::lock::FileLock;
+use crate::data::processor::{self, ProcessorList, TotalRounding};
+use crate::data::sanity;
+use crate::data::undo;
+
+pub use crate::data::activity::Activity;
+pub use crate::data::getter::{ActivityFilter, DatePresetArgs};
+
+// the running activity and the time tracked in the current periods
+#[derive(Debug, Clone, PartialEq)]
+pub struct Status {
+    pub running: Vec<Activity>,
+    pub today: Duration,
+    pub current_week: Duration,
+    pub current_month: Duration,
+    pub goals: Vec<GoalStatus>,
+}
+
+// the time tracked so far for a goal
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoalStatus {
+    pub goal: Goal,
+    pub tracked: Duration,
+}
+
+// the tracked time per project and description, like `bartib report`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub projects: Vec<ProjectReport>,
+    pub total: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectReport {
+    pub project: String,
+    pub descriptions: Vec<DescriptionReport>,
+    pub total: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DescriptionReport {
+    pub description: String,
+    pub total: Duration,
+}
+
+// how `start_activity` treats running activities which have been started longer than
+// `max_duration` ago, e.g. because stopping them has been forgotten
+#[derive(Debug, Clone, Copy)]
+pub struct StaleActivities {
+    pub max_duration: Duration,
+    // stop them at their likely end
+    pub auto_stop: bool,
+}
+
+// the options of `start_activity`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StartOptions<'a> {
+    pub tags: &'a [String],
+    pub issue: Option<&'a str>,
+    // the start of the activity, now by default
+    pub time: Option<NaiveDateTime>,
+    // keep a running activity with the same project and description instead of starting it again
+    pub idempotent: bool,
+    pub stale_activities: Option<StaleActivities>,
+    // the ends the user has chosen for stale activities, by the starts of the activities. Other
+    // stale activities are stopped at their likely end with `auto_stop`, otherwise they are stopped
+    // like all running activities
+    pub stale_ends: &'a [(NaiveDateTime, NaiveDateTime)],
+}
+
+// the changes of `start_activity`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Started {
+    // the started activity or the running one, if it has been kept
+    pub activity: Activity,
+    pub kept: bool,
+    pub stopped: Vec<Activity>,
+    // the stale activities which have been stopped before they were stopped with the others
+    pub stopped_stale: Vec<Activity>,
+}
+
+// the activities matching the filter, ordered by their start
+//
+// the processors (e.g. for rounding) are applied before filtering. Activities spanning multiple
+// days are split at midnight, so that each part matches the filter on its own day. Archived
+// activities are included if the filter reaches into their years.
+pub fn get_activities(
+    file_name: &str,
+    filter: &ActivityFilter,
+    processors: ProcessorList,
+) -> Result<Vec<Activity>> {
+    let (from_date, to_date) = filter.date_range_to_read();
+    let file_content = archive::get_file_content_with_archives(file_name, from_date, to_date)?;
+
+    Ok(select_activities(
+        parsed_activities(&file_content),
+        filter,
+        processors,
+    ))
+}
+
+// processes the activities, splits them at midnight and returns the ones matching the filter,
+// ordered by their start
+pub(crate) fn select_activities(
+    activities: Vec<&Activity>,
+    filter: &ActivityFilter,
+    processors: ProcessorList,
+) -> Vec<Activity> {
+    let processed_activities =
+        processor::split_at_midnight(processor::process_activities(activities, processors));
+
+    let mut activities: Vec<Activity> =
+        getter::filter_activities(processed_activities.iter().collect(), filter)
+            .into_iter()
+            .cloned()
+            .collect();
+    activities.sort_by_key(|activity| activity.start);
+
+    if let Some(number) = filter.number_of_activities {
+        activities.drain(..activities.len().saturating_sub(number));
+    }
+
+    activities
+}
+
+// the activities which have not been stopped yet
+pub fn get_running_activities(file_name: &str) -> Result<Vec<Activity>> {
+    let file_content = bartib_file::get_file_content(file_name)?;
+
+    Ok(parsed_activities(&file_content)
+        .into_iter()
+        .filter(|activity| !activity.is_stopped())
+        .cloned()
+        .collect())
+}
+
+// the total durations per project and description of the activities matching the filter
+//
+// like in `bartib report`, the totals of projects are rounded only if the rounding is done per
+// project and day.
+pub fn get_report(
+    file_name: &str,
+    filter: &ActivityFilter,
+    processors: ProcessorList,
+    total_rounding: Option<&TotalRounding>,
+) -> Result<Report> {
+    let activities = get_activities(file_name, filter, processors)?;
+    let activities: Vec<&Activity> = activities.iter().collect();
+
+    let mut projects: BTreeMap<&str, Vec<&Activity>> = BTreeMap::new();
+    for activity in &activities {
+        projects
+            .entry(&activity.project)
+            .or_default()
+            .push(activity);
+    }
+
+    let project_rounding = total_rounding.filter(|r| r.rounds_projects());
+
+    let projects = projects
+        .into_iter()
+        .map(|(project, activities)| {
+            let mut descriptions: BTreeMap<&str, Vec<&Activity>> = BTreeMap::new();
+            for activity in &activities {
+                descriptions
+                    .entry(&activity.description)
+                    .or_default()
+                    .push(activity);
+            }
+
+            ProjectReport {
+                project: project.to_string(),
+                descriptions: descriptions
+                    .into_iter()
+                    .map(|(description, activities)| DescriptionReport {
+                        description: description.to_string(),
+                        total: sum_duration(&activities, None),
+                    })
+                    .collect(),
+                total: sum_duration(&activities, project_rounding),
+            }
+        })
+        .collect();
+
+    Ok(Report {
+        projects,
+        total: sum_duration(&activities, total_rounding),
+    })
+}
+
+// the running activities and the time tracked today, in the current week and month and for
+// the goals
+pub fn get_status(
+    file_name: &str,
+    filter: &ActivityFilter,
+    processors: ProcessorList,
+    goals: &[Goal],
+) -> Result<Status> {
+    let file_content = bartib_file::get_file_content(file_name)?;
+    let activities = select_activities(parsed_activities(&file_content), filter, processors);
+
+    Ok(status_of(
+        &activities.iter().collect::<Vec<_>>(),
+        goals,
+        Local::now().date_naive(),
+    ))
+}
+
+// the status of activities which have already been selected by `select_activities`
+pub(crate) fn status_of(activities: &[&Activity], goals: &[Goal], today: NaiveDate) -> Status {
+    let sum_filtered = |filter: &dyn Fn(&&Activity) -> bool| -> Duration {
+        activities
+            .iter()
+            .filter(|activity| filter(activity))
+            .map(|activity| activity.get_duration())
+            .sum()
+    };
+
+    Status {
+        running: activities
+            .iter()
+            .filter(|activity| Filters::active(activity))
+            .map(|activity| (*activity).clone())
+            .collect(),
+        today: sum_filtered(&Filters::today(today)),
+        current_week: sum_filtered(&Filters::current_week(today)),
+        current_month: sum_filtered(&Filters::current_month(today)),
+        goals: goals
+            .iter()
+            .map(|goal| GoalStatus {
+                goal: goal.clone(),
+                tracked: goal.progress(activities, today).tracked,
+            })
+            .collect(),
+    }
+}
+
+// starts a new activity and stops all running activities at the same time, like `bartib start`
+//
+// the hooks of the configuration file are remembered for the started and stopped activities (see
+// `hooks::run_pending`), and activities running past midnight are split if `split_days` is set.
+pub fn start_activity(
+    file_name: &str,
+    project: &str,
+    description: &str,
+    options: &StartOptions,
+) -> Result<Started> {
+    let time = options.time.unwrap_or_else(|| Local::now().naive_local());
+    let _lock = FileLock::acquire(file_name)?;
+    let mut file_content = bartib_file::get_file_content_or_empty(file_name)?;
+
+    let stopped_stale = match options.stale_activities {
+        Some(stale_activities) => stop_stale_activities(
+            &mut file_content,
+            time,
+            stale_activities,
+            options.stale_ends,
+        ),
+        None => Vec::new(),
+    };
+
+    let is_same_activity =
+        |activity: &Activity| activity.project == project && activity.description == description;
+    let running_activity = getter::get_running_activities(&file_content)
+        .into_iter()
+        .find(|activity| is_same_activity(activity))
+        .cloned();
+
+    if options.idempotent
+        && let Some(activity) = running_activity
+    {
+        let stopped = stop_running_activities_except(&mut file_content, time, is_same_activity);
+        if !stopped.is_empty() || !stopped_stale.is_empty() {
+            write_to_file(file_name, &file_content)?;
+        }
+
+        return Ok(Started {
+            activity,
+            kept: true,
+            stopped,
+            stopped_stale,
+        });
+    }
+
+    // we must not assume that there is always only one activity running as the user may have
+    // started activities manually
+    let stopped = stop_running_activities_except(&mut file_content, time, |_| false);
+    let activity = Activity::start(project.to_string(), description.to_string(), Some(time))
+        .with_tags(options.tags.to_vec())
+        .with_issue(options.issue.map(str::to_string));
+    hooks::notify(Event::Start, &activity);
+    file_content.push(bartib_file::Line::for_activity(activity.clone()));
+
+    write_to_file(file_name, &file_content)?;
+    Ok(Started {
+        activity,
+        kept: false,
+        stopped,
+        stopped_stale,
+    })
+}
+
+// stops all running activities, like `bartib stop`. Returns the stopped activities
+pub fn stop_activities(file_name: &str, time: Option<NaiveDateTime>) -> Result<Vec<Activity>> {
+    let time = time.unwrap_or_else(|| Local::now().naive_local());
+    let _lock = FileLock::acquire(file_name)?;
+    let mut file_content = bartib_file::get_file_content(file_name)?;
+
+    let stopped = stop_running_activities_except(&mut file_content, time, |_| false);
+    if !stopped.is_empty() {
+        write_to_file(file_name, &file_content)?;
+    }
+
+    Ok(stopped)
+}
+
+fn parsed_activities(file_content: &[bartib_file::Line]) -> Vec<&Activity> {
+    file_content
+        .iter()
+        .filter_map(|line| line.activity.as_ref().ok())
+        .collect()
+}
+
+// stops all running activities for which `is_kept` returns false. Returns the stopped activities
+pub(crate) fn stop_running_activities_except(
+    file_content: &mut Vec<bartib_file::Line>,
+    time: NaiveDateTime,
+    is_kept: impl Fn(&Activity) -> bool,
+) -> Vec<Activity> {
+    let mut stopped = Vec::new();
+    let mut indexes = Vec::new();
+
+    for (index, line) in file_content.iter_mut().enumerate() {
+        if let Ok(activity) = &mut line.activity
+            && !activity.is_stopped()
+            && !is_kept(activity)
+        {
+            activity.stop(Some(time));
+            hooks::notify(Event::Stop, activity);
+            stopped.push(activity.clone());
+            line.set_changed();
+            indexes.push(index);
+        }
+    }
+
+    split_stopped_activities(file_content, indexes);
+    stopped
+}
+
+// stops the running activities started longer than the maximum duration before `now` at the end
+// chosen by the user or, with `auto_stop`, at their likely end. Returns the stopped activities
+fn stop_stale_activities(
+    file_content: &mut Vec<bartib_file::Line>,
+    now: NaiveDateTime,
+    stale_activities: StaleActivities,
+    ends: &[(NaiveDateTime, NaiveDateTime)],
+) -> Vec<Activity> {
+    let mut stopped = Vec::new();
+    let mut indexes = Vec::new();
+
+    for (index, line) in file_content.iter_mut().enumerate() {
+        if let Ok(activity) = &mut line.activity
+            && activity.is_stale(stale_activities.max_duration, now)
+        {
+            let end = ends
+                .iter()
+                .find(|(start, _)| *start == activity.start)
+                .map(|(_, end)| *end)
+                .or_else(|| {
+                    stale_activities
+                        .auto_stop
+                        .then(|| activity.likely_end(stale_activities.max_duration))
+                });
+
+            if let Some(end) = end {
+                activity.truncate(end);
+                hooks::notify(Event::Stop, activity);
+                stopped.push(activity.clone());
+                line.set_changed();
+                indexes.push(index);
+            }
+        }
+    }
+
+    split_stopped_activities(file_content, indexes);
+    stopped
+}
+
+// splits the activities of the lines with the given indexes at midnight if the setting
+// `split_days` is enabled (see `stop --split-days`)
+pub(crate) fn split_stopped_activities(
+    file_content: &mut Vec<bartib_file::Line>,
+    indexes: Vec<usize>,
+) {
+    if !conf::split_days() {
+        return;
+    }
+
+    // the parts are inserted after the split activity, so the later lines are split first
+    for index in indexes.into_iter().rev() {
+        if file_content[index]
+            .activity
+            .as_ref()
+            .is_ok_and(Activity::spans_multiple_days)
+        {
+            sanity::fix(
+                file_content,
+                sanity::Finding {
+                    index,
+                    issue: sanity::Issue::MultipleDays,
+                },
+            );
+        }
+    }
+}
+
+fn sum_duration(activities: &[&Activity], rounding: Option<&TotalRounding>) -> Duration {
+    rounding.map_or_else(
+        || {
+            activities
+                .iter()
+                .map(|activity| activity.get_duration())
+                .sum()
+        },
+        |rounding| rounding.sum_duration(activities),
+    )
+}
+
+// writes the file content after saving a copy of the file for `undo`. The hooks of the changes are
+// forgotten if it can not be written
+pub(crate) fn write_to_file(file_name: &str, file_content: &[bartib_file::Line]) -> Result<()> {
+    let result = undo::save_backup(file_name).and_then(|()| {
+        bartib_file::write_to_file(file_name, file_content)
+            .context(format!("Could not write to file: {file_name}"))
+    });
+    hooks::discard_pending_if_failed(result)
+}
@@ -1,18 +1,11 @@
-use chrono::Duration;
+use std::cell::Cell;
+use std::str::FromStr;
+
+use chrono::{Duration, Weekday};
 
 pub static FORMAT_MINUTE_PRECISION_DATETIME: &str = "%F %R";
 pub static FORMAT_SECOND_PRECISION_DATETIME: &str = "%F %T";
 
-#[cfg(not(feature = "second-precision"))]
-pub static FORMAT_DATETIME: &str = FORMAT_MINUTE_PRECISION_DATETIME;
-#[cfg(feature = "second-precision")]
-pub static FORMAT_DATETIME: &str = FORMAT_SECOND_PRECISION_DATETIME;
-
-#[cfg(not(feature = "second-precision"))]
-pub static FORMAT_TIME: &str = "%R";
-#[cfg(feature = "second-precision")]
-pub static FORMAT_TIME: &str = "%T";
-
 pub static FORMAT_DATE: &str = "%F";
 pub static DEFAULT_WIDTH: usize = usize::MAX;
 pub static REPORT_INDENTATION: usize = 4;
@@ -21,3 +14,126 @@ pub static REPORT_INDENTATION: usize = 4;
 pub struct ProcessConfig {
     pub round: Option<Duration>,
 }
+
+// the precision of the timestamps in the activity log and of displayed times and durations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Precision {
+    Minutes,
+    Seconds,
+}
+
+impl Precision {
+    #[must_use]
+    pub fn format_datetime(self) -> &'static str {
+        match self {
+            Self::Minutes => FORMAT_MINUTE_PRECISION_DATETIME,
+            Self::Seconds => FORMAT_SECOND_PRECISION_DATETIME,
+        }
+    }
+
+    #[must_use]
+    pub fn format_time(self) -> &'static str {
+        match self {
+            Self::Minutes => "%R",
+            Self::Seconds => "%T",
+        }
+    }
+}
+
+impl FromStr for Precision {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "minutes" => Ok(Self::Minutes),
+            "seconds" => Ok(Self::Seconds),
+            _ => Err("expected `minutes` or `seconds`".to_string()),
+        }
+    }
+}
+
+// builds with the feature `second-precision` use seconds unless configured otherwise
+#[cfg(not(feature = "second-precision"))]
+static DEFAULT_PRECISION: Precision = Precision::Minutes;
+#[cfg(feature = "second-precision")]
+static DEFAULT_PRECISION: Precision = Precision::Seconds;
+
+thread_local! {
+    static PRECISION: Cell<Precision> = const { Cell::new(DEFAULT_PRECISION) };
+    static WEEK_START: Cell<Weekday> = const { Cell::new(Weekday::Mon) };
+    static USE_INDEX: Cell<bool> = const { Cell::new(false) };
+    static SPLIT_DAYS: Cell<bool> = const { Cell::new(false) };
+    static QUIET: Cell<bool> = const { Cell::new(false) };
+    static COLOR: Cell<bool> = const { Cell::new(true) };
+}
+
+#[must_use]
+pub fn precision() -> Precision {
+    PRECISION.with(Cell::get)
+}
+
+pub fn set_precision(precision: Precision) {
+    PRECISION.with(|p| p.set(precision));
+}
+
+// the format of timestamps in the current precision
+#[must_use]
+pub fn format_datetime() -> &'static str {
+    precision().format_datetime()
+}
+
+// the format of times in the current precision
+#[must_use]
+pub fn format_time() -> &'static str {
+    precision().format_time()
+}
+
+// the first day of a week for presets like `--current-week` and weekly goals
+#[must_use]
+pub fn week_start() -> Weekday {
+    WEEK_START.with(Cell::get)
+}
+
+pub fn set_week_start(week_start: Weekday) {
+    WEEK_START.with(|w| w.set(week_start));
+}
+
+// whether an index of the activity log is kept to read only the activities of the requested dates
+#[must_use]
+pub fn use_index() -> bool {
+    USE_INDEX.with(Cell::get)
+}
+
+pub fn set_use_index(use_index: bool) {
+    USE_INDEX.with(|u| u.set(use_index));
+}
+
+// whether activities running past midnight are split into one activity per day when they are stopped
+#[must_use]
+pub fn split_days() -> bool {
+    SPLIT_DAYS.with(Cell::get)
+}
+
+pub fn set_split_days(split_days: bool) {
+    SPLIT_DAYS.with(|s| s.set(split_days));
+}
+
+// whether the normal output of commands is suppressed (see `--quiet`)
+#[must_use]
+pub fn quiet() -> bool {
+    QUIET.with(Cell::get)
+}
+
+pub fn set_quiet(quiet: bool) {
+    QUIET.with(|q| q.set(quiet));
+}
+
+// whether the output is colored (see `--color`)
+#[must_use]
+pub fn color() -> bool {
+    COLOR.with(Cell::get)
+}
+
+pub fn set_color(color: bool) {
+    COLOR.with(|c| c.set(color));
+}
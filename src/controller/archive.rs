@@ -0,0 +1,63 @@
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use std::path::Path;
+
+use crate::data::archive;
+use crate::data::bartib_file;
+use crate::data::encryption;
+use crate::data::undo;
+use crate::outputln;
+
+// moves all stopped activities started before the given date to yearly archive files
+//
+// activities of a year are appended to `<file>.<year>`. The archives are written before the
+// activities are removed from the file, so nothing gets lost if writing fails.
+pub fn archive(file_name: &str, before: NaiveDate, dry_run: bool) -> Result<()> {
+    let mut file_content = bartib_file::get_file_content(file_name)?;
+    let archived = archive::split_off_archived(&mut file_content, before);
+
+    if archived.is_empty() {
+        outputln!("No activities started before {before} to archive");
+        return Ok(());
+    }
+
+    for (year, lines) in archived {
+        let archive_file_name = archive::get_archive_file_name(file_name, year);
+
+        outputln!(
+            "{} {} activities to {}",
+            if dry_run { "Would archive" } else { "Archived" },
+            lines.len(),
+            archive_file_name
+        );
+
+        if dry_run {
+            continue;
+        }
+
+        let mut archive_content = if Path::new(&archive_file_name).exists() {
+            bartib_file::get_file_content(&archive_file_name)?
+        } else {
+            // a new archive of an encrypted activity log is encrypted as well
+            if encryption::is_encrypted(file_name) {
+                bartib_file::write_with_encryption(&archive_file_name, "", true)
+                    .context(format!("Could not write to file: {archive_file_name}"))?;
+            }
+            Vec::new()
+        };
+        archive_content.extend(lines);
+
+        bartib_file::write_to_file(&archive_file_name, &archive_content)
+            .context(format!("Could not write to file: {archive_file_name}"))?;
+    }
+
+    if dry_run {
+        return Ok(());
+    }
+
+    // restoring the log as it was before an earlier change would duplicate the archived
+    // activities, so that change can not be undone anymore
+    undo::discard_backup(file_name)?;
+    bartib_file::write_to_file(file_name, &file_content)
+        .context(format!("Could not write to file: {file_name}"))
+}
@@ -0,0 +1,74 @@
+use anyhow::Result;
+use chrono::Local;
+
+use crate::controller::exit_status::ExitStatus;
+use crate::data::activity::Activity;
+use crate::data::bartib_file;
+use crate::data::budget::{Budget, BudgetState, BudgetUsage};
+use crate::data::getter;
+use crate::view::status;
+
+// warns about the budgets which are nearly used up or have been exceeded in their current period.
+// With a project, only the budgets of this project are checked
+//
+// the warnings are written to stderr, so they do not mix with JSON output. Exceeded budgets end
+// bartib with the exit code of `ExitStatus::OverBudget` if `fail_over_budget` is set.
+pub fn check_budgets(
+    file_name: &str,
+    budgets: &[Budget],
+    project: Option<&str>,
+    fail_over_budget: bool,
+) -> Result<()> {
+    let usages = get_current_usages(file_name, budgets, project)?;
+
+    for usage in &usages {
+        if let Some(warning) = status::format_budget_warning(usage) {
+            eprintln!("{warning}");
+        }
+    }
+
+    if fail_over_budget
+        && usages
+            .iter()
+            .any(|usage| usage.state() == BudgetState::Over)
+    {
+        return Err(ExitStatus::OverBudget.into());
+    }
+
+    Ok(())
+}
+
+// the usage of the budgets in their current period read from the activity log. With a project, only
+// the budgets of this project are read
+pub(crate) fn get_current_usages<'a>(
+    file_name: &str,
+    budgets: &'a [Budget],
+    project: Option<&str>,
+) -> Result<Vec<BudgetUsage<'a>>> {
+    let budgets: Vec<&Budget> = budgets
+        .iter()
+        .filter(|budget| project.is_none_or(|project| budget.applies_to(project)))
+        .collect();
+
+    if budgets.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // activities of former years may have been archived, but budgets last one year at most
+    let file_content = bartib_file::get_file_content(file_name)?;
+    let activities: Vec<&Activity> = getter::get_activities(&file_content).collect();
+    Ok(get_usages(&budgets, &activities))
+}
+
+// the usage of the budgets in their current period
+pub(crate) fn get_usages<'a>(
+    budgets: &[&'a Budget],
+    activities: &[&Activity],
+) -> Vec<BudgetUsage<'a>> {
+    let today = Local::now().date_naive();
+
+    budgets
+        .iter()
+        .map(|budget| budget.usage(activities, today))
+        .collect()
+}
@@ -0,0 +1,120 @@
+use anyhow::{Context, Result, bail};
+use std::fs;
+use std::path::Path;
+
+use crate::data::aliases::Aliases;
+use crate::data::budget;
+use crate::data::config::{self, Config, Value};
+use crate::data::encryption::EncryptionSettings;
+use crate::data::goal;
+use crate::data::rates::Rates;
+use crate::data::recurrence;
+use crate::data::settings::{self, Settings};
+use crate::view::output::OutputFormat;
+use crate::view::theme::{ColorMode, Theme};
+use crate::{output, outputln};
+
+// prints the path and the content of the configuration file
+pub fn show(config: &Config) -> Result<()> {
+    let path = get_path(config)?;
+
+    if !path.exists() {
+        outputln!("No configuration file at {}", path.to_string_lossy());
+        return Ok(());
+    }
+
+    let content = fs::read_to_string(path).context(format!(
+        "Could not read config file: {}",
+        path.to_string_lossy()
+    ))?;
+
+    outputln!("# {}", path.to_string_lossy());
+    output!("{content}");
+
+    Ok(())
+}
+
+pub fn show_path(config: &Config) -> Result<()> {
+    outputln!("{}", get_path(config)?.to_string_lossy());
+    Ok(())
+}
+
+// prints the value of a setting like `week_start` or `aliases.cb`
+pub fn get(config: &Config, key: &str) -> Result<()> {
+    let (table, table_key) = config::split_key(key);
+
+    match config.get(table, table_key) {
+        Some(Value::String(s)) => outputln!("{s}"),
+        Some(value) => outputln!("{value}"),
+        None => bail!("`{key}` is not set"),
+    }
+
+    Ok(())
+}
+
+// changes a setting in the configuration file or removes it if no value is given
+//
+// the changed configuration is checked before it is written, so an invalid value does not break
+// the configuration file.
+pub fn set(config: &Config, key: &str, value: Option<&str>) -> Result<()> {
+    let path = get_path(config)?;
+    let (table, table_key) = config::split_key(key);
+
+    if value.is_none() && config.get(table, table_key).is_none() {
+        bail!("`{key}` is not set");
+    }
+
+    let content = if path.exists() {
+        fs::read_to_string(path).context(format!(
+            "Could not read config file: {}",
+            path.to_string_lossy()
+        ))?
+    } else {
+        String::new()
+    };
+
+    let value = value.map(config::parse_setting_value);
+    let new_content = config::set_value(&content, table, table_key, value.as_ref())?;
+    validate(&new_content.parse()?)?;
+
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory).context(format!(
+            "Could not create directory: {}",
+            directory.to_string_lossy()
+        ))?;
+    }
+    fs::write(path, new_content).context(format!(
+        "Could not write to file: {}",
+        path.to_string_lossy()
+    ))?;
+
+    match value {
+        Some(value) => outputln!("Set {key} = {value}"),
+        None => outputln!("Removed {key}"),
+    }
+
+    Ok(())
+}
+
+fn get_path(config: &Config) -> Result<&Path> {
+    config
+        .path
+        .as_deref()
+        .context("Could not determine the path of the config file, please use --config")
+}
+
+// reads all settings and tables of the configuration to find invalid values
+fn validate(config: &Config) -> Result<()> {
+    Settings::from_config(config)?;
+    settings::get_enum::<OutputFormat>(config, "output")?;
+    settings::get_enum::<ColorMode>(config, "color")?;
+    Theme::from_config(config)?;
+    Aliases::from_config(config)?;
+    Rates::from_config(config)?;
+    goal::get_goals(config)?;
+    budget::get_budgets(config)?;
+    EncryptionSettings::from_config(config)?;
+    recurrence::get_rules(config)?;
+
+    Ok(())
+}
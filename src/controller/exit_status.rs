@@ -0,0 +1,66 @@
+use std::process::ExitCode;
+
+use thiserror::Error;
+
+// results of commands which scripts tell apart by the exit code of bartib, e.g. to show the
+// running activity in a prompt. Controllers return them as errors, which end bartib without an
+// error message. Other errors end bartib with the exit code 1 and invalid arguments with 2, so
+// their codes start at 3
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum ExitStatus {
+    // `current` found no running activity
+    #[error("no activity is running")]
+    NotRunning,
+    // `check` or `sanity` found problems in the activity log
+    #[error("problems have been found in the activity log")]
+    ProblemsFound,
+    // `--fail-over-budget` found a budget which has been exceeded
+    #[error("a budget has been exceeded")]
+    OverBudget,
+    // `status --fail-under` found a goal below the percentage
+    #[error("a goal has not been reached")]
+    GoalMissed,
+}
+
+impl ExitStatus {
+    #[must_use]
+    pub fn code(self) -> u8 {
+        match self {
+            Self::NotRunning => 3,
+            Self::ProblemsFound => 4,
+            Self::OverBudget => 5,
+            Self::GoalMissed => 6,
+        }
+    }
+}
+
+impl From<ExitStatus> for ExitCode {
+    fn from(status: ExitStatus) -> Self {
+        ExitCode::from(status.code())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exit_codes_differ_from_errors() {
+        let statuses = [
+            ExitStatus::NotRunning,
+            ExitStatus::ProblemsFound,
+            ExitStatus::OverBudget,
+            ExitStatus::GoalMissed,
+        ];
+
+        for (i, status) in statuses.iter().enumerate() {
+            assert!(status.code() > 2, "{status:?} has the code of errors");
+            assert!(
+                statuses[i + 1..]
+                    .iter()
+                    .all(|other| other.code() != status.code()),
+                "{status:?} shares its code"
+            );
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use anyhow::{Context, Result, bail};
+use std::env;
+use std::fs;
+use std::process::{Command, Stdio};
+
+use crate::conf;
+use crate::controller::hooks;
+use crate::data::activity;
+use crate::data::archive;
+use crate::data::getter;
+use crate::data::jira::{self, JiraSettings};
+use crate::data::processor;
+use crate::data::redaction::Redaction;
+use crate::view::export;
+use crate::view::json::Json;
+use crate::{output, outputln};
+
+// exports the activities to stdout or to a file
+//
+// activities are redacted only after they have been filtered, so they may still be filtered by
+// their tags
+pub fn export(
+    file_name: &str,
+    filter: getter::ActivityFilter,
+    processors: processor::ProcessorList,
+    format: export::ExportFormat,
+    redaction: Option<Redaction>,
+    output_file: Option<&str>,
+) -> Result<()> {
+    let activities = get_exported_activities(file_name, &filter, processors)?;
+    let mut filtered_activities: Vec<&activity::Activity> = activities.iter().collect();
+
+    let redacted_activities: Vec<activity::Activity>;
+    if let Some(redaction) = redaction {
+        redacted_activities = filtered_activities
+            .iter()
+            .map(|activity| redaction.apply(activity))
+            .collect();
+        filtered_activities = redacted_activities.iter().collect();
+    }
+
+    let content = export::export_activities(&filtered_activities, format);
+
+    match output_file {
+        Some(output_file) => {
+            fs::write(output_file, content)
+                .context(format!("Could not write to file: {output_file}"))?;
+            outputln!(
+                "Exported {} activities to {}",
+                filtered_activities.len(),
+                output_file
+            );
+        }
+        None => output!("{content}"),
+    }
+
+    Ok(())
+}
+
+// adds a worklog for each finished activity with an issue to the issue in JIRA
+//
+// JIRA does not know which activities have been logged before, so pushing them again logs them
+// twice. The token is taken from the environment variable `BARTIB_JIRA_TOKEN` or the settings.
+pub fn push_to_jira(
+    file_name: &str,
+    filter: getter::ActivityFilter,
+    processors: processor::ProcessorList,
+    settings: &JiraSettings,
+) -> Result<()> {
+    if settings.url.is_none() {
+        bail!("Please set `url` in the `[jira]` table of the configuration file");
+    }
+
+    let Some(token) = env::var("BARTIB_JIRA_TOKEN")
+        .ok()
+        .or_else(|| settings.token.clone())
+    else {
+        bail!(
+            "Please set the environment variable BARTIB_JIRA_TOKEN or `token` in the `[jira]` table of the configuration file"
+        );
+    };
+
+    let activities = get_exported_activities(file_name, &filter, processors)?;
+    let activities: Vec<&activity::Activity> = activities.iter().collect();
+    let issues = jira::group_by_issue(&activities);
+
+    if issues.is_empty() {
+        outputln!("No finished activities with an issue to log");
+        return Ok(());
+    }
+
+    let credentials = curl_credentials(settings.user.as_deref(), &token);
+    let mut failures = 0;
+
+    for worklogs in &issues {
+        let url = settings.worklog_url(worklogs.issue).unwrap_or_default();
+
+        for activity in &worklogs.activities {
+            let duration = jira::logged_duration(activity);
+            let payload = Json::object([
+                ("started", Json::string(&jira::format_started(activity))),
+                ("timeSpentSeconds", Json::duration(&duration)),
+                ("comment", Json::string(&activity.description)),
+            ]);
+
+            match post_worklog(&url, &credentials, &payload.to_string()) {
+                Ok(()) => outputln!(
+                    "Logged {} on {}: \"{}\" started at {}",
+                    jira::format_duration(&duration),
+                    worklogs.issue,
+                    activity.description,
+                    activity.start.format(conf::format_datetime())
+                ),
+                Err(e) => {
+                    failures += 1;
+                    println!(
+                        "Warning: could not log \"{}\" started at {} on {}: {}",
+                        activity.description,
+                        activity.start.format(conf::format_datetime()),
+                        worklogs.issue,
+                        e
+                    );
+                }
+            }
+        }
+    }
+
+    if failures > 0 {
+        bail!("{failures} worklogs could not be added to JIRA");
+    }
+
+    Ok(())
+}
+
+// the finished and running activities to export, processed, filtered and sorted by their start
+fn get_exported_activities(
+    file_name: &str,
+    filter: &getter::ActivityFilter,
+    processors: processor::ProcessorList,
+) -> Result<Vec<activity::Activity>> {
+    let (from_date, to_date) = filter.date_range();
+    let file_content = archive::get_file_content_with_archives(file_name, from_date, to_date)?;
+    let activities = getter::get_activities(&file_content).collect();
+
+    let processed_activities: Vec<activity::Activity> =
+        processor::process_activities(activities, processors);
+
+    let mut filtered_activities: Vec<activity::Activity> =
+        getter::filter_activities(processed_activities.iter().collect(), filter)
+            .into_iter()
+            .cloned()
+            .collect();
+
+    filtered_activities.sort_by_key(|activity| activity.start);
+
+    Ok(filtered_activities)
+}
+
+// a curl config with the credentials, which is passed to curl on stdin to keep the token out of
+// the list of processes
+fn curl_credentials(user: Option<&str>, token: &str) -> String {
+    let quote = |value: &str| format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""));
+
+    match user {
+        Some(user) => format!("user = {}\n", quote(&format!("{user}:{token}"))),
+        None => format!(
+            "header = {}\n",
+            quote(&format!("Authorization: Bearer {token}"))
+        ),
+    }
+}
+
+// posts a worklog to the REST API of JIRA with curl
+fn post_worklog(url: &str, credentials: &str, payload: &str) -> std::io::Result<()> {
+    let mut curl = Command::new("curl");
+    curl.args([
+        "--silent",
+        "--show-error",
+        "--fail",
+        "--max-time",
+        "30",
+        "--config",
+        "-",
+        "-X",
+        "POST",
+        "-H",
+        "Content-Type: application/json",
+        "--data-binary",
+        payload,
+        url,
+    ])
+    .stdout(Stdio::null());
+
+    hooks::run_with_input(&mut curl, credentials)
+}
@@ -0,0 +1,140 @@
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+use crate::data::activity;
+use crate::data::hooks::{self, Event, Hooks};
+use crate::view::json::{Json, ToJson};
+
+thread_local! {
+    static HOOKS: RefCell<Hooks> = RefCell::new(Hooks::default());
+    static PENDING: RefCell<Vec<(Event, activity::Activity)>> = const { RefCell::new(Vec::new()) };
+}
+
+// sets the hooks defined in the configuration file and forgets the events of earlier commands
+pub fn set_hooks(hooks: Hooks) {
+    HOOKS.set(hooks);
+    PENDING.take();
+}
+
+// remembers an event of an activity. The hooks are run by `run_pending` once the change has been
+// written to the activity log and the log has been unlocked
+pub(crate) fn notify(event: Event, activity: &activity::Activity) {
+    if HOOKS.with_borrow(|hooks| hooks.handles(event)) {
+        PENDING.with_borrow_mut(|pending| pending.push((event, activity.clone())));
+    }
+}
+
+// runs the hooks of all remembered events. Failing hooks are reported, but do not fail the command
+pub fn run_pending() {
+    let pending = PENDING.take();
+
+    HOOKS.with_borrow(|hooks| {
+        for (event, activity) in &pending {
+            let payload = payload(*event, activity).to_string();
+
+            if let Some(command) = hooks.command(*event)
+                && let Err(e) = run_command(command, *event, activity, &payload)
+            {
+                eprintln!("Warning: The {event} hook `{command}` failed: {e}");
+            }
+
+            if let Some(url) = &hooks.webhook
+                && let Err(e) = post_webhook(url, &payload)
+            {
+                eprintln!("Warning: The webhook for the {event} event to {url} failed: {e}");
+            }
+        }
+    });
+}
+
+// forgets the remembered events, e.g. if the activity log could not be written
+pub(crate) fn discard_pending() {
+    PENDING.take();
+}
+
+// forgets the remembered events if their changes could not be written to the activity log
+pub(crate) fn discard_pending_if_failed<T>(result: anyhow::Result<T>) -> anyhow::Result<T> {
+    if result.is_err() {
+        discard_pending();
+    }
+    result
+}
+
+fn payload(event: Event, activity: &activity::Activity) -> Json {
+    Json::object([
+        ("event", Json::string(event.name())),
+        ("activity", activity.to_json()),
+    ])
+}
+
+// runs the command in a shell with the details of the activity as environment variables and as
+// JSON on its standard input
+fn run_command(
+    command: &str,
+    event: Event,
+    activity: &activity::Activity,
+    payload: &str,
+) -> io::Result<()> {
+    let mut shell = shell_command(command);
+    shell
+        .envs(hooks::environment(event, activity))
+        .stdout(Stdio::null());
+
+    run_with_input(&mut shell, payload)
+}
+
+// posts the JSON payload to the URL of the webhook with curl
+fn post_webhook(url: &str, payload: &str) -> io::Result<()> {
+    let mut curl = Command::new("curl");
+    curl.args([
+        "--silent",
+        "--show-error",
+        "--fail",
+        "--max-time",
+        "10",
+        "-X",
+        "POST",
+        "-H",
+        "Content-Type: application/json",
+        "--data-binary",
+        "@-",
+        url,
+    ])
+    .stdout(Stdio::null());
+
+    run_with_input(&mut curl, payload)
+}
+
+#[cfg(unix)]
+fn shell_command(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+#[cfg(not(unix))]
+fn shell_command(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+pub(crate) fn run_with_input(command: &mut Command, input: &str) -> io::Result<()> {
+    let mut child = command.stdin(Stdio::piped()).spawn()?;
+
+    // commands not reading their input close it early, which is no error
+    if let Some(mut stdin) = child.stdin.take()
+        && let Err(e) = stdin.write_all(input.as_bytes())
+        && e.kind() != io::ErrorKind::BrokenPipe
+    {
+        return Err(e);
+    }
+
+    let status = child.wait()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(status.to_string()))
+    }
+}
@@ -0,0 +1,47 @@
+use anyhow::{Context, Result, bail};
+use std::fs;
+use std::path::Path;
+
+use crate::controller::merge;
+use crate::data::activity::Activity;
+use crate::data::interop::{self, ImportFormat};
+use crate::data::merge::ConflictStrategy;
+
+// imports the activities exported by another time tracker into the file
+//
+// the import file may also be the data directory of the time tracker. Like with `merge`,
+// activities that have been imported before are skipped.
+pub fn import(
+    file_name: &str,
+    import_file_name: &str,
+    format: ImportFormat,
+    strategy: ConflictStrategy,
+    dry_run: bool,
+) -> Result<()> {
+    let path = Path::new(import_file_name);
+
+    let activities = if path.is_dir() {
+        let files = interop::get_data_files(path, format);
+        if files.is_empty() {
+            bail!("No files to import found in directory: {import_file_name}");
+        }
+
+        let mut activities = Vec::new();
+        for file in files {
+            activities.extend(import_file(&file, format)?);
+        }
+        activities
+    } else {
+        import_file(path, format)?
+    };
+
+    merge::merge_activities(file_name, activities, strategy, dry_run)
+}
+
+fn import_file(path: &Path, format: ImportFormat) -> Result<Vec<Activity>> {
+    let content = fs::read_to_string(path)
+        .context(format!("Could not read from file: {}", path.display()))?;
+
+    interop::import_activities(&content, format)
+        .context(format!("Could not import file: {}", path.display()))
+}
@@ -0,0 +1,79 @@
+use anyhow::{Context, Result, bail};
+use std::fs;
+use std::path::Path;
+
+use crate::data::archive;
+use crate::data::bartib_file;
+use crate::data::encryption;
+use crate::data::index;
+use crate::data::undo;
+use crate::outputln;
+
+// whether `init` encrypts or decrypts an activity log
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encryption {
+    Encrypt,
+    Decrypt,
+}
+
+// creates an empty activity log or changes whether an activity log and its archives are encrypted
+pub fn init(file_name: &str, encryption: Option<Encryption>) -> Result<()> {
+    let exists = Path::new(file_name).exists();
+    let encrypted = encryption::is_encrypted(file_name);
+
+    match encryption {
+        None if exists => outputln!("{file_name} exists already"),
+        None => {
+            bartib_file::write_with_encryption(file_name, "", false)
+                .context(format!("Could not write to file: {file_name}"))?;
+            outputln!("Created {file_name}");
+        }
+        Some(Encryption::Encrypt) if encrypted => bail!("{file_name} is encrypted already"),
+        Some(Encryption::Decrypt) if !encrypted => bail!("{file_name} is not encrypted"),
+        Some(Encryption::Encrypt) => {
+            for name in get_file_names(file_name) {
+                if encryption::is_encrypted(&name) {
+                    continue;
+                }
+
+                let content = if Path::new(&name).exists() {
+                    bartib_file::read_text(&name)?
+                } else {
+                    String::new()
+                };
+                bartib_file::write_with_encryption(&name, &content, true)
+                    .context(format!("Could not write to file: {name}"))?;
+                outputln!("Encrypted {name}");
+            }
+
+            // neither the copy for `undo` nor the dates in the index should be left unencrypted
+            undo::discard_backup(file_name)?;
+            let _ = fs::remove_file(index::get_index_file_name(file_name));
+        }
+        Some(Encryption::Decrypt) => {
+            for name in get_file_names(file_name) {
+                if !encryption::is_encrypted(&name) {
+                    continue;
+                }
+
+                let content = bartib_file::read_text(&name)?;
+                bartib_file::write_with_encryption(&name, &content, false)
+                    .context(format!("Could not write to file: {name}"))?;
+                outputln!("Decrypted {name}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// the activity log followed by its archives
+fn get_file_names(file_name: &str) -> Vec<String> {
+    let archives = archive::get_archived_years(file_name)
+        .into_iter()
+        .map(|year| archive::get_archive_file_name(file_name, year));
+
+    std::iter::once(file_name.to_string())
+        .chain(archives)
+        .collect()
+}
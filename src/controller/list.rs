@@ -1,38 +1,183 @@
-use anyhow::Result;
-use chrono::NaiveDateTime;
+use anyhow::{Result, bail};
+use chrono::{Duration, Local};
+use std::io;
+use std::io::{IsTerminal, Read, Write};
+use std::thread;
 use wildmatch::WildMatch;
 
-use crate::conf;
+use crate::controller::exit_status::ExitStatus;
 use crate::data::activity;
-use crate::data::activity::Activity;
+use crate::data::archive;
 use crate::data::bartib_file;
+use crate::data::filter::Filters;
 use crate::data::getter;
+use crate::data::index;
 use crate::data::processor;
+use crate::outputln;
+use crate::view::json;
 use crate::view::list;
+use crate::view::output::OutputFormat;
+use crate::view::terminal;
+
+// the running activities are shown again after this time while watching them
+const WATCH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
 
 // lists all currently running activities.
-pub fn list_running(file_name: &str) -> Result<()> {
-    let file_content = bartib_file::get_file_content(file_name)?;
+pub fn list_running(
+    file_name: &str,
+    daily_limit: Option<Duration>,
+    output: OutputFormat,
+    simple: bool,
+) -> Result<()> {
+    let file_content = get_file_content_of_today(file_name)?;
     let running_activities = getter::get_running_activities(&file_content);
 
-    list::list_running_activities(&running_activities);
+    if output == OutputFormat::Json {
+        list::print_running_activities_as_json(
+            &running_activities,
+            &get_duration_of_today(&file_content),
+            daily_limit.as_ref(),
+        );
+    } else if simple {
+        let today = daily_limit.map(|_| get_duration_of_today(&file_content));
+        outputln!(
+            "{}",
+            list::format_running_compact(
+                &running_activities,
+                today.as_ref().zip(daily_limit.as_ref())
+            )
+        );
+    } else {
+        list::list_running_activities(&running_activities);
+
+        if let Some(daily_limit) = daily_limit {
+            list::show_daily_limit(&get_duration_of_today(&file_content), &daily_limit);
+        }
+    }
+
+    if running_activities.is_empty() {
+        return Err(ExitStatus::NotRunning.into());
+    }
+
+    Ok(())
+}
+
+// shows the running activities and their durations every second until the user quits
+//
+// the simple output is a single line which is updated in place on a terminal. Otherwise a new
+// line is printed every second, e.g. for status bars reading the output of a command.
+pub fn watch_running(file_name: &str, daily_limit: Option<Duration>, simple: bool) -> Result<()> {
+    let is_terminal = io::stdout().is_terminal();
+    if !simple && !is_terminal {
+        bail!(
+            "Watching the running activities needs an interactive terminal. Use --simple for a single line instead"
+        );
+    }
+
+    bartib_file::keep_in_memory();
+    let _alternate_screen = if simple {
+        None
+    } else {
+        Some(terminal::AlternateScreen::enter()?)
+    };
+
+    loop {
+        let file_content = get_file_content_of_today(file_name)?;
+        let running_activities = getter::get_running_activities(&file_content);
+        let today = daily_limit.map(|_| get_duration_of_today(&file_content));
+
+        if simple {
+            let line = list::format_running_compact(
+                &running_activities,
+                today.as_ref().zip(daily_limit.as_ref()),
+            );
+
+            // the reader of the output, e.g. a status bar, has gone away
+            if print_line(&line, is_terminal).is_err() {
+                return Ok(());
+            }
+        } else {
+            terminal::clear_screen()?;
+            list::list_running_activities_elapsed(&running_activities);
+            if let Some((today, daily_limit)) = today.zip(daily_limit) {
+                list::show_daily_limit(&today, &daily_limit);
+            }
+            println!("\nPress q to quit");
+        }
+
+        if wait_for_quit()? {
+            break;
+        }
+    }
+
+    if simple && is_terminal {
+        println!();
+    }
 
     Ok(())
 }
 
+// replaces the line on a terminal instead of adding a new one
+fn print_line(line: &str, in_place: bool) -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+
+    if in_place {
+        write!(stdout, "\r\x1b[K{line}")?;
+    } else {
+        writeln!(stdout, "{line}")?;
+    }
+
+    stdout.flush()
+}
+
+// waits until the running activities should be shown again and tells whether q, Ctrl-C or
+// Ctrl-D has been pressed in the meantime. Without a terminal to read keys from, the watch is
+// stopped by the usual signals
+fn wait_for_quit() -> Result<bool> {
+    let Ok(_raw_mode) = terminal::RawMode::enable_with_timeout(WATCH_INTERVAL) else {
+        thread::sleep(WATCH_INTERVAL);
+        return Ok(false);
+    };
+
+    let mut buffer = [0u8; 1];
+    match io::stdin().lock().read(&mut buffer)? {
+        0 => Ok(false),
+        _ => Ok(matches!(buffer[0], b'q' | 3 | 4)),
+    }
+}
+
+// the running activities and the activities of today, which is all needed to show the current
+// activities
+fn get_file_content_of_today(file_name: &str) -> Result<Vec<bartib_file::Line>> {
+    let today = Local::now().date_naive();
+    index::get_file_content_of_dates(file_name, Some(today), Some(today))
+}
+
+fn get_duration_of_today(file_content: &[bartib_file::Line]) -> Duration {
+    getter::get_activities(file_content)
+        .filter(Filters::today(Local::now().date_naive()))
+        .map(activity::Activity::get_duration)
+        .sum()
+}
+
 // lists tracked activities
 //
-// the activities will be ordered chronologically.
+// the activities will be ordered chronologically. Activities spanning multiple days are listed on
+// each day as a part ending or starting at midnight.
 pub fn list(
     file_name: &str,
     filter: getter::ActivityFilter,
-    do_group_activities: bool,
+    grouping: Option<list::Grouping>,
     processors: processor::ProcessorList,
+    total_rounding: Option<processor::TotalRounding>,
+    verbose: bool,
+    output: OutputFormat,
 ) -> Result<()> {
-    let file_content = bartib_file::get_file_content(file_name)?;
+    let (from_date, to_date) = filter.date_range_to_read();
+    let file_content = archive::get_file_content_with_archives(file_name, from_date, to_date)?;
     let activities = getter::get_activities(&file_content).collect();
     let processed_activities_bind: Vec<activity::Activity> =
-        processor::process_activities(activities, processors);
+        processor::split_at_midnight(processor::process_activities(activities, processors));
     let processed_activities: Vec<&activity::Activity> = processed_activities_bind.iter().collect();
 
     let mut filtered_activities: Vec<&activity::Activity> =
@@ -46,90 +191,27 @@ pub fn list(
             .unwrap_or(filtered_activities.len()),
     );
 
-    if do_group_activities {
-        list::list_activities_grouped_by_date(&filtered_activities[first_element..]);
+    if output == OutputFormat::Json {
+        json::print(&filtered_activities[first_element..]);
+    } else if let Some(grouping) = grouping {
+        list::list_activities_grouped(
+            &filtered_activities[first_element..],
+            grouping,
+            total_rounding.as_ref(),
+            verbose,
+        );
     } else {
         let with_start_dates = filter.date.is_none();
-        list::list_activities(&filtered_activities[first_element..], with_start_dates);
-    }
-
-    Ok(())
-}
-
-// checks the file content for sanity
-pub fn sanity_check(file_name: &str) -> Result<()> {
-    let file_content = bartib_file::get_file_content(file_name)?;
-    let mut lines_with_activities: Vec<(Option<usize>, Activity)> = file_content
-        .into_iter()
-        .filter_map(|line| match line.activity {
-            Ok(a) => Some((line.line_number, a)),
-            Err(_) => None,
-        })
-        .collect();
-    lines_with_activities.sort_unstable_by_key(|(_, activity)| activity.start);
-
-    let mut has_finding: bool = false;
-    let mut last_end: Option<NaiveDateTime> = None;
-
-    for (line_number, activity) in lines_with_activities {
-        has_finding = !check_sanity(last_end, &activity, line_number) || has_finding;
-
-        if let Some(e) = last_end {
-            if let Some(this_end) = activity.end {
-                if this_end > e {
-                    last_end = Some(this_end);
-                }
-            }
-        } else {
-            last_end = activity.end;
-        }
-    }
-
-    if !has_finding {
-        println!("No unusual activities.");
+        list::list_activities(
+            &filtered_activities[first_element..],
+            with_start_dates,
+            verbose,
+        );
     }
 
     Ok(())
 }
 
-fn check_sanity(
-    last_end: Option<NaiveDateTime>,
-    activity: &Activity,
-    line_number: Option<usize>,
-) -> bool {
-    let mut sane = true;
-    if activity.get_duration().num_milliseconds() < 0 {
-        println!("Activity has negative duration");
-        sane = false;
-    }
-
-    if let Some(e) = last_end {
-        if e > activity.start {
-            println!("Activity started before another activity ended");
-            sane = false;
-        }
-    }
-
-    if !sane {
-        print_activity_with_line(activity, line_number.unwrap_or(0));
-    }
-
-    sane
-}
-
-fn print_activity_with_line(activity: &Activity, line_number: usize) {
-    println!(
-        "{} (Started: {}, Ended: {}, Line: {})\n",
-        activity.description,
-        activity.start.format(conf::FORMAT_DATETIME),
-        activity.end.map_or_else(
-            || String::from("--"),
-            |end| end.format(conf::FORMAT_DATETIME).to_string()
-        ),
-        line_number
-    )
-}
-
 // prints all errors that occurred when reading the bartib file
 pub fn check(file_name: &str) -> Result<()> {
     let file_content = bartib_file::get_file_content(file_name)?;
@@ -140,18 +222,18 @@ pub fn check(file_name: &str) -> Result<()> {
         .count();
 
     if number_of_errors == 0 {
-        println!("All lines in the file have been successfully parsed as activities.");
+        outputln!("All lines in the file have been successfully parsed as activities.");
         return Ok(());
     }
 
-    println!("Found {number_of_errors} line(s) with parsing errors");
+    outputln!("Found {number_of_errors} line(s) with parsing errors");
 
     file_content
         .iter()
         .filter(|line| line.activity.is_err() && line.plaintext.is_some())
         .for_each(|line| {
             if let Err(e) = &line.activity {
-                println!(
+                outputln!(
                     "\n{}\n  -> {} (Line: {})",
                     line.plaintext.as_ref().unwrap(),
                     e,
@@ -160,7 +242,7 @@ pub fn check(file_name: &str) -> Result<()> {
             }
         });
 
-    Ok(())
+    Err(ExitStatus::ProblemsFound.into())
 }
 
 // lists all projects
@@ -177,21 +259,29 @@ pub fn list_projects(file_name: &str, current: bool, no_quotes: bool) -> Result<
 
     for project in all_projects {
         if no_quotes {
-            println!("{project}");
+            outputln!("{project}");
         } else {
-            println!("\"{project}\"");
+            outputln!("\"{project}\"");
         }
     }
 
     Ok(())
 }
 
-// return last finished activity
-pub fn list_last_activities(file_name: &str, number: usize) -> Result<()> {
-    let file_content = bartib_file::get_file_content(file_name)?;
+// return last finished activity, of a project only if one is given
+pub fn list_last_activities(file_name: &str, number: usize, project: Option<&str>) -> Result<()> {
+    let file_content = bartib_file::get_file_tail(file_name, |lines| {
+        getter::count_descriptions_and_projects(lines, project) >= number
+    })?;
 
     let descriptions_and_projects: Vec<(&String, &String)> =
-        getter::get_descriptions_and_projects(&file_content);
+        getter::get_descriptions_and_projects(&file_content, project);
+    if let Some(project) = project
+        && descriptions_and_projects.is_empty()
+    {
+        outputln!("No activities of project {project} have been tracked yet");
+        return Ok(());
+    }
     let first_element = descriptions_and_projects.len().saturating_sub(number);
 
     list::list_descriptions_and_projects(&descriptions_and_projects[first_element..]);
@@ -199,6 +289,24 @@ pub fn list_last_activities(file_name: &str, number: usize) -> Result<()> {
     Ok(())
 }
 
+// lists the latest activities together with their numbers
+pub fn list_numbered_activities(file_name: &str, number: usize) -> Result<()> {
+    let file_content = bartib_file::get_file_content(file_name)?;
+
+    let mut numbered_activities: Vec<(usize, &activity::Activity)> =
+        getter::get_numbered_line_indexes(&file_content)
+            .into_iter()
+            .take(number)
+            .filter_map(|i| file_content[i].activity.as_ref().ok())
+            .enumerate()
+            .collect();
+    numbered_activities.reverse();
+
+    list::list_numbered_activities(&numbered_activities);
+
+    Ok(())
+}
+
 // searches for the term in descriptions and projects
 pub fn search(file_name: &str, search_term: Option<&str>) -> Result<()> {
     let search_term = search_term
@@ -207,7 +315,7 @@ pub fn search(file_name: &str, search_term: Option<&str>) -> Result<()> {
     let file_content = bartib_file::get_file_content(file_name)?;
 
     let descriptions_and_projects: Vec<(&String, &String)> =
-        getter::get_descriptions_and_projects(&file_content);
+        getter::get_descriptions_and_projects(&file_content, None);
     let search_term_wildmatch = WildMatch::new(&search_term);
     let matches: Vec<(usize, &(&String, &String))> = descriptions_and_projects
         .iter()
@@ -1,55 +1,265 @@
-use anyhow::{anyhow, bail, Context, Error, Result};
-use chrono::NaiveDateTime;
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{Duration, Local, NaiveDateTime, NaiveTime};
+use std::env;
+use std::ffi::OsStr;
+use std::fs::{self, OpenOptions};
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
 use std::process::Command;
 
+use crate::api;
 use crate::conf;
+use crate::controller::hooks;
 use crate::data::activity;
 use crate::data::bartib_file;
+use crate::data::date_parser;
+use crate::data::encryption;
 use crate::data::getter;
+use crate::data::hooks::Event;
+use crate::data::idle;
+use crate::data::index;
+use crate::data::undo;
 use crate::view::format_util;
+use crate::view::picker;
+use crate::{output, outputln};
+
+pub use crate::api::StaleActivities;
+
+// the tags and the issue of an activity. When editing an activity, no tags and no issue keep the
+// ones of the activity
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Labels<'a> {
+    pub tags: &'a [String],
+    pub issue: Option<&'a str>,
+}
+
+impl Labels<'_> {
+    fn apply(&self, activity: activity::Activity) -> activity::Activity {
+        activity
+            .with_tags(self.tags.to_vec())
+            .with_issue(self.issue.map(str::to_string))
+    }
+}
 
 // starts a new activity
+//
+// stale activities are stopped at the end the user is asked for, unless they are stopped at their
+// likely end automatically.
 pub fn start(
     file_name: &str,
     project_name: &str,
     activity_description: &str,
+    labels: Labels,
     time: Option<NaiveDateTime>,
+    idempotent: bool,
+    stale_activities: Option<StaleActivities>,
 ) -> Result<()> {
-    let mut file_content: Vec<bartib_file::Line> = Vec::new();
+    let stale_ends = match stale_activities {
+        Some(stale_activities) if !stale_activities.auto_stop => ask_for_ends_of_stale_activities(
+            file_name,
+            time.unwrap_or_else(|| Local::now().naive_local()),
+            stale_activities.max_duration,
+        )?,
+        _ => Vec::new(),
+    };
+
+    let started = api::start_activity(
+        file_name,
+        project_name,
+        activity_description,
+        &api::StartOptions {
+            tags: labels.tags,
+            issue: labels.issue,
+            time,
+            idempotent,
+            stale_activities,
+            stale_ends: &stale_ends,
+        },
+    )?;
 
-    if let Ok(mut previous_file_content) = bartib_file::get_file_content(file_name) {
-        // if we start a new activities programaticly, we stop all other activities first.
-        // However, we must not assume that there is always only one activity
-        // running as the user may have started activities manually
-        stop_all_running_activities(&mut previous_file_content, time);
+    for activity in &started.stopped_stale {
+        outputln!(
+            "Stopped stale activity: \"{}\" ({}) started at {} ({})",
+            activity.description,
+            activity.project,
+            activity.start.format(conf::format_datetime()),
+            format_util::format_duration(&activity.get_duration()),
+        );
+    }
+    print_stopped_activities(&started.stopped);
 
-        file_content.append(&mut previous_file_content);
+    if started.kept {
+        outputln!(
+            "Activity is already running: \"{}\" ({}) started at {}",
+            started.activity.description,
+            started.activity.project,
+            started.activity.start.format(conf::format_datetime())
+        );
+    } else {
+        print_started_activity(&started.activity);
     }
 
-    let activity = activity::Activity::start(
+    Ok(())
+}
+
+// adds an activity that has already been finished
+//
+// overlaps with other activities are allowed but reported, so they may be fixed afterwards.
+pub fn add(
+    file_name: &str,
+    project_name: &str,
+    activity_description: &str,
+    labels: Labels,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+) -> Result<()> {
+    if end <= start {
+        bail!("The end of an activity must be after its start");
+    }
+
+    let mut file_content = bartib_file::get_file_content_or_empty(file_name)?;
+
+    let mut activity = labels.apply(activity::Activity::start(
         project_name.to_string(),
         activity_description.to_string(),
-        time,
+        Some(start),
+    ));
+    activity.stop(Some(end));
+
+    let now = Local::now().naive_local();
+    let overlapping = getter::get_activities(&file_content).filter(|a| a.overlaps(&activity, now));
+
+    for other in overlapping {
+        eprintln!(
+            "Warning: the activity overlaps with \"{}\" ({}) started at {}",
+            other.description,
+            other.project,
+            other.start.format(conf::format_datetime())
+        );
+    }
+
+    outputln!(
+        "Added activity: \"{}\" ({}) from {} to {} ({})",
+        activity.description,
+        activity.project,
+        activity.start.format(conf::format_datetime()),
+        end.format(conf::format_datetime()),
+        format_util::format_duration(&activity.get_duration())
     );
 
-    save_new_activity(file_name, &mut file_content, activity)
+    file_content.push(bartib_file::Line::for_activity(activity));
+    write_to_file(file_name, &file_content)
 }
 
-fn save_new_activity(
+// stops all running activities and starts a new one with exactly the same timestamp
+pub fn switch(
     file_name: &str,
-    file_content: &mut Vec<bartib_file::Line>,
-    activity: activity::Activity,
-) -> Result<(), Error> {
-    println!(
-        "Started activity: \"{}\" ({}) at {}",
+    project_name: &str,
+    activity_description: &str,
+    labels: Labels,
+    time: Option<NaiveDateTime>,
+    idempotent: bool,
+) -> Result<()> {
+    let time = time.unwrap_or_else(|| Local::now().naive_local());
+    start(
+        file_name,
+        project_name,
+        activity_description,
+        labels,
+        Some(time),
+        idempotent,
+        None,
+    )
+}
+
+// asks the user when the running activities started longer than the maximum duration before `now`
+// have ended. Returns the starts and the chosen ends of the activities
+fn ask_for_ends_of_stale_activities(
+    file_name: &str,
+    now: NaiveDateTime,
+    max_duration: Duration,
+) -> Result<Vec<(NaiveDateTime, NaiveDateTime)>> {
+    let file_content = bartib_file::get_file_content_or_empty(file_name)?;
+
+    Ok(getter::get_running_activities(&file_content)
+        .into_iter()
+        .filter(|activity| activity.is_stale(max_duration, now))
+        .filter_map(|activity| {
+            ask_for_end_of_stale_activity(activity, activity.likely_end(max_duration), now)
+                .map(|end| (activity.start, end))
+        })
+        .collect())
+}
+
+// asks the user when a stale activity has ended. Returns `None` if it should be stopped when the
+// new activity starts, which is also the answer without input or without a terminal to ask on
+fn ask_for_end_of_stale_activity(
+    activity: &activity::Activity,
+    likely_end: NaiveDateTime,
+    now: NaiveDateTime,
+) -> Option<NaiveDateTime> {
+    if !io::stdin().is_terminal() {
+        return None;
+    }
+
+    outputln!(
+        "Activity \"{}\" ({}) has been running since {}",
         activity.description,
         activity.project,
-        activity.start.format(conf::FORMAT_DATETIME)
+        activity.start.format(conf::format_datetime()),
     );
 
-    file_content.push(bartib_file::Line::for_activity(activity));
-    bartib_file::write_to_file(file_name, file_content)
-        .context(format!("Could not write to file: {file_name}"))
+    loop {
+        output!(
+            "Stop it at {}? [y]es, [n]o or another time (e.g. 18:00): ",
+            likely_end.format(conf::format_datetime())
+        );
+        let _ = io::stdout().flush();
+
+        let mut answer = String::new();
+        match io::stdin().read_line(&mut answer) {
+            Ok(0) | Err(_) => return None,
+            Ok(_) => {}
+        }
+
+        match answer.trim().to_lowercase().as_str() {
+            "y" | "yes" => return Some(likely_end),
+            "n" | "no" => return None,
+            answer => match parse_end_of_stale_activity(answer, activity.start, now) {
+                Ok(end) => return Some(end),
+                Err(e) => outputln!("{e}"),
+            },
+        }
+    }
+}
+
+// a time without a date refers to the day the activity has been started or, if that is before
+// the start, to the day after
+fn parse_end_of_stale_activity(
+    answer: &str,
+    start: NaiveDateTime,
+    now: NaiveDateTime,
+) -> Result<NaiveDateTime, String> {
+    let end = if answer.contains(char::is_whitespace) {
+        date_parser::parse_datetime(answer, now.date())?
+    } else {
+        let end = date_parser::parse_datetime(answer, start.date())?;
+        if end < start {
+            end + Duration::days(1)
+        } else {
+            end
+        }
+    };
+
+    if end < start || end > now {
+        return Err(format!(
+            "The time must be between {} and {}",
+            start.format(conf::format_datetime()),
+            now.format(conf::format_datetime())
+        ));
+    }
+
+    Ok(end)
 }
 
 pub fn change(
@@ -61,47 +271,356 @@ pub fn change(
     let mut file_content = bartib_file::get_file_content(file_name)?;
 
     for line in &mut file_content {
-        if let Ok(activity) = &mut line.activity {
-            if !activity.is_stopped() {
-                let mut changed = false;
+        if let Ok(activity) = &mut line.activity
+            && !activity.is_stopped()
+        {
+            let mut changed = false;
 
-                if let Some(project_name) = project_name {
-                    activity.project = project_name.to_string();
-                    changed = true;
-                }
+            if let Some(project_name) = project_name {
+                activity.project = project_name.to_string();
+                changed = true;
+            }
 
-                if let Some(activity_description) = activity_description {
-                    activity.description = activity_description.to_string();
-                    changed = true;
-                }
+            if let Some(activity_description) = activity_description {
+                activity.description = activity_description.to_string();
+                changed = true;
+            }
 
-                if let Some(time) = time {
-                    activity.start = time;
-                    changed = true;
-                }
+            if let Some(time) = time {
+                activity.set_start(time);
+                changed = true;
+            }
 
-                if changed {
-                    println!(
-                        "Changed activity: \"{}\" ({}) started at {}",
-                        activity.description,
-                        activity.project,
-                        activity.start.format(conf::FORMAT_DATETIME)
-                    );
-                    line.set_changed();
-                }
+            if changed {
+                outputln!(
+                    "Changed activity: \"{}\" ({}) started at {}",
+                    activity.description,
+                    activity.project,
+                    activity.start.format(conf::format_datetime())
+                );
+                hooks::notify(Event::Change, activity);
+                line.set_changed();
             }
         }
     }
-    bartib_file::write_to_file(file_name, &file_content)
-        .context(format!("Could not write to file: {file_name}"))
+    write_to_file(file_name, &file_content)
+}
+
+// changes a single activity given by its number (see `last --activities`)
+//
+// new start and end times keep the dates of the activity. Setting the end of a running activity
+// stops it on the day it has been started.
+pub fn edit_activity(
+    file_name: &str,
+    number: usize,
+    project_name: Option<&str>,
+    activity_description: Option<&str>,
+    start: Option<NaiveTime>,
+    end: Option<NaiveTime>,
+    labels: Labels,
+) -> Result<()> {
+    if project_name.is_none()
+        && activity_description.is_none()
+        && start.is_none()
+        && end.is_none()
+        && labels.tags.is_empty()
+        && labels.issue.is_none()
+    {
+        bail!(
+            "Nothing to change. Please specify a project, description, start, end, tags or an issue"
+        );
+    }
+
+    let mut file_content = bartib_file::get_file_content(file_name)?;
+    let index = get_line_index_by_number(&file_content, number)?;
+    let line = &mut file_content[index];
+
+    let Ok(activity) = &mut line.activity else {
+        bail!("Line {index} does not contain a valid activity");
+    };
+
+    if let Some(project_name) = project_name {
+        activity.project = project_name.to_string();
+    }
+
+    if let Some(activity_description) = activity_description {
+        activity.description = activity_description.to_string();
+    }
+
+    if let Some(start) = start {
+        activity.set_start(activity.start.date().and_time(start));
+    }
+
+    if let Some(end) = end {
+        let end_date = activity.end.unwrap_or(activity.start).date();
+        activity.set_end(end_date.and_time(end));
+    }
+
+    if !labels.tags.is_empty() {
+        activity.tags = labels.tags.to_vec();
+    }
+
+    if let Some(issue) = labels.issue {
+        activity.issue = Some(issue.to_string());
+    }
+
+    if activity.end.is_some_and(|end| end < activity.start) {
+        bail!("The activity would end before it starts");
+    }
+
+    outputln!(
+        "Changed activity: \"{}\" ({}) started at {}",
+        activity.description,
+        activity.project,
+        activity.start.format(conf::format_datetime())
+    );
+    hooks::notify(Event::Change, activity);
+    line.set_changed();
+
+    write_to_file(file_name, &file_content)
+}
+
+// deletes a single activity given by its number (see `last --activities`)
+pub fn delete_activity(file_name: &str, number: usize) -> Result<()> {
+    let mut file_content = bartib_file::get_file_content(file_name)?;
+    let index = get_line_index_by_number(&file_content, number)?;
+    let line = file_content.remove(index);
+
+    if let Ok(activity) = &line.activity {
+        outputln!(
+            "Deleted activity: \"{}\" ({}) started at {}",
+            activity.description,
+            activity.project,
+            activity.start.format(conf::format_datetime())
+        );
+    }
+
+    write_to_file(file_name, &file_content)
+}
+
+// adds a note to an activity given by its number (see `last --activities`) or to the most
+// recently started running activity
+pub fn add_note(file_name: &str, number: Option<usize>, note: &str) -> Result<()> {
+    // the note is written on an indented line below the activity and must be read back as a note
+    if !activity::is_note_line(&format!(" {}", note.trim())) {
+        bail!("The note must neither be empty nor look like an activity");
+    }
+
+    let mut file_content = bartib_file::get_file_content(file_name)?;
+    let index = match number {
+        Some(number) => get_line_index_by_number(&file_content, number)?,
+        None => file_content
+            .iter()
+            .enumerate()
+            .filter_map(|(index, line)| line.activity.as_ref().ok().map(|a| (index, a)))
+            .filter(|(_, activity)| !activity.is_stopped())
+            .max_by_key(|(_, activity)| activity.start)
+            .map(|(index, _)| index)
+            .ok_or_else(|| {
+                anyhow!(
+                    "No activity is currently running. Use --number to add the note to a past activity (see `last --activities`)"
+                )
+            })?,
+    };
+    let line = &mut file_content[index];
+
+    let Ok(activity) = &mut line.activity else {
+        bail!("Line {index} does not contain a valid activity");
+    };
+
+    activity.add_note(note);
+    outputln!(
+        "Added note to activity: \"{}\" ({}) started at {}",
+        activity.description,
+        activity.project,
+        activity.start.format(conf::format_datetime())
+    );
+    line.set_changed();
+
+    write_to_file(file_name, &file_content)
 }
 
 // stops all currently running activities
 pub fn stop(file_name: &str, time: Option<NaiveDateTime>) -> Result<()> {
+    let stopped = api::stop_activities(file_name, time)?;
+    print_stopped_activities(&stopped);
+    Ok(())
+}
+
+// pauses all running activities until they are resumed
+pub fn pause(file_name: &str, time: Option<NaiveDateTime>) -> Result<()> {
     let mut file_content = bartib_file::get_file_content(file_name)?;
-    stop_all_running_activities(&mut file_content, time);
-    bartib_file::write_to_file(file_name, &file_content)
-        .context(format!("Could not write to file: {file_name}"))
+    let mut paused = false;
+
+    for line in &mut file_content {
+        if let Ok(activity) = &mut line.activity
+            && !activity.is_stopped()
+            && !activity.is_paused()
+        {
+            if time.is_some_and(|time| time < activity.start) {
+                bail!("An activity can not be paused before it has been started");
+            }
+
+            activity.pause(time);
+            outputln!(
+                "Paused activity: \"{}\" ({}) started at {} ({})",
+                activity.description,
+                activity.project,
+                activity.start.format(conf::format_datetime()),
+                format_util::format_duration(&activity.get_duration()),
+            );
+            line.set_changed();
+            paused = true;
+        }
+    }
+
+    if !paused {
+        bail!("No activity is currently running or all running activities are paused already");
+    }
+
+    write_to_file(file_name, &file_content)
+}
+
+// resumes all paused activities
+pub fn resume(file_name: &str, time: Option<NaiveDateTime>) -> Result<()> {
+    let mut file_content = bartib_file::get_file_content(file_name)?;
+    let mut resumed = false;
+
+    for line in &mut file_content {
+        if let Ok(activity) = &mut line.activity
+            && activity.is_paused()
+        {
+            if activity
+                .pauses
+                .last()
+                .is_some_and(|pause| time.is_some_and(|time| time < pause.start))
+            {
+                bail!("An activity can not be resumed before it has been paused");
+            }
+
+            activity.resume(time);
+            outputln!(
+                "Resumed activity: \"{}\" ({}) started at {}",
+                activity.description,
+                activity.project,
+                activity.start.format(conf::format_datetime()),
+            );
+            line.set_changed();
+            resumed = true;
+        }
+    }
+
+    if !resumed {
+        bail!("No activity is currently paused");
+    }
+
+    write_to_file(file_name, &file_content)
+}
+
+// stops all running activities at the time the user became idle, e.g. with `--since-idle 20m`
+// after returning from 20 minutes away from the keyboard. The idle time may be logged for an idle
+// project
+pub fn stop_since_idle(file_name: &str, idle: Duration, idle_project: Option<&str>) -> Result<()> {
+    let mut file_content = bartib_file::get_file_content(file_name)?;
+    let now = Local::now().naive_local();
+    let idle_start = now - idle;
+    let mut stopped = Vec::new();
+
+    for (index, line) in file_content.iter_mut().enumerate() {
+        if let Ok(activity) = &mut line.activity
+            && !activity.is_stopped()
+        {
+            activity.truncate(idle_start.max(activity.start));
+            outputln!(
+                "Stopped activity: \"{}\" ({}) started at {} ({})",
+                activity.description,
+                activity.project,
+                activity.start.format(conf::format_datetime()),
+                format_util::format_duration(&activity.get_duration()),
+            );
+            hooks::notify(Event::Stop, activity);
+            line.set_changed();
+            stopped.push(index);
+        }
+    }
+
+    if stopped.is_empty() {
+        bail!("No activity is currently running");
+    }
+
+    api::split_stopped_activities(&mut file_content, stopped);
+
+    if let Some(idle_project) = idle_project {
+        file_content.push(bartib_file::Line::for_activity(idle::idle_activity(
+            idle_project,
+            idle_start,
+            now,
+        )));
+        outputln!(
+            "Logged {} of idle time for project \"{idle_project}\"",
+            format_util::format_duration(&idle)
+        );
+    }
+
+    write_to_file(file_name, &file_content)
+}
+
+// remembers when the user became idle, e.g. when called by an idle detector
+pub fn idle_start(file_name: &str, time: Option<NaiveDateTime>) -> Result<()> {
+    let idle_start = match idle::get_idle_start(file_name)? {
+        Some(idle_start) => idle_start,
+        None => {
+            let idle_start = time.unwrap_or_else(|| Local::now().naive_local());
+            idle::save_idle_start(file_name, idle_start)?;
+            idle_start
+        }
+    };
+
+    outputln!("Idle since {}", idle_start.format(conf::format_datetime()));
+    Ok(())
+}
+
+// removes the time since `idle_start` from the running activities
+//
+// the idle time becomes a pause of the activities or, with an idle project, is logged for this
+// project between the interrupted activities and their continuations.
+pub fn idle_end(
+    file_name: &str,
+    time: Option<NaiveDateTime>,
+    idle_project: Option<&str>,
+) -> Result<()> {
+    let Some(idle_start) = idle::get_idle_start(file_name)? else {
+        bail!("No idle time has been started. Use `bartib idle-start` to start it");
+    };
+
+    let idle_end = time.unwrap_or_else(|| Local::now().naive_local());
+    if idle_end < idle_start {
+        bail!("The idle time can not end before it has been started");
+    }
+
+    let mut file_content = bartib_file::get_file_content(file_name)?;
+    let changed = idle::subtract_idle_time(&mut file_content, idle_start, idle_end, idle_project);
+    let idle = format_util::format_duration(&(idle_end - idle_start));
+
+    for activity in &changed {
+        outputln!(
+            "Subtracted {idle} of idle time from activity: \"{}\" ({}) started at {}",
+            activity.description,
+            activity.project,
+            activity.start.format(conf::format_datetime()),
+        );
+    }
+
+    if changed.is_empty() {
+        outputln!("No activity has been running while idle for {idle}");
+    } else {
+        if let Some(idle_project) = idle_project {
+            outputln!("Logged {idle} of idle time for project \"{idle_project}\"");
+        }
+        write_to_file(file_name, &file_content)?;
+    }
+
+    idle::discard_idle_start(file_name)
 }
 
 // cancels all currently running activities
@@ -115,39 +634,131 @@ pub fn cancel(file_name: &str) -> Result<()> {
                 if activity.is_stopped() {
                     new_file_content.push(line);
                 } else {
-                    println!(
+                    outputln!(
                         "Canceled activity: \"{}\" ({}) started at {}",
                         activity.description,
                         activity.project,
-                        activity.start.format(conf::FORMAT_DATETIME)
+                        activity.start.format(conf::format_datetime())
                     );
+                    hooks::notify(Event::Cancel, activity);
                 }
             }
             Err(_) => new_file_content.push(line),
         }
     }
 
-    bartib_file::write_to_file(file_name, &new_file_content)
-        .context(format!("Could not write to file: {file_name}"))
+    write_to_file(file_name, &new_file_content)
 }
 
 // continue last activity
+//
+// with `of_project` the activities are numbered like by `last --project`, so the most recent
+// activity of this project is continued by default
 pub fn continue_last_activity(
     file_name: &str,
     project_name: Option<&str>,
     activity_description: Option<&str>,
     time: Option<NaiveDateTime>,
     number: usize,
+    of_project: Option<&str>,
 ) -> Result<()> {
+    // without running activities to stop, the new activity is appended to the log, which then
+    // needs not be read completely
+    if index::has_running_activities(file_name)? == Some(false) {
+        let file_content = bartib_file::get_file_tail(file_name, |lines| {
+            getter::count_descriptions_and_projects(lines, of_project) > number
+        })?;
+        let new_activity = get_continued_activity(
+            &file_content,
+            project_name,
+            activity_description,
+            time,
+            number,
+            of_project,
+        )?;
+
+        hooks::notify(Event::Start, &new_activity);
+        print_started_activity(&new_activity);
+        let result = undo::save_backup(file_name).and_then(|()| {
+            bartib_file::append_to_file(file_name, &bartib_file::Line::for_activity(new_activity))
+                .context(format!("Could not write to file: {file_name}"))
+        });
+        return hooks::discard_pending_if_failed(result);
+    }
+
     let mut file_content = bartib_file::get_file_content(file_name)?;
+    let new_activity = get_continued_activity(
+        &file_content,
+        project_name,
+        activity_description,
+        time,
+        number,
+        of_project,
+    )?;
 
-    let descriptions_and_projects: Vec<(&String, &String)> =
-        getter::get_descriptions_and_projects(&file_content);
+    let stopped = api::stop_running_activities_except(
+        &mut file_content,
+        time.unwrap_or_else(|| Local::now().naive_local()),
+        |_| false,
+    );
+    print_stopped_activities(&stopped);
+    hooks::notify(Event::Start, &new_activity);
+    print_started_activity(&new_activity);
+    file_content.push(bartib_file::Line::for_activity(new_activity));
+    write_to_file(file_name, &file_content)
+}
+
+// lets the user pick one of the distinct descriptions and projects listed by `last` by typing parts
+// of them. Returns the number of the picked activity (see `last`) with its description and project
+pub fn pick_recent_activity(file_name: &str) -> Result<(usize, String, String)> {
+    let file_content = bartib_file::get_file_content(file_name)?;
+    let mut descriptions_and_projects = getter::get_descriptions_and_projects(&file_content, None);
 
     if descriptions_and_projects.is_empty() {
         bail!("No activity has been started before.")
     }
 
+    // the most recent activity comes first and gets the number 0, like with `continue`
+    descriptions_and_projects.reverse();
+
+    let description_width = descriptions_and_projects
+        .iter()
+        .map(|(description, _)| description.chars().count())
+        .max()
+        .unwrap_or_default();
+    let items: Vec<String> = descriptions_and_projects
+        .iter()
+        .map(|(description, project)| format!("{description:<description_width$}  {project}"))
+        .collect();
+
+    let number = picker::pick("Activity: ", &items)
+        .context("Could not read the picked activity")?
+        .ok_or_else(|| anyhow!("No activity has been picked"))?;
+    let (description, project) = descriptions_and_projects[number];
+
+    Ok((number, description.clone(), project.clone()))
+}
+
+// a new activity with the description and project of the activity `number` activities before the
+// last distinct one, unless another description or project is given
+fn get_continued_activity(
+    file_content: &[bartib_file::Line],
+    project_name: Option<&str>,
+    activity_description: Option<&str>,
+    time: Option<NaiveDateTime>,
+    number: usize,
+    of_project: Option<&str>,
+) -> Result<activity::Activity> {
+    let descriptions_and_projects: Vec<(&String, &String)> =
+        getter::get_descriptions_and_projects(file_content, of_project);
+
+    if descriptions_and_projects.is_empty() {
+        match of_project {
+            Some(project) => bail!("No activity of project {project} has been started before."),
+            None => bail!("No activity has been started before."),
+        }
+    }
+
     if number > descriptions_and_projects.len() {
         bail!(format!(
             "Less than {} distinct activities have been logged yet",
@@ -162,13 +773,11 @@ pub fn continue_last_activity(
     let optional_description_and_project = descriptions_and_projects.get(i);
 
     if let Some((description, project)) = optional_description_and_project {
-        let new_activity = activity::Activity::start(
+        Ok(activity::Activity::start(
             project_name.unwrap_or(project).to_string(),
             activity_description.unwrap_or(description).to_string(),
             time,
-        );
-        stop_all_running_activities(&mut file_content, time);
-        save_new_activity(file_name, &mut file_content, new_activity)
+        ))
     } else {
         bail!(format!(
             "Less than {} distinct activities have been logged yet",
@@ -179,6 +788,45 @@ pub fn continue_last_activity(
 
 pub fn start_editor(file_name: &str, optional_editor_command: Option<&str>) -> Result<()> {
     let editor_command = optional_editor_command.context("editor command is missing")?;
+    undo::save_backup(file_name)?;
+
+    if encryption::is_encrypted(file_name) {
+        return edit_encrypted(file_name, editor_command);
+    }
+
+    run_editor(editor_command, file_name)
+}
+
+// edits a decrypted copy of an encrypted activity log, which only the user may read, in the
+// temporary directory. The copy is removed and the log is encrypted again afterwards
+fn edit_encrypted(file_name: &str, editor_command: &str) -> Result<()> {
+    let content = bartib_file::read_text(file_name)?;
+    let temporary_file_name = env::temp_dir().join(format!("bartib-{}.txt", std::process::id()));
+
+    let edit = || -> Result<String> {
+        write_private_file(&temporary_file_name, &content).context(format!(
+            "Could not write to file: {}",
+            temporary_file_name.to_string_lossy()
+        ))?;
+        run_editor(editor_command, &temporary_file_name)?;
+        fs::read_to_string(&temporary_file_name).context(format!(
+            "Could not read from file: {}",
+            temporary_file_name.to_string_lossy()
+        ))
+    };
+    let edited = edit();
+    let _ = fs::remove_file(&temporary_file_name);
+    let edited = edited?;
+
+    if edited != content {
+        bartib_file::write_atomically(file_name, &edited)
+            .context(format!("Could not write to file: {file_name}"))?;
+    }
+
+    Ok(())
+}
+
+fn run_editor(editor_command: &str, file_name: impl AsRef<OsStr>) -> Result<()> {
     let command = Command::new(editor_command).arg(file_name).spawn();
 
     match command {
@@ -190,24 +838,72 @@ pub fn start_editor(file_name: &str, optional_editor_command: Option<&str>) -> R
     }
 }
 
-fn stop_all_running_activities(
-    file_content: &mut [bartib_file::Line],
-    time: Option<NaiveDateTime>,
-) {
-    for line in file_content {
-        if let Ok(activity) = &mut line.activity {
-            if !activity.is_stopped() {
-                activity.stop(time);
-                println!(
-                    "Stopped activity: \"{}\" ({}) started at {} ({})",
-                    activity.description,
-                    activity.project,
-                    activity.start.format(conf::FORMAT_DATETIME),
-                    format_util::format_duration(&activity.get_duration()),
-                );
+#[cfg(unix)]
+fn write_private_file(path: &Path, content: &str) -> io::Result<()> {
+    use std::os::unix::fs::OpenOptionsExt;
 
-                line.set_changed();
-            }
-        }
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)?
+        .write_all(content.as_bytes())
+}
+
+#[cfg(not(unix))]
+fn write_private_file(path: &Path, content: &str) -> io::Result<()> {
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)?
+        .write_all(content.as_bytes())
+}
+
+// reverts the last change of the activity log
+pub fn undo(file_name: &str) -> Result<()> {
+    let (current, restored) = undo::restore_backup(file_name)?;
+    let (removed_lines, restored_lines) = undo::get_changed_lines(&current, &restored);
+
+    outputln!("Undid the last change of {file_name}");
+    for line in removed_lines {
+        outputln!("- {line}");
+    }
+    for line in restored_lines {
+        outputln!("+ {line}");
+    }
+
+    Ok(())
+}
+
+// writes the file content after saving a copy of the file for `undo`
+fn write_to_file(file_name: &str, file_content: &[bartib_file::Line]) -> Result<()> {
+    api::write_to_file(file_name, file_content)
+}
+
+fn get_line_index_by_number(file_content: &[bartib_file::Line], number: usize) -> Result<usize> {
+    getter::get_numbered_line_indexes(file_content)
+        .get(number)
+        .copied()
+        .ok_or_else(|| anyhow!("Less than {} activities have been logged yet", number + 1))
+}
+
+fn print_started_activity(activity: &activity::Activity) {
+    outputln!(
+        "Started activity: \"{}\" ({}) at {}",
+        activity.description,
+        activity.project,
+        activity.start.format(conf::format_datetime())
+    );
+}
+
+fn print_stopped_activities(activities: &[activity::Activity]) {
+    for activity in activities {
+        outputln!(
+            "Stopped activity: \"{}\" ({}) started at {} ({})",
+            activity.description,
+            activity.project,
+            activity.start.format(conf::format_datetime()),
+            format_util::format_duration(&activity.get_duration()),
+        );
     }
 }
@@ -0,0 +1,198 @@
+use anyhow::{Context, Result, bail};
+use chrono::Local;
+use std::io;
+use std::io::Write;
+
+use crate::conf;
+use crate::data::activity::Activity;
+use crate::data::bartib_file;
+use crate::data::getter;
+use crate::data::lock::Snapshot;
+use crate::data::merge::{self, ConflictStrategy, Resolution};
+use crate::data::undo;
+use crate::outputln;
+
+// merges the activities of another bartib file into the file
+pub fn merge(
+    file_name: &str,
+    other_file_name: &str,
+    strategy: ConflictStrategy,
+    dry_run: bool,
+) -> Result<()> {
+    let incoming = get_activities(other_file_name)?;
+    merge_activities(file_name, incoming, strategy, dry_run)
+}
+
+// adds activities to the file unless they are already logged. Activities that overlap with
+// existing ones are resolved with the given strategy
+pub fn merge_activities(
+    file_name: &str,
+    incoming: Vec<Activity>,
+    strategy: ConflictStrategy,
+    dry_run: bool,
+) -> Result<()> {
+    let snapshot = Snapshot::take(file_name);
+    let mut file_content = bartib_file::get_file_content_or_empty(file_name)?;
+
+    let now = Local::now().naive_local();
+    let summary = merge::merge(&mut file_content, incoming, now, |activity, existing| {
+        print_conflict(activity, existing);
+        let resolution = match strategy {
+            ConflictStrategy::Ask => ask_for_resolution(),
+            ConflictStrategy::Ours => Resolution::Ours,
+            ConflictStrategy::Theirs => Resolution::Theirs,
+            ConflictStrategy::Both => Resolution::Both,
+        };
+        outputln!("  -> {}\n", describe_resolution(resolution));
+        resolution
+    });
+
+    outputln!(
+        "{} {} activities, skipped {} duplicates, dropped {} and replaced {} activities because of conflicts",
+        if dry_run { "Would add" } else { "Added" },
+        summary.added,
+        summary.duplicates,
+        summary.dropped,
+        summary.replaced
+    );
+
+    warn_about_running_activities(&file_content);
+
+    if dry_run || (summary.added == 0 && summary.replaced == 0) {
+        return Ok(());
+    }
+
+    let _lock = snapshot
+        .lock_unchanged()
+        .context(format!("Could not lock file: {file_name}"))?;
+    undo::save_backup(file_name)?;
+    bartib_file::write_to_file(file_name, &file_content)
+        .context(format!("Could not write to file: {file_name}"))
+}
+
+// merges two copies of a log that have been changed independently since they were the same, e.g.
+// as a merge driver for git
+//
+// activities deleted or changed in `theirs` are removed from `ours` and all other activities of
+// `theirs` are added. The result is written to `ours`. Overlapping activities are kept, but fail
+// the merge, so they can be fixed before the merge is finished.
+pub fn merge_driver(
+    base_file_name: &str,
+    ours_file_name: &str,
+    theirs_file_name: &str,
+) -> Result<()> {
+    let base = get_activities(base_file_name).unwrap_or_default();
+    let theirs = get_activities(theirs_file_name)?;
+    let mut file_content = bartib_file::get_file_content(ours_file_name)?;
+
+    let removed = merge::remove_deleted(&mut file_content, &base, &theirs);
+    let incoming: Vec<Activity> = theirs.into_iter().filter(|a| !base.contains(a)).collect();
+
+    let now = Local::now().naive_local();
+    let mut conflicts = 0;
+    let summary = merge::merge(&mut file_content, incoming, now, |activity, existing| {
+        print_conflict(activity, existing);
+        conflicts += 1;
+        Resolution::Both
+    });
+
+    outputln!(
+        "Added {} and removed {} activities, {} overlapping activities",
+        summary.added,
+        removed,
+        conflicts
+    );
+    let running = warn_about_running_activities(&file_content);
+
+    bartib_file::write_to_file(ours_file_name, &file_content)
+        .context(format!("Could not write to file: {ours_file_name}"))?;
+
+    if conflicts > 0 || running > 1 {
+        bail!("Merged with conflicts, check the activity log with `bartib sanity`");
+    }
+
+    Ok(())
+}
+
+// reads the activities of a file and skips the lines that can not be parsed
+fn get_activities(file_name: &str) -> Result<Vec<Activity>> {
+    let mut activities = Vec::new();
+
+    for line in bartib_file::get_file_content(file_name)? {
+        match line.activity {
+            Ok(activity) => activities.push(activity),
+            Err(e) => outputln!(
+                "Skipped line {} of {}: {}",
+                line.line_number.unwrap_or(0),
+                file_name,
+                e
+            ),
+        }
+    }
+
+    Ok(activities)
+}
+
+// prints a warning if more than one activity is running after the merge, e.g. if an activity has
+// been started on two machines. Returns the number of running activities
+fn warn_about_running_activities(file_content: &[bartib_file::Line]) -> usize {
+    let running = getter::get_running_activities(file_content);
+
+    if running.len() > 1 {
+        println!("Warning: {} activities are running:", running.len());
+        for activity in &running {
+            println!("  {}", describe_activity(activity));
+        }
+    }
+
+    running.len()
+}
+
+fn print_conflict(activity: &Activity, existing: &[&Activity]) {
+    outputln!("Conflict: {}", describe_activity(activity));
+    for existing_activity in existing {
+        outputln!("  overlaps with {}", describe_activity(existing_activity));
+    }
+}
+
+fn describe_activity(activity: &Activity) -> String {
+    format!(
+        "\"{}\" ({}) from {} to {}",
+        activity.description,
+        activity.project,
+        activity.start.format(conf::format_datetime()),
+        activity.end.map_or_else(
+            || "now".to_string(),
+            |end| end.format(conf::format_datetime()).to_string()
+        )
+    )
+}
+
+fn describe_resolution(resolution: Resolution) -> &'static str {
+    match resolution {
+        Resolution::Ours => "kept the existing activities",
+        Resolution::Theirs => "replaced the existing activities",
+        Resolution::Both => "kept all activities",
+    }
+}
+
+// asks the user how to resolve a conflict. Keeps the existing activities if there is no input
+fn ask_for_resolution() -> Resolution {
+    loop {
+        print!("Keep [o]urs, take [t]heirs or keep [b]oth? ");
+        let _ = io::stdout().flush();
+
+        let mut answer = String::new();
+        match io::stdin().read_line(&mut answer) {
+            Ok(0) | Err(_) => return Resolution::Ours,
+            Ok(_) => {}
+        }
+
+        match answer.trim().to_lowercase().as_str() {
+            "o" | "ours" => return Resolution::Ours,
+            "t" | "theirs" => return Resolution::Theirs,
+            "b" | "both" => return Resolution::Both,
+            _ => {}
+        }
+    }
+}
@@ -1,4 +1,20 @@
+pub mod archive;
+pub mod budget;
+pub mod config;
+pub mod exit_status;
+pub mod export;
+pub mod hooks;
+pub mod import;
+pub mod init;
 pub mod list;
 pub mod manipulation;
+pub mod merge;
+pub mod project;
+pub mod recur;
+pub mod remind;
 pub mod report;
+pub mod serve;
+pub mod sanity;
+pub mod shell;
 pub mod status;
+pub mod ui;
@@ -0,0 +1,137 @@
+use anyhow::{Context, Result, bail};
+
+use crate::data::archive;
+use crate::data::bartib_file;
+use crate::data::getter;
+use crate::data::undo;
+use crate::outputln;
+
+// renames a project in all activities of the log and its yearly archives
+//
+// the new name must not be used yet. Use `merge` to move the activities to an existing project.
+pub fn rename(file_name: &str, old_name: &str, new_name: &str, dry_run: bool) -> Result<()> {
+    let files = get_files_with_archives(file_name)?;
+
+    if has_project(&files, new_name) {
+        bail!(
+            "The project \"{new_name}\" exists already. Use `bartib project merge` to move the activities of \"{old_name}\" to it"
+        );
+    }
+
+    rewrite_project(file_name, files, old_name, new_name, dry_run)
+}
+
+// moves all activities of a project to another existing project in the log and its yearly
+// archives
+pub fn merge(file_name: &str, source: &str, target: &str, dry_run: bool) -> Result<()> {
+    let files = get_files_with_archives(file_name)?;
+
+    if !has_project(&files, target) {
+        bail!(
+            "The project \"{target}\" does not exist. Use `bartib project rename` to give \"{source}\" a new name"
+        );
+    }
+
+    rewrite_project(file_name, files, source, target, dry_run)
+}
+
+// the names and contents of the yearly archives of the log, followed by the log itself
+fn get_files_with_archives(file_name: &str) -> Result<Vec<(String, Vec<bartib_file::Line>)>> {
+    let mut files = Vec::new();
+
+    for year in archive::get_archived_years(file_name) {
+        let archive_file_name = archive::get_archive_file_name(file_name, year);
+        let file_content = bartib_file::get_file_content(&archive_file_name)?;
+        files.push((archive_file_name, file_content));
+    }
+
+    files.push((
+        file_name.to_string(),
+        bartib_file::get_file_content(file_name)?,
+    ));
+
+    Ok(files)
+}
+
+fn has_project(files: &[(String, Vec<bartib_file::Line>)], project: &str) -> bool {
+    files.iter().any(|(_, file_content)| {
+        getter::get_activities(file_content).any(|activity| activity.project == project)
+    })
+}
+
+// changes the project of the activities in all files and writes the files which have been changed
+//
+// the archives are written before the log. As undoing the change would only restore the log, it
+// can not be undone once an archive has been changed.
+fn rewrite_project(
+    file_name: &str,
+    mut files: Vec<(String, Vec<bartib_file::Line>)>,
+    old_name: &str,
+    new_name: &str,
+    dry_run: bool,
+) -> Result<()> {
+    if old_name == new_name {
+        bail!("The old and the new project are the same");
+    }
+
+    let mut dates = Vec::new();
+    let mut changed_files = Vec::new();
+
+    for (changed_file_name, file_content) in &mut files {
+        let changed_before = dates.len();
+
+        for line in file_content.iter_mut() {
+            if let Ok(activity) = &mut line.activity
+                && activity.project == old_name
+            {
+                activity.project = new_name.to_string();
+                dates.push(activity.start.date());
+                line.set_changed();
+            }
+        }
+
+        if dates.len() > changed_before {
+            changed_files.push(changed_file_name.clone());
+        }
+    }
+
+    let (Some(first), Some(last)) = (dates.iter().min(), dates.iter().max()) else {
+        bail!("There are no activities of the project \"{old_name}\"");
+    };
+
+    outputln!(
+        "{} the project of {} activities from {first} to {last} from \"{old_name}\" to \"{new_name}\"",
+        if dry_run { "Would change" } else { "Changed" },
+        dates.len()
+    );
+
+    let changed_archives: Vec<&String> = changed_files
+        .iter()
+        .filter(|name| *name != file_name)
+        .collect();
+    for archive_file_name in &changed_archives {
+        outputln!(
+            "{} the archive {archive_file_name}, too",
+            if dry_run { "Would change" } else { "Changed" }
+        );
+    }
+
+    if dry_run {
+        return Ok(());
+    }
+
+    if !changed_archives.is_empty() {
+        undo::discard_backup(file_name)?;
+    } else {
+        undo::save_backup(file_name)?;
+    }
+
+    for (changed_file_name, file_content) in &files {
+        if changed_files.contains(changed_file_name) {
+            bartib_file::write_to_file(changed_file_name, file_content)
+                .context(format!("Could not write to file: {changed_file_name}"))?;
+        }
+    }
+
+    Ok(())
+}
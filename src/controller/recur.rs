@@ -0,0 +1,91 @@
+use anyhow::{Context, Result};
+use chrono::{Local, NaiveDate};
+
+use crate::conf;
+use crate::data::activity::Activity;
+use crate::data::bartib_file;
+use crate::data::config::Config;
+use crate::data::recurrence;
+use crate::data::undo;
+use crate::outputln;
+use crate::view::list;
+
+// lists all rules for recurring activities
+pub fn list_rules(config: &Config) -> Result<()> {
+    let rules = recurrence::get_rules(config)?;
+    list::list_recurrence_rules(&rules);
+
+    Ok(())
+}
+
+// adds the recurring activities for a range of dates
+//
+// an activity is not added if it would overlap with another activity or if it has not ended yet.
+pub fn apply(
+    file_name: &str,
+    config: &Config,
+    from: NaiveDate,
+    to: NaiveDate,
+    dry_run: bool,
+) -> Result<()> {
+    let rules = recurrence::get_rules(config)?;
+    let mut file_content = bartib_file::get_file_content_or_empty(file_name)?;
+    let now = Local::now().naive_local();
+
+    let mut activities: Vec<Activity> = file_content
+        .iter()
+        .filter_map(|line| line.activity.as_ref().ok())
+        .cloned()
+        .collect();
+    let mut new_activities: Vec<Activity> = rules
+        .iter()
+        .flat_map(|rule| rule.activities_between(from, to))
+        .filter(|activity| activity.end.is_some_and(|end| end <= now))
+        .collect();
+    new_activities.sort_by_key(|activity| activity.start);
+
+    let mut number_of_added_activities = 0;
+
+    for new_activity in new_activities {
+        if let Some(conflict) = activities
+            .iter()
+            .find(|activity| activity.overlaps(&new_activity, now))
+        {
+            outputln!(
+                "Skipped activity: \"{}\" ({}) at {} as it overlaps with \"{}\" ({}) started at {}",
+                new_activity.description,
+                new_activity.project,
+                new_activity.start.format(conf::format_datetime()),
+                conflict.description,
+                conflict.project,
+                conflict.start.format(conf::format_datetime())
+            );
+            continue;
+        }
+
+        outputln!(
+            "{} activity: \"{}\" ({}) at {}",
+            if dry_run { "Would add" } else { "Added" },
+            new_activity.description,
+            new_activity.project,
+            new_activity.start.format(conf::format_datetime())
+        );
+
+        number_of_added_activities += 1;
+        activities.push(new_activity.clone());
+        file_content.push(bartib_file::Line::for_activity(new_activity));
+    }
+
+    if number_of_added_activities == 0 {
+        outputln!("No recurring activities to add.");
+        return Ok(());
+    }
+
+    if dry_run {
+        return Ok(());
+    }
+
+    undo::save_backup(file_name)?;
+    bartib_file::write_to_file(file_name, &file_content)
+        .context(format!("Could not write to file: {file_name}"))
+}
@@ -0,0 +1,64 @@
+use anyhow::Result;
+use chrono::{Duration, Local, NaiveDateTime};
+use std::mem;
+use std::thread;
+
+use crate::data::activity::Activity;
+use crate::data::bartib_file;
+use crate::data::getter;
+use crate::data::reminder::{self, Reminder, ReminderSettings};
+use crate::view::notification;
+
+// the activities are checked again after this time
+const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+// keeps running and reminds the user to start tracking during working hours, to stop an
+// activity which has been running for too long or to stop tracking beyond the daily limit
+//
+// a reminder is repeated after `repeat` as long as its reason persists. With `once`, the
+// activities are checked a single time, e.g. for a cron job.
+pub fn remind(
+    file_name: &str,
+    settings: &ReminderSettings,
+    repeat: Duration,
+    once: bool,
+) -> Result<()> {
+    bartib_file::keep_in_memory();
+    let mut last_reminder: Option<(Reminder, NaiveDateTime)> = None;
+
+    loop {
+        let file_content = bartib_file::get_file_content(file_name)?;
+        let activities: Vec<&Activity> = getter::get_activities(&file_content).collect();
+        let now = Local::now().naive_local();
+
+        match reminder::check(settings, &activities, now) {
+            Some(reminder) => {
+                let is_due = last_reminder.as_ref().is_none_or(|(last, sent)| {
+                    !concerns_same(last, &reminder) || now - *sent >= repeat
+                });
+
+                if is_due {
+                    notification::show_reminder(&reminder);
+                    last_reminder = Some((reminder, now));
+                }
+            }
+            None => last_reminder = None,
+        }
+
+        if once {
+            return Ok(());
+        }
+
+        thread::sleep(CHECK_INTERVAL);
+    }
+}
+
+// whether two reminders have the same reason, e.g. the same running activity
+fn concerns_same(a: &Reminder, b: &Reminder) -> bool {
+    match (a, b) {
+        (Reminder::Running(a), Reminder::Running(b)) => {
+            a.start == b.start && a.project == b.project
+        }
+        _ => mem::discriminant(a) == mem::discriminant(b),
+    }
+}
@@ -1,35 +1,119 @@
-use anyhow::Result;
+use anyhow::{Result, bail};
 
+use crate::api;
 use crate::data::activity;
-use crate::data::bartib_file;
+use crate::data::archive;
+use crate::data::date_parser::DateRange;
 use crate::data::getter;
 use crate::data::processor;
-use crate::view::report;
+use crate::data::processor::{ComparisonData, ReportData};
 
 pub fn show_report(
     file_name: &str,
     filter: getter::ActivityFilter,
     processors: processor::ProcessorList,
+    total_rounding: Option<processor::TotalRounding>,
+    project_depth: Option<usize>,
+    writer: &dyn processor::ReportWriter,
 ) -> Result<()> {
-    let file_content = bartib_file::get_file_content(file_name)?;
-    let activities = getter::get_activities(&file_content).collect();
+    let (from_date, to_date) = filter.date_range();
+    let (read_from_date, _) = filter.date_range_to_read();
+    let file_content = archive::get_file_content_with_archives(file_name, read_from_date, to_date)?;
 
-    let processed_activities_bind: Vec<activity::Activity> =
-        processor::process_activities(activities, processors);
-    let processed_activities: Vec<&activity::Activity> = processed_activities_bind.iter().collect();
+    let selected_activities = cut_projects(
+        api::select_activities(
+            getter::get_activities(&file_content).collect(),
+            &filter,
+            processors,
+        ),
+        project_depth,
+    );
+    let activities: Vec<&activity::Activity> = selected_activities.iter().collect();
+
+    writer.process(&ReportData {
+        activities: &activities,
+        total_rounding: total_rounding.as_ref(),
+        from_date,
+        to_date,
+    })
+}
 
-    let mut filtered_activities: Vec<&activity::Activity> =
-        getter::filter_activities(processed_activities, &filter);
+// shows the report of the filtered period next to the report of the period of the same length
+// before it, e.g. of the current week next to the last week
+pub fn show_comparison(
+    file_name: &str,
+    filter: getter::ActivityFilter,
+    processors: processor::ProcessorList,
+    total_rounding: Option<processor::TotalRounding>,
+    project_depth: Option<usize>,
+    writer: &dyn processor::ComparisonWriter,
+) -> Result<()> {
+    let (Some(from_date), Some(to_date)) = filter.date_range() else {
+        bail!(
+            "Please choose a period to compare with the period before, e.g. with --current-week, --last-month or --from and --to"
+        );
+    };
+    let previous_range = DateRange {
+        first: from_date,
+        last: to_date,
+    }
+    .preceding();
+    let previous_filter = filter.with_date_range(previous_range);
 
-    filtered_activities.sort_by_key(|activity| activity.start);
+    let (read_from_date, _) = previous_filter.date_range_to_read();
+    let file_content =
+        archive::get_file_content_with_archives(file_name, read_from_date, Some(to_date))?;
 
-    let first_element = filtered_activities.len().saturating_sub(
-        filter
-            .number_of_activities
-            .unwrap_or(filtered_activities.len()),
+    // the activities of both periods are processed at once, as the processors can only be used once
+    let all_activities = api::select_activities(
+        getter::get_activities(&file_content).collect(),
+        &filter.with_date_range(DateRange {
+            first: previous_range.first,
+            last: to_date,
+        }),
+        processors,
+    );
+    let current_activities = cut_projects(
+        getter::filter_activities(all_activities.iter().collect(), &filter)
+            .into_iter()
+            .cloned()
+            .collect(),
+        project_depth,
+    );
+    let previous_activities = cut_projects(
+        getter::filter_activities(all_activities.iter().collect(), &previous_filter)
+            .into_iter()
+            .cloned()
+            .collect(),
+        project_depth,
     );
 
-    report::show_activities(&filtered_activities[first_element..filtered_activities.len()]);
+    writer.process(&ComparisonData {
+        current: ReportData {
+            activities: &current_activities.iter().collect::<Vec<_>>(),
+            total_rounding: total_rounding.as_ref(),
+            from_date: Some(from_date),
+            to_date: Some(to_date),
+        },
+        previous: ReportData {
+            activities: &previous_activities.iter().collect::<Vec<_>>(),
+            total_rounding: total_rounding.as_ref(),
+            from_date: Some(previous_range.first),
+            to_date: Some(previous_range.last),
+        },
+    })
+}
 
-    Ok(())
+// the projects are cut after filtering, so that subprojects are still found by their names
+fn cut_projects(
+    activities: Vec<activity::Activity>,
+    project_depth: Option<usize>,
+) -> Vec<activity::Activity> {
+    match project_depth {
+        Some(depth) => processor::process_activities(
+            activities.iter().collect(),
+            vec![Box::new(processor::ProjectDepthProcessor { depth })],
+        ),
+        None => activities,
+    }
 }
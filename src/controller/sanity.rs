@@ -0,0 +1,192 @@
+use anyhow::{Context, Result};
+use chrono::Local;
+use std::io;
+use std::io::Write;
+use std::mem;
+
+use crate::conf;
+use crate::controller::exit_status::ExitStatus;
+use crate::data::activity::Activity;
+use crate::data::bartib_file;
+use crate::data::lock::Snapshot;
+use crate::data::sanity::{self, Finding, FixStrategy, Issue};
+use crate::data::undo;
+use crate::outputln;
+
+// the answer to the question whether an issue should be fixed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Answer {
+    Yes,
+    No,
+    All,
+}
+
+// checks the file content for sanity and optionally fixes the issues found
+pub fn sanity_check(file_name: &str, fix: Option<FixStrategy>) -> Result<()> {
+    let snapshot = Snapshot::take(file_name);
+    let mut file_content = bartib_file::get_file_content(file_name)?;
+    let now = Local::now().naive_local();
+
+    let Some(mut strategy) = fix else {
+        let findings = sanity::find_issues(&file_content, now);
+        for finding in &findings {
+            print_finding(&file_content, *finding);
+            outputln!();
+        }
+
+        if findings.is_empty() {
+            outputln!("No unusual activities.");
+            return Ok(());
+        }
+
+        return Err(ExitStatus::ProblemsFound.into());
+    };
+
+    // fixing an issue may resolve or reveal other issues, so the log is checked again after
+    // each fix. Issues that should not be fixed are remembered to not ask for them again
+    let mut skipped = Vec::new();
+    let mut fixed = 0;
+
+    while let Some(finding) = sanity::find_issues(&file_content, now)
+        .into_iter()
+        .find(|finding| !skipped.contains(&get_key(&file_content, *finding)))
+    {
+        print_finding(&file_content, finding);
+
+        let answer = match strategy {
+            FixStrategy::Ask => ask_for_fix(&file_content, finding),
+            FixStrategy::Auto => Answer::Yes,
+        };
+
+        if answer == Answer::All {
+            strategy = FixStrategy::Auto;
+        }
+
+        if answer == Answer::No {
+            outputln!("  -> skipped\n");
+            skipped.push(get_key(&file_content, finding));
+        } else {
+            outputln!("  -> {}\n", describe_fix(&file_content, finding));
+            sanity::fix(&mut file_content, finding);
+            fixed += 1;
+        }
+    }
+
+    if fixed == 0 && skipped.is_empty() {
+        outputln!("No unusual activities.");
+        return Ok(());
+    }
+
+    outputln!("Fixed {fixed} and skipped {} issues", skipped.len());
+
+    // skipped issues remain in the log
+    let result = if skipped.is_empty() {
+        Ok(())
+    } else {
+        Err(ExitStatus::ProblemsFound.into())
+    };
+
+    if fixed == 0 {
+        return result;
+    }
+
+    let _lock = snapshot
+        .lock_unchanged()
+        .context(format!("Could not lock file: {file_name}"))?;
+    undo::save_backup(file_name)?;
+    bartib_file::write_to_file(file_name, &file_content)
+        .context(format!("Could not write to file: {file_name}"))?;
+    result
+}
+
+fn print_finding(file_content: &[bartib_file::Line], finding: Finding) {
+    match finding.issue {
+        Issue::NegativeDuration => outputln!("Activity has negative duration"),
+        Issue::MultipleDays => outputln!("Activity spans multiple days"),
+        Issue::Overlap(other) => outputln!(
+            "Activity started before another activity ended (Line: {})",
+            file_content[other].line_number.unwrap_or(0)
+        ),
+    }
+
+    let line = &file_content[finding.index];
+    if let Ok(activity) = &line.activity {
+        print_activity_with_line(activity, line.line_number.unwrap_or(0));
+    }
+}
+
+fn print_activity_with_line(activity: &Activity, line_number: usize) {
+    outputln!(
+        "{} (Started: {}, Ended: {}, Line: {})",
+        activity.description,
+        activity.start.format(conf::format_datetime()),
+        activity.end.map_or_else(
+            || String::from("--"),
+            |end| end.format(conf::format_datetime()).to_string()
+        ),
+        line_number
+    );
+}
+
+fn describe_fix(file_content: &[bartib_file::Line], finding: Finding) -> String {
+    let Ok(activity) = &file_content[finding.index].activity else {
+        return String::new();
+    };
+
+    match finding.issue {
+        Issue::NegativeDuration => "swap its start and end".to_string(),
+        Issue::MultipleDays => format!(
+            "split it at midnight into {} activities",
+            activity.split_at_midnight().len()
+        ),
+        Issue::Overlap(other) => format!(
+            "end \"{}\" (Line: {}) at {}",
+            file_content[other]
+                .activity
+                .as_ref()
+                .map_or("", |other| other.description.as_str()),
+            file_content[other].line_number.unwrap_or(0),
+            activity.start.format(conf::format_datetime())
+        ),
+    }
+}
+
+// identifies an issue independently of the position of the lines in the file
+fn get_key(
+    file_content: &[bartib_file::Line],
+    finding: Finding,
+) -> (mem::Discriminant<Issue>, Option<Activity>, Option<Activity>) {
+    let other = match finding.issue {
+        Issue::Overlap(other) => file_content[other].activity.clone().ok(),
+        _ => None,
+    };
+
+    (
+        mem::discriminant(&finding.issue),
+        file_content[finding.index].activity.clone().ok(),
+        other,
+    )
+}
+
+fn ask_for_fix(file_content: &[bartib_file::Line], finding: Finding) -> Answer {
+    loop {
+        print!(
+            "Fix it ({})? [y]es, [n]o or [a]ll? ",
+            describe_fix(file_content, finding)
+        );
+        let _ = io::stdout().flush();
+
+        let mut answer = String::new();
+        match io::stdin().read_line(&mut answer) {
+            Ok(0) | Err(_) => return Answer::No,
+            Ok(_) => {}
+        }
+
+        match answer.trim().to_lowercase().as_str() {
+            "y" | "yes" => return Answer::Yes,
+            "n" | "no" => return Answer::No,
+            "a" | "all" => return Answer::All,
+            _ => {}
+        }
+    }
+}
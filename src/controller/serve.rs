@@ -0,0 +1,399 @@
+use anyhow::{Context, Result, bail};
+use chrono::{Local, NaiveDate};
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::io::{BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::time::Duration;
+use thiserror::Error;
+
+use crate::api;
+use crate::conf;
+use crate::controller::budget;
+use crate::controller::hooks;
+use crate::data::activity;
+use crate::data::aliases::Aliases;
+use crate::data::budget::Budget;
+use crate::data::date_parser;
+use crate::data::getter::{ActivityFilter, DatePresetArgs};
+use crate::data::http::{self, HttpError, Request};
+use crate::data::json_parser::{self, JsonValue};
+use crate::data::processor::{ProcessorList, TotalRounding};
+use crate::outputln;
+use crate::view::json::{Json, ToJson};
+use crate::view::status;
+
+// clients sending their requests slower than this are disconnected, so that they do not block
+// the server
+static READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+// the options of `bartib serve`
+pub struct ServeOptions<'a> {
+    pub address: &'a str,
+    pub port: u16,
+    // clients have to send this token as `Authorization: Bearer <token>`. Only servers on a
+    // loopback address may run without one
+    pub token: Option<&'a str>,
+    // the origin allowed to send requests from browsers, e.g. `moz-extension://<id>`. With a token,
+    // but without an origin, every origin is allowed, as browsers do not know the token
+    pub allow_origin: Option<&'a str>,
+    pub aliases: &'a Aliases,
+    // the budgets to warn about when activities are started
+    pub budgets: &'a [Budget],
+    pub stale_activities: Option<api::StaleActivities>,
+    // the processors and the rounding of the totals of reports
+    pub rounding: &'a dyn Fn() -> (ProcessorList, Option<TotalRounding>),
+}
+
+struct Response {
+    status: u16,
+    body: Json,
+}
+
+impl Response {
+    fn ok(body: Json) -> Self {
+        Self { status: 200, body }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            body: Json::object([("error", Json::string(message))]),
+        }
+    }
+}
+
+// answers requests to the HTTP API until the process is stopped
+//
+// requests are answered one after the other. Requests changing the activity log lock it like the
+// commands do, so the server may run next to the command line.
+pub fn serve(file_name: &str, options: &ServeOptions) -> Result<()> {
+    let listener = TcpListener::bind((options.address, options.port)).context(format!(
+        "Could not listen on {}:{}",
+        options.address, options.port
+    ))?;
+    let local_address = listener.local_addr()?;
+    if options.token.is_none() && !local_address.ip().is_loopback() {
+        bail!("A token is required to listen on {local_address}");
+    }
+    outputln!("Serving {file_name} on http://{local_address}");
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                if let Err(e) = handle_connection(stream, local_address, file_name, options) {
+                    eprintln!("Warning: Could not answer a request: {e}");
+                }
+            }
+            Err(e) => eprintln!("Warning: Could not accept a connection: {e}"),
+        }
+    }
+
+    Ok(())
+}
+
+// a random token for servers started without one
+//
+// the keys of `RandomState` are taken from the randomness of the operating system on every
+// platform, so two hashes with different keys give 128 bits which can not be guessed
+#[must_use]
+pub fn generate_token() -> String {
+    (0..2_u64)
+        .map(|i| format!("{:016x}", RandomState::new().hash_one(i)))
+        .collect()
+}
+
+fn handle_connection(
+    stream: TcpStream,
+    local_address: SocketAddr,
+    file_name: &str,
+    options: &ServeOptions,
+) -> Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+
+    let (request_line, response) = match http::read_request(&mut reader) {
+        Ok(request) => (
+            format!("{} {}", request.method, request.path),
+            answer(&request, local_address, file_name, options),
+        ),
+        Err(HttpError::Io(e)) => return Err(e.into()),
+        Err(e @ HttpError::TooLarge) => (String::new(), Response::error(413, &e.to_string())),
+        Err(e) => (String::new(), Response::error(400, &e.to_string())),
+    };
+
+    outputln!(
+        "{} {request_line} {}",
+        Local::now().naive_local().format(conf::format_datetime()),
+        response.status
+    );
+    let result = write_response(&stream, &response, allowed_origin(options));
+    drop(stream);
+
+    // the activity log has been unlocked already and the client needs not wait for the hooks
+    hooks::run_pending();
+    result
+}
+
+fn answer(
+    request: &Request,
+    local_address: SocketAddr,
+    file_name: &str,
+    options: &ServeOptions,
+) -> Response {
+    if !request
+        .header("Host")
+        .is_some_and(|host| http::is_allowed_host(host, local_address))
+    {
+        return Response::error(403, "Unknown host");
+    }
+
+    if !http::is_allowed_origin(request.header("Origin"), allowed_origin(options)) {
+        return Response::error(403, "Origin not allowed");
+    }
+
+    // browsers ask before sending requests from other origins, e.g. from extensions
+    if request.method == "OPTIONS" {
+        return Response::ok(Json::Null);
+    }
+
+    if let Some(token) = options.token
+        && request.header("Authorization") != Some(&format!("Bearer {token}"))
+    {
+        return Response::error(401, "Missing or wrong token");
+    }
+
+    let result = match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/current") => {
+            api::get_running_activities(file_name).map(|activities| activities.to_json())
+        }
+        ("POST", "/start") => start(request, file_name, options),
+        ("POST", "/stop") => api::stop_activities(file_name, None)
+            .map(|stopped| Json::object([("stopped", stopped.to_json())])),
+        ("GET", "/activities") => list(request, file_name, options),
+        ("GET", "/report") => report(request, file_name, options),
+        (_, "/current" | "/start" | "/stop" | "/activities" | "/report") => {
+            return Response::error(405, "Method not allowed");
+        }
+        _ => return Response::error(404, "Not found"),
+    };
+
+    match result {
+        Ok(body) => Response::ok(body),
+        Err(e) => Response::error(status_of_error(&e), &format!("{e:#}")),
+    }
+}
+
+// errors in the parameters of a request are the fault of the client
+#[derive(Error, Debug)]
+#[error("{0}")]
+struct BadRequest(String);
+
+fn status_of_error(error: &anyhow::Error) -> u16 {
+    if error.is::<BadRequest>() { 400 } else { 500 }
+}
+
+// starts an activity with the project, description, tags and issue of a JSON body like
+// `{"project": "p", "description": "d", "tags": ["t"], "issue": "PROJ-1"}` or of the query string
+//
+// stale activities are stopped at their likely end only with `--auto-stop-stale`, as there is no
+// one to ask.
+fn start(request: &Request, file_name: &str, options: &ServeOptions) -> Result<Json> {
+    let body = if request.body.trim().is_empty() {
+        JsonValue::Null
+    } else {
+        json_parser::parse_json(&request.body)
+            .map_err(|e| BadRequest(format!("Invalid JSON: {e}")))?
+    };
+
+    let optional_parameter = |name: &str| {
+        body.get(name)
+            .and_then(JsonValue::as_str)
+            .or_else(|| request.query_param(name))
+    };
+    let parameter = |name: &str| {
+        optional_parameter(name).ok_or_else(|| BadRequest(format!("Missing parameter `{name}`")))
+    };
+    let project = options
+        .aliases
+        .resolve(parameter("project")?)
+        .map_err(|e| BadRequest(e.to_string()))?;
+    let description = parameter("description")?;
+    let tags: Vec<&str> = match body.get("tags") {
+        Some(tags) => tags
+            .as_array()
+            .and_then(|tags| tags.iter().map(JsonValue::as_str).collect())
+            .ok_or_else(|| BadRequest("Expected `tags` to be an array of strings".to_string()))?,
+        None => request
+            .query_param("tags")
+            .map(|tags| tags.split(',').collect())
+            .unwrap_or_default(),
+    };
+    // the same checks as on the command line, so that no malformed lines are written
+    let tags = tags
+        .into_iter()
+        .map(activity::parse_tag)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| BadRequest(format!("Invalid parameter `tags`: {e}")))?;
+    let issue = optional_parameter("issue")
+        .map(activity::parse_issue)
+        .transpose()
+        .map_err(|e| BadRequest(format!("Invalid parameter `issue`: {e}")))?;
+
+    let started = api::start_activity(
+        file_name,
+        project,
+        description,
+        &api::StartOptions {
+            tags: &tags,
+            issue: issue.as_deref(),
+            stale_activities: options.stale_activities,
+            ..api::StartOptions::default()
+        },
+    )?;
+    let warnings: Vec<String> =
+        budget::get_current_usages(file_name, options.budgets, Some(project))?
+            .iter()
+            .filter_map(status::budget_warning)
+            .collect();
+
+    let stopped: Vec<api::Activity> = started
+        .stopped_stale
+        .into_iter()
+        .chain(started.stopped)
+        .collect();
+    Ok(Json::object([
+        ("started", started.activity.to_json()),
+        ("stopped", stopped.to_json()),
+        (
+            "warnings",
+            Json::Array(warnings.into_iter().map(Json::String).collect()),
+        ),
+    ]))
+}
+
+fn list(request: &Request, file_name: &str, options: &ServeOptions) -> Result<Json> {
+    let number = request
+        .query_param("last")
+        .map(|number| {
+            number
+                .parse::<usize>()
+                .map_err(|_| BadRequest(format!("Invalid number `{number}`")))
+        })
+        .transpose()?;
+    let dates = Dates::of(request)?;
+    let project = resolve_project(request, options.aliases)?;
+    let filter = dates.filter(number, project);
+    let (processors, _) = (options.rounding)();
+
+    Ok(api::get_activities(file_name, &filter, processors)?.to_json())
+}
+
+fn report(request: &Request, file_name: &str, options: &ServeOptions) -> Result<Json> {
+    let dates = Dates::of(request)?;
+    let project = resolve_project(request, options.aliases)?;
+    let filter = dates.filter(None, project);
+    let (processors, total_rounding) = (options.rounding)();
+
+    Ok(api::get_report(file_name, &filter, processors, total_rounding.as_ref())?.to_json())
+}
+
+fn resolve_project<'a>(request: &'a Request, aliases: &'a Aliases) -> Result<Option<&'a str>> {
+    let project = request
+        .query_param("project")
+        .map(|project| aliases.resolve(project))
+        .transpose()
+        .map_err(|e| BadRequest(e.to_string()))?;
+    Ok(project)
+}
+
+// the dates of the query parameters `from`, `to` and `date`, which are given like on the command
+// line, e.g. `2024-03-01` or `yesterday`
+struct Dates {
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    date: Option<date_parser::DateRange>,
+}
+
+impl Dates {
+    fn of(request: &Request) -> Result<Self> {
+        let today = Local::now().date_naive();
+        let date = |name: &str| {
+            request
+                .query_param(name)
+                .map(|date| date_parser::parse_date(date, today))
+                .transpose()
+                .map_err(|e| BadRequest(format!("Invalid parameter `{name}`: {e}")))
+        };
+
+        Ok(Self {
+            from: date("from")?,
+            to: date("to")?,
+            date: request
+                .query_param("date")
+                .map(|date| date_parser::parse_date_range(date, today))
+                .transpose()
+                .map_err(|e| BadRequest(format!("Invalid parameter `date`: {e}")))?,
+        })
+    }
+
+    fn filter<'a>(&self, number: Option<usize>, project: Option<&'a str>) -> ActivityFilter<'a> {
+        ActivityFilter::new(
+            number,
+            self.from,
+            self.to,
+            self.date,
+            project,
+            &[],
+            DatePresetArgs::default(),
+        )
+    }
+}
+
+// the origin allowed to read the responses in browsers. Without a token or an explicit origin,
+// browsers keep other origins from reading them
+fn allowed_origin<'a>(options: &ServeOptions<'a>) -> Option<&'a str> {
+    options.allow_origin.or_else(|| options.token.map(|_| "*"))
+}
+
+fn write_response(
+    mut stream: &TcpStream,
+    response: &Response,
+    allowed_origin: Option<&str>,
+) -> Result<()> {
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Content Too Large",
+        _ => "Internal Server Error",
+    };
+    let body = response.body.to_string();
+    let cors_headers = allowed_origin
+        .map(|origin| {
+            format!(
+                "Access-Control-Allow-Origin: {origin}\r\n\
+                 Access-Control-Allow-Methods: GET, POST, OPTIONS\r\n\
+                 Access-Control-Allow-Headers: Authorization, Content-Type\r\n"
+            )
+        })
+        .unwrap_or_default();
+
+    write!(
+        stream,
+        "HTTP/1.1 {} {reason}\r\n\
+         Content-Type: application/json\r\n\
+         Content-Length: {}\r\n\
+         {cors_headers}\
+         Connection: close\r\n\
+         \r\n\
+         {body}",
+        response.status,
+        body.len()
+    )?;
+    stream.flush()?;
+    Ok(())
+}
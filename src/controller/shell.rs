@@ -0,0 +1,99 @@
+use crate::data::activity::Activity;
+use crate::data::bartib_file;
+use crate::view::completions::LoggedValue;
+use crate::view::line_editor;
+use crate::view::line_editor::Completion;
+
+// completes the word currently typed in the interactive shell
+//
+// the first word is completed with the names of subcommands, values for `--project`,
+// `--description` and `--tag` with the projects, descriptions and tags used in the activity log.
+#[must_use]
+pub fn complete(file_name: &str, line: &str, subcommands: &[String]) -> Completion {
+    let (arguments, start, word) = line_editor::split_current_word(line);
+
+    let candidates: Vec<String> = match arguments.last().map(String::as_str) {
+        None => subcommands
+            .iter()
+            .filter(|subcommand| subcommand.starts_with(&word))
+            .cloned()
+            .collect(),
+        Some("-p" | "--project") => {
+            get_used_values(file_name, |a| std::slice::from_ref(&a.project), &word)
+        }
+        Some("-d" | "--description") => {
+            get_used_values(file_name, |a| std::slice::from_ref(&a.description), &word)
+        }
+        Some("--tag") => get_used_values(file_name, |a| &a.tags, &word),
+        Some(_) => Vec::new(),
+    };
+
+    Completion {
+        start,
+        candidates: candidates
+            .iter()
+            .map(|candidate| line_editor::quote_argument(candidate))
+            .collect(),
+    }
+}
+
+// completes a project name typed without any subcommand, e.g. when the ui asks for a project
+#[must_use]
+pub fn complete_project(file_name: &str, line: &str) -> Completion {
+    Completion {
+        start: 0,
+        candidates: get_used_values(file_name, |a| std::slice::from_ref(&a.project), line),
+    }
+}
+
+// completes a description typed without any subcommand
+#[must_use]
+pub fn complete_description(file_name: &str, line: &str) -> Completion {
+    Completion {
+        start: 0,
+        candidates: get_used_values(file_name, |a| std::slice::from_ref(&a.description), line),
+    }
+}
+
+// prints the projects, descriptions or tags used in the activity log, one per line
+//
+// the completion scripts call this to complete the values of `--project`, `--description` and
+// `--tag`.
+pub fn list_logged_values(file_name: &str, logged_value: LoggedValue) {
+    let values = match logged_value {
+        LoggedValue::Projects => {
+            get_used_values(file_name, |a| std::slice::from_ref(&a.project), "")
+        }
+        LoggedValue::Descriptions => {
+            get_used_values(file_name, |a| std::slice::from_ref(&a.description), "")
+        }
+        LoggedValue::Tags => get_used_values(file_name, |a| &a.tags, ""),
+    };
+
+    for value in values {
+        println!("{value}");
+    }
+}
+
+fn get_used_values(
+    file_name: &str,
+    select: fn(&Activity) -> &[String],
+    prefix: &str,
+) -> Vec<String> {
+    let Ok(file_content) = bartib_file::get_file_content(file_name) else {
+        return Vec::new();
+    };
+
+    // lines with parsing errors are skipped silently as warnings would mess up the prompt
+    let mut values: Vec<String> = file_content
+        .iter()
+        .filter_map(|line| line.activity.as_ref().ok())
+        .flat_map(select)
+        .filter(|value| value.starts_with(prefix))
+        .cloned()
+        .collect();
+
+    values.sort_unstable();
+    values.dedup();
+    values
+}
@@ -1,67 +1,109 @@
-use anyhow::Result;
-use chrono::Local;
+use anyhow::{Result, bail};
+use chrono::{Duration, Local};
 
-use crate::data::activity;
+use crate::api;
+use crate::controller::budget;
+use crate::controller::exit_status::ExitStatus;
 use crate::data::activity::Activity;
 use crate::data::bartib_file;
-use crate::data::filter::Filters;
+use crate::data::budget::{Budget, BudgetState};
 use crate::data::getter;
+use crate::data::goal::{Goal, GoalProgress};
 use crate::data::processor;
 use crate::data::processor::StatusReportData;
 
+// the goals and budgets of the configuration file, which the status shows, and whether it fails
+// if they are missed
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Targets<'a> {
+    pub goals: &'a [Goal],
+    // fail if less than this percentage of any goal has been reached
+    pub fail_under: Option<i64>,
+    pub budgets: &'a [Budget],
+    // fail if any budget has been exceeded
+    pub fail_over_budget: bool,
+}
+
 pub fn show_status(
     file_name: &str,
     filter: getter::ActivityFilter,
     processors: processor::ProcessorList,
     writer: &dyn processor::StatusReportWriter,
+    daily_limit: Option<Duration>,
+    targets: Targets,
 ) -> Result<()> {
-    let file_content = bartib_file::get_file_content(file_name)?;
-    let activities: Vec<&Activity> = getter::get_activities(&file_content).collect();
+    let Targets {
+        goals,
+        fail_under,
+        budgets,
+        fail_over_budget,
+    } = targets;
 
-    let processed_activities_bind: Vec<activity::Activity> =
-        processor::process_activities(activities, processors);
-    let processed_activities: Vec<&activity::Activity> = processed_activities_bind.iter().collect();
+    if fail_under.is_some() && goals.is_empty() {
+        bail!("No goals have been defined. Please add a [[goal]] table to the configuration file");
+    }
 
-    let mut filtered_activities: Vec<&activity::Activity> =
-        getter::filter_activities(processed_activities, &filter);
+    if fail_over_budget && budgets.is_empty() {
+        bail!(
+            "No budgets have been defined. Please add a [[budget]] table to the configuration file"
+        );
+    }
 
-    filtered_activities.sort_by_key(|activity| activity.start);
+    let file_content = bartib_file::get_file_content(file_name)?;
+    // budgets count all activities of their projects, regardless of the filter
+    let all_activities: Vec<&Activity> = getter::get_activities(&file_content).collect();
+    let selected_activities = api::select_activities(
+        getter::get_activities(&file_content).collect(),
+        &filter,
+        processors,
+    );
+    let activities: Vec<&Activity> = selected_activities.iter().collect();
 
-    let now = Local::now().naive_local();
+    let status = api::status_of(&activities, goals, Local::now().date_naive());
 
-    let current: Option<&Activity> = filtered_activities
-        .clone()
-        .into_iter()
-        .filter(Filters::active)
-        .take(1)
-        .last();
+    let status_report_data = StatusReportData {
+        activity: status.running.first(),
+        today: status.today,
+        current_week: status.current_week,
+        current_month: status.current_month,
+        project: filter.project,
+        daily_limit,
+        goals: status
+            .goals
+            .iter()
+            .map(|goal_status| GoalProgress {
+                goal: &goal_status.goal,
+                tracked: goal_status.tracked,
+            })
+            .collect(),
+        budgets: budget::get_usages(&budgets.iter().collect::<Vec<_>>(), &all_activities),
+    };
+    writer.process(&status_report_data)?;
 
-    let today = filtered_activities
-        .clone()
-        .into_iter()
-        .filter(Filters::today(now.date()))
-        .map(|f| f.get_duration())
-        .sum();
+    if let Some(fail_under) = fail_under {
+        let missed = status_report_data
+            .goals
+            .iter()
+            .filter(|progress| progress.percent() < fail_under)
+            .count();
 
-    let current_week = filtered_activities
-        .clone()
-        .into_iter()
-        .filter(Filters::current_week(now.date()))
-        .map(|f| f.get_duration())
-        .sum();
+        if missed > 0 {
+            eprintln!(
+                "{missed} of {} goals are below {fail_under}%",
+                status_report_data.goals.len()
+            );
+            return Err(ExitStatus::GoalMissed.into());
+        }
+    }
 
-    let current_month = filtered_activities
-        .into_iter()
-        .filter(Filters::current_month(now.date()))
-        .map(|f| f.get_duration())
-        .sum();
+    if fail_over_budget
+        && status_report_data
+            .budgets
+            .iter()
+            .any(|usage| usage.state() == BudgetState::Over)
+    {
+        return Err(ExitStatus::OverBudget.into());
+    }
 
-    let status_report_data = StatusReportData {
-        activity: current,
-        today,
-        current_week,
-        current_month,
-        project: filter.project,
-    };
-    writer.process(&status_report_data)
+    Ok(())
 }
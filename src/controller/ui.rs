@@ -0,0 +1,162 @@
+use anyhow::{Result, bail};
+use chrono::Local;
+use std::io;
+use std::io::{IsTerminal, Read};
+use std::time::Duration;
+
+use crate::controller::hooks;
+use crate::controller::manipulation;
+use crate::controller::shell;
+use crate::data::activity;
+use crate::data::bartib_file;
+use crate::data::filter::Filters;
+use crate::data::getter;
+use crate::view::line_editor::{Completion, LineEditor};
+use crate::view::terminal;
+use crate::view::ui;
+
+// the screen is redrawn after this time even if no key has been pressed
+const REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+// the number of recent activities that may be continued with the number keys
+const RECENT_ACTIVITIES: usize = 5;
+
+// shows the running activity, the activities of today and a report until the user quits
+//
+// the ui is redrawn after each key press and regularly to keep the durations up to date.
+pub fn run(file_name: &str) -> Result<()> {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        bail!("The ui needs an interactive terminal");
+    }
+
+    bartib_file::keep_in_memory();
+
+    let _alternate_screen = terminal::AlternateScreen::enter()?;
+    let mut line_editor = LineEditor::new();
+    let mut report_range = ui::ReportRange::default();
+    let mut message = String::new();
+
+    loop {
+        terminal::clear_screen()?;
+        show_screen(file_name, report_range, &message)?;
+
+        let Some(key) = read_key()? else {
+            continue;
+        };
+
+        let result = match key {
+            // q, Ctrl-C and Ctrl-D
+            b'q' | 3 | 4 => return Ok(()),
+            b'n' => start_new_activity(file_name, &mut line_editor),
+            b's' => manipulation::stop(file_name, None).map(|()| "Stopped all running activities"),
+            b'x' => manipulation::cancel(file_name).map(|()| "Canceled all running activities"),
+            b'c' => manipulation::continue_last_activity(file_name, None, None, None, 0, None)
+                .map(|()| "Continued the last activity"),
+            b'0'..=b'9' => manipulation::continue_last_activity(
+                file_name,
+                None,
+                None,
+                None,
+                usize::from(key - b'0'),
+                None,
+            )
+            .map(|()| "Continued a recent activity"),
+            b'w' => {
+                report_range = report_range.toggle();
+                Ok("")
+            }
+            _ => Ok(""),
+        };
+        hooks::run_pending();
+
+        message = match result {
+            Ok(text) => text.to_string(),
+            Err(e) => ui::format_error(&e),
+        };
+    }
+}
+
+fn show_screen(file_name: &str, report_range: ui::ReportRange, message: &str) -> Result<()> {
+    let file_content = bartib_file::get_file_content(file_name)?;
+    let now = Local::now().naive_local();
+    let today = now.date();
+
+    let running = getter::get_running_activities(&file_content);
+
+    let mut activities_of_today: Vec<&activity::Activity> = getter::get_activities(&file_content)
+        .filter(Filters::today(today))
+        .collect();
+    activities_of_today.sort_by_key(|activity| activity.start);
+
+    let first_day = report_range.first_day(today);
+    let report: Vec<&activity::Activity> = getter::get_activities(&file_content)
+        .filter(|activity| activity.start.date() >= first_day && activity.start.date() <= today)
+        .collect();
+
+    let descriptions_and_projects = getter::get_descriptions_and_projects(&file_content, None);
+    let first_recent = descriptions_and_projects
+        .len()
+        .saturating_sub(RECENT_ACTIVITIES);
+
+    ui::show_screen(&ui::Screen {
+        file_name,
+        now,
+        running: &running,
+        today: &activities_of_today,
+        report: &report,
+        report_range,
+        recent: &descriptions_and_projects[first_recent..],
+        message,
+    });
+
+    Ok(())
+}
+
+// waits for a key press. Returns `None` if no key has been pressed within the refresh interval
+fn read_key() -> Result<Option<u8>> {
+    let _raw_mode = terminal::RawMode::enable_with_timeout(REFRESH_INTERVAL)?;
+    let mut buffer = [0u8; 1];
+
+    match io::stdin().lock().read(&mut buffer)? {
+        0 => Ok(None),
+        _ => Ok(Some(buffer[0])),
+    }
+}
+
+// asks for a project and a description and starts a new activity
+fn start_new_activity(file_name: &str, line_editor: &mut LineEditor) -> Result<&'static str> {
+    let complete_project = |line: &str| shell::complete_project(file_name, line);
+    let complete_description = |line: &str| shell::complete_description(file_name, line);
+
+    let Some(project) = read_value(line_editor, "Project: ", &complete_project)? else {
+        return Ok("No activity has been started");
+    };
+    let Some(description) = read_value(line_editor, "Description: ", &complete_description)? else {
+        return Ok("No activity has been started");
+    };
+
+    manipulation::start(
+        file_name,
+        &project,
+        &description,
+        manipulation::Labels::default(),
+        None,
+        false,
+        None,
+    )?;
+
+    Ok("Started a new activity")
+}
+
+// reads a value from the user. Returns `None` if the value is empty or the input has been closed
+fn read_value(
+    line_editor: &mut LineEditor,
+    prompt: &str,
+    complete: &dyn Fn(&str) -> Completion,
+) -> Result<Option<String>> {
+    let value = line_editor.read_line(prompt, complete)?;
+
+    Ok(value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty()))
+}
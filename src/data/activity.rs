@@ -1,24 +1,41 @@
-#[cfg(not(feature = "second-precision"))]
-use chrono::DurationRound;
-#[cfg(feature = "second-precision")]
-use chrono::Timelike;
-use chrono::{Duration, Local, NaiveDateTime};
+use chrono::{Duration, DurationRound, FixedOffset, Local, NaiveDateTime, NaiveTime, Timelike};
 use std::fmt;
 use std::str::{Chars, FromStr};
 use thiserror::Error;
 
 use crate::conf;
+use crate::data::timezone;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Activity {
     pub start: NaiveDateTime,
     pub end: Option<NaiveDateTime>,
+    // the offsets from UTC of the local times `start` and `end`. Activities of older logs have no
+    // offsets, their times count as local times of the system's timezone
+    pub start_offset: Option<FixedOffset>,
+    pub end_offset: Option<FixedOffset>,
 
     pub project: String,
     pub description: String,
+    pub tags: Vec<String>,
+    // the key of an issue of a ticket system like JIRA which the activity has been spent on, e.g.
+    // `PROJ-123`
+    pub issue: Option<String>,
+    pub pauses: Vec<Pause>,
+    // free-form notes, which are written as indented lines below the activity in the log
+    pub notes: Vec<String>,
 }
 
-#[derive(Error, Debug)]
+// an interruption of an activity (e.g. a break) which does not count towards its duration
+//
+// the pause of a running activity may be open. It ends when the activity is resumed or stopped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pause {
+    pub start: NaiveDateTime,
+    pub end: Option<NaiveDateTime>,
+}
+
+#[derive(Error, Debug, Clone)]
 pub enum ActivityError {
     #[error("could not parse date or time of activity")]
     DateTimeParseError,
@@ -29,16 +46,107 @@ pub enum ActivityError {
 impl Activity {
     #[must_use]
     pub fn start(project: String, description: String, time: Option<NaiveDateTime>) -> Self {
+        let (start, start_offset) = with_local_offset(time);
+
         Self {
-            start: time.unwrap_or_else(|| Local::now().naive_local()),
+            start,
             end: None,
+            start_offset: Some(start_offset),
+            end_offset: None,
             project,
             description,
+            tags: Vec::new(),
+            issue: None,
+            pauses: Vec::new(),
+            notes: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    #[must_use]
+    pub fn with_issue(mut self, issue: Option<String>) -> Self {
+        self.issue = issue;
+        self
+    }
+
+    // adds a note to the activity. Notes are kept on a single line
+    pub fn add_note(&mut self, note: &str) {
+        let note = note.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        if !note.is_empty() {
+            self.notes.push(note);
         }
     }
 
+    // whether the activity has all of the given tags
+    #[must_use]
+    pub fn has_tags(&self, tags: &[String]) -> bool {
+        tags.iter().all(|tag| self.tags.contains(tag))
+    }
+
+    // stops the activity. An open pause ends at the same time
     pub fn stop(&mut self, time: Option<NaiveDateTime>) {
-        self.end = time.or_else(|| Some(Local::now().naive_local()));
+        let (end, end_offset) = with_local_offset(time);
+        self.end = Some(end);
+        self.end_offset = Some(end_offset);
+
+        if let Some(pause) = self.pauses.last_mut()
+            && pause.end.is_none()
+        {
+            pause.end = Some(end);
+        }
+    }
+
+    // stops the activity at an earlier time, e.g. when another activity has been started. Pauses
+    // after this time are dropped or shortened
+    pub fn truncate(&mut self, end: NaiveDateTime) {
+        self.stop(Some(end));
+        self.pauses.retain(|pause| pause.start < end);
+        for pause in &mut self.pauses {
+            pause.end = pause.end.map(|pause_end| pause_end.min(end));
+        }
+    }
+
+    // moves the start to a local time of the system's timezone
+    pub fn set_start(&mut self, start: NaiveDateTime) {
+        self.start = start;
+        self.start_offset = Some(timezone::local_offset(start));
+    }
+
+    // moves the end to a local time of the system's timezone
+    pub fn set_end(&mut self, end: NaiveDateTime) {
+        self.end = Some(end);
+        self.end_offset = Some(timezone::local_offset(end));
+    }
+
+    // pauses a running activity until it is resumed or stopped
+    pub fn pause(&mut self, time: Option<NaiveDateTime>) {
+        if !self.is_stopped() && !self.is_paused() {
+            self.pauses.push(Pause {
+                start: time.unwrap_or_else(|| Local::now().naive_local()),
+                end: None,
+            });
+        }
+    }
+
+    // ends the open pause of an activity
+    pub fn resume(&mut self, time: Option<NaiveDateTime>) {
+        if let Some(pause) = self.pauses.last_mut()
+            && pause.end.is_none()
+        {
+            pause.end = Some(time.unwrap_or_else(|| Local::now().naive_local()));
+        }
+    }
+
+    // whether the activity is running but paused at the moment
+    #[must_use]
+    pub fn is_paused(&self) -> bool {
+        !self.is_stopped() && self.pauses.last().is_some_and(|pause| pause.end.is_none())
     }
 
     #[must_use]
@@ -46,38 +154,226 @@ impl Activity {
         self.end.is_some()
     }
 
+    // the duration of the activity without its pauses
     #[must_use]
     pub fn get_duration(&self) -> Duration {
-        if let Some(end) = self.end {
-            end.signed_duration_since(self.start)
+        self.get_duration_at(Local::now().naive_local())
+    }
+
+    // the duration of the activity without its pauses. Running activities last until `now`
+    //
+    // the duration is measured between the UTC times of start and end, so that it stays correct
+    // across changes of daylight saving time.
+    #[must_use]
+    pub fn get_duration_at(&self, now: NaiveDateTime) -> Duration {
+        let (end, end_offset) = self.end_at(now);
+        timezone::elapsed(self.start, self.start_offset, end, end_offset)
+            - self.get_pause_duration(end)
+    }
+
+    // the end of the activity with its offset. Running activities last until `now`
+    fn end_at(&self, now: NaiveDateTime) -> (NaiveDateTime, Option<FixedOffset>) {
+        match self.end {
+            Some(end) => (end, self.end_offset),
+            None => (now, None),
+        }
+    }
+
+    // the total duration of all pauses within the activity. Open pauses last until `end`
+    fn get_pause_duration(&self, end: NaiveDateTime) -> Duration {
+        self.pauses
+            .iter()
+            .map(|pause| {
+                let pause_end = pause.end.unwrap_or(end).min(end);
+                pause_end
+                    .signed_duration_since(pause.start.max(self.start))
+                    .max(Duration::zero())
+            })
+            .sum()
+    }
+
+    // whether both activities share some time. Running activities last until `now`
+    #[must_use]
+    pub fn overlaps(&self, other: &Self, now: NaiveDateTime) -> bool {
+        let starts_before_end = |activity: &Self, other: &Self| {
+            let (end, end_offset) = other.end_at(now);
+            timezone::elapsed(activity.start, activity.start_offset, end, end_offset)
+                > Duration::zero()
+        };
+
+        starts_before_end(self, other) && starts_before_end(other, self)
+    }
+
+    // whether a running activity has been started longer than `max_duration` before `now`, which
+    // most likely means that stopping it has been forgotten
+    #[must_use]
+    pub fn is_stale(&self, max_duration: Duration, now: NaiveDateTime) -> bool {
+        !self.is_stopped()
+            && timezone::elapsed(self.start, self.start_offset, now, None) > max_duration
+    }
+
+    // the time a stale activity has most likely been left at: the start of its last pause if it
+    // is paused, otherwise after `max_duration`
+    #[must_use]
+    pub fn likely_end(&self, max_duration: Duration) -> NaiveDateTime {
+        match self.pauses.last() {
+            Some(pause) if self.is_paused() => pause.start,
+            _ => self.start + max_duration,
+        }
+    }
+
+    // whether a stopped activity ends on a later day than it started. Activities ending exactly
+    // at midnight belong to the day they started
+    #[must_use]
+    pub fn spans_multiple_days(&self) -> bool {
+        self.end.is_some_and(|end| {
+            self.start
+                .date()
+                .succ_opt()
+                .is_some_and(|next_day| end > next_day.and_time(NaiveTime::MIN))
+        })
+    }
+
+    // splits a stopped activity at each midnight into one activity per day
+    //
+    // the parts keep project, description and tags. Pauses are cut at the boundaries of the
+    // parts as well. Running activities and activities within a single day are returned as they are.
+    #[must_use]
+    pub fn split_at_midnight(&self) -> Vec<Self> {
+        let Some(end) = self.end.filter(|_| self.spans_multiple_days()) else {
+            return vec![self.clone()];
+        };
+
+        let mut parts = Vec::new();
+        let mut part_start = self.start;
+
+        while part_start < end {
+            let part_end = part_start
+                .date()
+                .succ_opt()
+                .map_or(end, |next_day| next_day.and_time(NaiveTime::MIN).min(end));
+
+            let pauses = self
+                .pauses
+                .iter()
+                .filter_map(|pause| {
+                    let pause_start = pause.start.max(part_start);
+                    let pause_end = pause.end.unwrap_or(end).min(part_end);
+                    (pause_start < pause_end).then_some(Pause {
+                        start: pause_start,
+                        end: Some(pause_end),
+                    })
+                })
+                .collect();
+
+            parts.push(Self {
+                start: part_start,
+                end: Some(part_end),
+                start_offset: self.offset_within(part_start),
+                end_offset: self.offset_within(part_end),
+                pauses,
+                ..self.clone()
+            });
+
+            part_start = part_end;
+        }
+
+        parts
+    }
+
+    // the offset of a time between the start and the end of the activity, which is the offset of
+    // the system's timezone at this time if daylight saving time changes during the activity
+    fn offset_within(&self, time: NaiveDateTime) -> Option<FixedOffset> {
+        if time == self.start || self.start_offset == self.end_offset {
+            self.start_offset
+        } else if Some(time) == self.end {
+            self.end_offset
         } else {
-            Local::now().naive_local().signed_duration_since(self.start)
+            Some(timezone::local_offset(time))
         }
     }
 }
 
+// the given local time or the current time, with its offset from UTC
+fn with_local_offset(time: Option<NaiveDateTime>) -> (NaiveDateTime, FixedOffset) {
+    match time {
+        Some(time) => (time, timezone::local_offset(time)),
+        None => timezone::now(),
+    }
+}
+
 impl fmt::Display for Activity {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let escaped_project_name = escape_special_chars(self.project.as_str());
         let escaped_description = escape_special_chars(&self.description);
 
         match self.end {
-            None => writeln!(
+            None => write!(
                 f,
                 "{} | {} | {}",
-                self.start.format(conf::FORMAT_DATETIME),
+                format_timestamp(self.start, self.start_offset),
                 escaped_project_name,
                 escaped_description
-            ),
-            Some(end) => writeln!(
+            )?,
+            Some(end) => write!(
                 f,
                 "{} - {} | {} | {}",
-                self.start.format(conf::FORMAT_DATETIME),
-                end.format(conf::FORMAT_DATETIME),
+                format_timestamp(self.start, self.start_offset),
+                format_timestamp(end, self.end_offset),
                 escaped_project_name,
                 escaped_description
-            ),
+            )?,
         }
+
+        if !self.tags.is_empty() {
+            let escaped_tags: Vec<String> = self
+                .tags
+                .iter()
+                .map(|tag| escape_special_chars(tag))
+                .collect();
+            let tags = escaped_tags.join(", ");
+
+            // tags starting like a pause or an issue are written after an empty tag, so they are
+            // read as tags
+            if starts_with_keyword(&tags) {
+                write!(f, " | , {tags}")?;
+            } else {
+                write!(f, " | {tags}")?;
+            }
+        }
+
+        if let Some(issue) = &self.issue {
+            write!(f, " | {ISSUE_KEYWORD} {}", escape_special_chars(issue))?;
+        }
+
+        for pause in &self.pauses {
+            write!(
+                f,
+                " | {PAUSE_KEYWORD} {}",
+                pause.start.format(conf::format_datetime())
+            )?;
+
+            if let Some(end) = pause.end {
+                write!(f, " - {}", end.format(conf::format_datetime()))?;
+            }
+        }
+
+        for note in &self.notes {
+            write!(f, "\n{NOTE_INDENTATION}{note}")?;
+        }
+
+        writeln!(f)
+    }
+}
+
+// a timestamp of the activity log, followed by its offset from UTC if known, e.g.
+// `2024-03-31 01:30 +01:00`
+fn format_timestamp(time: NaiveDateTime, offset: Option<FixedOffset>) -> String {
+    let time = time.format(conf::format_datetime());
+
+    match offset {
+        Some(offset) => format!("{time} {offset}"),
+        None => time.to_string(),
     }
 }
 
@@ -98,81 +394,182 @@ impl FromStr for Activity {
 
         let time_parts: Vec<&str> = parts[0].split(" - ").collect();
 
-        let starttime = parse_timepart(time_parts[0])?;
-        let endtime: Option<NaiveDateTime> = if time_parts.len() > 1 {
-            Some(parse_timepart(time_parts[1])?)
+        let (starttime, start_offset) = parse_timepart(time_parts[0])?;
+        let (endtime, end_offset) = if time_parts.len() > 1 {
+            let (endtime, end_offset) = parse_timepart(time_parts[1])?;
+            (Some(endtime), end_offset)
         } else {
-            None
+            (None, None)
         };
 
         let project = parts[1].trim();
         let description = if parts.len() > 2 { parts[2].trim() } else { "" };
+        let mut tags = Vec::new();
+        let mut issue = None;
+        let mut pauses = Vec::new();
+
+        // tags, the issue and pauses follow the description
+        for part in parts.iter().skip(3) {
+            if let Some(pause) = strip_keyword(part, PAUSE_KEYWORD) {
+                pauses.push(parse_pause(pause)?);
+            } else if let Some(key) = strip_keyword(part, ISSUE_KEYWORD) {
+                issue = Some(key.trim().to_string());
+            } else {
+                tags.extend(parse_tags(part));
+            }
+        }
 
         let activity = Self {
             start: starttime,
             end: endtime,
+            start_offset,
+            end_offset,
             project: project.to_string(),
             description: description.to_string(),
+            tags,
+            issue,
+            pauses,
+            notes: Vec::new(),
         };
 
         Ok(activity)
     }
 }
 
-#[cfg(not(feature = "second-precision"))]
-fn parse_timepart(time_part: &str) -> Result<NaiveDateTime, ActivityError> {
-    match NaiveDateTime::parse_from_str(time_part.trim(), conf::FORMAT_DATETIME) {
-        Ok(datetime) => Ok(datetime),
-        Err(_) => {
-            // Presume that the timestamp has second-precision and that is the cause of the error
-            match NaiveDateTime::parse_from_str(
-                time_part.trim(),
-                conf::FORMAT_SECOND_PRECISION_DATETIME,
-            ) {
-                Ok(datetime) => {
-                    // Successfully parsed timestamp. Notify the user about the mismatch
-                    // and round to the nearest minute
-                    eprintln!("WARNING: Bartib log encountered timestamps with minute precision.");
-                    eprintln!("This version of Bartib has been compiled with second precision");
-
-                    let datetime = datetime
-                        .duration_round(Duration::minutes(1))
-                        .map_err(|_| ActivityError::DateTimeParseError)?;
-
-                    Ok(datetime)
-                }
-                Err(_) => Err(ActivityError::DateTimeParseError),
-            }
-        }
+// the indentation of the lines with the notes of an activity in the activity log
+static NOTE_INDENTATION: &str = "    ";
+
+// whether a line of the activity log is a note of the activity above it
+//
+// notes are indented. Indented lines which are activities themselves are no notes, as the
+// activities of older logs may have been indented, too.
+#[must_use]
+pub fn is_note_line(line: &str) -> bool {
+    line.starts_with([' ', '\t']) && !line.trim().is_empty() && Activity::from_str(line).is_err()
+}
+
+// the separator of the levels of hierarchical projects like `client/backend/api`
+pub static PROJECT_SEPARATOR: char = '/';
+
+// a project and its parent projects, from the project itself up to the top level, e.g.
+// `client/backend/api`, `client/backend` and `client`
+pub fn project_hierarchy(project: &str) -> impl Iterator<Item = &str> {
+    std::iter::once(project).chain(
+        project
+            .rmatch_indices(PROJECT_SEPARATOR)
+            .map(|(index, _)| &project[..index]),
+    )
+}
+
+// the parent project at a level of the hierarchy, e.g. `client` at depth 1 for
+// `client/backend/api`. Projects with less levels are returned unchanged
+#[must_use]
+pub fn project_at_depth(project: &str, depth: usize) -> &str {
+    project
+        .match_indices(PROJECT_SEPARATOR)
+        .nth(depth.saturating_sub(1))
+        .map_or(project, |(index, _)| &project[..index])
+}
+
+// the keyword which marks pauses in the activity log, e.g. `| pause 2024-03-01 12:00 - 2024-03-01 12:30`
+static PAUSE_KEYWORD: &str = "pause";
+
+// the keyword which marks the issue of an activity in the activity log, e.g. `| issue PROJ-123`
+static ISSUE_KEYWORD: &str = "issue";
+
+// the rest of a part of an activity which starts with a keyword followed by a space
+fn strip_keyword<'a>(part: &'a str, keyword: &str) -> Option<&'a str> {
+    part.trim()
+        .strip_prefix(keyword)
+        .filter(|rest| rest.starts_with(' '))
+}
+
+// whether a part of an activity would be read as a pause or an issue
+fn starts_with_keyword(part: &str) -> bool {
+    [PAUSE_KEYWORD, ISSUE_KEYWORD]
+        .iter()
+        .any(|keyword| strip_keyword(part, keyword).is_some())
+}
+
+// parses the start and optionally the end of a pause
+fn parse_pause(pause: &str) -> Result<Pause, ActivityError> {
+    let time_parts: Vec<&str> = pause.split(" - ").collect();
+
+    Ok(Pause {
+        start: parse_timepart(time_parts[0])?.0,
+        end: time_parts
+            .get(1)
+            .map(|end| parse_timepart(end).map(|(end, _)| end))
+            .transpose()?,
+    })
+}
+
+// parses a comma separated list of tags
+fn parse_tags(tags: &str) -> Vec<String> {
+    tags.split(',')
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
+
+// checks a tag given by the user, which must not be empty or contain commas, as commas separate
+// the tags in the activity log
+pub fn parse_tag(tag: &str) -> Result<String, String> {
+    let tag = tag.trim();
+
+    if tag.is_empty() || tag.contains(',') {
+        return Err("tags must not be empty or contain commas".to_string());
     }
+
+    Ok(tag.to_string())
 }
 
-#[cfg(feature = "second-precision")]
-fn parse_timepart(time_part: &str) -> Result<NaiveDateTime, ActivityError> {
-    match NaiveDateTime::parse_from_str(time_part.trim(), conf::FORMAT_DATETIME) {
-        Ok(datetime) => Ok(datetime),
-        Err(_) => {
-            // Presume that the timestamp has minute-precision and that is the cause of the error
-            match NaiveDateTime::parse_from_str(
-                time_part.trim(),
-                conf::FORMAT_MINUTE_PRECISION_DATETIME,
-            ) {
-                Ok(datetime) => {
-                    // Successfully parsed timestamp. Notify the user about the mismatch
-                    // and set seconds to zero
-                    eprintln!("WARNING: Bartib log encountered timestamps with minute precision.");
-                    eprintln!("This version of Bartib has been compiled with second precision");
-
-                    let datetime = datetime
-                        .with_second(0)
-                        .ok_or(ActivityError::DateTimeParseError)?;
-
-                    Ok(datetime)
-                }
-                Err(_) => Err(ActivityError::DateTimeParseError),
-            }
-        }
+// checks the key of an issue given by the user, which must be a single word like `PROJ-123`
+pub fn parse_issue(issue: &str) -> Result<String, String> {
+    let issue = issue.trim();
+
+    if issue.is_empty() || issue.contains(char::is_whitespace) {
+        return Err("issue keys must not be empty or contain spaces".to_string());
     }
+
+    Ok(issue.to_string())
+}
+
+// parses a timestamp with either minute or second precision and an optional offset from UTC
+//
+// timestamps with seconds are rounded to the nearest minute if the configured precision is minutes.
+fn parse_timepart(time_part: &str) -> Result<(NaiveDateTime, Option<FixedOffset>), ActivityError> {
+    let time_part = time_part.trim();
+    let (time_part, offset) = match time_part.rsplit_once(' ') {
+        Some((time_part, offset)) if offset.starts_with(['+', '-']) => (
+            time_part.trim_end(),
+            Some(
+                offset
+                    .parse::<FixedOffset>()
+                    .map_err(|_| ActivityError::DateTimeParseError)?,
+            ),
+        ),
+        _ => (time_part, None),
+    };
+    let datetime = NaiveDateTime::parse_from_str(time_part, conf::FORMAT_SECOND_PRECISION_DATETIME)
+        .or_else(|_| {
+            NaiveDateTime::parse_from_str(time_part, conf::FORMAT_MINUTE_PRECISION_DATETIME)
+        })
+        .map_err(|_| ActivityError::DateTimeParseError)?;
+
+    if conf::precision() == conf::Precision::Minutes && datetime.second() != 0 {
+        // Notify the user about the mismatch and round to the nearest minute
+        eprintln!("WARNING: Bartib log encountered timestamps with second precision.");
+        eprintln!("Set `precision = \"seconds\"` in the configuration file to keep the seconds");
+
+        return datetime
+            .duration_round(Duration::minutes(1))
+            .map(|datetime| (datetime, offset))
+            .map_err(|_| ActivityError::DateTimeParseError);
+    }
+
+    Ok((datetime, offset))
 }
 
 /**
@@ -216,10 +613,30 @@ impl Iterator for StringSplitter<'_> {
     }
 }
 
-fn split_with_escaped_delimiter(s: &str) -> StringSplitter {
+fn split_with_escaped_delimiter(s: &str) -> StringSplitter<'_> {
     StringSplitter { chars: s.chars() }
 }
 
+// a stopped activity of a project for the tests of other modules, with times like
+// `2024-03-01 09:00`
+#[cfg(test)]
+pub(crate) fn activity(project: &str, start: &str, end: &str) -> Activity {
+    let datetime = |s| NaiveDateTime::parse_from_str(s, "%F %R").unwrap();
+
+    Activity {
+        start: datetime(start),
+        end: Some(datetime(end)),
+        start_offset: None,
+        end_offset: None,
+        project: project.to_string(),
+        description: "d".to_string(),
+        tags: Vec::new(),
+        issue: None,
+        pauses: Vec::new(),
+        notes: Vec::new(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -249,6 +666,89 @@ mod tests {
         assert_ne!(t.end, None);
     }
 
+    #[test]
+    fn overlaps() {
+        let now = NaiveDateTime::parse_from_str("2024-03-11 12:00", "%F %R").unwrap();
+        let activity = |start: u32, end: Option<u32>| Activity {
+            start: now.date().and_hms_opt(start, 0, 0).unwrap(),
+            end: end.map(|end| now.date().and_hms_opt(end, 0, 0).unwrap()),
+            start_offset: None,
+            end_offset: None,
+            project: "p".to_string(),
+            description: "d".to_string(),
+            tags: Vec::new(),
+            issue: None,
+            pauses: Vec::new(),
+            notes: Vec::new(),
+        };
+
+        assert!(activity(9, Some(11)).overlaps(&activity(10, Some(12)), now));
+        assert!(!activity(9, Some(10)).overlaps(&activity(10, Some(12)), now));
+        assert!(activity(9, None).overlaps(&activity(11, Some(13)), now));
+        assert!(!activity(9, None).overlaps(&activity(13, Some(14)), now));
+    }
+
+    #[test]
+    fn stale_activities() {
+        let time = |s: &str| NaiveDateTime::parse_from_str(s, "%F %R").unwrap();
+        let mut activity = Activity::start(
+            "p".to_string(),
+            "d".to_string(),
+            Some(time("2024-03-11 09:00")),
+        );
+        let max_duration = Duration::hours(12);
+
+        assert!(!activity.is_stale(max_duration, time("2024-03-11 21:00")));
+        assert!(activity.is_stale(max_duration, time("2024-03-12 08:00")));
+        assert_eq!(activity.likely_end(max_duration), time("2024-03-11 21:00"));
+
+        activity.pause(Some(time("2024-03-11 17:30")));
+        assert_eq!(activity.likely_end(max_duration), time("2024-03-11 17:30"));
+
+        activity.stop(Some(time("2024-03-11 18:00")));
+        assert!(!activity.is_stale(max_duration, time("2024-03-12 08:00")));
+    }
+
+    #[test]
+    fn split_at_midnight() {
+        let time = |s: &str| NaiveDateTime::parse_from_str(s, "%F %R").unwrap();
+        let activity = Activity {
+            start: time("2024-03-11 22:00"),
+            end: Some(time("2024-03-13 02:00")),
+            start_offset: None,
+            end_offset: None,
+            project: "p".to_string(),
+            description: "d".to_string(),
+            tags: vec!["t".to_string()],
+            issue: None,
+            pauses: vec![Pause {
+                start: time("2024-03-11 23:00"),
+                end: Some(time("2024-03-12 01:00")),
+            }],
+            notes: Vec::new(),
+        };
+
+        assert!(activity.spans_multiple_days());
+        let parts = activity.split_at_midnight();
+
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[0].start, time("2024-03-11 22:00"));
+        assert_eq!(parts[0].end, Some(time("2024-03-12 00:00")));
+        assert_eq!(parts[0].pauses[0].end, Some(time("2024-03-12 00:00")));
+        assert_eq!(parts[1].start, time("2024-03-12 00:00"));
+        assert_eq!(parts[1].pauses[0].start, time("2024-03-12 00:00"));
+        assert_eq!(parts[2].end, Some(time("2024-03-13 02:00")));
+        assert!(parts[2].pauses.is_empty());
+        assert_eq!(parts[2].tags, vec!["t"]);
+
+        let until_midnight = Activity {
+            end: Some(time("2024-03-12 00:00")),
+            ..activity
+        };
+        assert!(!until_midnight.spans_multiple_days());
+        assert_eq!(until_midnight.split_at_midnight(), vec![until_midnight]);
+    }
+
     #[test]
     #[cfg(not(feature = "second-precision"))]
     fn display() {
@@ -257,17 +757,26 @@ mod tests {
             "test\\description".to_string(),
             None,
         );
-        t.start = NaiveDateTime::parse_from_str("2021-02-16 16:14", conf::FORMAT_DATETIME).unwrap();
+        t.start =
+            NaiveDateTime::parse_from_str("2021-02-16 16:14", conf::format_datetime()).unwrap();
+        t.start_offset = None;
         assert_eq!(
             format!("{t}"),
             "2021-02-16 16:14 | test project\\| 1 | test\\\\description\n"
         );
-        t.end =
-            Some(NaiveDateTime::parse_from_str("2021-02-16 18:23", conf::FORMAT_DATETIME).unwrap());
+        t.end = Some(
+            NaiveDateTime::parse_from_str("2021-02-16 18:23", conf::format_datetime()).unwrap(),
+        );
         assert_eq!(
             format!("{t}"),
             "2021-02-16 16:14 - 2021-02-16 18:23 | test project\\| 1 | test\\\\description\n"
         );
+
+        t.end_offset = FixedOffset::east_opt(3600);
+        assert_eq!(
+            format!("{t}"),
+            "2021-02-16 16:14 - 2021-02-16 18:23 +01:00 | test project\\| 1 | test\\\\description\n"
+        );
     }
 
     #[test]
@@ -279,18 +788,25 @@ mod tests {
             None,
         );
         t.start =
-            NaiveDateTime::parse_from_str("2021-02-16 16:14:53", conf::FORMAT_DATETIME).unwrap();
+            NaiveDateTime::parse_from_str("2021-02-16 16:14:53", conf::format_datetime()).unwrap();
+        t.start_offset = None;
         assert_eq!(
             format!("{t}"),
             "2021-02-16 16:14:53 | test project\\| 1 | test\\\\description\n"
         );
         t.end = Some(
-            NaiveDateTime::parse_from_str("2021-02-16 18:23:17", conf::FORMAT_DATETIME).unwrap(),
+            NaiveDateTime::parse_from_str("2021-02-16 18:23:17", conf::format_datetime()).unwrap(),
         );
         assert_eq!(
             format!("{t}"),
             "2021-02-16 16:14:53 - 2021-02-16 18:23:17 | test project\\| 1 | test\\\\description\n"
         );
+
+        t.end_offset = FixedOffset::east_opt(3600);
+        assert_eq!(
+            format!("{t}"),
+            "2021-02-16 16:14:53 - 2021-02-16 18:23:17 +01:00 | test project\\| 1 | test\\\\description\n"
+        );
     }
 
     #[test]
@@ -476,6 +992,132 @@ mod tests {
         assert_eq!(t.description, t2.description);
     }
 
+    #[test]
+    fn configured_precision() {
+        let line = "2021-02-16 16:14:53 - 2021-02-16 18:23 | test project | test description\n";
+
+        conf::set_precision(conf::Precision::Seconds);
+        let t = Activity::from_str(line).unwrap();
+        assert_eq!(t.start.time().second(), 53);
+        assert_eq!(t.end.unwrap().time().second(), 0);
+        assert_eq!(
+            format!("{t}"),
+            "2021-02-16 16:14:53 - 2021-02-16 18:23:00 | test project | test description\n"
+        );
+
+        conf::set_precision(conf::Precision::Minutes);
+        let t = Activity::from_str(line).unwrap();
+        assert_eq!(
+            format!("{t}"),
+            "2021-02-16 16:15 - 2021-02-16 18:23 | test project | test description\n"
+        );
+    }
+
+    #[test]
+    fn tags() {
+        let t = Activity::from_str(
+            "2021-02-16 16:14 - 2021-02-16 18:23 | test project | test description | billable,  re\\|mote,",
+        )
+        .unwrap();
+
+        assert_eq!(t.tags, vec!["billable", "re|mote"]);
+        assert!(t.has_tags(&["re|mote".to_string()]));
+        assert!(!t.has_tags(&["billable".to_string(), "internal".to_string()]));
+
+        let t2 = Activity::from_str(&t.to_string()).unwrap();
+        assert_eq!(t, t2);
+        assert!(t.to_string().ends_with(" | billable, re\\|mote\n"));
+    }
+
+    #[test]
+    fn pauses() {
+        conf::set_precision(conf::Precision::Minutes);
+        let datetime =
+            |time: &str| NaiveDateTime::parse_from_str(&format!("2024-03-01 {time}"), "%F %R").ok();
+        let mut t = Activity::from_str("2024-03-01 09:00 | p | d | billable").unwrap();
+
+        t.pause(datetime("10:00"));
+        assert!(t.is_paused());
+        t.pause(datetime("10:05"));
+        assert_eq!(t.pauses.len(), 1);
+        t.resume(datetime("10:30"));
+        assert!(!t.is_paused());
+        t.pause(datetime("11:00"));
+        t.stop(datetime("12:00"));
+
+        assert_eq!(t.pauses[1].end, datetime("12:00"));
+        assert_eq!(t.get_duration(), Duration::minutes(90));
+
+        let t2 = Activity::from_str(&t.to_string()).unwrap();
+        assert_eq!(t, t2);
+
+        let t = Activity::from_str(
+            "2024-03-01 09:00 - 2024-03-01 10:00 | p | d | pause 2024-03-01 09:15 - 2024-03-01 09:30",
+        )
+        .unwrap();
+        assert!(t.tags.is_empty());
+        assert_eq!(t.get_duration(), Duration::minutes(45));
+        assert!(
+            t.to_string()
+                .ends_with(" | d | pause 2024-03-01 09:15 - 2024-03-01 09:30\n")
+        );
+
+        assert!(Activity::from_str("2024-03-01 09:00 | p | d | pause 09:15").is_err());
+    }
+
+    #[test]
+    fn tags_starting_like_a_pause() {
+        let t = Activity::from_str("2024-03-01 09:00 | p | d")
+            .unwrap()
+            .with_tags(vec!["pause later".to_string(), "b".to_string()]);
+
+        assert!(t.to_string().ends_with(" | d | , pause later, b\n"));
+        assert_eq!(Activity::from_str(&t.to_string()).unwrap(), t);
+    }
+
+    #[test]
+    fn from_str_with_issue() {
+        let t = Activity::from_str(
+            "2024-03-01 09:00 - 2024-03-01 10:00 | p | d | a, b | issue PROJ-123 | pause 2024-03-01 09:15 - 2024-03-01 09:30",
+        )
+        .unwrap();
+
+        assert_eq!(t.issue.as_deref(), Some("PROJ-123"));
+        assert_eq!(t.tags, vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(t.pauses.len(), 1);
+        assert_eq!(Activity::from_str(&t.to_string()).unwrap(), t);
+
+        // a tag may still be called `issue`
+        let t = Activity::from_str("2024-03-01 09:00 | p | d | issue").unwrap();
+        assert_eq!(t.issue, None);
+        assert_eq!(t.tags, vec!["issue".to_string()]);
+    }
+
+    #[test]
+    fn tags_starting_like_an_issue() {
+        let t = Activity::from_str("2024-03-01 09:00 | p | d")
+            .unwrap()
+            .with_tags(vec!["issue tracking".to_string()])
+            .with_issue(Some("PROJ-1".to_string()));
+
+        assert!(
+            t.to_string()
+                .ends_with(" | d | , issue tracking | issue PROJ-1\n")
+        );
+        assert_eq!(Activity::from_str(&t.to_string()).unwrap(), t);
+    }
+
+    #[test]
+    fn parse_tags_and_issues_of_users() {
+        assert_eq!(parse_tag(" billable "), Ok("billable".to_string()));
+        assert!(parse_tag("").is_err());
+        assert!(parse_tag("a, b").is_err());
+
+        assert_eq!(parse_issue(" PROJ-1"), Ok("PROJ-1".to_string()));
+        assert!(parse_issue(" ").is_err());
+        assert!(parse_issue("PROJ-1 | pause").is_err());
+    }
+
     #[test]
     fn from_str_errors() {
         let t = Activity::from_str("2021 test project");
@@ -542,4 +1184,57 @@ mod tests {
         assert_eq!(end.time().minute(), 23);
         assert_eq!(end.time().second(), 0);
     }
+
+    #[test]
+    #[cfg(not(feature = "second-precision"))]
+    fn notes() {
+        let mut t = Activity::from_str("2021-02-16 16:14 | p | d").unwrap();
+        t.add_note("  fixed   the flaky test ");
+        t.add_note(" ");
+
+        assert_eq!(t.notes, vec!["fixed the flaky test".to_string()]);
+        assert_eq!(
+            format!("{t}"),
+            "2021-02-16 16:14 | p | d\n    fixed the flaky test\n"
+        );
+
+        assert!(is_note_line("    fixed the flaky test"));
+        assert!(is_note_line("\tsee ticket 42"));
+        assert!(!is_note_line("fixed the flaky test"));
+        assert!(!is_note_line("    "));
+        assert!(!is_note_line("  2021-02-16 16:14 | p | d"));
+    }
+
+    #[test]
+    fn from_str_with_offsets() {
+        let t = Activity::from_str("2024-03-31 01:30 +01:00 - 2024-03-31 03:30 +02:00 | p | d")
+            .unwrap();
+
+        assert_eq!(t.start_offset, FixedOffset::east_opt(3600));
+        assert_eq!(t.end_offset, FixedOffset::east_opt(7200));
+        assert_eq!(t.get_duration(), Duration::hours(1));
+        assert_eq!(
+            Activity::from_str(&t.to_string()).unwrap().end_offset,
+            t.end_offset
+        );
+
+        assert!(Activity::from_str("2024-03-31 01:30 +1:00 | p | d").is_err());
+    }
+
+    #[test]
+    fn hierarchical_projects() {
+        assert_eq!(
+            project_hierarchy("client/backend/api").collect::<Vec<_>>(),
+            vec!["client/backend/api", "client/backend", "client"]
+        );
+        assert_eq!(project_hierarchy("p").collect::<Vec<_>>(), vec!["p"]);
+
+        assert_eq!(project_at_depth("client/backend/api", 1), "client");
+        assert_eq!(project_at_depth("client/backend/api", 2), "client/backend");
+        assert_eq!(
+            project_at_depth("client/backend/api", 5),
+            "client/backend/api"
+        );
+        assert_eq!(project_at_depth("p", 1), "p");
+    }
 }
@@ -0,0 +1,78 @@
+use thiserror::Error;
+
+use crate::data::config::{Config, ConfigError};
+
+// the name of the table defining aliases of projects in the configuration file
+pub static CONFIG_TABLE: &str = "aliases";
+
+// short names for projects which may be used as `@name` wherever a project is given
+//
+// in the configuration file the aliases are defined like this:
+//
+//     [aliases]
+//     cb = "Client/Backend"
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Aliases {
+    aliases: Vec<(String, String)>,
+}
+
+#[derive(Error, Debug, PartialEq)]
+#[error(
+    "unknown project alias `@{0}`, aliases are defined in the [aliases] table of the configuration file"
+)]
+pub struct UnknownAlias(String);
+
+impl Aliases {
+    pub fn from_config(config: &Config) -> Result<Self, ConfigError> {
+        let mut aliases = Vec::new();
+
+        for table in config.tables(CONFIG_TABLE) {
+            for (alias, value) in &table.entries {
+                let project = value.as_str().ok_or_else(|| {
+                    ConfigError::InvalidSetting(
+                        table.qualified_key(alias),
+                        "expected the name of a project".to_string(),
+                    )
+                })?;
+                aliases.push((alias.clone(), project.to_string()));
+            }
+        }
+
+        Ok(Self { aliases })
+    }
+
+    // the project an alias like `@cb` stands for. Other names are returned unchanged
+    pub fn resolve<'a>(&'a self, project: &'a str) -> Result<&'a str, UnknownAlias> {
+        let Some(alias) = project.strip_prefix('@') else {
+            return Ok(project);
+        };
+
+        self.aliases
+            .iter()
+            .rev()
+            .find(|(name, _)| name == alias)
+            .map(|(_, project)| project.as_str())
+            .ok_or_else(|| UnknownAlias(alias.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_aliases() {
+        let config: Config = "[aliases]\ncb = \"Client/Backend\"".parse().unwrap();
+        let aliases = Aliases::from_config(&config).unwrap();
+
+        assert_eq!(aliases.resolve("@cb"), Ok("Client/Backend"));
+        assert_eq!(aliases.resolve("cb"), Ok("cb"));
+        assert_eq!(
+            aliases.resolve("@other"),
+            Err(UnknownAlias("other".to_string()))
+        );
+
+        let invalid: Config = "[aliases]\ncb = 1".parse().unwrap();
+        assert!(Aliases::from_config(&invalid).is_err());
+    }
+}
@@ -0,0 +1,161 @@
+use anyhow::Result;
+use chrono::{Datelike, NaiveDate};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::data::bartib_file;
+use crate::data::index;
+
+// the name of the file to which the activities of a year are archived, e.g. `activities.bartib.2023`
+#[must_use]
+pub fn get_archive_file_name(file_name: &str, year: i32) -> String {
+    format!("{file_name}.{year}")
+}
+
+// the years for which an archive of the given file exists, in ascending order
+#[must_use]
+pub fn get_archived_years(file_name: &str) -> Vec<i32> {
+    let path = Path::new(file_name);
+    let Some(base_name) = path.file_name().and_then(|name| name.to_str()) else {
+        return Vec::new();
+    };
+    let directory = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+
+    let Ok(entries) = fs::read_dir(directory) else {
+        return Vec::new();
+    };
+
+    let mut years: Vec<i32> = entries
+        .map_while(Result::ok)
+        .filter_map(|entry| parse_archive_year(base_name, entry.file_name().to_str()?))
+        .collect();
+
+    years.sort_unstable();
+    years
+}
+
+// reads the content of a file together with its archives of all years within the given range
+//
+// the lines of the archives come first. A missing bound includes all archived years before or
+// after the other bound.
+pub fn get_file_content_with_archives(
+    file_name: &str,
+    from_date: Option<NaiveDate>,
+    to_date: Option<NaiveDate>,
+) -> Result<Vec<bartib_file::Line>> {
+    let mut file_content = Vec::new();
+
+    let years = get_archived_years(file_name).into_iter().filter(|year| {
+        from_date.is_none_or(|date| date.year() <= *year)
+            && to_date.is_none_or(|date| date.year() >= *year)
+    });
+
+    for year in years {
+        file_content.extend(bartib_file::get_file_content(&get_archive_file_name(
+            file_name, year,
+        ))?);
+    }
+
+    file_content.extend(index::get_file_content_of_dates(
+        file_name, from_date, to_date,
+    )?);
+
+    Ok(file_content)
+}
+
+// removes all stopped activities started before the given date from the file content
+//
+// the removed lines are returned grouped by the year in which the activities started. Running
+// activities and lines that could not be parsed stay in the file.
+pub fn split_off_archived(
+    file_content: &mut Vec<bartib_file::Line>,
+    before: NaiveDate,
+) -> BTreeMap<i32, Vec<bartib_file::Line>> {
+    let mut archived: BTreeMap<i32, Vec<bartib_file::Line>> = BTreeMap::new();
+    let mut remaining = Vec::with_capacity(file_content.len());
+
+    for line in file_content.drain(..) {
+        let year = match &line.activity {
+            Ok(activity) if activity.is_stopped() && activity.start.date() < before => {
+                Some(activity.start.year())
+            }
+            _ => None,
+        };
+
+        match year {
+            Some(year) => archived.entry(year).or_default().push(line),
+            None => remaining.push(line),
+        }
+    }
+
+    *file_content = remaining;
+    archived
+}
+
+// the year of an archive file name like `activities.bartib.2023`
+fn parse_archive_year(base_name: &str, file_name: &str) -> Option<i32> {
+    let year = file_name.strip_prefix(base_name)?.strip_prefix('.')?;
+
+    if year.len() == 4 && year.chars().all(|c| c.is_ascii_digit()) {
+        year.parse().ok()
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_archive_year_test() {
+        assert_eq!(
+            parse_archive_year("activities.bartib", "activities.bartib.2023"),
+            Some(2023)
+        );
+        assert_eq!(
+            parse_archive_year("activities.bartib", "activities.bartib"),
+            None
+        );
+        assert_eq!(
+            parse_archive_year("activities.bartib", "activities.bartib.bak"),
+            None
+        );
+        assert_eq!(
+            parse_archive_year("activities.bartib", "activities.bartib.20234"),
+            None
+        );
+        assert_eq!(parse_archive_year("activities.bartib", "other.2023"), None);
+    }
+
+    #[test]
+    fn split_off_archived_test() {
+        let mut file_content = vec![
+            bartib_file::Line::new("2022-12-31 10:00 - 2022-12-31 11:00 | p1 | d1", 1),
+            bartib_file::Line::new("2023-06-01 10:00 - 2023-06-01 11:00 | p2 | d2", 2),
+            bartib_file::Line::new("no activity", 3),
+            bartib_file::Line::new("2023-06-02 10:00 | p3 | d3", 4),
+            bartib_file::Line::new("2024-01-01 10:00 - 2024-01-01 11:00 | p4 | d4", 5),
+        ];
+
+        let archived = split_off_archived(
+            &mut file_content,
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+        );
+
+        assert_eq!(
+            archived.keys().copied().collect::<Vec<_>>(),
+            vec![2022, 2023]
+        );
+        assert_eq!(archived[&2022][0].line_number, Some(1));
+        assert_eq!(archived[&2023][0].line_number, Some(2));
+
+        let remaining: Vec<Option<usize>> =
+            file_content.iter().map(|line| line.line_number).collect();
+        assert_eq!(remaining, vec![Some(3), Some(4), Some(5)]);
+    }
+}
@@ -1,19 +1,26 @@
 use anyhow::{Context, Result};
+use std::cell::RefCell;
+use std::fs;
 use std::fs::{File, OpenOptions};
 use std::io;
-use std::io::{BufRead, BufReader, Write};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::SystemTime;
 
 use crate::data::activity;
+use crate::data::encryption;
+use crate::data::index;
+use crate::data::lock::FileLock;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum LineStatus {
     Unchanged,
     Changed,
 }
 
 // a line in a bartib file
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Line {
     // the plaintext of the line as it has been read from the file
     // we save this to be able write untouched lines back to file without changing them
@@ -55,42 +62,352 @@ impl Line {
     }
 }
 
+// creates the lines of a file from its lines of text and their line numbers
+//
+// the notes of an activity are written on indented lines below it. These lines are added to the
+// line of the activity instead of becoming lines of their own.
+pub fn parse_lines<S: AsRef<str>>(
+    text_lines: impl IntoIterator<Item = (Option<usize>, S)>,
+) -> Vec<Line> {
+    let mut lines: Vec<Line> = Vec::new();
+
+    for (line_number, text) in text_lines {
+        let text = text.as_ref();
+
+        if let Some(Line {
+            plaintext: Some(plaintext),
+            activity: Ok(activity),
+            ..
+        }) = lines.last_mut()
+            && activity::is_note_line(text)
+        {
+            plaintext.push('\n');
+            plaintext.push_str(text.trim_end());
+            activity.add_note(text);
+            continue;
+        }
+
+        lines.push(Line {
+            line_number,
+            ..Line::new(text, line_number.unwrap_or_default())
+        });
+    }
+
+    lines
+}
+
+// the number of bytes read from the end of a file at first to find the most recent activities
+const TAIL_CHUNK_SIZE: u64 = 16 * 1024;
+
+// the size and modification time of a file, used to find out whether it has been changed
+type FileState = (u64, SystemTime);
+
+// the parsed content of a file together with the state of the file when it was read
+struct CachedContent {
+    file_name: String,
+    file_state: FileState,
+    lines: Vec<Line>,
+}
+
+#[derive(Default)]
+struct Cache {
+    enabled: bool,
+    content: Option<CachedContent>,
+}
+
+thread_local! {
+    static CACHE: RefCell<Cache> = RefCell::new(Cache::default());
+}
+
+// keeps the parsed file content in memory between reads as long as the file does not change
+//
+// this is meant for long running processes like the interactive shell that would otherwise
+// parse the whole file again for each command.
+pub fn keep_in_memory() {
+    CACHE.with(|cache| cache.borrow_mut().enabled = true);
+}
+
+// reads the content of a file like `get_file_content`, but a file that does not exist yet is empty
+//
+// any other error is returned, so that a log which could not be read or decrypted is not
+// overwritten by the activities written to it. As errors of gpg or of the passphrase file may be
+// `NotFound` as well, the file itself has to be missing.
+pub fn get_file_content_or_empty(file_name: &str) -> Result<Vec<Line>> {
+    match get_file_content(file_name) {
+        Err(e)
+            if e.downcast_ref::<io::Error>()
+                .is_some_and(|e| e.kind() == io::ErrorKind::NotFound)
+                && !Path::new(file_name).exists() =>
+        {
+            Ok(Vec::new())
+        }
+        result => result,
+    }
+}
+
 // reads the content of a file to a vector of lines
 pub fn get_file_content(file_name: &str) -> Result<Vec<Line>> {
-    let file_handler =
-        File::open(file_name).context(format!("Could not read from file: {file_name}"))?;
-    let reader = BufReader::new(file_handler);
+    let file_state = get_file_state(file_name);
 
-    let lines = reader
-        .lines()
-        .map_while(Result::ok)
-        .enumerate()
-        .map(|(line_number, line)| Line::new(&line, line_number.saturating_add(1)))
-        .collect();
+    let cached_lines = CACHE.with(|cache| match &cache.borrow().content {
+        Some(cached) if cached.file_name == file_name && Some(cached.file_state) == file_state => {
+            Some(cached.lines.clone())
+        }
+        _ => None,
+    });
+
+    if let Some(lines) = cached_lines {
+        return Ok(lines);
+    }
+
+    let lines = if encryption::is_encrypted(file_name) {
+        parse_lines(
+            read_text(file_name)?
+                .lines()
+                .enumerate()
+                .map(|(line_number, line)| (Some(line_number.saturating_add(1)), line)),
+        )
+    } else {
+        let file_handler =
+            File::open(file_name).context(format!("Could not read from file: {file_name}"))?;
+        let reader = BufReader::new(file_handler);
+
+        parse_lines(
+            reader
+                .lines()
+                .map_while(Result::ok)
+                .enumerate()
+                .map(|(line_number, line)| (Some(line_number.saturating_add(1)), line)),
+        )
+    };
+
+    update_cache(file_name, file_state, &lines);
 
     Ok(lines)
 }
 
 // writes a vector of lines into a file
 pub fn write_to_file(file_name: &str, file_content: &[Line]) -> Result<(), io::Error> {
-    let file_handler = get_bartib_file_writable(file_name)?;
-
-    for line in file_content {
-        match &line.status {
-            LineStatus::Unchanged => {
-                if let Some(plaintext) = &line.plaintext {
-                    writeln!(&file_handler, "{plaintext}")?
-                } else {
-                    write!(&file_handler, "{}", line.activity.as_ref().unwrap())?
-                }
-            }
-            LineStatus::Changed => write!(&file_handler, "{}", line.activity.as_ref().unwrap())?,
+    let plaintext_lines: Vec<String> = file_content.iter().map(get_plaintext).collect();
+
+    let mut content = String::new();
+    for plaintext in &plaintext_lines {
+        content.push_str(plaintext);
+        content.push('\n');
+    }
+
+    write_atomically(file_name, &content)?;
+
+    let indexed_lines: Vec<(u64, usize, Option<&activity::Activity>)> = plaintext_lines
+        .iter()
+        .zip(file_content)
+        .map(|(plaintext, line)| {
+            (
+                plaintext.len() as u64 + 1,
+                plaintext.lines().count().max(1),
+                line.activity.as_ref().ok(),
+            )
+        })
+        .collect();
+    index::update(file_name, &indexed_lines);
+
+    if CACHE.with(|cache| cache.borrow().enabled) {
+        let written_lines = parse_lines(
+            content
+                .lines()
+                .enumerate()
+                .map(|(line_number, line)| (Some(line_number.saturating_add(1)), line)),
+        );
+        update_cache(file_name, get_file_state(file_name), &written_lines);
+    }
+
+    Ok(())
+}
+
+// appends a line to a file without reading it
+//
+// an encrypted file has to be read and written completely.
+pub fn append_to_file(file_name: &str, line: &Line) -> Result<(), io::Error> {
+    if encryption::is_encrypted(file_name) {
+        let mut file_content = get_file_content(file_name).map_err(io::Error::other)?;
+        file_content.push(line.clone());
+        return write_to_file(file_name, &file_content);
+    }
+
+    let _lock = FileLock::acquire(file_name)?;
+    let mut previous_file_state = index::get_file_state(file_name);
+
+    let mut file_handler = OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(file_name)?;
+
+    let mut plaintext = get_plaintext(line);
+    let line_count = plaintext.lines().count().max(1);
+    plaintext.push('\n');
+
+    // the last line may lack its line break if it has been added in an editor. As this changes the
+    // last line, the index has to be created again
+    if file_handler.metadata()?.len() > 0 {
+        let mut last_byte = [0u8; 1];
+        file_handler.seek(SeekFrom::End(-1))?;
+        file_handler.read_exact(&mut last_byte)?;
+
+        if last_byte[0] != b'\n' {
+            plaintext.insert(0, '\n');
+            previous_file_state = None;
         }
     }
 
+    file_handler.write_all(plaintext.as_bytes())?;
+    file_handler.sync_all()?;
+    drop(file_handler);
+
+    index::update_appended(
+        file_name,
+        previous_file_state,
+        plaintext.len() as u64,
+        line_count,
+        line.activity.as_ref().ok(),
+    );
+
     Ok(())
 }
 
+// reads lines from the end of a file until there are enough of them
+//
+// activities are appended to the file when they are started, so the most recent activities are
+// found without reading the whole file. The lines are returned in the order of the file but
+// without line numbers. Encrypted files are read completely.
+pub fn get_file_tail(file_name: &str, is_enough: impl Fn(&[Line]) -> bool) -> Result<Vec<Line>> {
+    if encryption::is_encrypted(file_name) {
+        return get_file_content(file_name);
+    }
+
+    let mut file_handler =
+        File::open(file_name).context(format!("Could not read from file: {file_name}"))?;
+    let file_length = file_handler.metadata()?.len();
+    let mut tail_length = TAIL_CHUNK_SIZE;
+
+    loop {
+        // one byte more is read to know whether the first line is complete
+        let start = file_length.saturating_sub(tail_length.saturating_add(1));
+        let mut buffer = Vec::new();
+        file_handler.seek(SeekFrom::Start(start))?;
+        (&mut file_handler)
+            .take(file_length - start)
+            .read_to_end(&mut buffer)
+            .context(format!("Could not read from file: {file_name}"))?;
+
+        let text = String::from_utf8_lossy(&buffer);
+        let complete_lines = if start == 0 {
+            &text[..]
+        } else {
+            text.split_once('\n').map_or("", |(_, rest)| rest)
+        };
+
+        // notes at the beginning belong to an activity which has not been read yet
+        let lines = parse_lines(
+            complete_lines
+                .lines()
+                .skip_while(|line| activity::is_note_line(line))
+                .map(|line| (None, line)),
+        );
+
+        if start == 0 || is_enough(&lines) {
+            return Ok(lines);
+        }
+
+        tail_length = tail_length.saturating_mul(2);
+    }
+}
+
+// replaces the content of a file so that other processes either see the old or the new content
+//
+// the content is written to a temporary file next to the file which then replaces it. While
+// writing, the file is locked so that simultaneous bartib processes do not interleave their
+// changes. A symlinked file is replaced at the target of the link. An encrypted file stays
+// encrypted.
+pub fn write_atomically(file_name: &str, content: &str) -> Result<(), io::Error> {
+    write_with_encryption(file_name, content, encryption::is_encrypted(file_name))
+}
+
+// replaces the content of a file like `write_atomically`, but encrypts it or writes it as plain
+// text regardless of whether the file has been encrypted before
+pub fn write_with_encryption(
+    file_name: &str,
+    content: &str,
+    encrypted: bool,
+) -> Result<(), io::Error> {
+    let content = if encrypted {
+        encryption::encrypt(content)?
+    } else {
+        content.as_bytes().to_vec()
+    };
+
+    let _lock = FileLock::acquire(file_name)?;
+
+    let target = fs::canonicalize(file_name).unwrap_or_else(|_| PathBuf::from(file_name));
+    let target_name = target.to_string_lossy();
+
+    let temporary_file_name = format!("{target_name}.{}.tmp", std::process::id());
+    let write_temporary_file = || -> Result<(), io::Error> {
+        let mut file_handler = get_bartib_file_writable(&temporary_file_name)?;
+        file_handler.write_all(&content)?;
+        file_handler.sync_all()?;
+
+        if let Ok(metadata) = fs::metadata(&target) {
+            fs::set_permissions(&temporary_file_name, metadata.permissions())?;
+        }
+
+        fs::rename(&temporary_file_name, &target)
+    };
+
+    write_temporary_file().inspect_err(|_| {
+        let _ = fs::remove_file(&temporary_file_name);
+    })
+}
+
+// reads the whole text of a file, which is decrypted if it has been encrypted
+pub fn read_text(file_name: &str) -> Result<String> {
+    let content = fs::read(file_name).context(format!("Could not read from file: {file_name}"))?;
+
+    if encryption::is_ciphertext(&content) {
+        Ok(encryption::decrypt(&content)?)
+    } else {
+        String::from_utf8(content).context(format!("Could not read from file: {file_name}"))
+    }
+}
+
+// returns the text for a line as it should be written to the file
+fn get_plaintext(line: &Line) -> String {
+    match (&line.status, &line.plaintext, &line.activity) {
+        (LineStatus::Unchanged, Some(plaintext), _) => plaintext.clone(),
+        (_, _, Ok(activity)) => activity.to_string().trim_end_matches('\n').to_string(),
+        (_, plaintext, Err(_)) => plaintext.clone().unwrap_or_default(),
+    }
+}
+
+fn update_cache(file_name: &str, file_state: Option<FileState>, lines: &[Line]) {
+    CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+
+        if cache.enabled {
+            cache.content = file_state.map(|file_state| CachedContent {
+                file_name: file_name.to_string(),
+                file_state,
+                lines: lines.to_vec(),
+            });
+        }
+    });
+}
+
+fn get_file_state(file_name: &str) -> Option<FileState> {
+    let metadata = fs::metadata(file_name).ok()?;
+    Some((metadata.len(), metadata.modified().ok()?))
+}
+
 // create a write handle to a file
 fn get_bartib_file_writable(file_name: &str) -> Result<File, io::Error> {
     OpenOptions::new()
@@ -0,0 +1,242 @@
+use chrono::{Datelike, Duration, NaiveDate};
+use wildmatch::WildMatch;
+
+use crate::data::activity::{self, Activity};
+use crate::data::config::{Config, ConfigError, Table};
+use crate::data::date_parser::DateRange;
+use crate::data::duration_parser;
+
+// the name of the tables defining budgets in the configuration file
+pub static CONFIG_TABLE: &str = "budget";
+
+// the share of a budget in percent at which bartib warns if no other is configured
+static DEFAULT_WARN_AT: i64 = 80;
+
+// the period a budget is granted for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetPeriod {
+    Week,
+    Month,
+    Year,
+}
+
+// the maximum duration which should be spent on a project per week, month or year
+//
+// in the configuration file a budget is defined like this. Bartib warns when `warn_at` percent of
+// the budget have been used (80 by default):
+//
+//     [[budget]]
+//     project = "Client X"
+//     period = "month"
+//     duration = "20h"
+//     warn_at = 90
+#[derive(Debug, Clone, PartialEq)]
+pub struct Budget {
+    // the projects the budget applies to. May contain `?` and `*` as wildcards
+    pub project: String,
+    pub period: BudgetPeriod,
+    pub limit: Duration,
+    pub warn_at: i64,
+}
+
+// how much of a budget has been used
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetState {
+    Within,
+    Near,
+    Over,
+}
+
+// the time spent so far in the current period of a budget
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetUsage<'a> {
+    pub budget: &'a Budget,
+    pub used: Duration,
+}
+
+impl Budget {
+    pub fn from_table(table: &Table) -> Result<Self, ConfigError> {
+        let project = table.get_str("project")?.to_string();
+
+        let period = match table.get_str("period")? {
+            "week" => BudgetPeriod::Week,
+            "month" => BudgetPeriod::Month,
+            "year" => BudgetPeriod::Year,
+            _ => {
+                return Err(ConfigError::InvalidSetting(
+                    table.qualified_key("period"),
+                    "expected `week`, `month` or `year`".to_string(),
+                ));
+            }
+        };
+
+        let limit = duration_parser::parse_duration(table.get_str("duration")?)
+            .map_err(|e| ConfigError::InvalidSetting(table.qualified_key("duration"), e))?;
+
+        let warn_at = match table.get("warn_at") {
+            None => DEFAULT_WARN_AT,
+            Some(value) => value
+                .as_f64()
+                .filter(|percent| *percent >= 0.0)
+                .map(|percent| percent.round() as i64)
+                .ok_or_else(|| {
+                    ConfigError::InvalidSetting(
+                        table.qualified_key("warn_at"),
+                        "expected a percentage like 80".to_string(),
+                    )
+                })?,
+        };
+
+        Ok(Self {
+            project,
+            period,
+            limit,
+            warn_at,
+        })
+    }
+
+    // whether the budget applies to a project or to one of its parents
+    #[must_use]
+    pub fn applies_to(&self, project: &str) -> bool {
+        let project_pattern = WildMatch::new(&self.project);
+        activity::project_hierarchy(project).any(|p| project_pattern.matches(p))
+    }
+
+    // the dates of the period of the budget which contains the date
+    #[must_use]
+    pub fn period_of(&self, date: NaiveDate) -> DateRange {
+        match self.period {
+            BudgetPeriod::Week => DateRange::week_of(date),
+            BudgetPeriod::Month => DateRange::month_of(date),
+            BudgetPeriod::Year => DateRange {
+                first: date.with_ordinal(1).unwrap_or(date),
+                last: NaiveDate::from_ymd_opt(date.year(), 12, 31).unwrap_or(date),
+            },
+        }
+    }
+
+    // sums up the durations of the activities of the budget's projects in its current period
+    #[must_use]
+    pub fn usage<'a>(&'a self, activities: &[&Activity], today: NaiveDate) -> BudgetUsage<'a> {
+        let period = self.period_of(today);
+
+        let used = activities
+            .iter()
+            .filter(|activity| {
+                activity.start.date() >= period.first && activity.start.date() <= period.last
+            })
+            .filter(|activity| self.applies_to(&activity.project))
+            .map(|activity| activity.get_duration())
+            .sum();
+
+        BudgetUsage { budget: self, used }
+    }
+}
+
+impl BudgetUsage<'_> {
+    // the share of the budget that has been used in percent. May be more than 100
+    #[must_use]
+    pub fn percent(&self) -> i64 {
+        self.used.num_seconds() * 100 / self.budget.limit.num_seconds().max(1)
+    }
+
+    // the time left until the budget is used up
+    #[must_use]
+    pub fn remaining(&self) -> Duration {
+        (self.budget.limit - self.used).max(Duration::zero())
+    }
+
+    #[must_use]
+    pub fn state(&self) -> BudgetState {
+        if self.used > self.budget.limit {
+            BudgetState::Over
+        } else if self.percent() >= self.budget.warn_at {
+            BudgetState::Near
+        } else {
+            BudgetState::Within
+        }
+    }
+}
+
+// reads all budgets from the configuration
+pub fn get_budgets(config: &Config) -> Result<Vec<Budget>, ConfigError> {
+    config
+        .tables(CONFIG_TABLE)
+        .map(Budget::from_table)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::activity::activity;
+
+    #[test]
+    fn parse_budgets() {
+        let config: Config = "[[budget]]\nproject = \"Client X\"\nperiod = \"month\"\nduration = \"20h\"\n[[budget]]\nproject = \"Client *\"\nperiod = \"year\"\nduration = \"100h\"\nwarn_at = 90"
+            .parse()
+            .unwrap();
+        let budgets = get_budgets(&config).unwrap();
+
+        assert_eq!(budgets.len(), 2);
+        assert_eq!(budgets[0].project, "Client X");
+        assert_eq!(budgets[0].period, BudgetPeriod::Month);
+        assert_eq!(budgets[0].limit, Duration::hours(20));
+        assert_eq!(budgets[0].warn_at, 80);
+        assert_eq!(budgets[1].period, BudgetPeriod::Year);
+        assert_eq!(budgets[1].warn_at, 90);
+    }
+
+    #[test]
+    fn parse_invalid_budgets() {
+        let day: Config = "[[budget]]\nproject = \"p\"\nperiod = \"day\"\nduration = \"8h\""
+            .parse()
+            .unwrap();
+        assert!(get_budgets(&day).is_err());
+
+        let missing_project: Config = "[[budget]]\nperiod = \"week\"\nduration = \"8h\""
+            .parse()
+            .unwrap();
+        assert!(get_budgets(&missing_project).is_err());
+
+        let warn_at: Config =
+            "[[budget]]\nproject = \"p\"\nperiod = \"week\"\nduration = \"8h\"\nwarn_at = \"80%\""
+                .parse()
+                .unwrap();
+        assert!(get_budgets(&warn_at).is_err());
+    }
+
+    #[test]
+    fn budget_usage() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 19).unwrap();
+        let a1 = activity("Client X/backend", "2024-02-28 09:00", "2024-02-28 17:00");
+        let a2 = activity("Client X/backend", "2024-03-01 09:00", "2024-03-01 15:00");
+        let a3 = activity("Client X", "2024-03-18 09:00", "2024-03-18 11:00");
+        let a4 = activity("Internal", "2024-03-19 09:00", "2024-03-19 17:00");
+        let activities = vec![&a1, &a2, &a3, &a4];
+
+        let mut budget = Budget {
+            project: "Client X".to_string(),
+            period: BudgetPeriod::Month,
+            limit: Duration::hours(10),
+            warn_at: 80,
+        };
+        let usage = budget.usage(&activities, today);
+        assert_eq!(usage.used, Duration::hours(8));
+        assert_eq!(usage.percent(), 80);
+        assert_eq!(usage.remaining(), Duration::hours(2));
+        assert_eq!(usage.state(), BudgetState::Near);
+
+        budget.period = BudgetPeriod::Year;
+        let usage = budget.usage(&activities, today);
+        assert_eq!(usage.used, Duration::hours(16));
+        assert_eq!(usage.remaining(), Duration::zero());
+        assert_eq!(usage.state(), BudgetState::Over);
+
+        budget.limit = Duration::hours(100);
+        assert_eq!(
+            budget.usage(&activities, today).state(),
+            BudgetState::Within
+        );
+    }
+}
@@ -0,0 +1,606 @@
+use anyhow::{Context, Result};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use thiserror::Error;
+
+// a value in the configuration file
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    Array(Vec<Value>),
+}
+
+// a table of the configuration file, e.g. `[rates]` or an entry of `[[recur]]`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Table {
+    pub name: String,
+    pub entries: Vec<(String, Value)>,
+}
+
+// the content of the configuration file
+//
+// the file is written in a subset of TOML: tables (`[name]`), arrays of tables (`[[name]]`) and
+// `key = value` pairs with strings, numbers, booleans and single line arrays as values. Keys
+// before the first table belong to a table with an empty name.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Config {
+    pub tables: Vec<Table>,
+    // the file the configuration has been read from or would be read from if it existed
+    pub path: Option<PathBuf>,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ConfigError {
+    #[error("invalid table header in line {0}")]
+    InvalidTable(usize),
+    #[error("expected `key = value` in line {0}")]
+    InvalidEntry(usize),
+    #[error("invalid value in line {0}")]
+    InvalidValue(usize),
+    #[error("invalid value for `{0}`: {1}")]
+    InvalidSetting(String, String),
+    #[error("missing value for `{0}`")]
+    MissingSetting(String),
+    #[error("`{0}` is an array of tables and can not be changed as a single setting")]
+    ArrayOfTables(String),
+}
+
+impl Value {
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Integer(i) => Some(*i as f64),
+            Self::Float(f) => Some(*f),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Value {
+    // writes the value as it would be written in the configuration file
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::String(s) => write!(f, "{}", quote(s)),
+            Self::Integer(i) => write!(f, "{i}"),
+            Self::Float(x) => write!(f, "{x}"),
+            Self::Boolean(b) => write!(f, "{b}"),
+            Self::Array(values) => {
+                let values: Vec<String> = values.iter().map(ToString::to_string).collect();
+                write!(f, "[{}]", values.join(", "))
+            }
+        }
+    }
+}
+
+impl Table {
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value)
+    }
+
+    // returns the string value for a key or an error if the value is missing or not a string
+    pub fn get_str(&self, key: &str) -> Result<&str, ConfigError> {
+        match self.get(key) {
+            Some(Value::String(s)) => Ok(s),
+            Some(_) => Err(ConfigError::InvalidSetting(
+                self.qualified_key(key),
+                "expected a string".to_string(),
+            )),
+            None => Err(ConfigError::MissingSetting(self.qualified_key(key))),
+        }
+    }
+
+    #[must_use]
+    pub fn qualified_key(&self, key: &str) -> String {
+        if self.name.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", self.name, key)
+        }
+    }
+}
+
+impl Config {
+    // all tables with a given name. Arrays of tables may return more than one
+    pub fn tables<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Table> {
+        self.tables.iter().filter(move |table| table.name == name)
+    }
+
+    // the value of a key in a table. If the table exists more than once, the last value wins
+    #[must_use]
+    pub fn get(&self, table: &str, key: &str) -> Option<&Value> {
+        self.tables
+            .iter()
+            .filter(|t| t.name == table)
+            .filter_map(|t| t.get(key))
+            .next_back()
+    }
+}
+
+impl std::str::FromStr for Config {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tables = vec![Table::default()];
+
+        for (i, line) in s.lines().enumerate() {
+            let line_number = i + 1;
+            let line = strip_comment(line).trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            if line.starts_with('[') {
+                tables.push(Table {
+                    name: parse_table_header(line).ok_or(ConfigError::InvalidTable(line_number))?,
+                    entries: Vec::new(),
+                });
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or(ConfigError::InvalidEntry(line_number))?;
+            let key = parse_key(key.trim()).ok_or(ConfigError::InvalidEntry(line_number))?;
+            let value = parse_value(value.trim()).ok_or(ConfigError::InvalidValue(line_number))?;
+
+            if let Some(table) = tables.last_mut() {
+                table.entries.push((key, value));
+            }
+        }
+
+        Ok(Self { tables, path: None })
+    }
+}
+
+// the path of the configuration file
+//
+// the path may be given explicitly. Otherwise it is `$XDG_CONFIG_HOME/bartib/config.toml` or
+// `~/.config/bartib/config.toml`.
+#[must_use]
+pub fn get_config_path(config_file: Option<&str>) -> Option<PathBuf> {
+    if let Some(config_file) = config_file {
+        return Some(PathBuf::from(config_file));
+    }
+
+    let config_dir = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+    Some(config_dir.join("bartib").join("config.toml"))
+}
+
+// reads the configuration file
+//
+// a missing file results in an empty configuration unless the file has been given explicitly.
+pub fn load(config_file: Option<&str>) -> Result<Config> {
+    read(config_file, config_file.is_none())
+}
+
+// reads the configuration file like `load`, but a missing file always results in an empty
+// configuration, e.g. to create the file
+pub fn load_optional(config_file: Option<&str>) -> Result<Config> {
+    read(config_file, true)
+}
+
+fn read(config_file: Option<&str>, allow_missing: bool) -> Result<Config> {
+    let Some(path) = get_config_path(config_file) else {
+        return Ok(Config::default());
+    };
+
+    if allow_missing && !path.exists() {
+        return Ok(Config {
+            path: Some(path),
+            ..Config::default()
+        });
+    }
+
+    let content = fs::read_to_string(&path).context(format!(
+        "Could not read config file: {}",
+        path.to_string_lossy()
+    ))?;
+
+    let config: Config = content.parse().context(format!(
+        "Could not parse config file: {}",
+        path.to_string_lossy()
+    ))?;
+
+    Ok(Config {
+        path: Some(path),
+        ..config
+    })
+}
+
+// splits a key like `aliases.cb` into the name of the table and the key within the table
+//
+// keys without a table name belong to the general settings at the top of the file.
+#[must_use]
+pub fn split_key(key: &str) -> (&str, &str) {
+    key.split_once('.').unwrap_or(("", key))
+}
+
+// reads a value given on the command line. Anything that is not a valid value is a string
+#[must_use]
+pub fn parse_setting_value(value: &str) -> Value {
+    parse_value(value.trim()).unwrap_or_else(|| Value::String(value.to_string()))
+}
+
+// changes the value of a key in the content of a configuration file or removes the key if no
+// value is given
+//
+// comments and all other lines are kept. A missing key is added at the end of its table and a
+// missing table at the end of the file.
+pub fn set_value(
+    content: &str,
+    table: &str,
+    key: &str,
+    value: Option<&Value>,
+) -> Result<String, ConfigError> {
+    let mut lines: Vec<String> = content.lines().map(ToString::to_string).collect();
+    let mut current_table: Option<String> = Some(String::new());
+    let mut table_exists = table.is_empty();
+    let mut key_line = None;
+    let mut table_end = 0;
+
+    for (i, line) in lines.iter().enumerate() {
+        let line = strip_comment(line).trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        if line.starts_with('[') {
+            let name = parse_table_header(line);
+
+            if line.starts_with("[[") && name.as_deref() == Some(table) {
+                return Err(ConfigError::ArrayOfTables(table.to_string()));
+            }
+
+            current_table = name.filter(|_| !line.starts_with("[["));
+            if current_table.as_deref() == Some(table) {
+                table_exists = true;
+                table_end = i + 1;
+            }
+            continue;
+        }
+
+        if current_table.as_deref() == Some(table) {
+            table_end = i + 1;
+
+            let entry_key = line.split_once('=').and_then(|(k, _)| parse_key(k.trim()));
+            if entry_key.as_deref() == Some(key) {
+                key_line = Some(i);
+            }
+        }
+    }
+
+    let entry = value.map(|value| format!("{} = {value}", format_key(key)));
+
+    match (entry, key_line) {
+        (Some(entry), Some(i)) => lines[i] = entry,
+        (Some(entry), None) if table_exists => lines.insert(table_end, entry),
+        (Some(entry), None) => {
+            if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+                lines.push(String::new());
+            }
+            lines.push(format!("[{}]", format_key(table)));
+            lines.push(entry);
+        }
+        (None, Some(i)) => {
+            lines.remove(i);
+        }
+        (None, None) => {}
+    }
+
+    let mut content = lines.join("\n");
+    content.push('\n');
+    Ok(content)
+}
+
+// a key as it is written in the configuration file, quoted if necessary
+fn format_key(key: &str) -> String {
+    let is_bare_key = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-'));
+
+    if is_bare_key {
+        key.to_string()
+    } else {
+        quote(key)
+    }
+}
+
+fn quote(s: &str) -> String {
+    let escaped = s
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t");
+    format!("\"{escaped}\"")
+}
+
+fn parse_table_header(line: &str) -> Option<String> {
+    let name = line
+        .strip_prefix("[[")
+        .and_then(|l| l.strip_suffix("]]"))
+        .or_else(|| line.strip_prefix('[').and_then(|l| l.strip_suffix(']')))?;
+
+    parse_key(name.trim())
+}
+
+fn parse_key(key: &str) -> Option<String> {
+    if let Some(quoted) = key.strip_prefix('"').and_then(|k| k.strip_suffix('"')) {
+        return Some(quoted.to_string());
+    }
+
+    let is_bare_key = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'));
+
+    is_bare_key.then(|| key.to_string())
+}
+
+fn parse_value(value: &str) -> Option<Value> {
+    let (value, rest) = parse_value_prefix(value)?;
+
+    if rest.trim().is_empty() {
+        Some(value)
+    } else {
+        None
+    }
+}
+
+// parses a value at the start of the string and returns the rest of the string
+fn parse_value_prefix(s: &str) -> Option<(Value, &str)> {
+    let s = s.trim_start();
+
+    if let Some(rest) = s.strip_prefix('"') {
+        let mut value = String::new();
+        let mut chars = rest.char_indices();
+
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => return Some((Value::String(value), &rest[i + 1..])),
+                '\\' => match chars.next()?.1 {
+                    'n' => value.push('\n'),
+                    't' => value.push('\t'),
+                    c => value.push(c),
+                },
+                c => value.push(c),
+            }
+        }
+
+        return None;
+    }
+
+    if let Some(rest) = s.strip_prefix('\'') {
+        let end = rest.find('\'')?;
+        return Some((Value::String(rest[..end].to_string()), &rest[end + 1..]));
+    }
+
+    if let Some(mut rest) = s.strip_prefix('[') {
+        let mut values = Vec::new();
+
+        loop {
+            rest = rest.trim_start();
+
+            if let Some(after) = rest.strip_prefix(']') {
+                return Some((Value::Array(values), after));
+            }
+
+            let (value, after) = parse_value_prefix(rest)?;
+            values.push(value);
+            rest = after.trim_start();
+
+            if let Some(after) = rest.strip_prefix(',') {
+                rest = after;
+            } else if !rest.starts_with(']') {
+                return None;
+            }
+        }
+    }
+
+    let end = s.find([',', ']']).unwrap_or(s.len());
+    let (literal, rest) = s.split_at(end);
+    let literal = literal.trim();
+
+    let value = match literal {
+        "true" => Value::Boolean(true),
+        "false" => Value::Boolean(false),
+        _ => {
+            let number = literal.replace('_', "");
+            if let Ok(i) = number.parse() {
+                Value::Integer(i)
+            } else {
+                Value::Float(number.parse().ok()?)
+            }
+        }
+    };
+
+    Some((value, rest))
+}
+
+// removes a comment from a line while respecting `#` within strings
+fn strip_comment(line: &str) -> &str {
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (Some('"'), '\\') if !escaped => {
+                escaped = true;
+                continue;
+            }
+            (Some(q), c) if c == q && !escaped => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '#') => return &line[..i],
+            _ => {}
+        }
+        escaped = false;
+    }
+
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_config() {
+        let config: Config = r#"
+# a comment
+file = "/home/alice/activities.bartib" # another comment
+
+[rates]
+"Important Project" = 85.5
+internal = 0
+
+[[recur]]
+days = 'mon-fri'
+tags = ["a", "b#c"]
+
+[[recur]]
+days = "sat"
+enabled = false
+"#
+        .parse()
+        .unwrap();
+
+        assert_eq!(
+            config.get("", "file"),
+            Some(&Value::String("/home/alice/activities.bartib".to_string()))
+        );
+        assert_eq!(
+            config.get("rates", "Important Project"),
+            Some(&Value::Float(85.5))
+        );
+        assert_eq!(config.get("rates", "internal"), Some(&Value::Integer(0)));
+        assert_eq!(config.tables("recur").count(), 2);
+        assert_eq!(
+            config.tables("recur").next().unwrap().get("tags"),
+            Some(&Value::Array(vec![
+                Value::String("a".to_string()),
+                Value::String("b#c".to_string())
+            ]))
+        );
+        assert_eq!(
+            config.tables("recur").last().unwrap().get("enabled"),
+            Some(&Value::Boolean(false))
+        );
+    }
+
+    #[test]
+    fn parse_escaped_strings() {
+        let config: Config = r#"key = "say \"hi\" \\ # not a comment""#.parse().unwrap();
+        assert_eq!(
+            config.get("", "key"),
+            Some(&Value::String("say \"hi\" \\ # not a comment".to_string()))
+        );
+    }
+
+    #[test]
+    fn set_values() {
+        let content = "# settings\nfile = \"a.bartib\"\n\n[rates]\np1 = 80 # per hour\n\n[[recur]]\ndays = \"mon\"\n";
+
+        assert_eq!(
+            set_value(content, "", "file", Some(&Value::String("b".to_string()))).unwrap(),
+            "# settings\nfile = \"b\"\n\n[rates]\np1 = 80 # per hour\n\n[[recur]]\ndays = \"mon\"\n"
+        );
+        assert_eq!(
+            set_value(
+                content,
+                "",
+                "week_start",
+                Some(&Value::String("sunday".to_string()))
+            )
+            .unwrap(),
+            "# settings\nfile = \"a.bartib\"\nweek_start = \"sunday\"\n\n[rates]\np1 = 80 # per hour\n\n[[recur]]\ndays = \"mon\"\n"
+        );
+        assert_eq!(
+            set_value(content, "rates", "Client A", Some(&Value::Float(60.5))).unwrap(),
+            "# settings\nfile = \"a.bartib\"\n\n[rates]\np1 = 80 # per hour\n\"Client A\" = 60.5\n\n[[recur]]\ndays = \"mon\"\n"
+        );
+        assert_eq!(
+            set_value(
+                content,
+                "aliases",
+                "cb",
+                Some(&Value::String("client".to_string()))
+            )
+            .unwrap(),
+            format!("{content}\n[aliases]\ncb = \"client\"\n")
+        );
+        assert_eq!(
+            set_value(content, "rates", "p1", None).unwrap(),
+            "# settings\nfile = \"a.bartib\"\n\n[rates]\n\n[[recur]]\ndays = \"mon\"\n"
+        );
+        assert_eq!(
+            set_value(content, "recur", "days", None),
+            Err(ConfigError::ArrayOfTables("recur".to_string()))
+        );
+    }
+
+    #[test]
+    fn parse_setting_values() {
+        assert_eq!(parse_setting_value("85.5"), Value::Float(85.5));
+        assert_eq!(parse_setting_value("true"), Value::Boolean(true));
+        assert_eq!(
+            parse_setting_value("client/backend"),
+            Value::String("client/backend".to_string())
+        );
+        assert_eq!(
+            parse_setting_value("\"say \\\"hi\\\"\""),
+            Value::String("say \"hi\"".to_string())
+        );
+        assert_eq!(
+            Value::String("say \"hi\"".to_string()).to_string(),
+            "\"say \\\"hi\\\"\""
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            "[table".parse::<Config>(),
+            Err(ConfigError::InvalidTable(1))
+        );
+        assert_eq!("\nkey".parse::<Config>(), Err(ConfigError::InvalidEntry(2)));
+        assert_eq!(
+            "key = \"open".parse::<Config>(),
+            Err(ConfigError::InvalidValue(1))
+        );
+        assert_eq!(
+            "key = value".parse::<Config>(),
+            Err(ConfigError::InvalidValue(1))
+        );
+    }
+}
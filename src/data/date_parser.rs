@@ -0,0 +1,514 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+
+use crate::conf;
+use crate::data::duration_parser;
+
+// a range of dates (inclusive), e.g. a single day, a week or a month
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateRange {
+    pub first: NaiveDate,
+    pub last: NaiveDate,
+}
+
+impl DateRange {
+    #[must_use]
+    pub fn day(date: NaiveDate) -> Self {
+        Self {
+            first: date,
+            last: date,
+        }
+    }
+
+    // the week which contains the date, starting at the configured first day of the week
+    #[must_use]
+    pub fn week_of(date: NaiveDate) -> Self {
+        let first = most_recent(date, conf::week_start());
+
+        Self {
+            first,
+            last: first + Duration::days(6),
+        }
+    }
+
+    // the whole month which contains the date
+    #[must_use]
+    pub fn month_of(date: NaiveDate) -> Self {
+        let first = date.with_day(1).unwrap_or(date);
+        let last = first
+            .checked_add_months(chrono::Months::new(1))
+            .and_then(|next_month| next_month.pred_opt())
+            .unwrap_or(first);
+
+        Self { first, last }
+    }
+
+    // the whole month before the month which contains the date
+    #[must_use]
+    pub fn month_before(date: NaiveDate) -> Self {
+        let first = date.with_day(1).unwrap_or(date);
+        Self::month_of(first.pred_opt().unwrap_or(first))
+    }
+
+    // the range of the same length right before this range, e.g. the last week for a week.
+    // Ranges of whole months are preceded by the same number of whole months
+    #[must_use]
+    pub fn preceding(&self) -> Self {
+        let next_day = self.last.succ_opt().unwrap_or(self.last);
+
+        if self.first.day() == 1 && next_day.day() == 1 {
+            let months = (next_day.year() - self.first.year()) * 12 + next_day.month() as i32
+                - self.first.month() as i32;
+            let first = u32::try_from(months)
+                .ok()
+                .and_then(|months| self.first.checked_sub_months(chrono::Months::new(months)));
+
+            if let Some(first) = first {
+                return Self {
+                    first,
+                    last: self.first.pred_opt().unwrap_or(self.first),
+                };
+            }
+        }
+
+        let length = self.last.signed_duration_since(self.first) + Duration::days(1);
+        Self {
+            first: self.first - length,
+            last: self.last - length,
+        }
+    }
+
+    // the date if the range consists of a single day
+    #[must_use]
+    pub fn single_date(&self) -> Option<NaiveDate> {
+        (self.first == self.last).then_some(self.first)
+    }
+}
+
+// parses a date given either as ISO date (e.g. 2024-03-01) or in a natural language
+//
+// supported expressions are `today`, `yesterday`, `tomorrow`, weekdays (e.g. `monday` or `mon`
+// for the most recent monday including today), `last <weekday>` (the most recent weekday before
+// today), `<n> days ago` or `<n> weeks ago` and the short forms `-<n>d` and `-<n>w`. All
+// expressions are relative to `today`.
+pub fn parse_date(date_string: &str, today: NaiveDate) -> Result<NaiveDate, String> {
+    let normalized = date_string.trim().to_lowercase();
+
+    if let Ok(date) = NaiveDate::parse_from_str(&normalized, conf::FORMAT_DATE) {
+        return Ok(date);
+    }
+
+    let words: Vec<&str> = normalized.split_whitespace().collect();
+
+    let date = match words.as_slice() {
+        ["today"] => Some(today),
+        ["yesterday"] => Some(today - Duration::days(1)),
+        ["tomorrow"] => Some(today + Duration::days(1)),
+        [word] => parse_weekday(word)
+            .map(|weekday| most_recent(today, weekday))
+            .or_else(|| parse_offset(word).and_then(|duration| today.checked_sub_signed(duration))),
+        ["last", weekday] => {
+            parse_weekday(weekday).map(|weekday| most_recent(today - Duration::days(1), weekday))
+        }
+        [number, unit, "ago"] => {
+            parse_relative(number, unit).and_then(|duration| today.checked_sub_signed(duration))
+        }
+        _ => None,
+    };
+
+    date.ok_or_else(|| {
+        format!(
+            "invalid date '{date_string}', expected a date like 2024-03-01, `today`, `yesterday`, `monday`, `last friday`, `3 days ago` or `-3d`"
+        )
+    })
+}
+
+// parses a single date or a range of dates
+//
+// besides all dates `parse_date` understands, whole months (e.g. `2024-03`, `this month` or
+// `last month`) and weeks (`this week` or `last week`) are accepted.
+pub fn parse_date_range(range_string: &str, today: NaiveDate) -> Result<DateRange, String> {
+    let normalized = range_string.trim().to_lowercase();
+    let words: Vec<&str> = normalized.split_whitespace().collect();
+
+    let range = match words.as_slice() {
+        ["this", "month"] => Some(DateRange::month_of(today)),
+        ["last", "month"] => Some(DateRange::month_before(today)),
+        ["this", "week"] => Some(DateRange::week_of(today)),
+        ["last", "week"] => Some(DateRange::week_of(today - Duration::weeks(1))),
+        [year_and_month] => parse_year_and_month(year_and_month),
+        _ => None,
+    };
+
+    match range {
+        Some(range) => Ok(range),
+        None => parse_date(range_string, today).map(DateRange::day).map_err(|_| {
+            format!(
+                "invalid date '{range_string}', expected a date like 2024-03-01, `yesterday`, `monday`, `3 days ago` or `-3d` or a range like 2024-03, `last month` or `this week`"
+            )
+        }),
+    }
+}
+
+// parses a whole month given as year and month like `2024-03`
+pub fn parse_month(month_string: &str) -> Result<DateRange, String> {
+    parse_year_and_month(month_string.trim())
+        .ok_or_else(|| format!("invalid month '{month_string}', expected a month like 2024-03"))
+}
+
+// parses a calendar week given as its number in the current year (e.g. `11`) or as year and
+// number like `2024-W11`. The week starts on the configured first day of the week that begins
+// the ISO week
+pub fn parse_week(week_string: &str, today: NaiveDate) -> Result<DateRange, String> {
+    let normalized = week_string.trim().to_uppercase();
+
+    let (year, week) = match normalized.split_once("-W") {
+        Some((year, week)) if year.len() == 4 => (year.parse().ok(), week),
+        Some(_) => (None, ""),
+        None => (Some(today.iso_week().year()), normalized.as_str()),
+    };
+
+    year.zip(week.parse().ok())
+        .and_then(|(year, week)| NaiveDate::from_isoywd_opt(year, week, Weekday::Mon))
+        .map(DateRange::week_of)
+        .ok_or_else(|| format!("invalid week '{week_string}', expected a week like 11 or 2024-W11"))
+}
+
+// parses a time like `09:00`, `09:00:30`, `9am` or `2:30pm`
+pub fn parse_time_of_day(time_string: &str) -> Result<NaiveTime, String> {
+    let normalized = time_string.trim().to_lowercase();
+
+    let time = NaiveTime::parse_from_str(&normalized, "%T")
+        .or_else(|_| NaiveTime::parse_from_str(&normalized, "%R"))
+        .ok()
+        .or_else(|| parse_twelve_hour_time(&normalized));
+
+    time.ok_or_else(|| {
+        format!("invalid time '{time_string}', expected a time like 09:00, 09:00:30, 9am or 2:30pm")
+    })
+}
+
+// parses a point in time of today like `09:00` or `2pm`, `now` or a time relative to now like
+// `-15m` or `-1h30m`
+pub fn parse_time(time_string: &str, now: NaiveDateTime) -> Result<NaiveDateTime, String> {
+    let normalized = time_string.trim().to_lowercase();
+
+    if normalized == "now" {
+        return Ok(now);
+    }
+
+    if let Some(duration) = normalized.strip_prefix('-') {
+        return duration_parser::parse_duration(duration).and_then(|duration| {
+            now.checked_sub_signed(duration)
+                .ok_or_else(|| format!("invalid time '{time_string}', the time is too far ago"))
+        });
+    }
+
+    parse_time_of_day(&normalized).map(|time| now.date().and_time(time))
+}
+
+// parses a date and a time like `2024-03-01 09:00`, `yesterday 14:30` or just `09:00` for today
+//
+// the date may be given in any form `parse_date` understands. The time is given as HH:MM or
+// HH:MM:SS.
+pub fn parse_datetime(datetime_string: &str, today: NaiveDate) -> Result<NaiveDateTime, String> {
+    let datetime_string = datetime_string.trim();
+    let (date_string, time_string) = datetime_string
+        .rsplit_once(char::is_whitespace)
+        .unwrap_or(("", datetime_string));
+
+    let time = parse_time_of_day(time_string).map_err(|_| {
+        format!(
+            "invalid time '{time_string}', expected a date and time like `2024-03-01 09:00`, `yesterday 2pm` or `09:00`"
+        )
+    })?;
+
+    let date = if date_string.trim().is_empty() {
+        today
+    } else {
+        parse_date(date_string, today)?
+    };
+
+    Ok(date.and_time(time))
+}
+
+fn parse_weekday(weekday: &str) -> Option<Weekday> {
+    match weekday {
+        "monday" | "mon" => Some(Weekday::Mon),
+        "tuesday" | "tue" => Some(Weekday::Tue),
+        "wednesday" | "wed" => Some(Weekday::Wed),
+        "thursday" | "thu" => Some(Weekday::Thu),
+        "friday" | "fri" => Some(Weekday::Fri),
+        "saturday" | "sat" => Some(Weekday::Sat),
+        "sunday" | "sun" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+fn parse_relative(number: &str, unit: &str) -> Option<Duration> {
+    let number: i64 = number.parse().ok()?;
+
+    match unit {
+        "day" | "days" => Duration::try_days(number),
+        "week" | "weeks" => Duration::try_weeks(number),
+        _ => None,
+    }
+}
+
+// parses a relative date like `-3d` or `-2w`
+fn parse_offset(offset: &str) -> Option<Duration> {
+    let offset = offset.strip_prefix('-')?;
+    let unit_start = offset.find(|c: char| !c.is_ascii_digit())?;
+    let (number, unit) = offset.split_at(unit_start);
+
+    match unit {
+        "d" => parse_relative(number, "days"),
+        "w" => parse_relative(number, "weeks"),
+        _ => None,
+    }
+}
+
+// parses a time with `am` or `pm` like `9am` or `2:30pm`
+fn parse_twelve_hour_time(time: &str) -> Option<NaiveTime> {
+    let (time, is_pm) = match (time.strip_suffix("am"), time.strip_suffix("pm")) {
+        (Some(time), _) => (time.trim(), false),
+        (_, Some(time)) => (time.trim(), true),
+        _ => return None,
+    };
+    let (hour, minute) = time.split_once(':').unwrap_or((time, "0"));
+    let hour: u32 = hour.parse().ok().filter(|hour| (1..=12).contains(hour))?;
+    let minute: u32 = minute.parse().ok()?;
+
+    NaiveTime::from_hms_opt(hour % 12 + if is_pm { 12 } else { 0 }, minute, 0)
+}
+
+fn parse_year_and_month(year_and_month: &str) -> Option<DateRange> {
+    let (year, month) = year_and_month.split_once('-')?;
+
+    if year.len() != 4 || month.len() != 2 {
+        return None;
+    }
+
+    NaiveDate::from_ymd_opt(year.parse().ok()?, month.parse().ok()?, 1).map(DateRange::month_of)
+}
+
+// the latest date on or before `date` that falls on the given weekday
+fn most_recent(date: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let days_back =
+        (7 + date.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+    date - Duration::days(i64::from(days_back))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a wednesday
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, 13).unwrap()
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn parse_iso_date() {
+        assert_eq!(parse_date("2021-09-03", today()), Ok(date(2021, 9, 3)));
+    }
+
+    #[test]
+    fn parse_relative_days() {
+        assert_eq!(parse_date("today", today()), Ok(today()));
+        assert_eq!(parse_date(" Yesterday ", today()), Ok(date(2024, 3, 12)));
+        assert_eq!(parse_date("tomorrow", today()), Ok(date(2024, 3, 14)));
+        assert_eq!(parse_date("3 days ago", today()), Ok(date(2024, 3, 10)));
+        assert_eq!(parse_date("1 week ago", today()), Ok(date(2024, 3, 6)));
+    }
+
+    #[test]
+    fn parse_weekdays() {
+        assert_eq!(parse_date("monday", today()), Ok(date(2024, 3, 11)));
+        assert_eq!(parse_date("wed", today()), Ok(today()));
+        assert_eq!(parse_date("thursday", today()), Ok(date(2024, 3, 7)));
+        assert_eq!(parse_date("last monday", today()), Ok(date(2024, 3, 11)));
+        assert_eq!(parse_date("last wednesday", today()), Ok(date(2024, 3, 6)));
+    }
+
+    #[test]
+    fn parse_datetimes() {
+        let datetime = |date: NaiveDate, hour: u32, minute: u32| {
+            Ok(date.and_hms_opt(hour, minute, 0).unwrap())
+        };
+
+        assert_eq!(
+            parse_datetime("2024-03-01 09:00", today()),
+            datetime(date(2024, 3, 1), 9, 0)
+        );
+        assert_eq!(
+            parse_datetime("last monday 14:30", today()),
+            datetime(date(2024, 3, 11), 14, 30)
+        );
+        assert_eq!(parse_datetime(" 8:15 ", today()), datetime(today(), 8, 15));
+        assert_eq!(
+            parse_datetime("2024-03-01 09:00:30", today()),
+            Ok(date(2024, 3, 1).and_hms_opt(9, 0, 30).unwrap())
+        );
+        assert!(parse_datetime("2024-03-01", today()).is_err());
+        assert!(parse_datetime("someday 09:00", today()).is_err());
+    }
+
+    #[test]
+    fn parse_offsets() {
+        assert_eq!(parse_date("-3d", today()), Ok(date(2024, 3, 10)));
+        assert_eq!(parse_date("-2w", today()), Ok(date(2024, 2, 28)));
+        assert!(parse_date("-3x", today()).is_err());
+        assert!(parse_date("-d", today()).is_err());
+        assert!(parse_date("-99999999d", today()).is_err());
+        assert!(parse_date("-99999999999w", today()).is_err());
+    }
+
+    #[test]
+    fn parse_date_ranges() {
+        let range = |first: NaiveDate, last: NaiveDate| Ok(DateRange { first, last });
+
+        assert_eq!(
+            parse_date_range("2024-02", today()),
+            range(date(2024, 2, 1), date(2024, 2, 29))
+        );
+        assert_eq!(
+            parse_date_range("this month", today()),
+            range(date(2024, 3, 1), date(2024, 3, 31))
+        );
+        assert_eq!(
+            parse_date_range("Last Month", today()),
+            range(date(2024, 2, 1), date(2024, 2, 29))
+        );
+        assert_eq!(
+            parse_date_range("this week", today()),
+            range(date(2024, 3, 11), date(2024, 3, 17))
+        );
+        assert_eq!(
+            parse_date_range("last week", today()),
+            range(date(2024, 3, 4), date(2024, 3, 10))
+        );
+        assert_eq!(
+            parse_date_range("yesterday", today()),
+            Ok(DateRange::day(date(2024, 3, 12)))
+        );
+        assert_eq!(
+            parse_date_range("yesterday", today())
+                .unwrap()
+                .single_date(),
+            Some(date(2024, 3, 12))
+        );
+        assert!(parse_date_range("2024-13", today()).is_err());
+        assert!(parse_date_range("next month", today()).is_err());
+    }
+
+    #[test]
+    fn parse_months() {
+        let range = |first: NaiveDate, last: NaiveDate| DateRange { first, last };
+
+        assert_eq!(
+            parse_month("2024-05"),
+            Ok(range(date(2024, 5, 1), date(2024, 5, 31)))
+        );
+        assert!(parse_month("2024-13").is_err());
+        assert!(parse_month("2024-05-01").is_err());
+        assert!(parse_month("last month").is_err());
+
+        assert_eq!(
+            DateRange::month_before(date(2024, 1, 15)),
+            range(date(2023, 12, 1), date(2023, 12, 31))
+        );
+    }
+
+    #[test]
+    fn preceding_ranges() {
+        let range = |first: NaiveDate, last: NaiveDate| DateRange { first, last };
+
+        assert_eq!(
+            DateRange::day(date(2024, 3, 1)).preceding(),
+            DateRange::day(date(2024, 2, 29))
+        );
+        assert_eq!(
+            range(date(2024, 3, 11), date(2024, 3, 17)).preceding(),
+            range(date(2024, 3, 4), date(2024, 3, 10))
+        );
+        assert_eq!(
+            range(date(2024, 3, 1), date(2024, 3, 31)).preceding(),
+            range(date(2024, 2, 1), date(2024, 2, 29))
+        );
+        assert_eq!(
+            range(date(2024, 1, 1), date(2024, 3, 31)).preceding(),
+            range(date(2023, 10, 1), date(2023, 12, 31))
+        );
+        assert_eq!(
+            range(date(2024, 3, 1), date(2024, 3, 10)).preceding(),
+            range(date(2024, 2, 20), date(2024, 2, 29))
+        );
+    }
+
+    #[test]
+    fn weeks_start_at_configured_day() {
+        // a wednesday
+        let wednesday = date(2024, 3, 13);
+        assert_eq!(DateRange::week_of(wednesday).first, date(2024, 3, 11));
+        assert_eq!(
+            parse_week("11", wednesday),
+            Ok(DateRange {
+                first: date(2024, 3, 11),
+                last: date(2024, 3, 17)
+            })
+        );
+        assert_eq!(
+            parse_week("2021-w01", wednesday).map(|week| week.first),
+            Ok(date(2021, 1, 4))
+        );
+        assert!(parse_week("54", wednesday).is_err());
+        assert!(parse_week("24-W01", wednesday).is_err());
+        assert!(parse_week("this week", wednesday).is_err());
+
+        conf::set_week_start(Weekday::Sun);
+        assert_eq!(
+            DateRange::week_of(wednesday),
+            DateRange {
+                first: date(2024, 3, 10),
+                last: date(2024, 3, 16)
+            }
+        );
+        assert_eq!(
+            parse_week("11", wednesday).map(|week| week.first),
+            Ok(date(2024, 3, 10))
+        );
+        conf::set_week_start(Weekday::Mon);
+    }
+
+    #[test]
+    fn parse_times() {
+        let now = today().and_hms_opt(10, 30, 0).unwrap();
+        let time = |hour: u32, minute: u32| Ok(today().and_hms_opt(hour, minute, 0).unwrap());
+
+        assert_eq!(parse_time("now", now), Ok(now));
+        assert_eq!(parse_time("09:15", now), time(9, 15));
+        assert_eq!(parse_time("9am", now), time(9, 0));
+        assert_eq!(parse_time("12am", now), time(0, 0));
+        assert_eq!(parse_time("2:30 PM", now), time(14, 30));
+        assert_eq!(parse_time("12pm", now), time(12, 0));
+        assert_eq!(parse_time("-15m", now), time(10, 15));
+        assert_eq!(parse_time("-1h30m", now), time(9, 0));
+        assert!(parse_time("13pm", now).is_err());
+        assert!(parse_time("-15", now).is_err());
+        assert!(parse_time("soon", now).is_err());
+        assert!(parse_time("-99999999999h", now).is_err());
+    }
+
+    #[test]
+    fn parse_invalid_dates() {
+        assert!(parse_date("2021-13-01", today()).is_err());
+        assert!(parse_date("someday", today()).is_err());
+        assert!(parse_date("three days ago", today()).is_err());
+        assert!(parse_date("99999999 days ago", today()).is_err());
+        assert!(parse_date("99999999999 weeks ago", today()).is_err());
+    }
+}
@@ -0,0 +1,88 @@
+use chrono::Duration;
+
+// parses a duration like `15m`, `4h`, `1h30m`, `90m` or `1.5h`
+//
+// a duration consists of one or more numbers, each followed by one of the units `h` (hours),
+// `m` (minutes) or `s` (seconds). Numbers may have a fractional part. The duration must be
+// greater than zero.
+pub fn parse_duration(duration_string: &str) -> Result<Duration, String> {
+    let mut seconds = 0.0;
+    let mut number = String::new();
+
+    for c in duration_string.trim().chars() {
+        match c {
+            '0'..='9' | '.' => number.push(c),
+            'h' | 'm' | 's' => {
+                let value: f64 = number
+                    .parse()
+                    .map_err(|_| format!("invalid number '{number}' in duration"))?;
+                seconds += value * unit_in_seconds(c);
+                number.clear();
+            }
+            c if c.is_whitespace() => {}
+            _ => {
+                return Err(format!(
+                    "invalid duration unit '{c}', expected 'h', 'm' or 's'"
+                ));
+            }
+        }
+    }
+
+    if !number.is_empty() {
+        return Err(format!(
+            "missing unit after '{number}', expected 'h', 'm' or 's' (e.g. 15m or 1h30m)"
+        ));
+    }
+
+    let duration = Duration::try_seconds(seconds.round() as i64)
+        .ok_or_else(|| format!("invalid duration '{duration_string}', the duration is too long"))?;
+
+    if duration <= Duration::zero() {
+        return Err(format!(
+            "invalid duration '{duration_string}', expected a duration greater than zero (e.g. 15m or 1h30m)"
+        ));
+    }
+
+    Ok(duration)
+}
+
+fn unit_in_seconds(unit: char) -> f64 {
+    match unit {
+        'h' => 3600.0,
+        'm' => 60.0,
+        _ => 1.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_single_units() {
+        assert_eq!(parse_duration("15m"), Ok(Duration::minutes(15)));
+        assert_eq!(parse_duration("90m"), Ok(Duration::minutes(90)));
+        assert_eq!(parse_duration("4h"), Ok(Duration::hours(4)));
+        assert_eq!(parse_duration("30s"), Ok(Duration::seconds(30)));
+    }
+
+    #[test]
+    fn parse_combined_and_fractional_units() {
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::minutes(90)));
+        assert_eq!(parse_duration("1h 30m"), Ok(Duration::minutes(90)));
+        assert_eq!(parse_duration("1.5h"), Ok(Duration::minutes(90)));
+        assert_eq!(parse_duration("0.25h"), Ok(Duration::minutes(15)));
+        assert_eq!(parse_duration("2m30s"), Ok(Duration::seconds(150)));
+    }
+
+    #[test]
+    fn parse_invalid_durations() {
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("15").is_err());
+        assert!(parse_duration("15d").is_err());
+        assert!(parse_duration("h").is_err());
+        assert!(parse_duration("1.2.3h").is_err());
+        assert!(parse_duration("0m").is_err());
+        assert!(parse_duration("99999999999999999h").is_err());
+    }
+}
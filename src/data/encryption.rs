@@ -0,0 +1,228 @@
+use std::cell::RefCell;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+
+use crate::data::config::{Config, ConfigError};
+use crate::data::settings;
+
+// the name of the table defining the encryption in the configuration file
+pub static CONFIG_TABLE: &str = "encryption";
+
+// the environment variable with the passphrase of encrypted activity logs
+pub static PASSPHRASE_VARIABLE: &str = "BARTIB_PASSPHRASE";
+
+// the beginning of files encrypted by gpg with `--armor`, which bartib writes
+static ARMOR_HEADER: &[u8] = b"-----BEGIN PGP MESSAGE-----";
+
+// how activity logs are encrypted and decrypted with gpg
+//
+// in the configuration file it is defined like this. With a recipient, logs are encrypted with the
+// key of the recipient. Otherwise they are encrypted with a passphrase, which is taken from the
+// environment variable `BARTIB_PASSPHRASE`, from the first line of the passphrase file or else
+// asked for by gpg:
+//
+//     [encryption]
+//     recipient = "me@example.com"
+//     passphrase_file = "~/.config/bartib/passphrase"
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EncryptionSettings {
+    pub recipient: Option<String>,
+    pub passphrase_file: Option<String>,
+}
+
+thread_local! {
+    static SETTINGS: RefCell<EncryptionSettings> = RefCell::new(EncryptionSettings::default());
+}
+
+impl EncryptionSettings {
+    pub fn from_config(config: &Config) -> Result<Self, ConfigError> {
+        let get_str = |key: &str| match config.get(CONFIG_TABLE, key) {
+            None => Ok(None),
+            Some(value) => value.as_str().map(|s| Some(s.to_string())).ok_or_else(|| {
+                ConfigError::InvalidSetting(
+                    format!("{CONFIG_TABLE}.{key}"),
+                    "expected a string".to_string(),
+                )
+            }),
+        };
+
+        Ok(Self {
+            recipient: get_str("recipient")?,
+            passphrase_file: get_str("passphrase_file")?.map(|path| settings::expand_home(&path)),
+        })
+    }
+
+    // the passphrase from the environment or the passphrase file, if there is one
+    fn get_passphrase(&self) -> io::Result<Option<String>> {
+        if let Ok(passphrase) = env::var(PASSPHRASE_VARIABLE)
+            && !passphrase.is_empty()
+        {
+            return Ok(Some(passphrase));
+        }
+
+        match &self.passphrase_file {
+            None => Ok(None),
+            Some(passphrase_file) => {
+                let content = fs::read_to_string(passphrase_file).map_err(|e| {
+                    io::Error::new(
+                        e.kind(),
+                        format!("Could not read passphrase file {passphrase_file}: {e}"),
+                    )
+                })?;
+                Ok(content.lines().next().map(str::to_string))
+            }
+        }
+    }
+}
+
+pub fn set_settings(settings: EncryptionSettings) {
+    SETTINGS.set(settings);
+}
+
+// whether a file has been encrypted, judged by its first bytes. Files which can not be read are
+// not encrypted
+#[must_use]
+pub fn is_encrypted(file_name: &str) -> bool {
+    let mut header = Vec::new();
+
+    File::open(file_name)
+        .and_then(|file| {
+            file.take(ARMOR_HEADER.len() as u64)
+                .read_to_end(&mut header)
+        })
+        .is_ok_and(|_| is_ciphertext(&header))
+}
+
+// whether a content has been encrypted by gpg, either with `--armor` or as binary. Binary messages
+// start with a packet of a key for the session, which is never the first byte of a line of text
+#[must_use]
+pub fn is_ciphertext(content: &[u8]) -> bool {
+    content.starts_with(ARMOR_HEADER)
+        || content
+            .first()
+            .is_some_and(|byte| matches!(byte, 0x84..=0x87 | 0x8c..=0x8f | 0xc1 | 0xc3))
+}
+
+// encrypts a text with the key of the recipient or with a passphrase
+pub fn encrypt(plaintext: &str) -> io::Result<Vec<u8>> {
+    let settings = SETTINGS.with_borrow(Clone::clone);
+    let mut gpg = Command::new("gpg");
+    gpg.args(["--quiet", "--armor"]);
+
+    let input = match &settings.recipient {
+        Some(recipient) => {
+            gpg.args(["--encrypt", "--recipient", recipient]);
+            plaintext.to_string()
+        }
+        None => match settings.get_passphrase()? {
+            Some(passphrase) => {
+                gpg.args(passphrase_arguments()).arg("--symmetric");
+                format!("{passphrase}\n{plaintext}")
+            }
+            None => {
+                gpg.arg("--symmetric");
+                plaintext.to_string()
+            }
+        },
+    };
+
+    run_gpg(&mut gpg, input.into_bytes())
+        .map_err(|e| io::Error::new(e.kind(), format!("Could not encrypt the activity log: {e}")))
+}
+
+// decrypts a text encrypted by `encrypt` or by gpg otherwise
+pub fn decrypt(ciphertext: &[u8]) -> io::Result<String> {
+    let settings = SETTINGS.with_borrow(Clone::clone);
+    let mut gpg = Command::new("gpg");
+    gpg.args(["--quiet", "--decrypt"]);
+
+    // the passphrase unlocks the secret key of a recipient, too
+    let input = match settings.get_passphrase()? {
+        Some(passphrase) => {
+            gpg.args(passphrase_arguments());
+            [format!("{passphrase}\n").as_bytes(), ciphertext].concat()
+        }
+        None => ciphertext.to_vec(),
+    };
+
+    let plaintext = run_gpg(&mut gpg, input).map_err(|e| {
+        io::Error::new(e.kind(), format!("Could not decrypt the activity log: {e}"))
+    })?;
+
+    String::from_utf8(plaintext).map_err(io::Error::other)
+}
+
+// gpg reads the passphrase from the first line of its input instead of asking for it
+fn passphrase_arguments() -> [&'static str; 5] {
+    [
+        "--batch",
+        "--pinentry-mode",
+        "loopback",
+        "--passphrase-fd",
+        "0",
+    ]
+}
+
+// runs gpg with an input and returns its output. The input is written by another thread, as gpg
+// may not read all of it before its output has been read
+fn run_gpg(gpg: &mut Command, input: Vec<u8>) -> io::Result<Vec<u8>> {
+    let mut child = gpg
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| io::Error::new(e.kind(), format!("could not run gpg: {e}")))?;
+
+    let mut stdin = child.stdin.take();
+    let writer = thread::spawn(move || match stdin.as_mut() {
+        Some(stdin) => stdin.write_all(&input),
+        None => Ok(()),
+    });
+
+    let output = child.wait_with_output()?;
+    let written = writer
+        .join()
+        .unwrap_or_else(|_| Err(io::Error::other("could not write to gpg")));
+
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "gpg failed with {}",
+            output.status
+        )));
+    }
+    written?;
+
+    Ok(output.stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_ciphertext() {
+        assert!(is_ciphertext(b"-----BEGIN PGP MESSAGE-----\n\njA0ECQMI..."));
+        assert!(is_ciphertext(&[0x8c, 0x0d, 0x04, 0x09]));
+        assert!(is_ciphertext(&[0xc1, 0x4c]));
+        assert!(!is_ciphertext(
+            b"2024-03-01 09:00 - 2024-03-01 10:00 | p | d"
+        ));
+        assert!(!is_ciphertext("\u{feff}2024-03-01 09:00".as_bytes()));
+        assert!(!is_ciphertext(b""));
+    }
+
+    #[test]
+    fn encryption_settings() {
+        let config: Config = "[encryption]\nrecipient = \"me@example.com\""
+            .parse()
+            .unwrap();
+        let settings = EncryptionSettings::from_config(&config).unwrap();
+        assert_eq!(settings.recipient.as_deref(), Some("me@example.com"));
+        assert_eq!(settings.passphrase_file, None);
+
+        let invalid: Config = "[encryption]\nrecipient = 1".parse().unwrap();
+        assert!(EncryptionSettings::from_config(&invalid).is_err());
+    }
+}
@@ -1,6 +1,8 @@
 use crate::data::activity::Activity;
 
-use chrono::{Datelike, Duration, NaiveDate};
+use crate::data::date_parser::DateRange;
+
+use chrono::{Datelike, NaiveDate};
 
 pub struct Filters {}
 
@@ -12,7 +14,7 @@ impl Filters {
         move |activity: &&Activity| activity.start.date() == today
     }
     pub fn current_week(today: NaiveDate) -> impl Fn(&&Activity) -> bool {
-        let from_date = today - Duration::days(i64::from(today.weekday().num_days_from_monday()));
+        let from_date = DateRange::week_of(today).first;
         let to_date = today;
         move |activity: &&Activity| {
             activity.start.date() >= from_date && activity.start.date() <= to_date
@@ -29,7 +31,7 @@ impl Filters {
 
 #[cfg(test)]
 mod tests {
-    use chrono::NaiveDateTime;
+    use chrono::{Duration, NaiveDateTime};
 
     use crate::data::activity;
 
@@ -85,30 +87,60 @@ mod tests {
             description: "d0".to_string(),
             start: date(2024, 2, 11),
             end: Some(date(2024, 2, 11) + Duration::hours(2)),
+            start_offset: None,
+            end_offset: None,
+            tags: Vec::new(),
+            issue: None,
+            pauses: Vec::new(),
+            notes: Vec::new(),
         };
         let a1 = activity::Activity {
             project: "p1".to_string(),
             description: "d1".to_string(),
             start: date(2024, 3, 11),
             end: Some(date(2024, 3, 11) + Duration::hours(2)),
+            start_offset: None,
+            end_offset: None,
+            tags: Vec::new(),
+            issue: None,
+            pauses: Vec::new(),
+            notes: Vec::new(),
         };
         let a2 = activity::Activity {
             project: "p1".to_string(),
             description: "d2".to_string(),
             start: date(2024, 3, 18),
             end: Some(date(2024, 3, 18) + Duration::hours(2)),
+            start_offset: None,
+            end_offset: None,
+            tags: Vec::new(),
+            issue: None,
+            pauses: Vec::new(),
+            notes: Vec::new(),
         };
         let a3 = activity::Activity {
             project: "p1".to_string(),
             description: "d3".to_string(),
             start: date(2024, 3, 19),
             end: Some(date(2024, 3, 19) + Duration::hours(2)),
+            start_offset: None,
+            end_offset: None,
+            tags: Vec::new(),
+            issue: None,
+            pauses: Vec::new(),
+            notes: Vec::new(),
         };
         let a4 = activity::Activity {
             project: "p1".to_string(),
             description: "d4".to_string(),
             start: date(2024, 3, 19),
             end: None,
+            start_offset: None,
+            end_offset: None,
+            tags: Vec::new(),
+            issue: None,
+            pauses: Vec::new(),
+            notes: Vec::new(),
         };
         vec![a0, a1, a2, a3, a4]
     }
@@ -1,10 +1,11 @@
-use chrono::{Datelike, Duration, Local, NaiveDate};
+use chrono::{Duration, Local, NaiveDate};
 use std::collections::HashSet;
 use wildmatch::WildMatch;
 
 use crate::data::activity;
 use crate::data::activity::Activity;
 use crate::data::bartib_file;
+use crate::data::date_parser::DateRange;
 use crate::data::filter::Filters;
 
 pub struct ActivityFilter<'a> {
@@ -13,13 +14,17 @@ pub struct ActivityFilter<'a> {
     pub to_date: Option<NaiveDate>,
     pub date: Option<NaiveDate>,
     pub project: Option<&'a str>,
+    pub tags: &'a [String],
 }
 
+#[derive(Default)]
 pub struct DatePresetArgs {
     pub today: bool,
     pub yesterday: bool,
     pub current_week: bool,
     pub last_week: bool,
+    pub current_month: bool,
+    pub last_month: bool,
 }
 
 impl<'a> ActivityFilter<'a> {
@@ -27,18 +32,31 @@ impl<'a> ActivityFilter<'a> {
         number_of_activities: Option<usize>,
         from_date: Option<NaiveDate>,
         to_date: Option<NaiveDate>,
-        date: Option<NaiveDate>,
+        date: Option<DateRange>,
         project: Option<&'a str>,
+        tags: &'a [String],
         date_presets: DatePresetArgs,
     ) -> Self {
         let mut filter = Self {
             number_of_activities,
             from_date,
             to_date,
-            date,
+            date: None,
             project,
+            tags,
         };
 
+        // a range of dates like a month is filtered like `--from` and `--to`
+        if let Some(range) = date {
+            match range.single_date() {
+                Some(date) => filter.date = Some(date),
+                None => {
+                    filter.from_date = Some(range.first);
+                    filter.to_date = Some(range.last);
+                }
+            }
+        }
+
         let now = Local::now().naive_local().date();
         if date_presets.today {
             filter.date = Some(now);
@@ -49,40 +67,94 @@ impl<'a> ActivityFilter<'a> {
         }
 
         if date_presets.current_week {
-            filter.from_date =
-                Some(now - Duration::days(i64::from(now.weekday().num_days_from_monday())));
-            filter.to_date = Some(
-                now - Duration::days(i64::from(now.weekday().num_days_from_monday()))
-                    + Duration::days(6),
-            );
+            let week = DateRange::week_of(now);
+            filter.from_date = Some(week.first);
+            filter.to_date = Some(week.last);
         }
 
         if date_presets.last_week {
-            filter.from_date = Some(
-                now
-                    - Duration::days(i64::from(now.weekday().num_days_from_monday()))
-                    - Duration::weeks(1),
-            );
-            filter.to_date = Some(
-                now
-                    - Duration::days(i64::from(now.weekday().num_days_from_monday()))
-                    - Duration::weeks(1)
-                    + Duration::days(6),
-            )
+            let week = DateRange::week_of(now - Duration::weeks(1));
+            filter.from_date = Some(week.first);
+            filter.to_date = Some(week.last);
+        }
+
+        if date_presets.current_month {
+            let month = DateRange::month_of(now);
+            filter.from_date = Some(month.first);
+            filter.to_date = Some(month.last);
+        }
+
+        if date_presets.last_month {
+            let month = DateRange::month_before(now);
+            filter.from_date = Some(month.first);
+            filter.to_date = Some(month.last);
         }
 
         filter
     }
+
+    // the first and the last date an activity may start at to match the filter, if limited
+    #[must_use]
+    pub fn date_range(&self) -> (Option<NaiveDate>, Option<NaiveDate>) {
+        match self.date {
+            Some(date) => (Some(date), Some(date)),
+            None => (self.from_date, self.to_date),
+        }
+    }
+
+    // the same filter for another range of dates
+    #[must_use]
+    pub fn with_date_range(&self, range: DateRange) -> Self {
+        Self {
+            number_of_activities: self.number_of_activities,
+            from_date: Some(range.first),
+            to_date: Some(range.last),
+            date: None,
+            project: self.project,
+            tags: self.tags,
+        }
+    }
+
+    // the range of dates whose activities have to be read to find all activities matching the
+    // filter. It starts a day earlier, as activities may run into the range past midnight
+    #[must_use]
+    pub fn date_range_to_read(&self) -> (Option<NaiveDate>, Option<NaiveDate>) {
+        let (from_date, to_date) = self.date_range();
+        (
+            from_date.map(|date| date.pred_opt().unwrap_or(date)),
+            to_date,
+        )
+    }
 }
 
+// the distinct descriptions and projects of all activities or, if a project is given, of the
+// activities of this project and its subprojects only
 #[must_use]
-pub fn get_descriptions_and_projects(
-    file_content: &[bartib_file::Line],
-) -> Vec<(&String, &String)> {
-    let mut activities: Vec<&activity::Activity> = get_activities(file_content).collect();
+pub fn get_descriptions_and_projects<'a>(
+    file_content: &'a [bartib_file::Line],
+    project: Option<&str>,
+) -> Vec<(&'a String, &'a String)> {
+    let mut activities: Vec<&activity::Activity> = get_activities(file_content)
+        .filter(|activity| project.is_none_or(|p| matches_project(activity, p)))
+        .collect();
     get_descriptions_and_projects_from_activities(&mut activities)
 }
 
+// the number of distinct descriptions and projects, without warnings about invalid lines
+#[must_use]
+pub fn count_descriptions_and_projects(
+    file_content: &[bartib_file::Line],
+    project: Option<&str>,
+) -> usize {
+    file_content
+        .iter()
+        .filter_map(|line| line.activity.as_ref().ok())
+        .filter(|activity| project.is_none_or(|p| matches_project(activity, p)))
+        .map(|activity| (&activity.description, &activity.project))
+        .collect::<HashSet<_>>()
+        .len()
+}
+
 fn get_descriptions_and_projects_from_activities<'a>(
     activities: &mut [&'a Activity],
 ) -> Vec<(&'a String, &'a String)> {
@@ -127,10 +199,14 @@ pub fn get_activities(
         .filter_map(|line: &bartib_file::Line| match &line.activity {
             Ok(activity) => Some(activity),
             Err(_) => {
-                println!(
-                    "Warning: Ignoring line {}. Please see `bartib check` for further information",
-                    line.line_number.unwrap_or(0),
-                );
+                match line.line_number {
+                    Some(line_number) => println!(
+                        "Warning: Ignoring line {line_number}. Please see `bartib check` for further information",
+                    ),
+                    None => println!(
+                        "Warning: Ignoring an invalid line. Please see `bartib check` for further information",
+                    ),
+                }
                 None
             }
         })
@@ -140,30 +216,27 @@ pub fn filter_activities<'a>(
     activities: Vec<&'a activity::Activity>,
     filter: &'a ActivityFilter,
 ) -> Vec<&'a activity::Activity> {
-    let from_date: NaiveDate;
-    let to_date: NaiveDate;
-
-    if let Some(date) = filter.date {
-        from_date = date;
-        to_date = date;
-    } else {
-        from_date = filter.from_date.unwrap_or(NaiveDate::MIN);
-        to_date = filter.to_date.unwrap_or(NaiveDate::MAX);
-    }
+    let (from_date, to_date) = filter.date_range();
+    let from_date = from_date.unwrap_or(NaiveDate::MIN);
+    let to_date = to_date.unwrap_or(NaiveDate::MAX);
 
     activities
         .into_iter()
         .filter(move |activity| {
             activity.start.date() >= from_date && activity.start.date() <= to_date
         })
-        .filter(move |activity| {
-            filter
-                .project
-                .is_none_or(|p| WildMatch::new(p).matches(&activity.project))
-        })
+        .filter(move |activity| filter.project.is_none_or(|p| matches_project(activity, p)))
+        .filter(move |activity| activity.has_tags(filter.tags))
         .collect()
 }
 
+// whether the project of an activity or one of its parent projects matches a pattern like
+// `client*`
+fn matches_project(activity: &activity::Activity, project: &str) -> bool {
+    let project_pattern = WildMatch::new(project);
+    activity::project_hierarchy(&activity.project).any(|p| project_pattern.matches(p))
+}
+
 #[must_use]
 pub fn get_last_activity_by_end(file_content: &[bartib_file::Line]) -> Option<&activity::Activity> {
     get_activities(file_content)
@@ -182,6 +255,22 @@ pub fn get_last_activity_by_start(
     get_activities(file_content).max_by_key(|activity| activity.start)
 }
 
+// the indexes of the lines with activities, ordered from the latest to the earliest start
+//
+// the position of an activity in this list is its number for `edit-activity` and `delete`. The
+// number 0 refers to the latest activity, just like in `last`.
+#[must_use]
+pub fn get_numbered_line_indexes(file_content: &[bartib_file::Line]) -> Vec<usize> {
+    let mut indexes: Vec<(usize, &activity::Activity)> = file_content
+        .iter()
+        .enumerate()
+        .filter_map(|(i, line)| line.activity.as_ref().ok().map(|activity| (i, activity)))
+        .collect();
+
+    indexes.sort_by_key(|(i, activity)| std::cmp::Reverse((activity.start, *i)));
+    indexes.into_iter().map(|(i, _)| i).collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -207,6 +296,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn get_descriptions_and_projects_of_project() {
+        let file_content: Vec<bartib_file::Line> = [
+            "2024-03-01 09:00 - 2024-03-01 10:00 | client/api | d1",
+            "2024-03-01 10:00 - 2024-03-01 11:00 | other | d2",
+            "2024-03-01 11:00 - 2024-03-01 12:00 | client | d3",
+        ]
+        .iter()
+        .map(|line| bartib_file::Line::new(line, 0))
+        .collect();
+
+        assert_eq!(
+            get_descriptions_and_projects(&file_content, Some("client")),
+            vec![
+                (&"d1".to_string(), &"client/api".to_string()),
+                (&"d3".to_string(), &"client".to_string())
+            ]
+        );
+        assert_eq!(
+            count_descriptions_and_projects(&file_content, Some("other")),
+            1
+        );
+        assert_eq!(count_descriptions_and_projects(&file_content, None), 3);
+    }
+
     #[test]
     fn get_descriptions_and_projects_test_restarted_activity() {
         let a1 = activity::Activity::start("p1".to_string(), "d1".to_string(), None);
@@ -227,4 +341,47 @@ mod tests {
             (&"d1".to_string(), &"p1".to_string())
         );
     }
+
+    #[test]
+    fn get_numbered_line_indexes_test() {
+        let file_content = vec![
+            bartib_file::Line::new("2024-03-01 10:00 - 2024-03-01 11:00 | p | second", 1),
+            bartib_file::Line::new("no activity", 2),
+            bartib_file::Line::new("2024-03-01 12:00 | p | fourth", 3),
+            bartib_file::Line::new("2024-03-01 09:00 - 2024-03-01 10:00 | p | first", 4),
+            bartib_file::Line::new("2024-03-01 10:00 - 2024-03-01 10:30 | p | third", 5),
+        ];
+
+        assert_eq!(get_numbered_line_indexes(&file_content), vec![2, 4, 0, 3]);
+    }
+
+    #[test]
+    fn filter_activities_of_subprojects() {
+        let file_content = vec![
+            bartib_file::Line::new("2024-03-01 09:00 | client/backend/api | a", 1),
+            bartib_file::Line::new("2024-03-01 10:00 | client/frontend | b", 2),
+            bartib_file::Line::new("2024-03-01 11:00 | client-x | c", 3),
+        ];
+        let activities: Vec<&Activity> = get_activities(&file_content).collect();
+
+        let filter_by_project = |project| {
+            let filter = ActivityFilter::new(
+                None,
+                None,
+                None,
+                None,
+                Some(project),
+                &[],
+                DatePresetArgs::default(),
+            );
+            filter_activities(activities.clone(), &filter)
+                .iter()
+                .map(|activity| activity.description.clone())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(filter_by_project("client"), vec!["a", "b"]);
+        assert_eq!(filter_by_project("client/backend"), vec!["a"]);
+        assert_eq!(filter_by_project("client*"), vec!["a", "b", "c"]);
+    }
 }
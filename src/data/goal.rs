@@ -0,0 +1,182 @@
+use chrono::{Duration, NaiveDate};
+use wildmatch::WildMatch;
+
+use crate::data::activity::{self, Activity};
+use crate::data::config::{Config, ConfigError, Table};
+use crate::data::date_parser::DateRange;
+use crate::data::duration_parser;
+
+// the name of the tables defining goals in the configuration file
+pub static CONFIG_TABLE: &str = "goal";
+
+// the period in which the time of a goal should be tracked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GoalPeriod {
+    Day,
+    Week,
+}
+
+// a duration that should be tracked per day or per week, optionally for some projects only
+//
+// in the configuration file a goal is defined like this:
+//
+//     [[goal]]
+//     period = "week"
+//     duration = "40h"
+//     project = "Client *"
+#[derive(Debug, Clone, PartialEq)]
+pub struct Goal {
+    pub period: GoalPeriod,
+    pub target: Duration,
+    // the projects the goal applies to. May contain `?` and `*` as wildcards
+    pub project: Option<String>,
+}
+
+// the time tracked so far for a goal
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoalProgress<'a> {
+    pub goal: &'a Goal,
+    pub tracked: Duration,
+}
+
+impl Goal {
+    pub fn from_table(table: &Table) -> Result<Self, ConfigError> {
+        let period = match table.get_str("period")? {
+            "day" => GoalPeriod::Day,
+            "week" => GoalPeriod::Week,
+            _ => {
+                return Err(ConfigError::InvalidSetting(
+                    table.qualified_key("period"),
+                    "expected `day` or `week`".to_string(),
+                ));
+            }
+        };
+
+        let target = duration_parser::parse_duration(table.get_str("duration")?)
+            .map_err(|e| ConfigError::InvalidSetting(table.qualified_key("duration"), e))?;
+
+        let project = match table.get("project") {
+            None => None,
+            Some(_) => Some(table.get_str("project")?.to_string()),
+        };
+
+        Ok(Self {
+            period,
+            target,
+            project,
+        })
+    }
+
+    // sums up the durations of the activities in the current period of the goal
+    #[must_use]
+    pub fn progress<'a>(&'a self, activities: &[&Activity], today: NaiveDate) -> GoalProgress<'a> {
+        let first_day = match self.period {
+            GoalPeriod::Day => today,
+            GoalPeriod::Week => DateRange::week_of(today).first,
+        };
+
+        let tracked = activities
+            .iter()
+            .filter(|activity| activity.start.date() >= first_day && activity.start.date() <= today)
+            .filter(|activity| {
+                self.project.as_ref().is_none_or(|p| {
+                    let project_pattern = WildMatch::new(p);
+                    activity::project_hierarchy(&activity.project)
+                        .any(|p| project_pattern.matches(p))
+                })
+            })
+            .map(|activity| activity.get_duration())
+            .sum();
+
+        GoalProgress {
+            goal: self,
+            tracked,
+        }
+    }
+}
+
+impl GoalProgress<'_> {
+    // the share of the goal that has been reached in percent. May be more than 100
+    #[must_use]
+    pub fn percent(&self) -> i64 {
+        self.tracked.num_seconds() * 100 / self.goal.target.num_seconds().max(1)
+    }
+
+    // the time left to reach the goal
+    #[must_use]
+    pub fn remaining(&self) -> Duration {
+        (self.goal.target - self.tracked).max(Duration::zero())
+    }
+}
+
+// reads all goals from the configuration
+pub fn get_goals(config: &Config) -> Result<Vec<Goal>, ConfigError> {
+    config.tables(CONFIG_TABLE).map(Goal::from_table).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::activity::activity;
+
+    #[test]
+    fn parse_goals() {
+        let config: Config = "[[goal]]\nperiod = \"day\"\nduration = \"8h\"\n[[goal]]\nperiod = \"week\"\nduration = \"10h\"\nproject = \"Client *\""
+            .parse()
+            .unwrap();
+        let goals = get_goals(&config).unwrap();
+
+        assert_eq!(goals.len(), 2);
+        assert_eq!(goals[0].period, GoalPeriod::Day);
+        assert_eq!(goals[0].target, Duration::hours(8));
+        assert_eq!(goals[0].project, None);
+        assert_eq!(goals[1].period, GoalPeriod::Week);
+        assert_eq!(goals[1].project.as_deref(), Some("Client *"));
+    }
+
+    #[test]
+    fn parse_invalid_goals() {
+        let month: Config = "[[goal]]\nperiod = \"month\"\nduration = \"8h\""
+            .parse()
+            .unwrap();
+        assert!(get_goals(&month).is_err());
+
+        let duration: Config = "[[goal]]\nperiod = \"day\"\nduration = \"8\""
+            .parse()
+            .unwrap();
+        assert!(get_goals(&duration).is_err());
+
+        let missing: Config = "[[goal]]\nperiod = \"day\"".parse().unwrap();
+        assert!(get_goals(&missing).is_err());
+    }
+
+    #[test]
+    fn goal_progress() {
+        // a tuesday
+        let today = NaiveDate::from_ymd_opt(2024, 3, 19).unwrap();
+        let a1 = activity("Client A", "2024-03-18 09:00", "2024-03-18 13:00");
+        let a2 = activity("Client A", "2024-03-19 09:00", "2024-03-19 11:00");
+        let a3 = activity("Internal", "2024-03-19 11:00", "2024-03-19 13:00");
+        let activities = vec![&a1, &a2, &a3];
+
+        let daily = Goal {
+            period: GoalPeriod::Day,
+            target: Duration::hours(8),
+            project: None,
+        };
+        let progress = daily.progress(&activities, today);
+        assert_eq!(progress.tracked, Duration::hours(4));
+        assert_eq!(progress.percent(), 50);
+        assert_eq!(progress.remaining(), Duration::hours(4));
+
+        let weekly = Goal {
+            period: GoalPeriod::Week,
+            target: Duration::hours(5),
+            project: Some("Client *".to_string()),
+        };
+        let progress = weekly.progress(&activities, today);
+        assert_eq!(progress.tracked, Duration::hours(6));
+        assert_eq!(progress.percent(), 120);
+        assert_eq!(progress.remaining(), Duration::zero());
+    }
+}
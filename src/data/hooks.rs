@@ -0,0 +1,195 @@
+use std::fmt;
+
+use crate::conf;
+use crate::data::activity::Activity;
+use crate::data::config::{Config, ConfigError};
+
+// the name of the table defining hooks in the configuration file
+pub static CONFIG_TABLE: &str = "hooks";
+
+// the changes of activities which hooks are run for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    Start,
+    Stop,
+    Change,
+    Cancel,
+}
+
+impl Event {
+    const ALL: [Self; 4] = [Self::Start, Self::Stop, Self::Change, Self::Cancel];
+
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Start => "start",
+            Self::Stop => "stop",
+            Self::Change => "change",
+            Self::Cancel => "cancel",
+        }
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+// shell commands run after activities have been started, stopped, changed or canceled, e.g. to
+// update a chat status. A webhook receives all events as JSON
+//
+// in the configuration file the hooks are defined like this:
+//
+//     [hooks]
+//     start = "notify-send \"Started $BARTIB_DESCRIPTION\""
+//     stop = "~/bin/lights off"
+//     webhook = "https://example.com/bartib"
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Hooks {
+    commands: Vec<(Event, String)>,
+    pub webhook: Option<String>,
+}
+
+impl Hooks {
+    pub fn from_config(config: &Config) -> Result<Self, ConfigError> {
+        let mut hooks = Self::default();
+
+        for table in config.tables(CONFIG_TABLE) {
+            for (key, value) in &table.entries {
+                let command = value.as_str().ok_or_else(|| {
+                    ConfigError::InvalidSetting(
+                        table.qualified_key(key),
+                        "expected a command".to_string(),
+                    )
+                })?;
+
+                if key == "webhook" {
+                    hooks.webhook = Some(command.to_string());
+                    continue;
+                }
+
+                let event = Event::ALL
+                    .into_iter()
+                    .find(|event| event.name() == key)
+                    .ok_or_else(|| {
+                        ConfigError::InvalidSetting(
+                            table.qualified_key(key),
+                            "expected one of `start`, `stop`, `change`, `cancel` or `webhook`"
+                                .to_string(),
+                        )
+                    })?;
+                hooks.commands.retain(|(e, _)| *e != event);
+                hooks.commands.push((event, command.to_string()));
+            }
+        }
+
+        Ok(hooks)
+    }
+
+    // the command to run for an event, if any
+    #[must_use]
+    pub fn command(&self, event: Event) -> Option<&str> {
+        self.commands
+            .iter()
+            .find(|(e, _)| *e == event)
+            .map(|(_, command)| command.as_str())
+    }
+
+    // whether anything is run for an event
+    #[must_use]
+    pub fn handles(&self, event: Event) -> bool {
+        self.webhook.is_some() || self.command(event).is_some()
+    }
+}
+
+// the details of the activity passed to the commands of hooks as environment variables
+#[must_use]
+pub fn environment(event: Event, activity: &Activity) -> Vec<(&'static str, String)> {
+    let format_datetime = conf::format_datetime();
+
+    vec![
+        ("BARTIB_EVENT", event.name().to_string()),
+        ("BARTIB_PROJECT", activity.project.clone()),
+        ("BARTIB_DESCRIPTION", activity.description.clone()),
+        ("BARTIB_TAGS", activity.tags.join(",")),
+        (
+            "BARTIB_START",
+            activity.start.format(format_datetime).to_string(),
+        ),
+        (
+            "BARTIB_END",
+            activity
+                .end
+                .map(|end| end.format(format_datetime).to_string())
+                .unwrap_or_default(),
+        ),
+        (
+            "BARTIB_DURATION",
+            activity.get_duration().num_seconds().to_string(),
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDateTime;
+
+    use super::*;
+
+    #[test]
+    fn hooks_from_config() {
+        let config: Config = "[hooks]\nstart = \"echo started\"\nstop = \"echo stopped\"\nwebhook = \"https://example.com/hook\"\n[hooks]\nstart = \"echo again\""
+            .parse()
+            .unwrap();
+        let hooks = Hooks::from_config(&config).unwrap();
+
+        assert_eq!(hooks.command(Event::Start), Some("echo again"));
+        assert_eq!(hooks.command(Event::Stop), Some("echo stopped"));
+        assert_eq!(hooks.command(Event::Cancel), None);
+        assert_eq!(hooks.webhook.as_deref(), Some("https://example.com/hook"));
+        assert!(hooks.handles(Event::Cancel));
+
+        assert!(!Hooks::default().handles(Event::Start));
+
+        for invalid in ["[hooks]\nstart = 1", "[hooks]\nresume = \"echo\""] {
+            let config: Config = invalid.parse().unwrap();
+            assert!(Hooks::from_config(&config).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn environment_of_activity() {
+        let time = |s: &str| NaiveDateTime::parse_from_str(s, "%F %R").unwrap();
+        let mut activity = Activity::start(
+            "p".to_string(),
+            "d".to_string(),
+            Some(time("2024-03-11 09:00")),
+        )
+        .with_tags(vec!["a".to_string(), "b".to_string()]);
+        activity.stop(Some(time("2024-03-11 10:30")));
+
+        assert_eq!(
+            environment(Event::Stop, &activity),
+            vec![
+                ("BARTIB_EVENT", "stop".to_string()),
+                ("BARTIB_PROJECT", "p".to_string()),
+                ("BARTIB_DESCRIPTION", "d".to_string()),
+                ("BARTIB_TAGS", "a,b".to_string()),
+                (
+                    "BARTIB_START",
+                    time("2024-03-11 09:00")
+                        .format(conf::format_datetime())
+                        .to_string()
+                ),
+                (
+                    "BARTIB_END",
+                    time("2024-03-11 10:30")
+                        .format(conf::format_datetime())
+                        .to_string()
+                ),
+                ("BARTIB_DURATION", "5400".to_string()),
+            ]
+        );
+    }
+}
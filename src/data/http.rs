@@ -0,0 +1,273 @@
+use std::io::{self, BufRead, Read};
+use std::net::{IpAddr, SocketAddr};
+
+use thiserror::Error;
+
+// requests with larger headers or bodies are rejected
+static MAX_HEADER_LINES: usize = 100;
+static MAX_BODY_LENGTH: usize = 64 * 1024;
+
+// a request to the HTTP API of `bartib serve`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub query: Vec<(String, String)>,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+#[derive(Error, Debug)]
+pub enum HttpError {
+    #[error("malformed request: {0}")]
+    Malformed(String),
+    #[error("the request is too large")]
+    TooLarge,
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+impl Request {
+    // the value of a header, whose name is case insensitive
+    #[must_use]
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    // the value of a parameter of the query string. Empty values count as missing
+    #[must_use]
+    pub fn query_param(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(key, value)| key == name && !value.is_empty())
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+// whether the `Host` header of a request names the address the server listens on. Otherwise web
+// pages could reach a server on the loopback address by a domain resolving to it (DNS rebinding).
+// A server listening on all addresses accepts every name, as it does not know them
+#[must_use]
+pub fn is_allowed_host(host: &str, local_address: SocketAddr) -> bool {
+    let (name, port) = match host.rsplit_once(':') {
+        Some((name, port)) if !port.contains(']') => (name, port.parse::<u16>().ok()),
+        _ => (host, Some(80)),
+    };
+    if port != Some(local_address.port()) {
+        return false;
+    }
+
+    let name = name.trim_start_matches('[').trim_end_matches(']');
+    let ip = local_address.ip();
+    if ip.is_unspecified() {
+        return true;
+    }
+
+    match name.parse::<IpAddr>() {
+        Ok(name) => name == ip || (ip.is_loopback() && name.is_loopback()),
+        Err(_) => ip.is_loopback() && name.eq_ignore_ascii_case("localhost"),
+    }
+}
+
+// whether a request may come from its `Origin`, which browsers send with requests from web pages.
+// Browsers send forms to other origins without asking the server first, so requests from an
+// origin which is not allowed are refused, not only kept from reading the response. Requests
+// without an origin do not come from web pages
+#[must_use]
+pub fn is_allowed_origin(origin: Option<&str>, allowed_origin: Option<&str>) -> bool {
+    origin.is_none_or(|origin| {
+        allowed_origin.is_some_and(|allowed| allowed == "*" || allowed == origin)
+    })
+}
+
+// reads a request line, its headers and a body of the length given by `Content-Length`
+pub fn read_request(reader: &mut impl BufRead) -> Result<Request, HttpError> {
+    let request_line = read_line(reader)?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target), Some(_version), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(HttpError::Malformed(format!(
+            "invalid request line `{request_line}`"
+        )));
+    };
+
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    let mut headers = Vec::new();
+    loop {
+        let line = read_line(reader)?;
+        if line.is_empty() {
+            break;
+        }
+        if headers.len() >= MAX_HEADER_LINES {
+            return Err(HttpError::TooLarge);
+        }
+
+        let (name, value) = line
+            .split_once(':')
+            .ok_or_else(|| HttpError::Malformed(format!("invalid header `{line}`")))?;
+        headers.push((name.trim().to_string(), value.trim().to_string()));
+    }
+
+    let mut request = Request {
+        method: method.to_string(),
+        path: decode_component(path)?,
+        query: parse_query(query)?,
+        headers,
+        body: String::new(),
+    };
+
+    let content_length = match request.header("Content-Length") {
+        Some(length) => length
+            .parse::<usize>()
+            .map_err(|_| HttpError::Malformed(format!("invalid content length `{length}`")))?,
+        None => 0,
+    };
+    if content_length > MAX_BODY_LENGTH {
+        return Err(HttpError::TooLarge);
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    request.body = String::from_utf8(body)
+        .map_err(|_| HttpError::Malformed("the body is not valid UTF-8".to_string()))?;
+
+    Ok(request)
+}
+
+fn read_line(reader: &mut impl BufRead) -> Result<String, HttpError> {
+    let mut line = Vec::new();
+    reader
+        .by_ref()
+        .take(8 * 1024)
+        .read_until(b'\n', &mut line)?;
+
+    if !line.ends_with(b"\n") {
+        return Err(if line.is_empty() {
+            HttpError::Malformed("the request ended unexpectedly".to_string())
+        } else {
+            HttpError::TooLarge
+        });
+    }
+
+    String::from_utf8(line)
+        .map(|line| line.trim_end_matches(['\r', '\n']).to_string())
+        .map_err(|_| HttpError::Malformed("the request is not valid UTF-8".to_string()))
+}
+
+// splits a query string like `from=2024-03-01&project=Client%2FBackend` into its parameters
+fn parse_query(query: &str) -> Result<Vec<(String, String)>, HttpError> {
+    query
+        .split('&')
+        .filter(|parameter| !parameter.is_empty())
+        .map(|parameter| {
+            let (key, value) = parameter.split_once('=').unwrap_or((parameter, ""));
+            Ok((decode_component(key)?, decode_component(value)?))
+        })
+        .collect()
+}
+
+// decodes the percent-encoding of URLs, e.g. `Client%2FBackend` to `Client/Backend`. In query
+// strings `+` stands for a space
+fn decode_component(component: &str) -> Result<String, HttpError> {
+    let invalid = || HttpError::Malformed(format!("invalid percent-encoding in `{component}`"));
+    let mut bytes = Vec::with_capacity(component.len());
+    let mut rest = component.bytes();
+
+    while let Some(byte) = rest.next() {
+        match byte {
+            b'%' => {
+                let hex = [
+                    rest.next().ok_or_else(invalid)?,
+                    rest.next().ok_or_else(invalid)?,
+                ];
+                let hex = std::str::from_utf8(&hex).map_err(|_| invalid())?;
+                bytes.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+            }
+            b'+' => bytes.push(b' '),
+            byte => bytes.push(byte),
+        }
+    }
+
+    String::from_utf8(bytes).map_err(|_| invalid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_request_with_query_and_body() {
+        let mut input = "POST /start?project=Client%2FBackend&description=Fix+login&empty= HTTP/1.1\r\nHost: localhost\r\ncontent-length: 5\r\n\r\nhello"
+            .as_bytes();
+        let request = read_request(&mut input).unwrap();
+
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/start");
+        assert_eq!(request.query_param("project"), Some("Client/Backend"));
+        assert_eq!(request.query_param("description"), Some("Fix login"));
+        assert_eq!(request.query_param("empty"), None);
+        assert_eq!(request.header("Content-Length"), Some("5"));
+        assert_eq!(request.body, "hello");
+    }
+
+    #[test]
+    fn allowed_hosts() {
+        let loopback: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+        assert!(is_allowed_host("localhost:8080", loopback));
+        assert!(is_allowed_host("127.0.0.1:8080", loopback));
+        assert!(is_allowed_host("[::1]:8080", loopback));
+        assert!(!is_allowed_host("localhost:9090", loopback));
+        assert!(!is_allowed_host("localhost", loopback));
+        assert!(!is_allowed_host("attacker.example:8080", loopback));
+
+        let lan: SocketAddr = "192.168.1.2:80".parse().unwrap();
+        assert!(is_allowed_host("192.168.1.2", lan));
+        assert!(!is_allowed_host("localhost", lan));
+        assert!(!is_allowed_host("192.168.1.3", lan));
+
+        let all: SocketAddr = "0.0.0.0:8080".parse().unwrap();
+        assert!(is_allowed_host("laptop.local:8080", all));
+        assert!(!is_allowed_host("laptop.local:80", all));
+    }
+
+    #[test]
+    fn allowed_origins() {
+        let extension = "moz-extension://1234";
+        assert!(is_allowed_origin(None, None));
+        assert!(!is_allowed_origin(Some("https://attacker.example"), None));
+        assert!(is_allowed_origin(Some(extension), Some(extension)));
+        assert!(!is_allowed_origin(
+            Some("https://attacker.example"),
+            Some(extension)
+        ));
+        assert!(is_allowed_origin(
+            Some("https://attacker.example"),
+            Some("*")
+        ));
+    }
+
+    #[test]
+    fn read_invalid_requests() {
+        for input in [
+            "",
+            "GET /\r\n\r\n",
+            "GET / HTTP/1.1\r\nHost\r\n\r\n",
+            "GET /?a=%4 HTTP/1.1\r\n\r\n",
+            "GET / HTTP/1.1\r\nContent-Length: x\r\n\r\n",
+            "GET / HTTP/1.1\r\nContent-Length: 10\r\n\r\nshort",
+        ] {
+            assert!(read_request(&mut input.as_bytes()).is_err(), "{input:?}");
+        }
+
+        let too_large = format!("POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n", 1024 * 1024);
+        assert!(matches!(
+            read_request(&mut too_large.as_bytes()),
+            Err(HttpError::TooLarge)
+        ));
+    }
+}
@@ -0,0 +1,217 @@
+use anyhow::{Context, Result};
+use chrono::NaiveDateTime;
+use std::fs;
+use std::path::Path;
+
+use crate::data::activity::{Activity, Pause};
+use crate::data::bartib_file::Line;
+
+// the project the idle time is logged for if no other project is given
+pub static DEFAULT_IDLE_PROJECT: &str = "idle";
+
+// the description of activities logging idle time
+static IDLE_DESCRIPTION: &str = "Away from keyboard";
+
+// the format of the begin of the idle time in the idle file
+static FORMAT_IDLE_START: &str = "%Y-%m-%d %H:%M:%S";
+
+// the file that keeps the begin of the current idle time, e.g. `activities.bartib.idle`
+#[must_use]
+pub fn get_idle_file_name(file_name: &str) -> String {
+    format!("{file_name}.idle")
+}
+
+// the begin of the current idle time, if the user is idle
+pub fn get_idle_start(file_name: &str) -> Result<Option<NaiveDateTime>> {
+    let idle_file_name = get_idle_file_name(file_name);
+
+    if !Path::new(&idle_file_name).exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(&idle_file_name)
+        .context(format!("Could not read from file: {idle_file_name}"))?;
+
+    NaiveDateTime::parse_from_str(content.trim(), FORMAT_IDLE_START)
+        .map(Some)
+        .context(format!("Could not read from file: {idle_file_name}"))
+}
+
+// remembers the begin of the idle time until it ends
+pub fn save_idle_start(file_name: &str, time: NaiveDateTime) -> Result<()> {
+    let idle_file_name = get_idle_file_name(file_name);
+
+    fs::write(&idle_file_name, time.format(FORMAT_IDLE_START).to_string())
+        .context(format!("Could not write to file: {idle_file_name}"))
+}
+
+pub fn discard_idle_start(file_name: &str) -> Result<()> {
+    let idle_file_name = get_idle_file_name(file_name);
+
+    if Path::new(&idle_file_name).exists() {
+        fs::remove_file(&idle_file_name)
+            .context(format!("Could not remove file: {idle_file_name}"))?;
+    }
+
+    Ok(())
+}
+
+// removes the idle time from all running activities which have been started before it began
+//
+// without an idle project, the idle time becomes a pause of the activities. Otherwise the
+// activities are stopped when the idle time began and continued when it ended, and the idle time
+// is logged for the idle project. Paused activities are left alone. Returns the activities as
+// they were before.
+pub fn subtract_idle_time(
+    file_content: &mut Vec<Line>,
+    idle_start: NaiveDateTime,
+    idle_end: NaiveDateTime,
+    idle_project: Option<&str>,
+) -> Vec<Activity> {
+    let mut changed = Vec::new();
+    let mut continued = Vec::new();
+
+    for line in file_content.iter_mut() {
+        let Ok(activity) = &mut line.activity else {
+            continue;
+        };
+
+        if activity.is_stopped() || activity.is_paused() || activity.start >= idle_start {
+            continue;
+        }
+
+        changed.push(activity.clone());
+
+        if idle_project.is_some() {
+            activity.truncate(idle_start);
+            continued.push(
+                Activity::start(
+                    activity.project.clone(),
+                    activity.description.clone(),
+                    Some(idle_end),
+                )
+                .with_tags(activity.tags.clone())
+                .with_issue(activity.issue.clone()),
+            );
+        } else {
+            activity.pauses.push(Pause {
+                start: idle_start,
+                end: Some(idle_end),
+            });
+        }
+
+        line.set_changed();
+    }
+
+    if let Some(idle_project) = idle_project
+        && !changed.is_empty()
+    {
+        file_content.push(Line::for_activity(idle_activity(
+            idle_project,
+            idle_start,
+            idle_end,
+        )));
+        file_content.extend(continued.into_iter().map(Line::for_activity));
+    }
+
+    changed
+}
+
+// an activity logging the idle time for the idle project
+#[must_use]
+pub fn idle_activity(
+    idle_project: &str,
+    idle_start: NaiveDateTime,
+    idle_end: NaiveDateTime,
+) -> Activity {
+    let mut activity = Activity::start(
+        idle_project.to_string(),
+        IDLE_DESCRIPTION.to_string(),
+        Some(idle_start),
+    );
+    activity.stop(Some(idle_end));
+    activity
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn datetime(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn running(start: &str) -> Line {
+        Line::for_activity(
+            Activity::start("p".to_string(), "d".to_string(), Some(datetime(start)))
+                .with_tags(vec!["t".to_string()]),
+        )
+    }
+
+    fn activities(file_content: &[Line]) -> Vec<Activity> {
+        file_content
+            .iter()
+            .map(|line| line.activity.clone().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn subtract_idle_time_as_pause() {
+        let mut file_content = vec![running("2024-03-11 09:00"), running("2024-03-11 10:30")];
+
+        let changed = subtract_idle_time(
+            &mut file_content,
+            datetime("2024-03-11 10:00"),
+            datetime("2024-03-11 10:45"),
+            None,
+        );
+
+        assert_eq!(changed.len(), 1);
+        let activities = activities(&file_content);
+        assert_eq!(
+            activities[0].pauses,
+            vec![Pause {
+                start: datetime("2024-03-11 10:00"),
+                end: Some(datetime("2024-03-11 10:45"))
+            }]
+        );
+        assert!(activities[1].pauses.is_empty());
+    }
+
+    #[test]
+    fn split_off_idle_time() {
+        let mut file_content = vec![running("2024-03-11 09:00")];
+
+        subtract_idle_time(
+            &mut file_content,
+            datetime("2024-03-11 10:00"),
+            datetime("2024-03-11 10:45"),
+            Some(DEFAULT_IDLE_PROJECT),
+        );
+
+        let activities = activities(&file_content);
+        assert_eq!(activities.len(), 3);
+        assert_eq!(activities[0].end, Some(datetime("2024-03-11 10:00")));
+        assert_eq!(activities[1].project, "idle");
+        assert_eq!(activities[1].start, datetime("2024-03-11 10:00"));
+        assert_eq!(activities[1].end, Some(datetime("2024-03-11 10:45")));
+        assert_eq!(activities[2].start, datetime("2024-03-11 10:45"));
+        assert_eq!(activities[2].end, None);
+        assert_eq!(activities[2].tags, vec!["t".to_string()]);
+    }
+
+    #[test]
+    fn no_idle_time_without_running_activities() {
+        let mut file_content = vec![running("2024-03-11 11:00")];
+
+        let changed = subtract_idle_time(
+            &mut file_content,
+            datetime("2024-03-11 10:00"),
+            datetime("2024-03-11 10:45"),
+            Some(DEFAULT_IDLE_PROJECT),
+        );
+
+        assert!(changed.is_empty());
+        assert_eq!(file_content.len(), 1);
+    }
+}
@@ -0,0 +1,449 @@
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::time::UNIX_EPOCH;
+
+use crate::conf;
+use crate::data::activity::{self, Activity};
+use crate::data::bartib_file::{self, Line};
+use crate::data::encryption;
+
+// the first line of an index file, changed whenever the format of the index changes
+static INDEX_HEADER: &str = "bartib-index 1";
+
+static FORMAT_DATE: &str = "%Y-%m-%d";
+
+// the file caching where the activities of each day are in the activity log, e.g.
+// `activities.bartib.index`
+#[must_use]
+pub fn get_index_file_name(file_name: &str) -> String {
+    format!("{file_name}.index")
+}
+
+// the size and the modification time of a file in nanoseconds
+pub type FileState = (u64, u128);
+
+// what the lines of an entry of the index contain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Content {
+    // stopped activities started on that day
+    Stopped(NaiveDate),
+    // running activities started on that day
+    Running(NaiveDate),
+    // lines which are no activities
+    Invalid,
+}
+
+// consecutive lines of the activity log with the same content
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry {
+    content: Content,
+    first_line_number: usize,
+    line_count: usize,
+    // the byte offsets of the lines in the file
+    start: u64,
+    end: u64,
+}
+
+// the positions of the activities of each day in an activity log
+//
+// the index is only valid as long as the activity log has the same size and modification time as
+// when the index was created. Otherwise it is created again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Index {
+    file_state: FileState,
+    entries: Vec<Entry>,
+}
+
+impl Index {
+    // creates the index of lines given with their length in the file, including the line breaks,
+    // and the number of lines in the file they take together with their notes
+    fn from_lines<'a>(
+        lines: impl IntoIterator<Item = (u64, usize, Option<&'a Activity>)>,
+        file_state: FileState,
+    ) -> Self {
+        let mut index = Self {
+            file_state,
+            entries: Vec::new(),
+        };
+
+        for (length, line_count, activity) in lines {
+            index.add_line(length, line_count, activity);
+        }
+
+        index
+    }
+
+    fn add_line(&mut self, length: u64, line_count: usize, activity: Option<&Activity>) {
+        let content = match activity {
+            Some(activity) if activity.is_stopped() => Content::Stopped(activity.start.date()),
+            Some(activity) => Content::Running(activity.start.date()),
+            None => Content::Invalid,
+        };
+
+        match self.entries.last_mut() {
+            Some(entry) if entry.content == content => {
+                entry.line_count += line_count;
+                entry.end += length;
+            }
+            last_entry => {
+                let (line_number, offset) = last_entry.map_or((1, 0), |entry| {
+                    (entry.first_line_number + entry.line_count, entry.end)
+                });
+
+                self.entries.push(Entry {
+                    content,
+                    first_line_number: line_number,
+                    line_count,
+                    start: offset,
+                    end: offset + length,
+                });
+            }
+        }
+    }
+
+    fn has_running_activities(&self) -> bool {
+        self.entries
+            .iter()
+            .any(|entry| matches!(entry.content, Content::Running(_)))
+    }
+}
+
+impl std::fmt::Display for Index {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{INDEX_HEADER}")?;
+        writeln!(f, "{} {}", self.file_state.0, self.file_state.1)?;
+
+        for entry in &self.entries {
+            let (kind, date) = match entry.content {
+                Content::Stopped(date) => ("stopped", date.format(FORMAT_DATE).to_string()),
+                Content::Running(date) => ("running", date.format(FORMAT_DATE).to_string()),
+                Content::Invalid => ("invalid", "-".to_string()),
+            };
+
+            writeln!(
+                f,
+                "{kind} {date} {} {} {} {}",
+                entry.first_line_number, entry.line_count, entry.start, entry.end
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for Index {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines();
+
+        if lines.next() != Some(INDEX_HEADER) {
+            return Err(());
+        }
+
+        let file_state = match lines.next().ok_or(())?.split_once(' ') {
+            Some((size, modified)) => (
+                size.parse().map_err(|_| ())?,
+                modified.parse().map_err(|_| ())?,
+            ),
+            None => return Err(()),
+        };
+
+        let entries = lines
+            .map(|line| {
+                let parts: Vec<&str> = line.split(' ').collect();
+                let [kind, date, first_line_number, line_count, start, end] = parts[..] else {
+                    return Err(());
+                };
+                let date = || NaiveDate::parse_from_str(date, FORMAT_DATE).map_err(|_| ());
+
+                Ok(Entry {
+                    content: match kind {
+                        "stopped" => Content::Stopped(date()?),
+                        "running" => Content::Running(date()?),
+                        "invalid" => Content::Invalid,
+                        _ => return Err(()),
+                    },
+                    first_line_number: first_line_number.parse().map_err(|_| ())?,
+                    line_count: line_count.parse().map_err(|_| ())?,
+                    start: start.parse().map_err(|_| ())?,
+                    end: end.parse().map_err(|_| ())?,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            file_state,
+            entries,
+        })
+    }
+}
+
+// reads the lines of the activities started within a range of dates
+//
+// the running activities and the lines which are no activities are read as well, so that the
+// running activities may be shown and invalid lines may be reported. With an index (see the
+// setting `index`), only these lines are read from the file, otherwise the whole file is read.
+pub fn get_file_content_of_dates(
+    file_name: &str,
+    from_date: Option<NaiveDate>,
+    to_date: Option<NaiveDate>,
+) -> Result<Vec<Line>> {
+    if !is_used(file_name) || (from_date.is_none() && to_date.is_none()) {
+        return bartib_file::get_file_content(file_name);
+    }
+
+    let index = get_index(file_name)?;
+    let is_in_range = |date: NaiveDate| {
+        from_date.is_none_or(|from_date| from_date <= date)
+            && to_date.is_none_or(|to_date| date <= to_date)
+    };
+
+    let file_content = read_entries(file_name, &index, |content| match content {
+        Content::Stopped(date) => is_in_range(*date),
+        Content::Running(_) | Content::Invalid => true,
+    })?;
+
+    // the file has been changed while it was read
+    if get_file_state(file_name) != Some(index.file_state) {
+        return bartib_file::get_file_content(file_name);
+    }
+
+    Ok(file_content)
+}
+
+// whether activities are running according to the index, if there is an index
+pub fn has_running_activities(file_name: &str) -> Result<Option<bool>> {
+    if !is_used(file_name) {
+        return Ok(None);
+    }
+
+    Ok(Some(get_index(file_name)?.has_running_activities()))
+}
+
+// saves the index of the lines which have just been written to the file
+//
+// nothing is saved if the index is not used.
+pub fn update(file_name: &str, lines: &[(u64, usize, Option<&Activity>)]) {
+    if !is_used(file_name) {
+        return;
+    }
+
+    if let Some(file_state) = get_file_state(file_name) {
+        save_index(
+            file_name,
+            &Index::from_lines(lines.iter().copied(), file_state),
+        );
+    }
+}
+
+// adds a line which has just been appended to the file to the index
+//
+// the index must have been valid before the line has been appended. Otherwise it is left alone
+// and created again when it is used next.
+pub fn update_appended(
+    file_name: &str,
+    previous_file_state: Option<FileState>,
+    length: u64,
+    line_count: usize,
+    activity: Option<&Activity>,
+) {
+    if !is_used(file_name) {
+        return;
+    }
+
+    let Some(mut index) = load_index(file_name, previous_file_state) else {
+        return;
+    };
+
+    if let Some(file_state) = get_file_state(file_name) {
+        index.add_line(length, line_count, activity);
+        index.file_state = file_state;
+        save_index(file_name, &index);
+    }
+}
+
+#[must_use]
+pub fn get_file_state(file_name: &str) -> Option<FileState> {
+    let metadata = fs::metadata(file_name).ok()?;
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+
+    Some((metadata.len(), modified.as_nanos()))
+}
+
+// whether the index is used for a file (see the setting `index`). The lines of encrypted files can
+// not be read on their own, so they have no index
+fn is_used(file_name: &str) -> bool {
+    conf::use_index() && !encryption::is_encrypted(file_name)
+}
+
+// the valid index of the file, which is created if there is none yet
+fn get_index(file_name: &str) -> Result<Index> {
+    if let Some(index) = load_index(file_name, get_file_state(file_name)) {
+        return Ok(index);
+    }
+
+    let index = create_index(file_name)?;
+    save_index(file_name, &index);
+    Ok(index)
+}
+
+fn load_index(file_name: &str, file_state: Option<FileState>) -> Option<Index> {
+    let index: Index = fs::read_to_string(get_index_file_name(file_name))
+        .ok()?
+        .parse()
+        .ok()?;
+
+    (Some(index.file_state) == file_state).then_some(index)
+}
+
+// the index is only a cache, so the activity log is read completely if it can not be saved
+fn save_index(file_name: &str, index: &Index) {
+    let _ = fs::write(get_index_file_name(file_name), index.to_string());
+}
+
+fn create_index(file_name: &str) -> Result<Index> {
+    let file_state = get_file_state(file_name);
+    let content = fs::read(file_name).context(format!("Could not read from file: {file_name}"))?;
+
+    let mut lines: Vec<(u64, usize, Option<Activity>)> = Vec::new();
+
+    for line in content.split_inclusive(|byte| *byte == b'\n') {
+        let text = String::from_utf8_lossy(line);
+        let text = text.trim_end_matches(['\n', '\r']);
+
+        // notes are read together with their activity
+        if let Some((length, line_count, Some(_))) = lines.last_mut()
+            && activity::is_note_line(text)
+        {
+            *length += line.len() as u64;
+            *line_count += 1;
+            continue;
+        }
+
+        lines.push((line.len() as u64, 1, text.parse().ok()));
+    }
+
+    Ok(Index::from_lines(
+        lines
+            .iter()
+            .map(|(length, line_count, activity)| (*length, *line_count, activity.as_ref())),
+        file_state.unwrap_or_default(),
+    ))
+}
+
+// reads the lines of the entries with the selected content in the order of the file
+fn read_entries(
+    file_name: &str,
+    index: &Index,
+    select: impl Fn(&Content) -> bool,
+) -> Result<Vec<Line>> {
+    let mut file =
+        File::open(file_name).context(format!("Could not read from file: {file_name}"))?;
+    let mut file_content = Vec::new();
+
+    for entry in index.entries.iter().filter(|entry| select(&entry.content)) {
+        let mut buffer = Vec::new();
+        file.seek(SeekFrom::Start(entry.start))?;
+        (&mut file)
+            .take(entry.end - entry.start)
+            .read_to_end(&mut buffer)
+            .context(format!("Could not read from file: {file_name}"))?;
+
+        file_content.extend(bartib_file::parse_lines(
+            String::from_utf8_lossy(&buffer)
+                .lines()
+                .zip(entry.first_line_number..)
+                .map(|(line, line_number)| (Some(line_number), line)),
+        ));
+    }
+
+    Ok(file_content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::activity::activity;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, day).unwrap()
+    }
+
+    #[test]
+    fn index_groups_lines_by_day() {
+        let a1 = activity("p", "2024-03-11 09:00", "2024-03-11 10:00");
+        let a2 = activity("p", "2024-03-11 11:00", "2024-03-11 12:00");
+        let a3 = Activity {
+            end: None,
+            ..activity("p", "2024-03-12 09:00", "2024-03-12 09:00")
+        };
+
+        let index = Index::from_lines(
+            [
+                (10, 1, Some(&a1)),
+                (20, 2, Some(&a2)),
+                (5, 1, None),
+                (10, 1, Some(&a3)),
+            ],
+            (45, 1),
+        );
+
+        assert_eq!(
+            index.entries,
+            vec![
+                Entry {
+                    content: Content::Stopped(date(11)),
+                    first_line_number: 1,
+                    line_count: 3,
+                    start: 0,
+                    end: 30
+                },
+                Entry {
+                    content: Content::Invalid,
+                    first_line_number: 4,
+                    line_count: 1,
+                    start: 30,
+                    end: 35
+                },
+                Entry {
+                    content: Content::Running(date(12)),
+                    first_line_number: 5,
+                    line_count: 1,
+                    start: 35,
+                    end: 45
+                },
+            ]
+        );
+        assert!(index.has_running_activities());
+    }
+
+    #[test]
+    fn index_is_saved_as_text() {
+        let a1 = activity("p", "2024-03-11 09:00", "2024-03-11 10:00");
+        let a2 = Activity {
+            end: None,
+            ..activity("p", "2024-03-12 09:00", "2024-03-12 09:00")
+        };
+        let index = Index::from_lines(
+            [(10, 1, Some(&a1)), (3, 1, None), (10, 1, Some(&a2))],
+            (23, 42),
+        );
+
+        let text = index.to_string();
+        assert_eq!(
+            text,
+            "bartib-index 1\n23 42\nstopped 2024-03-11 1 1 0 10\ninvalid - 2 1 10 13\nrunning 2024-03-12 3 1 13 23\n"
+        );
+        assert_eq!(text.parse::<Index>(), Ok(index));
+
+        assert!("bartib-index 0\n23 42\n".parse::<Index>().is_err());
+        assert!(
+            "bartib-index 1\n23 42\nstopped 2024-03-11 1 1 0\n"
+                .parse::<Index>()
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,637 @@
+use chrono::{DateTime, Duration, DurationRound, Local, NaiveDate, NaiveDateTime, NaiveTime};
+use clap::ValueEnum;
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::conf;
+use crate::data::activity::Activity;
+use crate::data::json_parser::{self, JsonValue};
+use crate::data::timezone;
+
+// the project of imported time entries without a project
+pub static NO_PROJECT: &str = "No project";
+
+// the formats of other time trackers activities can be imported from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ImportFormat {
+    // the CSV export of Toggl Track's detailed report or its time entries as JSON
+    Toggl,
+    // the data files of Timewarrior (e.g. `~/.timewarrior/data`) or the output of `timew export`
+    Timew,
+    // the frames of Watson (e.g. `~/.config/watson/frames`) and its running frame in `state`
+    Watson,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ImportError {
+    #[error("invalid JSON: {0}")]
+    InvalidJson(String),
+    #[error("missing column `{0}`")]
+    MissingColumn(String),
+    #[error("could not read entry {0}: {1}")]
+    InvalidEntry(usize, String),
+}
+
+// reads the activities from the content of a file exported by another time tracker
+pub fn import_activities(
+    content: &str,
+    format: ImportFormat,
+) -> Result<Vec<Activity>, ImportError> {
+    match format {
+        ImportFormat::Toggl if is_json(content) => from_toggl_json(content),
+        ImportFormat::Toggl => from_toggl_csv(content),
+        ImportFormat::Timew if is_json(content) => from_timew_json(content),
+        ImportFormat::Timew => from_timew_data(content),
+        ImportFormat::Watson => from_watson_json(content),
+    }
+}
+
+// the files to import from the data directory of a time tracker, so the whole history can be
+// imported at once
+//
+// these are the monthly data files like `2024-03.data` of Timewarrior and the files `frames` and
+// `state` of Watson.
+#[must_use]
+pub fn get_data_files(directory: &Path, format: ImportFormat) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = match format {
+        ImportFormat::Toggl => Vec::new(),
+        ImportFormat::Timew => [directory.to_path_buf(), directory.join("data")]
+            .iter()
+            .filter_map(|directory| fs::read_dir(directory).ok())
+            .flat_map(|entries| entries.map_while(Result::ok))
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(is_timew_data_file_name)
+            })
+            .collect(),
+        ImportFormat::Watson => vec![directory.join("frames"), directory.join("state")],
+    };
+
+    files.retain(|file| file.is_file());
+    files.sort();
+    files
+}
+
+// whether a file name is the name of a monthly data file of Timewarrior like `2024-03.data`
+fn is_timew_data_file_name(file_name: &str) -> bool {
+    file_name.strip_suffix(".data").is_some_and(|month| {
+        month.len() == 7
+            && month
+                .chars()
+                .enumerate()
+                .all(|(i, c)| if i == 4 { c == '-' } else { c.is_ascii_digit() })
+    })
+}
+
+fn is_json(content: &str) -> bool {
+    content
+        .trim_start_matches('\u{feff}')
+        .trim_start()
+        .starts_with(['[', '{'])
+}
+
+// reads the rows of Toggl's detailed report
+//
+// Toggl projects become projects and Toggl tags become tags. Entries without an end end after
+// their duration.
+fn from_toggl_csv(content: &str) -> Result<Vec<Activity>, ImportError> {
+    let mut rows = parse_csv(content).into_iter();
+    let header = rows.next().unwrap_or_default();
+
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|column| column.trim().eq_ignore_ascii_case(name))
+    };
+    let required_column =
+        |name: &str| column(name).ok_or_else(|| ImportError::MissingColumn(name.to_string()));
+
+    let start_date = required_column("Start date")?;
+    let start_time = required_column("Start time")?;
+    let end_date = column("End date");
+    let end_time = column("End time");
+    let duration = column("Duration");
+    let project = column("Project");
+    let description = column("Description");
+    let tags = column("Tags");
+
+    if (end_date.is_none() || end_time.is_none()) && duration.is_none() {
+        return Err(ImportError::MissingColumn("Duration".to_string()));
+    }
+
+    let mut activities = Vec::new();
+
+    for (index, row) in rows.enumerate() {
+        let cell = |column: Option<usize>| column.and_then(|c| row.get(c)).map_or("", |c| c.trim());
+        let invalid = |message: String| ImportError::InvalidEntry(index + 1, message);
+
+        let start =
+            parse_date_and_time(cell(Some(start_date)), cell(Some(start_time))).map_err(invalid)?;
+        let end = if cell(end_date).is_empty() || cell(end_time).is_empty() {
+            start + parse_toggl_duration(cell(duration)).map_err(invalid)?
+        } else {
+            parse_date_and_time(cell(end_date), cell(end_time)).map_err(invalid)?
+        };
+
+        activities.push(new_activity(
+            start,
+            Some(end),
+            cell(project),
+            cell(description),
+            cell(tags)
+                .split(',')
+                .map(str::trim)
+                .filter(|tag| !tag.is_empty())
+                .map(ToString::to_string)
+                .collect(),
+        ));
+    }
+
+    Ok(activities)
+}
+
+// reads time entries as returned by Toggl's API, e.g. by `/me/time_entries?meta=true`
+//
+// the entries may be given as an array or in the `data` array of an object. Entries without
+// a stop time are running.
+fn from_toggl_json(content: &str) -> Result<Vec<Activity>, ImportError> {
+    let json = json_parser::parse_json(content).map_err(ImportError::InvalidJson)?;
+    let entries = json
+        .as_array()
+        .or_else(|| json.get("data").and_then(JsonValue::as_array))
+        .ok_or_else(|| ImportError::InvalidJson("expected an array of time entries".to_string()))?;
+
+    let mut activities = Vec::new();
+
+    for (index, entry) in entries.iter().enumerate() {
+        let invalid = |message: String| ImportError::InvalidEntry(index + 1, message);
+        let get_str = |key: &str| entry.get(key).and_then(JsonValue::as_str);
+
+        let start =
+            parse_timestamp(get_str("start").ok_or_else(|| invalid("missing start".to_string()))?)
+                .map_err(invalid)?;
+        let end = get_str("stop")
+            .or_else(|| get_str("end"))
+            .map(parse_timestamp)
+            .transpose()
+            .map_err(invalid)?;
+
+        activities.push(new_activity(
+            start,
+            end,
+            get_str("project_name")
+                .or_else(|| get_str("project"))
+                .unwrap_or_default(),
+            get_str("description").unwrap_or_default(),
+            get_strings(entry, "tags"),
+        ));
+    }
+
+    Ok(activities)
+}
+
+// reads the lines of Timewarrior's data files like
+// `inc 20240301T090000Z - 20240301T100000Z # Website "code review" # "Fix header"`
+//
+// Timewarrior has no projects, so the first tag becomes the project and the other tags become
+// tags. The annotation becomes the description. Intervals without an end are running.
+fn from_timew_data(content: &str) -> Result<Vec<Activity>, ImportError> {
+    let mut activities = Vec::new();
+
+    for (index, line) in content.lines().enumerate() {
+        let invalid = |message: String| ImportError::InvalidEntry(index + 1, message);
+        let Some(interval) = line.trim().strip_prefix("inc ") else {
+            if line.trim().is_empty() {
+                continue;
+            }
+            return Err(invalid(format!("unknown line '{line}'")));
+        };
+
+        let (times, tags_and_annotation) = interval.split_once(" #").unwrap_or((interval, ""));
+        let (start, end) = match times.split_once(" - ") {
+            Some((start, end)) => (start, Some(end)),
+            None => (times, None),
+        };
+
+        let mut words = split_quoted_words(tags_and_annotation).into_iter();
+        let mut tags: Vec<String> = words.by_ref().take_while(|word| word != "#").collect();
+        let annotation = words.collect::<Vec<_>>().join(" ");
+
+        activities.push(new_activity(
+            parse_timew_timestamp(start).map_err(invalid)?,
+            end.map(parse_timew_timestamp)
+                .transpose()
+                .map_err(invalid)?,
+            &take_first(&mut tags),
+            &annotation,
+            tags,
+        ));
+    }
+
+    Ok(activities)
+}
+
+// reads the intervals of `timew export`, mapping them like the lines of the data files
+fn from_timew_json(content: &str) -> Result<Vec<Activity>, ImportError> {
+    let json = json_parser::parse_json(content).map_err(ImportError::InvalidJson)?;
+    let intervals = json
+        .as_array()
+        .ok_or_else(|| ImportError::InvalidJson("expected an array of intervals".to_string()))?;
+
+    let mut activities = Vec::new();
+
+    for (index, interval) in intervals.iter().enumerate() {
+        let invalid = |message: String| ImportError::InvalidEntry(index + 1, message);
+        let get_str = |key: &str| interval.get(key).and_then(JsonValue::as_str);
+
+        let start = get_str("start").ok_or_else(|| invalid("missing start".to_string()))?;
+        let mut tags = get_strings(interval, "tags");
+
+        activities.push(new_activity(
+            parse_timew_timestamp(start).map_err(invalid)?,
+            get_str("end")
+                .map(parse_timew_timestamp)
+                .transpose()
+                .map_err(invalid)?,
+            &take_first(&mut tags),
+            get_str("annotation").unwrap_or_default(),
+            tags,
+        ));
+    }
+
+    Ok(activities)
+}
+
+// reads Watson's frames, which are arrays like `[start, stop, project, id, tags, updated_at]`
+// with timestamps in seconds since the epoch, or its state with the running frame
+//
+// Watson has no descriptions, so the activities have empty descriptions.
+fn from_watson_json(content: &str) -> Result<Vec<Activity>, ImportError> {
+    let json = json_parser::parse_json(content).map_err(ImportError::InvalidJson)?;
+
+    // the state is an object, which is empty if no frame is running
+    if let JsonValue::Object(_) = json {
+        return match json.get("start") {
+            None => Ok(Vec::new()),
+            Some(start) => Ok(vec![new_activity(
+                parse_unix_timestamp(start).map_err(|e| ImportError::InvalidEntry(1, e))?,
+                None,
+                json.get("project")
+                    .and_then(JsonValue::as_str)
+                    .unwrap_or_default(),
+                "",
+                get_strings(&json, "tags"),
+            )]),
+        };
+    }
+
+    let frames = json
+        .as_array()
+        .ok_or_else(|| ImportError::InvalidJson("expected an array of frames".to_string()))?;
+
+    let mut activities = Vec::new();
+
+    for (index, frame) in frames.iter().enumerate() {
+        let invalid = |message: String| ImportError::InvalidEntry(index + 1, message);
+        let fields = frame
+            .as_array()
+            .filter(|fields| fields.len() >= 3)
+            .ok_or_else(|| {
+                invalid("expected an array like [start, stop, project, ...]".to_string())
+            })?;
+
+        activities.push(new_activity(
+            parse_unix_timestamp(&fields[0]).map_err(invalid)?,
+            Some(parse_unix_timestamp(&fields[1]).map_err(invalid)?),
+            fields[2].as_str().unwrap_or_default(),
+            "",
+            fields
+                .get(4)
+                .and_then(JsonValue::as_array)
+                .unwrap_or_default()
+                .iter()
+                .filter_map(JsonValue::as_str)
+                .map(ToString::to_string)
+                .collect(),
+        ));
+    }
+
+    Ok(activities)
+}
+
+// the strings of an array in an object
+fn get_strings(object: &JsonValue, key: &str) -> Vec<String> {
+    object
+        .get(key)
+        .and_then(JsonValue::as_array)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(JsonValue::as_str)
+        .map(ToString::to_string)
+        .collect()
+}
+
+// removes the first of the strings, or returns an empty string if there is none
+fn take_first(strings: &mut Vec<String>) -> String {
+    if strings.is_empty() {
+        String::new()
+    } else {
+        strings.remove(0)
+    }
+}
+
+fn new_activity(
+    start: NaiveDateTime,
+    end: Option<NaiveDateTime>,
+    project: &str,
+    description: &str,
+    tags: Vec<String>,
+) -> Activity {
+    let project = if project.is_empty() {
+        NO_PROJECT
+    } else {
+        project
+    };
+
+    let start = round_to_precision(start);
+    let end = end.map(round_to_precision);
+
+    Activity {
+        start,
+        end,
+        start_offset: Some(timezone::local_offset(start)),
+        end_offset: end.map(timezone::local_offset),
+        project: project.to_string(),
+        description: description.to_string(),
+        tags,
+        issue: None,
+        pauses: Vec::new(),
+        notes: Vec::new(),
+    }
+}
+
+// other time trackers record seconds, which are rounded to the nearest minute unless the log
+// has second precision
+fn round_to_precision(datetime: NaiveDateTime) -> NaiveDateTime {
+    match conf::precision() {
+        conf::Precision::Minutes => datetime
+            .duration_round(Duration::minutes(1))
+            .unwrap_or(datetime),
+        conf::Precision::Seconds => datetime,
+    }
+}
+
+fn parse_date_and_time(date: &str, time: &str) -> Result<NaiveDateTime, String> {
+    let date = NaiveDate::parse_from_str(date, conf::FORMAT_DATE)
+        .map_err(|_| format!("invalid date '{date}', expected a date like 2024-03-01"))?;
+    let time = NaiveTime::parse_from_str(time, "%T")
+        .or_else(|_| NaiveTime::parse_from_str(time, "%R"))
+        .map_err(|_| format!("invalid time '{time}', expected a time like 09:30:00"))?;
+
+    Ok(date.and_time(time))
+}
+
+// parses a timestamp with a UTC offset like `2024-03-01T09:30:00+00:00` into local time
+fn parse_timestamp(timestamp: &str) -> Result<NaiveDateTime, String> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|datetime| datetime.with_timezone(&Local).naive_local())
+        .map_err(|_| format!("invalid timestamp '{timestamp}'"))
+}
+
+// parses a UTC timestamp of Timewarrior like `20240301T093000Z` into local time
+fn parse_timew_timestamp(timestamp: &str) -> Result<NaiveDateTime, String> {
+    NaiveDateTime::parse_from_str(timestamp.trim(), "%Y%m%dT%H%M%SZ")
+        .map(|datetime| datetime.and_utc().with_timezone(&Local).naive_local())
+        .map_err(|_| format!("invalid timestamp '{timestamp}'"))
+}
+
+// parses a number of seconds since the epoch into local time
+fn parse_unix_timestamp(timestamp: &JsonValue) -> Result<NaiveDateTime, String> {
+    timestamp
+        .as_f64()
+        .and_then(|seconds| DateTime::from_timestamp(seconds.round() as i64, 0))
+        .map(|datetime| datetime.with_timezone(&Local).naive_local())
+        .ok_or_else(|| "expected a timestamp in seconds since the epoch".to_string())
+}
+
+// parses a duration like `01:30:00`
+fn parse_toggl_duration(duration: &str) -> Result<Duration, String> {
+    let parts: Option<Vec<i64>> = duration.split(':').map(|part| part.parse().ok()).collect();
+
+    match parts.as_deref() {
+        Some([hours, minutes, seconds]) => {
+            Ok(Duration::hours(*hours) + Duration::minutes(*minutes) + Duration::seconds(*seconds))
+        }
+        _ => Err(format!(
+            "invalid duration '{duration}', expected a duration like 01:30:00"
+        )),
+    }
+}
+
+// splits words separated by spaces. Words in double quotes may contain spaces and quotes escaped
+// as `\\"`
+fn split_quoted_words(s: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut quoted = false;
+    let mut chars = s.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if quoted => word.extend(chars.next()),
+            '"' => quoted = !quoted,
+            ' ' if !quoted => {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+            }
+            c => word.push(c),
+        }
+    }
+
+    if !word.is_empty() {
+        words.push(word);
+    }
+
+    words
+}
+
+// splits CSV content into rows of fields. Quoted fields may contain commas, line breaks and
+// quotes written as `""`. Empty lines are skipped
+fn parse_csv(content: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = content.trim_start_matches('\u{feff}').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.next_if_eq(&'"').is_some() => field.push('"'),
+            '"' => quoted = !quoted,
+            ',' if !quoted => row.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut field));
+                if row.iter().any(|field| !field.is_empty()) {
+                    rows.push(std::mem::take(&mut row));
+                }
+                row.clear();
+            }
+            c => field.push(c),
+        }
+    }
+
+    row.push(field);
+    if row.iter().any(|field| !field.is_empty()) {
+        rows.push(row);
+    }
+
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time, "%F %R").unwrap()
+    }
+
+    #[test]
+    fn parse_csv_test() {
+        assert_eq!(
+            parse_csv("a,b,c\r\n\"x, \"\"y\"\"\",\"line\nbreak\",\n\n1,2,3"),
+            vec![
+                vec!["a", "b", "c"],
+                vec!["x, \"y\"", "line\nbreak", ""],
+                vec!["1", "2", "3"]
+            ]
+        );
+    }
+
+    #[test]
+    fn import_toggl_csv() {
+        conf::set_precision(conf::Precision::Minutes);
+        let csv = "User,Email,Client,Project,Task,Description,Billable,Start date,Start time,End date,End time,Duration,Tags,Amount (EUR)\n\
+                   Jane,jane@example.com,Acme,Website,,\"Fix header, footer\",Yes,2024-03-01,09:00:00,2024-03-01,10:29:50,01:29:50,\"billable, remote\",\n\
+                   Jane,jane@example.com,,,,Reading,No,2024-03-01,23:30:00,,,01:00:00,,";
+
+        let activities = import_activities(csv, ImportFormat::Toggl).unwrap();
+
+        assert_eq!(activities.len(), 2);
+        assert_eq!(activities[0].start, time("2024-03-01 09:00"));
+        assert_eq!(activities[0].end, Some(time("2024-03-01 10:30")));
+        assert_eq!(activities[0].project, "Website");
+        assert_eq!(activities[0].description, "Fix header, footer");
+        assert_eq!(activities[0].tags, vec!["billable", "remote"]);
+        assert_eq!(activities[1].end, Some(time("2024-03-02 00:30")));
+        assert_eq!(activities[1].project, NO_PROJECT);
+
+        assert_eq!(
+            import_activities("Project,Start date\np,2024-03-01", ImportFormat::Toggl),
+            Err(ImportError::MissingColumn("Start time".to_string()))
+        );
+        assert_eq!(
+            import_activities(
+                "Start date,Start time,Duration\n2024-03-01,9 o'clock,01:00:00",
+                ImportFormat::Toggl
+            ),
+            Err(ImportError::InvalidEntry(
+                1,
+                "invalid time '9 o'clock', expected a time like 09:30:00".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn import_toggl_json() {
+        let start = "2024-03-01T09:00:00+00:00";
+        let json = format!(
+            r#"[{{"start": "{start}", "stop": "2024-03-01T10:00:00+00:00", "project_name": "Website", "description": "Header", "tags": ["billable"]}},
+                {{"start": "{start}", "stop": null, "duration": -1709283600, "description": "Running", "tags": null}}]"#
+        );
+
+        let activities = import_activities(&json, ImportFormat::Toggl).unwrap();
+        let local_start = parse_timestamp(start).unwrap();
+
+        assert_eq!(activities.len(), 2);
+        assert_eq!(activities[0].start, local_start);
+        assert_eq!(activities[0].end, Some(local_start + Duration::hours(1)));
+        assert_eq!(activities[0].project, "Website");
+        assert_eq!(activities[0].tags, vec!["billable"]);
+        assert_eq!(activities[1].end, None);
+        assert_eq!(activities[1].project, NO_PROJECT);
+
+        assert!(import_activities("[{\"stop\": null}]", ImportFormat::Toggl).is_err());
+    }
+
+    // the local time of a timestamp in UTC
+    fn local(utc: &str) -> NaiveDateTime {
+        time(utc).and_utc().with_timezone(&Local).naive_local()
+    }
+
+    #[test]
+    fn import_timew() {
+        let data = "inc 20240301T090000Z - 20240301T100000Z # Website \"code review\" # \"Fix \\\"the\\\" header\"\n\
+                    inc 20240301T100000Z - 20240301T103000Z # # \"No tags\"\n\
+                    inc 20240301T110000Z # Website";
+
+        let activities = import_activities(data, ImportFormat::Timew).unwrap();
+
+        assert_eq!(activities.len(), 3);
+        assert_eq!(activities[0].start, local("2024-03-01 09:00"));
+        assert_eq!(activities[0].end, Some(local("2024-03-01 10:00")));
+        assert_eq!(activities[0].project, "Website");
+        assert_eq!(activities[0].tags, vec!["code review"]);
+        assert_eq!(activities[0].description, "Fix \"the\" header");
+        assert_eq!(activities[1].project, NO_PROJECT);
+        assert_eq!(activities[1].description, "No tags");
+        assert_eq!(activities[2].end, None);
+
+        let json = r#"[{"id":1,"start":"20240301T090000Z","end":"20240301T100000Z","tags":["Website","billable"],"annotation":"Header"}]"#;
+        let activities = import_activities(json, ImportFormat::Timew).unwrap();
+
+        assert_eq!(activities[0].end, Some(local("2024-03-01 10:00")));
+        assert_eq!(activities[0].project, "Website");
+        assert_eq!(activities[0].tags, vec!["billable"]);
+        assert_eq!(activities[0].description, "Header");
+
+        assert!(import_activities("inc yesterday", ImportFormat::Timew).is_err());
+    }
+
+    #[test]
+    fn import_watson() {
+        // 2024-03-01 09:00 and 10:00 UTC
+        let frames = r#"[[1709283600, 1709287200, "Website", "a1b2", ["billable"], 1709287200]]"#;
+        let activities = import_activities(frames, ImportFormat::Watson).unwrap();
+
+        assert_eq!(activities.len(), 1);
+        assert_eq!(activities[0].start, local("2024-03-01 09:00"));
+        assert_eq!(activities[0].end, Some(local("2024-03-01 10:00")));
+        assert_eq!(activities[0].project, "Website");
+        assert_eq!(activities[0].description, "");
+        assert_eq!(activities[0].tags, vec!["billable"]);
+
+        let state = r#"{"project": "Website", "start": 1709283600, "tags": []}"#;
+        let activities = import_activities(state, ImportFormat::Watson).unwrap();
+        assert_eq!(activities[0].end, None);
+
+        assert!(
+            import_activities("{}", ImportFormat::Watson)
+                .unwrap()
+                .is_empty()
+        );
+        assert!(import_activities("[[1709283600]]", ImportFormat::Watson).is_err());
+    }
+
+    #[test]
+    fn timew_data_file_names() {
+        assert!(is_timew_data_file_name("2024-03.data"));
+        assert!(!is_timew_data_file_name("tags.data"));
+        assert!(!is_timew_data_file_name("2024-03.data.bak"));
+    }
+}
@@ -0,0 +1,201 @@
+use chrono::Duration;
+
+use crate::data::activity::Activity;
+use crate::data::config::{Config, ConfigError};
+use crate::data::timezone;
+
+// the name of the table defining the connection to JIRA in the configuration file
+pub static CONFIG_TABLE: &str = "jira";
+
+// the connection to a JIRA instance worklogs are pushed to
+//
+// in the configuration file it is defined like this. The token may be given by the environment
+// variable `BARTIB_JIRA_TOKEN` instead. With a user, the token is sent as API token of the user
+// (JIRA Cloud), otherwise as personal access token (JIRA Server and Data Center):
+//
+//     [jira]
+//     url = "https://example.atlassian.net"
+//     user = "me@example.com"
+//     token = "..."
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JiraSettings {
+    pub url: Option<String>,
+    pub user: Option<String>,
+    pub token: Option<String>,
+}
+
+impl JiraSettings {
+    pub fn from_config(config: &Config) -> Result<Self, ConfigError> {
+        let get_str = |key: &str| match config.get(CONFIG_TABLE, key) {
+            None => Ok(None),
+            Some(value) => value.as_str().map(|s| Some(s.to_string())).ok_or_else(|| {
+                ConfigError::InvalidSetting(
+                    format!("{CONFIG_TABLE}.{key}"),
+                    "expected a string".to_string(),
+                )
+            }),
+        };
+
+        Ok(Self {
+            url: get_str("url")?.map(|url| url.trim_end_matches('/').to_string()),
+            user: get_str("user")?,
+            token: get_str("token")?,
+        })
+    }
+
+    // the url of the REST API to add worklogs to an issue
+    #[must_use]
+    pub fn worklog_url(&self, issue: &str) -> Option<String> {
+        self.url
+            .as_ref()
+            .map(|url| format!("{url}/rest/api/2/issue/{issue}/worklog"))
+    }
+}
+
+// the finished activities spent on an issue
+#[derive(Debug, Clone, PartialEq)]
+pub struct IssueWorklogs<'a> {
+    pub issue: &'a str,
+    pub activities: Vec<&'a Activity>,
+}
+
+impl IssueWorklogs<'_> {
+    #[must_use]
+    pub fn total(&self) -> Duration {
+        self.activities
+            .iter()
+            .map(|activity| logged_duration(activity))
+            .sum()
+    }
+}
+
+// groups the finished activities by their issues, ordered by issue. Activities without an issue
+// are skipped, as are running ones which would be logged with a wrong duration
+#[must_use]
+pub fn group_by_issue<'a>(activities: &[&'a Activity]) -> Vec<IssueWorklogs<'a>> {
+    let mut worklogs: Vec<IssueWorklogs> = Vec::new();
+
+    for activity in activities.iter().filter(|activity| activity.end.is_some()) {
+        let Some(issue) = activity.issue.as_deref() else {
+            continue;
+        };
+
+        match worklogs.binary_search_by(|worklog| worklog.issue.cmp(issue)) {
+            Ok(index) => worklogs[index].activities.push(activity),
+            Err(index) => worklogs.insert(
+                index,
+                IssueWorklogs {
+                    issue,
+                    activities: vec![activity],
+                },
+            ),
+        }
+    }
+
+    worklogs
+}
+
+// the duration of an activity as it is logged in JIRA, which counts in minutes and requires at
+// least one
+#[must_use]
+pub fn logged_duration(activity: &Activity) -> Duration {
+    Duration::minutes(activity.get_duration().num_minutes().max(1))
+}
+
+// a duration in the format JIRA uses for the time spent, e.g. `2h 30m`. Days are left out, as JIRA
+// counts days of 8 hours by default
+#[must_use]
+pub fn format_duration(duration: &Duration) -> String {
+    let hours = duration.num_hours();
+    let minutes = duration.num_minutes() % 60;
+
+    match (hours, minutes) {
+        (0, minutes) => format!("{minutes}m"),
+        (hours, 0) => format!("{hours}h"),
+        (hours, minutes) => format!("{hours}h {minutes}m"),
+    }
+}
+
+// the start of an activity in the format JIRA expects for the start of worklogs, e.g.
+// `2024-03-01T09:00:00.000+0100`
+#[must_use]
+pub fn format_started(activity: &Activity) -> String {
+    let offset = activity
+        .start_offset
+        .unwrap_or_else(|| timezone::local_offset(activity.start));
+
+    format!(
+        "{}{}",
+        activity.start.format("%Y-%m-%dT%H:%M:%S.000"),
+        offset.to_string().replace(':', "")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{FixedOffset, NaiveDateTime};
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn time(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn group_activities_by_issue() {
+        let activity = |start: &str, end: Option<&str>, issue: Option<&str>| {
+            let mut activity = Activity::start("p".to_string(), "d".to_string(), Some(time(start)))
+                .with_issue(issue.map(str::to_string));
+            activity.end = end.map(time);
+            activity
+        };
+        let activities = [
+            activity("2024-03-01 09:00", Some("2024-03-01 10:30"), Some("B-2")),
+            activity("2024-03-01 10:30", Some("2024-03-01 11:00"), None),
+            activity("2024-03-01 11:00", Some("2024-03-01 11:00"), Some("A-1")),
+            activity("2024-03-01 13:00", Some("2024-03-01 14:15"), Some("B-2")),
+            activity("2024-03-01 15:00", None, Some("A-1")),
+        ];
+        let activities: Vec<&Activity> = activities.iter().collect();
+
+        let worklogs = group_by_issue(&activities);
+
+        assert_eq!(worklogs.len(), 2);
+        assert_eq!(worklogs[0].issue, "A-1");
+        assert_eq!(worklogs[0].activities.len(), 1);
+        assert_eq!(worklogs[0].total(), Duration::minutes(1));
+        assert_eq!(worklogs[1].issue, "B-2");
+        assert_eq!(worklogs[1].total(), Duration::minutes(165));
+    }
+
+    #[test]
+    fn format_durations() {
+        assert_eq!(format_duration(&Duration::minutes(1)), "1m");
+        assert_eq!(format_duration(&Duration::minutes(120)), "2h");
+        assert_eq!(format_duration(&Duration::minutes(165)), "2h 45m");
+        assert_eq!(format_duration(&Duration::hours(26)), "26h");
+    }
+
+    #[test]
+    fn format_start_with_offset() {
+        let mut activity =
+            Activity::from_str("2024-03-01 09:00 - 2024-03-01 10:00 | p | d").unwrap();
+        activity.start_offset = FixedOffset::east_opt(3600);
+
+        assert_eq!(format_started(&activity), "2024-03-01T09:00:00.000+0100");
+    }
+
+    #[test]
+    fn worklog_url() {
+        let config: Config = "[jira]\nurl = \"https://jira.example.com/\""
+            .parse()
+            .unwrap();
+        let settings = JiraSettings::from_config(&config).unwrap();
+
+        assert_eq!(
+            settings.worklog_url("PROJ-1").as_deref(),
+            Some("https://jira.example.com/rest/api/2/issue/PROJ-1/worklog")
+        );
+    }
+}
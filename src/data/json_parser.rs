@@ -0,0 +1,259 @@
+use std::iter::Peekable;
+use std::str::Chars;
+
+// a JSON value read from a file, e.g. from the export of another time tracker
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    // the value of a key of an object. Null values count as missing
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&Self> {
+        match self {
+            Self::Object(entries) => entries
+                .iter()
+                .find(|(k, value)| k == key && *value != Self::Null)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_array(&self) -> Option<&[Self]> {
+        match self {
+            Self::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+// parses a JSON document
+pub fn parse_json(json: &str) -> Result<JsonValue, String> {
+    let mut chars = json.trim_start_matches('\u{feff}').chars().peekable();
+    let value = parse_value(&mut chars)?;
+
+    skip_whitespace(&mut chars);
+    match chars.next() {
+        None => Ok(value),
+        Some(c) => Err(format!("unexpected '{c}' after the end of the document")),
+    }
+}
+
+fn parse_value(chars: &mut Peekable<Chars>) -> Result<JsonValue, String> {
+    skip_whitespace(chars);
+
+    match chars.peek() {
+        None => Err("unexpected end of the document".to_string()),
+        Some('{') => parse_object(chars),
+        Some('[') => parse_array(chars),
+        Some('"') => parse_string(chars).map(JsonValue::String),
+        Some('t') => parse_keyword(chars, "true", JsonValue::Bool(true)),
+        Some('f') => parse_keyword(chars, "false", JsonValue::Bool(false)),
+        Some('n') => parse_keyword(chars, "null", JsonValue::Null),
+        Some(_) => parse_number(chars),
+    }
+}
+
+fn parse_object(chars: &mut Peekable<Chars>) -> Result<JsonValue, String> {
+    chars.next();
+    let mut entries = Vec::new();
+
+    skip_whitespace(chars);
+    if chars.next_if_eq(&'}').is_some() {
+        return Ok(JsonValue::Object(entries));
+    }
+
+    loop {
+        skip_whitespace(chars);
+        let key = parse_string(chars)?;
+
+        skip_whitespace(chars);
+        expect(chars, ':')?;
+        entries.push((key, parse_value(chars)?));
+
+        skip_whitespace(chars);
+        match chars.next() {
+            Some(',') => {}
+            Some('}') => return Ok(JsonValue::Object(entries)),
+            _ => return Err("expected ',' or '}' in object".to_string()),
+        }
+    }
+}
+
+fn parse_array(chars: &mut Peekable<Chars>) -> Result<JsonValue, String> {
+    chars.next();
+    let mut values = Vec::new();
+
+    skip_whitespace(chars);
+    if chars.next_if_eq(&']').is_some() {
+        return Ok(JsonValue::Array(values));
+    }
+
+    loop {
+        values.push(parse_value(chars)?);
+
+        skip_whitespace(chars);
+        match chars.next() {
+            Some(',') => {}
+            Some(']') => return Ok(JsonValue::Array(values)),
+            _ => return Err("expected ',' or ']' in array".to_string()),
+        }
+    }
+}
+
+fn parse_string(chars: &mut Peekable<Chars>) -> Result<String, String> {
+    expect(chars, '"')?;
+    let mut s = String::new();
+
+    loop {
+        match chars.next() {
+            None => return Err("unterminated string".to_string()),
+            Some('"') => return Ok(s),
+            Some('\\') => match chars.next() {
+                Some('n') => s.push('\n'),
+                Some('r') => s.push('\r'),
+                Some('t') => s.push('\t'),
+                Some('b') => s.push('\u{8}'),
+                Some('f') => s.push('\u{c}'),
+                Some('u') => s.push(parse_unicode_escape(chars)?),
+                Some(c @ ('"' | '\\' | '/')) => s.push(c),
+                _ => return Err("invalid escape sequence in string".to_string()),
+            },
+            Some(c) => s.push(c),
+        }
+    }
+}
+
+// parses the digits of an escape like `\u00e4`. Characters outside of the basic multilingual
+// plane are escaped as two surrogates like `\ud83d\ude00`
+fn parse_unicode_escape(chars: &mut Peekable<Chars>) -> Result<char, String> {
+    let first = parse_hex(chars)?;
+
+    let code_point = if (0xd800..0xdc00).contains(&first) {
+        if chars.next() != Some('\\') || chars.next() != Some('u') {
+            return Err("missing low surrogate in string".to_string());
+        }
+        let second = parse_hex(chars)?;
+        0x10000 + ((first - 0xd800) << 10) + (second.wrapping_sub(0xdc00) & 0x3ff)
+    } else {
+        first
+    };
+
+    char::from_u32(code_point).ok_or_else(|| "invalid unicode escape in string".to_string())
+}
+
+fn parse_hex(chars: &mut Peekable<Chars>) -> Result<u32, String> {
+    let digits: String = chars.take(4).collect();
+
+    u32::from_str_radix(&digits, 16)
+        .ok()
+        .filter(|_| digits.len() == 4)
+        .ok_or_else(|| format!("invalid unicode escape '\\u{digits}' in string"))
+}
+
+fn parse_number(chars: &mut Peekable<Chars>) -> Result<JsonValue, String> {
+    let mut number = String::new();
+
+    while let Some(c) = chars.next_if(|c| c.is_ascii_digit() || "+-.eE".contains(*c)) {
+        number.push(c);
+    }
+
+    number
+        .parse()
+        .map(JsonValue::Number)
+        .map_err(|_| match chars.peek() {
+            Some(c) if number.is_empty() => format!("unexpected '{c}'"),
+            _ => format!("invalid number '{number}'"),
+        })
+}
+
+fn parse_keyword(
+    chars: &mut Peekable<Chars>,
+    keyword: &str,
+    value: JsonValue,
+) -> Result<JsonValue, String> {
+    for expected in keyword.chars() {
+        if chars.next() != Some(expected) {
+            return Err(format!("expected '{keyword}'"));
+        }
+    }
+
+    Ok(value)
+}
+
+fn expect(chars: &mut Peekable<Chars>, expected: char) -> Result<(), String> {
+    match chars.next() {
+        Some(c) if c == expected => Ok(()),
+        Some(c) => Err(format!("expected '{expected}' but found '{c}'")),
+        None => Err(format!("expected '{expected}' but the document ended")),
+    }
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_json_test() {
+        let value = parse_json(
+            r#" [{"a": 1.5, "b": [true, false, null], "c": "say \"hi\"\n\u00e4\ud83d\ude00"}, -2e3, {}] "#,
+        )
+        .unwrap();
+
+        let object = &value.as_array().unwrap()[0];
+        assert_eq!(object.get("a").and_then(JsonValue::as_f64), Some(1.5));
+        assert_eq!(
+            object.get("b"),
+            Some(&JsonValue::Array(vec![
+                JsonValue::Bool(true),
+                JsonValue::Bool(false),
+                JsonValue::Null
+            ]))
+        );
+        assert_eq!(
+            object.get("c").and_then(JsonValue::as_str),
+            Some("say \"hi\"\nä😀")
+        );
+        assert_eq!(object.get("d"), None);
+        assert_eq!(value.as_array().unwrap()[1], JsonValue::Number(-2000.0));
+        assert_eq!(value.as_array().unwrap()[2], JsonValue::Object(Vec::new()));
+    }
+
+    #[test]
+    fn parse_invalid_json() {
+        assert!(parse_json("").is_err());
+        assert!(parse_json("[1, 2").is_err());
+        assert!(parse_json("{\"a\" 1}").is_err());
+        assert!(parse_json("\"unterminated").is_err());
+        assert!(parse_json("[1] 2").is_err());
+        assert!(parse_json("nul").is_err());
+        assert!(parse_json("[x]").is_err());
+    }
+}
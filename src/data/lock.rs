@@ -0,0 +1,317 @@
+use std::cell::RefCell;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+// how long to wait for another process to finish writing the activity log
+static LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+
+// how long to wait before trying to lock the file again
+static LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+// lock files are created with the id of the process, so a lock file without an id that is older
+// than this is broken, e.g. by a crash of an older version of bartib which wrote the id afterwards
+static STALE_LOCK_AGE: Duration = Duration::from_secs(60);
+
+thread_local! {
+    // the lock files held by this process, so that a file locked for a whole command may be
+    // written without waiting for itself
+    static HELD_LOCKS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+// the file that marks the activity log as being changed, e.g. `activities.bartib.lock`
+//
+// a symlinked file is locked at the target of the link.
+#[must_use]
+pub fn get_lock_file_name(file_name: &str) -> String {
+    format!("{}.lock", resolve(file_name).to_string_lossy())
+}
+
+// the absolute path of a file, so that it is locked by the same lock file however it is named.
+// A file that does not exist yet is resolved by its directory
+fn resolve(file_name: &str) -> PathBuf {
+    let path = Path::new(file_name);
+
+    fs::canonicalize(path)
+        .or_else(|_| {
+            let directory = match path.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent,
+                _ => Path::new("."),
+            };
+            fs::canonicalize(directory)
+                .map(|directory| directory.join(path.file_name().unwrap_or_default()))
+        })
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+// an exclusive lock on a file, released when it is dropped
+//
+// the lock is a file next to the locked one that contains the id of the process holding it. Other
+// bartib processes wait until it is removed. A lock file of a process that is not running anymore
+// is stale and is removed with a warning. Locking a file again while holding its lock does nothing.
+#[derive(Debug)]
+pub struct FileLock {
+    // the lock file, if it has been created by this lock and not by an outer one
+    lock_file_name: Option<String>,
+}
+
+impl FileLock {
+    pub fn acquire(file_name: &str) -> io::Result<Self> {
+        let lock_file_name = get_lock_file_name(file_name);
+
+        if HELD_LOCKS.with(|held| held.borrow().contains(&lock_file_name)) {
+            return Ok(Self {
+                lock_file_name: None,
+            });
+        }
+
+        let started = SystemTime::now();
+
+        loop {
+            match create_lock_file(&lock_file_name) {
+                Ok(()) => {
+                    HELD_LOCKS.with(|held| held.borrow_mut().push(lock_file_name.clone()));
+                    return Ok(Self {
+                        lock_file_name: Some(lock_file_name),
+                    });
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+                Err(e) => return Err(e),
+            }
+
+            let owner = read_owner(&lock_file_name);
+
+            if is_stale(&lock_file_name, owner) {
+                eprintln!(
+                    "Warning: Removing stale lock file {lock_file_name} of process {} which is not running anymore",
+                    owner.map_or_else(|| "?".to_string(), |pid| pid.to_string())
+                );
+                remove_stale_lock_file(&lock_file_name, owner)?;
+                continue;
+            }
+
+            if started.elapsed().unwrap_or_default() >= LOCK_TIMEOUT {
+                return Err(io::Error::new(
+                    io::ErrorKind::WouldBlock,
+                    format!(
+                        "the file {file_name} is locked by process {}. If no other bartib process is running, remove the lock file {lock_file_name}",
+                        owner.map_or_else(|| "?".to_string(), |pid| pid.to_string())
+                    ),
+                ));
+            }
+
+            thread::sleep(LOCK_RETRY_INTERVAL);
+        }
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        if let Some(lock_file_name) = &self.lock_file_name {
+            HELD_LOCKS.with(|held| held.borrow_mut().retain(|held| held != lock_file_name));
+            let _ = fs::remove_file(lock_file_name);
+        }
+    }
+}
+
+// the content of a file at the time it has been read
+//
+// commands asking the user how to change a file read it without locking it, as answering may take
+// a while. The file is locked only to write it, which fails if another process has changed it in
+// the meantime.
+#[derive(Debug)]
+pub struct Snapshot {
+    file_name: String,
+    content: Option<Vec<u8>>,
+}
+
+impl Snapshot {
+    #[must_use]
+    pub fn take(file_name: &str) -> Self {
+        Self {
+            file_name: file_name.to_string(),
+            content: fs::read(file_name).ok(),
+        }
+    }
+
+    // locks the file unless it has been changed since the snapshot has been taken
+    pub fn lock_unchanged(&self) -> io::Result<FileLock> {
+        let lock = FileLock::acquire(&self.file_name)?;
+
+        if fs::read(&self.file_name).ok() != self.content {
+            return Err(io::Error::other(format!(
+                "the file {} has been changed by another process in the meantime. Please try again",
+                self.file_name
+            )));
+        }
+
+        Ok(lock)
+    }
+}
+
+// the id of the process holding the lock
+fn read_owner(lock_file_name: &str) -> Option<u32> {
+    fs::read_to_string(lock_file_name).ok()?.trim().parse().ok()
+}
+
+fn is_stale(lock_file_name: &str, owner: Option<u32>) -> bool {
+    let age = fs::metadata(lock_file_name)
+        .and_then(|metadata| metadata.modified())
+        .map(|modified| modified.elapsed().unwrap_or_default());
+
+    match age {
+        // the lock file has been removed in the meantime
+        Err(_) => false,
+        // a running process may hold the lock for a long time, e.g. while it asks the user
+        Ok(age) => match owner {
+            Some(pid) => !is_running(pid),
+            None => age >= STALE_LOCK_AGE,
+        },
+    }
+}
+
+// creates the lock file with the id of this process at once, so that no other process sees it
+// without one. Unlike renaming, linking the file fails if the lock file exists already
+fn create_lock_file(lock_file_name: &str) -> io::Result<()> {
+    let new_file_name = format!("{lock_file_name}.{}", std::process::id());
+    fs::write(&new_file_name, format!("{}\n", std::process::id()))?;
+    let result = fs::hard_link(&new_file_name, lock_file_name);
+    let _ = fs::remove_file(&new_file_name);
+    result
+}
+
+// removes a stale lock file of the given owner. Another process may have removed it and locked
+// the file again in the meantime, so the lock file is moved away first and put back if it does
+// not belong to the stale owner anymore
+fn remove_stale_lock_file(lock_file_name: &str, owner: Option<u32>) -> io::Result<()> {
+    let stale_file_name = format!("{lock_file_name}.stale.{}", std::process::id());
+
+    match fs::rename(lock_file_name, &stale_file_name) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        result => result?,
+    }
+
+    if read_owner(&stale_file_name) != owner {
+        // fails only if a third process has locked the file in the meantime, which keeps it
+        let _ = fs::hard_link(&stale_file_name, lock_file_name);
+    }
+
+    fs::remove_file(&stale_file_name)
+}
+
+#[cfg(unix)]
+fn is_running(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+
+    // sending no signal at all only checks whether the process exists
+    pid > 0
+        && (unsafe { libc::kill(pid, 0) } == 0
+            || io::Error::last_os_error().raw_os_error() != Some(libc::ESRCH))
+}
+
+// without a way to look up the process, only the age of the lock file tells whether it is stale
+#[cfg(not(unix))]
+fn is_running(_pid: u32) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file_name(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("bartib-lock-{}-{name}", std::process::id()))
+            .to_string_lossy()
+            .to_string()
+    }
+
+    #[test]
+    fn lock_is_released_when_dropped() {
+        let file_name = file_name("released");
+        let lock_file_name = get_lock_file_name(&file_name);
+
+        let lock = FileLock::acquire(&file_name).unwrap();
+        assert_eq!(
+            read_owner(&lock_file_name),
+            Some(std::process::id()),
+            "lock file contains the process id"
+        );
+        assert!(!is_stale(&lock_file_name, read_owner(&lock_file_name)));
+
+        let inner_lock = FileLock::acquire(&file_name).unwrap();
+        drop(inner_lock);
+        assert!(fs::metadata(&lock_file_name).is_ok(), "outer lock is kept");
+
+        drop(lock);
+        assert!(fs::metadata(&lock_file_name).is_err());
+    }
+
+    #[test]
+    fn lock_of_running_process_is_not_stale() {
+        let file_name = file_name("running");
+        let lock_file_name = get_lock_file_name(&file_name);
+
+        let lock = FileLock::acquire(&file_name).unwrap();
+        let old = SystemTime::now() - STALE_LOCK_AGE * 2;
+        fs::File::options()
+            .write(true)
+            .open(&lock_file_name)
+            .and_then(|file| file.set_modified(old))
+            .unwrap();
+
+        assert!(!is_stale(&lock_file_name, read_owner(&lock_file_name)));
+        assert!(is_stale(&lock_file_name, None));
+        drop(lock);
+    }
+
+    #[test]
+    fn snapshot_detects_changes() {
+        let file_name = file_name("snapshot");
+        fs::write(&file_name, "a\n").unwrap();
+
+        let snapshot = Snapshot::take(&file_name);
+        drop(snapshot.lock_unchanged().unwrap());
+
+        fs::write(&file_name, "b\n").unwrap();
+        assert!(snapshot.lock_unchanged().is_err());
+        assert!(fs::metadata(get_lock_file_name(&file_name)).is_err());
+
+        fs::remove_file(&file_name).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stale_lock_is_removed() {
+        let file_name = file_name("stale");
+        let lock_file_name = get_lock_file_name(&file_name);
+
+        // larger than the maximum process id of linux and macos
+        fs::write(&lock_file_name, "999999999\n").unwrap();
+        assert!(is_stale(&lock_file_name, read_owner(&lock_file_name)));
+
+        let lock = FileLock::acquire(&file_name).unwrap();
+        assert_eq!(read_owner(&lock_file_name), Some(std::process::id()));
+        drop(lock);
+    }
+
+    #[test]
+    fn lock_replacing_a_stale_one_is_kept() {
+        let file_name = file_name("replaced");
+        let lock_file_name = get_lock_file_name(&file_name);
+
+        // another process has removed the stale lock file and locked the file in the meantime
+        let lock = FileLock::acquire(&file_name).unwrap();
+        remove_stale_lock_file(&lock_file_name, Some(999_999_999)).unwrap();
+        assert_eq!(read_owner(&lock_file_name), Some(std::process::id()));
+
+        remove_stale_lock_file(&lock_file_name, Some(std::process::id())).unwrap();
+        assert!(fs::metadata(&lock_file_name).is_err());
+        remove_stale_lock_file(&lock_file_name, None).unwrap();
+        drop(lock);
+    }
+}
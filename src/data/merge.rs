@@ -0,0 +1,220 @@
+use chrono::NaiveDateTime;
+use clap::ValueEnum;
+
+use crate::data::activity::Activity;
+use crate::data::bartib_file::Line;
+
+// how to handle activities of another log that overlap with existing activities
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ConflictStrategy {
+    // ask for each conflict
+    #[default]
+    Ask,
+    // keep the existing activities and drop the other one
+    Ours,
+    // replace the existing activities with the other one
+    Theirs,
+    // keep all of them
+    Both,
+}
+
+// the decision for a single conflict
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Ours,
+    Theirs,
+    Both,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct MergeSummary {
+    // activities that have been added
+    pub added: usize,
+    // activities that were already present
+    pub duplicates: usize,
+    // activities that have been dropped because of a conflict
+    pub dropped: usize,
+    // existing activities that have been replaced because of a conflict
+    pub replaced: usize,
+}
+
+// merges activities of another log into the lines of a bartib file
+//
+// identical activities are skipped. For every activity that overlaps with existing ones, `resolve`
+// decides which of them to keep. Running activities last until `now`. New activities are inserted
+// before the first activity that started later, so a chronological log stays chronological.
+pub fn merge(
+    lines: &mut Vec<Line>,
+    incoming: Vec<Activity>,
+    now: NaiveDateTime,
+    mut resolve: impl FnMut(&Activity, &[&Activity]) -> Resolution,
+) -> MergeSummary {
+    let mut summary = MergeSummary::default();
+
+    for activity in incoming {
+        let is_duplicate = lines
+            .iter()
+            .any(|line| line.activity.as_ref().is_ok_and(|a| *a == activity));
+
+        if is_duplicate {
+            summary.duplicates += 1;
+            continue;
+        }
+
+        let conflicting_lines: Vec<usize> = lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| {
+                line.activity
+                    .as_ref()
+                    .is_ok_and(|a| a.overlaps(&activity, now))
+            })
+            .map(|(i, _)| i)
+            .collect();
+
+        if !conflicting_lines.is_empty() {
+            let existing: Vec<&Activity> = conflicting_lines
+                .iter()
+                .filter_map(|i| lines[*i].activity.as_ref().ok())
+                .collect();
+
+            match resolve(&activity, &existing) {
+                Resolution::Ours => {
+                    summary.dropped += 1;
+                    continue;
+                }
+                Resolution::Theirs => {
+                    for i in conflicting_lines.iter().rev() {
+                        lines.remove(*i);
+                    }
+                    summary.replaced += conflicting_lines.len();
+                }
+                Resolution::Both => {}
+            }
+        }
+
+        let position = lines
+            .iter()
+            .position(|line| {
+                line.activity
+                    .as_ref()
+                    .is_ok_and(|a| a.start > activity.start)
+            })
+            .unwrap_or(lines.len());
+        lines.insert(position, Line::for_activity(activity));
+        summary.added += 1;
+    }
+
+    summary
+}
+
+// removes the activities which have been deleted or changed in another copy of a log since both
+// copies have been the same `base`, e.g. for a three-way merge in git
+//
+// returns the number of removed lines. Changed activities are added again by merging the other
+// copy.
+pub fn remove_deleted(lines: &mut Vec<Line>, base: &[Activity], other: &[Activity]) -> usize {
+    let deleted: Vec<&Activity> = base.iter().filter(|a| !other.contains(a)).collect();
+    let number_of_lines = lines.len();
+
+    lines.retain(|line| {
+        line.activity
+            .as_ref()
+            .map_or(true, |activity| !deleted.contains(&activity))
+    });
+
+    number_of_lines - lines.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::activity::activity;
+
+    fn lines() -> Vec<Line> {
+        vec![
+            Line::for_activity(activity("a", "2024-03-01 09:00", "2024-03-01 10:00")),
+            Line::for_activity(activity("b", "2024-03-01 11:00", "2024-03-01 12:00")),
+        ]
+    }
+
+    fn incoming() -> Vec<Activity> {
+        vec![
+            activity("a", "2024-03-01 09:00", "2024-03-01 10:00"),
+            activity("c", "2024-03-01 10:00", "2024-03-01 11:00"),
+            activity("d", "2024-03-01 11:30", "2024-03-01 12:30"),
+        ]
+    }
+
+    fn projects(lines: &[Line]) -> Vec<&str> {
+        lines
+            .iter()
+            .map(|line| line.activity.as_ref().unwrap().project.as_str())
+            .collect()
+    }
+
+    fn now() -> NaiveDateTime {
+        NaiveDateTime::parse_from_str("2024-03-02 00:00", "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn merge_keeping_ours() {
+        let mut lines = lines();
+        let summary = merge(&mut lines, incoming(), now(), |activity, existing| {
+            assert_eq!(activity.project, "d");
+            assert_eq!(existing.len(), 1);
+            Resolution::Ours
+        });
+
+        assert_eq!(projects(&lines), vec!["a", "c", "b"]);
+        assert_eq!(
+            summary,
+            MergeSummary {
+                added: 1,
+                duplicates: 1,
+                dropped: 1,
+                replaced: 0
+            }
+        );
+    }
+
+    #[test]
+    fn merge_taking_theirs_or_both() {
+        let mut lines = lines();
+        let summary = merge(&mut lines, incoming(), now(), |_, _| Resolution::Theirs);
+
+        assert_eq!(projects(&lines), vec!["a", "c", "d"]);
+        assert_eq!(summary.replaced, 1);
+
+        let mut lines = self::lines();
+        merge(&mut lines, incoming(), now(), |_, _| Resolution::Both);
+
+        assert_eq!(projects(&lines), vec!["a", "c", "b", "d"]);
+    }
+
+    #[test]
+    fn remove_deleted_activities() {
+        let base = vec![
+            activity("a", "2024-03-01 09:00", "2024-03-01 10:00"),
+            activity("b", "2024-03-01 11:00", "2024-03-01 12:00"),
+        ];
+        // `b` has been changed in the other copy
+        let other = vec![
+            activity("a", "2024-03-01 09:00", "2024-03-01 10:00"),
+            activity("b", "2024-03-01 11:00", "2024-03-01 12:30"),
+        ];
+
+        let mut lines = lines();
+        lines.push(Line::for_activity(activity(
+            "new",
+            "2024-03-01 13:00",
+            "2024-03-01 14:00",
+        )));
+
+        assert_eq!(remove_deleted(&mut lines, &base, &other), 1);
+        assert_eq!(projects(&lines), vec!["a", "new"]);
+
+        merge(&mut lines, other, now(), |_, _| Resolution::Both);
+        assert_eq!(projects(&lines), vec!["a", "b", "new"]);
+    }
+}
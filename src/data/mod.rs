@@ -1,6 +1,32 @@
+pub mod archive;
 pub mod activity;
+pub mod aliases;
 pub mod bartib_file;
+pub mod budget;
+pub mod config;
+pub mod date_parser;
+pub mod duration_parser;
+pub mod encryption;
 pub mod filter;
 pub mod getter;
+pub mod goal;
+pub mod hooks;
+pub mod http;
+pub mod idle;
+pub mod index;
+pub mod interop;
+pub mod jira;
+pub mod json_parser;
+pub mod lock;
+pub mod merge;
 pub mod processor;
+pub mod rates;
+pub mod recurrence;
+pub mod redaction;
+pub mod reminder;
 pub mod round_util;
+pub mod sanity;
+pub mod settings;
+pub mod stats;
+pub mod timezone;
+pub mod undo;
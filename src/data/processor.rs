@@ -1,8 +1,12 @@
 use anyhow::Result;
-use chrono::Duration;
+use chrono::{Duration, FixedOffset, NaiveDate, NaiveDateTime};
+use std::collections::BTreeMap;
 
 use crate::data::activity;
-use crate::data::round_util::round_datetime;
+use crate::data::budget::BudgetUsage;
+use crate::data::goal::GoalProgress;
+use crate::data::round_util::{RoundingMode, RoundingScope, round_datetime, round_duration};
+use crate::data::timezone::{self, Timezone};
 
 pub type ProcessorList = Vec<Box<dyn ActivityProcessor>>;
 
@@ -16,26 +20,202 @@ pub struct StatusReportData<'a> {
     pub today: Duration,
     pub current_week: Duration,
     pub current_month: Duration,
+    pub daily_limit: Option<Duration>,
+    pub goals: Vec<GoalProgress<'a>>,
+    pub budgets: Vec<BudgetUsage<'a>>,
 }
 pub trait StatusReportWriter {
     fn process(&self, data: &StatusReportData) -> Result<()>;
 }
 
+pub struct ReportData<'a> {
+    pub activities: &'a [&'a activity::Activity],
+    pub total_rounding: Option<&'a TotalRounding>,
+    // the range of dates the report has been restricted to
+    pub from_date: Option<NaiveDate>,
+    pub to_date: Option<NaiveDate>,
+}
+pub trait ReportWriter {
+    fn process(&self, data: &ReportData) -> Result<()>;
+}
+
+// the report of a period next to the report of the period before, e.g. of the last week
+pub struct ComparisonData<'a> {
+    pub current: ReportData<'a>,
+    pub previous: ReportData<'a>,
+}
+pub trait ComparisonWriter {
+    fn process(&self, data: &ComparisonData) -> Result<()>;
+}
+
+// rounds the start and end times of activities and their pauses
+//
+// the mode tells how the duration of an activity is rounded: `Up` rounds the start down and the
+// end up, so activities never get shorter, and `Down` does the reverse, so they never get longer.
+// Pauses are rounded the other way round, as they are subtracted from the duration. If rounding
+// leaves nothing of an activity or a pause, it ends when it starts.
 pub struct RoundProcessor {
     pub round: Duration,
+    pub mode: RoundingMode,
+}
+
+impl RoundProcessor {
+    // the rounded start and end of an activity, or of a pause with the reversed mode
+    fn round_times(
+        &self,
+        start: NaiveDateTime,
+        end: Option<NaiveDateTime>,
+        mode: RoundingMode,
+    ) -> (NaiveDateTime, Option<NaiveDateTime>) {
+        let start = round_datetime(&start, &self.round, mode.reversed());
+        let end = end.map(|end| round_datetime(&end, &self.round, mode).max(start));
+        (start, end)
+    }
 }
 
 impl ActivityProcessor for RoundProcessor {
     fn process(&self, activity: &activity::Activity) -> activity::Activity {
-        let start = round_datetime(&activity.start, &self.round);
-        let end = activity.end.map(|end| round_datetime(&end, &self.round));
+        let (start, end) = self.round_times(activity.start, activity.end, self.mode);
 
         activity::Activity {
             start,
             end,
+            start_offset: activity.start_offset,
+            end_offset: activity.end_offset,
             project: activity.project.clone(),
             description: activity.description.clone(),
+            tags: activity.tags.clone(),
+            issue: activity.issue.clone(),
+            pauses: activity
+                .pauses
+                .iter()
+                .map(|pause| {
+                    let (start, end) =
+                        self.round_times(pause.start, pause.end, self.mode.reversed());
+                    activity::Pause { start, end }
+                })
+                .collect(),
+            notes: activity.notes.clone(),
+        }
+    }
+}
+
+// extends stopped activities which are shorter than a minimum duration, e.g. to bill at least a
+// minimum increment for a short call. Combined with a `RoundProcessor`, it is applied after
+// rounding so that activities rounded to nothing are extended, too
+pub struct MinDurationProcessor {
+    pub min_duration: Duration,
+}
+
+impl ActivityProcessor for MinDurationProcessor {
+    fn process(&self, activity: &activity::Activity) -> activity::Activity {
+        let mut activity = activity.clone();
+
+        if let Some(end) = activity.end {
+            let duration = activity.get_duration();
+
+            if duration < self.min_duration {
+                activity.end = Some(end + (self.min_duration - duration));
+            }
+        }
+
+        activity
+    }
+}
+
+// shows the times of activities in another timezone (see `--timezone`)
+//
+// the activities are converted before they are filtered and split at midnight, so they belong to
+// the days of this timezone. Pauses have no offsets of their own and are converted with the offset
+// of the start of their activity.
+pub struct TimezoneProcessor {
+    pub timezone: Timezone,
+}
+
+impl ActivityProcessor for TimezoneProcessor {
+    fn process(&self, activity: &activity::Activity) -> activity::Activity {
+        let start_offset = activity
+            .start_offset
+            .unwrap_or_else(|| timezone::local_offset(activity.start));
+        let convert =
+            |time: NaiveDateTime, offset: FixedOffset| self.timezone.convert(time, offset);
+
+        let (start, start_offset_in_timezone) = convert(activity.start, start_offset);
+        let end = activity.end.map(|end| {
+            convert(
+                end,
+                activity
+                    .end_offset
+                    .unwrap_or_else(|| timezone::local_offset(end)),
+            )
+        });
+
+        activity::Activity {
+            start,
+            end: end.map(|(end, _)| end),
+            start_offset: Some(start_offset_in_timezone),
+            end_offset: end.map(|(_, offset)| offset),
+            pauses: activity
+                .pauses
+                .iter()
+                .map(|pause| activity::Pause {
+                    start: convert(pause.start, start_offset).0,
+                    end: pause.end.map(|end| convert(end, start_offset).0),
+                })
+                .collect(),
+            ..activity.clone()
+        }
+    }
+}
+
+// replaces hierarchical projects like `client/backend/api` by their parent project at a level of
+// the hierarchy, e.g. `client` at depth 1, to sum up the durations of all subprojects
+pub struct ProjectDepthProcessor {
+    pub depth: usize,
+}
+
+impl ActivityProcessor for ProjectDepthProcessor {
+    fn process(&self, activity: &activity::Activity) -> activity::Activity {
+        activity::Activity {
+            project: activity::project_at_depth(&activity.project, self.depth).to_string(),
+            ..activity.clone()
+        }
+    }
+}
+
+// rounds the summed up durations of days (or projects per day) instead of single activities
+pub struct TotalRounding {
+    pub round: Duration,
+    pub mode: RoundingMode,
+    pub scope: RoundingScope,
+}
+
+impl TotalRounding {
+    // sums up the exact durations per unit of the rounding scope and rounds each of these sums
+    pub fn sum_duration(&self, activities: &[&activity::Activity]) -> Duration {
+        let mut totals: BTreeMap<(NaiveDate, Option<&str>), Duration> = BTreeMap::new();
+
+        for activity in activities {
+            let project = match self.scope {
+                RoundingScope::ProjectDay => Some(activity.project.as_str()),
+                RoundingScope::Activity | RoundingScope::Day => None,
+            };
+
+            let total = totals
+                .entry((activity.start.date(), project))
+                .or_insert_with(|| Duration::seconds(0));
+            *total += activity.get_duration();
         }
+
+        totals
+            .values()
+            .map(|total| round_duration(total, &self.round, self.mode))
+            .sum()
+    }
+
+    // whether the totals of single projects are rounded, too
+    pub fn rounds_projects(&self) -> bool {
+        matches!(self.scope, RoundingScope::ProjectDay)
     }
 }
 
@@ -53,3 +233,152 @@ pub fn process_activities(
         })
         .collect()
 }
+
+// splits the activities spanning multiple days at midnight, so that their time counts towards
+// each day it has been spent on, e.g. for night shifts. The activities keep their order
+#[must_use]
+pub fn split_at_midnight(activities: Vec<activity::Activity>) -> Vec<activity::Activity> {
+    activities
+        .into_iter()
+        .flat_map(|activity| activity.split_at_midnight())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDateTime;
+
+    use super::*;
+    use crate::data::activity::activity;
+
+    #[test]
+    fn total_rounding_per_day() {
+        let a1 = activity("p1", "2024-03-01 09:00", "2024-03-01 09:05");
+        let a2 = activity("p2", "2024-03-01 10:00", "2024-03-01 10:05");
+        let a3 = activity("p1", "2024-03-02 09:00", "2024-03-02 09:20");
+        let activities = vec![&a1, &a2, &a3];
+
+        let rounding = TotalRounding {
+            round: Duration::minutes(15),
+            mode: RoundingMode::Up,
+            scope: RoundingScope::Day,
+        };
+
+        assert_eq!(rounding.sum_duration(&activities), Duration::minutes(45));
+    }
+
+    #[test]
+    fn total_rounding_per_project_and_day() {
+        let a1 = activity("p1", "2024-03-01 09:00", "2024-03-01 09:05");
+        let a2 = activity("p2", "2024-03-01 10:00", "2024-03-01 10:05");
+        let a3 = activity("p1", "2024-03-02 09:00", "2024-03-02 09:20");
+        let activities = vec![&a1, &a2, &a3];
+
+        let rounding = TotalRounding {
+            round: Duration::minutes(15),
+            mode: RoundingMode::Up,
+            scope: RoundingScope::ProjectDay,
+        };
+
+        assert_eq!(rounding.sum_duration(&activities), Duration::minutes(60));
+    }
+
+    #[test]
+    fn convert_to_timezone() {
+        let offset = |hours: i32| FixedOffset::east_opt(hours * 3600).unwrap();
+        let mut a1 = activity("p1", "2024-03-01 22:00", "2024-03-01 23:30");
+        a1.start_offset = Some(offset(1));
+        a1.end_offset = Some(offset(1));
+
+        let converted = TimezoneProcessor {
+            timezone: Timezone::Fixed(offset(-5)),
+        }
+        .process(&a1);
+
+        assert_eq!(converted.start.to_string(), "2024-03-01 16:00:00");
+        assert_eq!(
+            converted.end.map(|end| end.to_string()),
+            Some("2024-03-01 17:30:00".to_string())
+        );
+        assert_eq!(converted.end_offset, Some(offset(-5)));
+        assert_eq!(converted.get_duration(), a1.get_duration());
+    }
+
+    #[test]
+    fn rounding_up_never_shortens_activities() {
+        let mut with_pause = activity("p1", "2024-03-01 09:01", "2024-03-01 10:44");
+        with_pause.pauses.push(activity::Pause {
+            start: NaiveDateTime::parse_from_str("2024-03-01 09:20", "%F %R").unwrap(),
+            end: NaiveDateTime::parse_from_str("2024-03-01 09:50", "%F %R").ok(),
+        });
+        let activities = [
+            activity("p1", "2024-03-01 09:01", "2024-03-01 09:14"),
+            activity("p1", "2024-03-01 09:00", "2024-03-01 09:15"),
+            activity("p1", "2024-03-01 09:14", "2024-03-01 09:46"),
+            activity("p1", "2024-03-01 23:50", "2024-03-02 00:05"),
+            with_pause,
+        ];
+
+        for a in &activities {
+            let round = |mode| {
+                RoundProcessor {
+                    round: Duration::minutes(15),
+                    mode,
+                }
+                .process(a)
+                .get_duration()
+            };
+
+            assert!(round(RoundingMode::Up) >= a.get_duration(), "{a}");
+            assert!(round(RoundingMode::Down) <= a.get_duration(), "{a}");
+            assert!(round(RoundingMode::Down) >= Duration::zero(), "{a}");
+        }
+
+        let rounded = RoundProcessor {
+            round: Duration::minutes(15),
+            mode: RoundingMode::Up,
+        }
+        .process(&activities[0]);
+        assert_eq!(rounded.start.to_string(), "2024-03-01 09:00:00");
+        assert_eq!(rounded.get_duration(), Duration::minutes(15));
+    }
+
+    #[test]
+    fn min_duration_after_rounding() {
+        let a1 = activity("p1", "2024-03-01 09:00", "2024-03-01 09:04");
+        let a2 = activity("p1", "2024-03-01 10:00", "2024-03-01 10:50");
+        let mut running = activity("p1", "2024-03-01 11:00", "2024-03-01 11:00");
+        running.end = None;
+
+        let processors: ProcessorList = vec![
+            Box::new(RoundProcessor {
+                round: Duration::minutes(5),
+                mode: RoundingMode::Nearest,
+            }),
+            Box::new(MinDurationProcessor {
+                min_duration: Duration::minutes(15),
+            }),
+        ];
+        let processed = process_activities(vec![&a1, &a2, &running], processors);
+
+        assert_eq!(processed[0].get_duration(), Duration::minutes(15));
+        assert_eq!(processed[0].start, a1.start);
+        assert_eq!(processed[1].get_duration(), Duration::minutes(50));
+        assert_eq!(processed[2].end, None);
+    }
+
+    #[test]
+    fn split_night_shift_at_midnight() {
+        let night_shift = activity("p1", "2024-03-01 22:00", "2024-03-02 06:00");
+        let day_shift = activity("p2", "2024-03-02 08:00", "2024-03-02 12:00");
+
+        let split = split_at_midnight(vec![night_shift, day_shift.clone()]);
+
+        assert_eq!(split.len(), 3);
+        assert_eq!(split[0].end.unwrap().to_string(), "2024-03-02 00:00:00");
+        assert_eq!(split[0].get_duration(), Duration::hours(2));
+        assert_eq!(split[1].start.date().to_string(), "2024-03-02");
+        assert_eq!(split[1].get_duration(), Duration::hours(6));
+        assert_eq!(split[2], day_shift);
+    }
+}
@@ -0,0 +1,124 @@
+use chrono::Duration;
+use wildmatch::WildMatch;
+
+use crate::data::activity;
+use crate::data::config::{Config, ConfigError};
+
+// the name of the table defining the hourly rates of projects in the configuration file
+pub static CONFIG_TABLE: &str = "rates";
+
+// the hourly rates of projects
+//
+// in the configuration file the rates are defined like this, optionally with a currency which is
+// printed after all amounts:
+//
+//     currency = "EUR"
+//
+//     [rates]
+//     "Important Project" = 85.5
+//     "Maintenance *" = 60
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Rates {
+    // the rates by project names which may contain `?` and `*` as wildcards
+    rates: Vec<(String, f64)>,
+    pub currency: Option<String>,
+}
+
+impl Rates {
+    pub fn from_config(config: &Config) -> Result<Self, ConfigError> {
+        let mut rates = Vec::new();
+
+        for table in config.tables(CONFIG_TABLE) {
+            for (project, value) in &table.entries {
+                let rate = value.as_f64().filter(|rate| *rate >= 0.0).ok_or_else(|| {
+                    ConfigError::InvalidSetting(
+                        table.qualified_key(project),
+                        "expected a positive number like 85.5".to_string(),
+                    )
+                })?;
+                rates.push((project.clone(), rate));
+            }
+        }
+
+        let currency = match config.get("", "currency") {
+            None => None,
+            Some(value) => Some(value.as_str().map(ToString::to_string).ok_or_else(|| {
+                ConfigError::InvalidSetting("currency".to_string(), "expected a string".to_string())
+            })?),
+        };
+
+        Ok(Self { rates, currency })
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.rates.is_empty()
+    }
+
+    // the hourly rate of a project
+    //
+    // a rate for the exact name of the project wins, then the rate of the nearest parent project,
+    // e.g. of `client` for `client/backend`. Otherwise the first rate with a pattern matching the
+    // project or one of its parents is used.
+    #[must_use]
+    pub fn get_rate(&self, project: &str) -> Option<f64> {
+        activity::project_hierarchy(project)
+            .find_map(|project| self.rates.iter().find(|(name, _)| name == project))
+            .or_else(|| {
+                activity::project_hierarchy(project).find_map(|project| {
+                    self.rates
+                        .iter()
+                        .find(|(name, _)| WildMatch::new(name).matches(project))
+                })
+            })
+            .map(|(_, rate)| *rate)
+    }
+}
+
+// the earnings for a duration at an hourly rate, rounded to cents
+#[must_use]
+pub fn calculate_earnings(duration: &Duration, rate: f64) -> f64 {
+    let earnings = duration.num_seconds() as f64 / 3600.0 * rate;
+    (earnings * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rates() {
+        let config: Config =
+            "currency = \"EUR\"\n[rates]\n\"Client A\" = 80\n\"Client *\" = 60.5\ninternal = 0"
+                .parse()
+                .unwrap();
+        let rates = Rates::from_config(&config).unwrap();
+
+        assert_eq!(rates.currency.as_deref(), Some("EUR"));
+        assert_eq!(rates.get_rate("Client A"), Some(80.0));
+        assert_eq!(rates.get_rate("Client B"), Some(60.5));
+        assert_eq!(rates.get_rate("internal"), Some(0.0));
+        assert_eq!(rates.get_rate("Other"), None);
+        assert_eq!(rates.get_rate("internal/meetings"), Some(0.0));
+        assert_eq!(rates.get_rate("Client A/Backend"), Some(80.0));
+    }
+
+    #[test]
+    fn parse_invalid_rates() {
+        let negative: Config = "[rates]\np = -5".parse().unwrap();
+        assert!(Rates::from_config(&negative).is_err());
+
+        let text: Config = "[rates]\np = \"80\"".parse().unwrap();
+        assert!(Rates::from_config(&text).is_err());
+
+        let currency: Config = "currency = 1".parse().unwrap();
+        assert!(Rates::from_config(&currency).is_err());
+    }
+
+    #[test]
+    fn calculate_earnings_test() {
+        assert_eq!(calculate_earnings(&Duration::minutes(90), 80.0), 120.0);
+        assert_eq!(calculate_earnings(&Duration::minutes(20), 50.0), 16.67);
+        assert_eq!(calculate_earnings(&Duration::zero(), 50.0), 0.0);
+    }
+}
@@ -0,0 +1,255 @@
+use chrono::{Datelike, NaiveDate, NaiveTime, Weekday};
+
+use crate::data::activity::Activity;
+use crate::data::config::{Config, ConfigError, Table, Value};
+use crate::data::timezone;
+
+// the name of the tables defining recurring activities in the configuration file
+pub static CONFIG_TABLE: &str = "recur";
+
+// a rule for an activity that takes place on the same weekdays at the same time
+//
+// in the configuration file a rule is defined like this:
+//
+//     [[recur]]
+//     days = "mon-fri"
+//     start = "09:30"
+//     end = "09:45"
+//     project = "Meetings"
+//     description = "Standup"
+//     tags = ["internal"]
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecurrenceRule {
+    pub weekdays: Vec<Weekday>,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    pub project: String,
+    pub description: String,
+    pub tags: Vec<String>,
+}
+
+impl RecurrenceRule {
+    pub fn from_table(table: &Table) -> Result<Self, ConfigError> {
+        let weekdays = parse_weekdays(table.get_str("days")?).ok_or_else(|| {
+            ConfigError::InvalidSetting(
+                table.qualified_key("days"),
+                "expected weekdays like `mon-fri`, `mon,wed,fri` or `daily`".to_string(),
+            )
+        })?;
+        let start = parse_time(table, "start")?;
+        let end = parse_time(table, "end")?;
+
+        if end <= start {
+            return Err(ConfigError::InvalidSetting(
+                table.qualified_key("end"),
+                "the end must be after the start".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            weekdays,
+            start,
+            end,
+            project: table.get_str("project")?.to_string(),
+            description: table.get_str("description")?.to_string(),
+            tags: parse_tags(table)?,
+        })
+    }
+
+    // the activities this rule defines for all dates within the given range (inclusive)
+    #[must_use]
+    pub fn activities_between(&self, from: NaiveDate, to: NaiveDate) -> Vec<Activity> {
+        from.iter_days()
+            .take_while(|date| *date <= to)
+            .filter(|date| self.weekdays.contains(&date.weekday()))
+            .map(|date| Activity {
+                start: date.and_time(self.start),
+                end: Some(date.and_time(self.end)),
+                start_offset: Some(timezone::local_offset(date.and_time(self.start))),
+                end_offset: Some(timezone::local_offset(date.and_time(self.end))),
+                project: self.project.clone(),
+                description: self.description.clone(),
+                tags: self.tags.clone(),
+                issue: None,
+                pauses: Vec::new(),
+                notes: Vec::new(),
+            })
+            .collect()
+    }
+
+    // a short description of the weekdays, e.g. "Mon, Tue, Fri"
+    #[must_use]
+    pub fn format_weekdays(&self) -> String {
+        self.weekdays
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+// reads all recurrence rules from the configuration
+pub fn get_rules(config: &Config) -> Result<Vec<RecurrenceRule>, ConfigError> {
+    config
+        .tables(CONFIG_TABLE)
+        .map(RecurrenceRule::from_table)
+        .collect()
+}
+
+// the optional tags of a rule, given as an array of strings
+fn parse_tags(table: &Table) -> Result<Vec<String>, ConfigError> {
+    let invalid_tags = || {
+        ConfigError::InvalidSetting(
+            table.qualified_key("tags"),
+            "expected an array of strings like [\"billable\"]".to_string(),
+        )
+    };
+
+    match table.get("tags") {
+        None => Ok(Vec::new()),
+        Some(Value::Array(values)) => values
+            .iter()
+            .map(|value| {
+                value
+                    .as_str()
+                    .map(ToString::to_string)
+                    .ok_or_else(invalid_tags)
+            })
+            .collect(),
+        Some(_) => Err(invalid_tags()),
+    }
+}
+
+fn parse_time(table: &Table, key: &str) -> Result<NaiveTime, ConfigError> {
+    let time = table.get_str(key)?;
+
+    NaiveTime::parse_from_str(time, "%R")
+        .or_else(|_| NaiveTime::parse_from_str(time, "%T"))
+        .map_err(|_| {
+            ConfigError::InvalidSetting(
+                table.qualified_key(key),
+                "expected a time like 09:30".to_string(),
+            )
+        })
+}
+
+// parses weekdays like `daily`, `mon-fri`, `sat,sun` or `mon,wed-fri`
+fn parse_weekdays(days: &str) -> Option<Vec<Weekday>> {
+    let days = days.trim().to_lowercase();
+
+    if days == "daily" {
+        return Some(weekdays_from(Weekday::Mon, Weekday::Sun));
+    }
+
+    let mut weekdays = Vec::new();
+
+    for part in days.split(',') {
+        match part.split_once('-') {
+            Some((first, last)) => {
+                let first: Weekday = first.trim().parse().ok()?;
+                let last: Weekday = last.trim().parse().ok()?;
+                weekdays.extend(weekdays_from(first, last));
+            }
+            None => weekdays.push(part.trim().parse().ok()?),
+        }
+    }
+
+    Some(weekdays)
+}
+
+// all weekdays from first to last (inclusive). The range may wrap around the end of the week
+fn weekdays_from(first: Weekday, last: Weekday) -> Vec<Weekday> {
+    let mut weekdays = vec![first];
+    let mut weekday = first;
+
+    while weekday != last {
+        weekday = weekday.succ();
+        weekdays.push(weekday);
+    }
+
+    weekdays
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule_config(days: &str, start: &str, end: &str) -> Config {
+        format!(
+            "[[recur]]\ndays = \"{days}\"\nstart = \"{start}\"\nend = \"{end}\"\nproject = \"Meetings\"\ndescription = \"Standup\""
+        )
+        .parse()
+        .unwrap()
+    }
+
+    #[test]
+    fn parse_rule() {
+        let rules = get_rules(&rule_config("mon-fri", "09:30", "09:45")).unwrap();
+
+        assert_eq!(rules.len(), 1);
+        assert_eq!(
+            rules[0].weekdays,
+            vec![
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri
+            ]
+        );
+        assert_eq!(rules[0].start, NaiveTime::from_hms_opt(9, 30, 0).unwrap());
+        assert_eq!(rules[0].end, NaiveTime::from_hms_opt(9, 45, 0).unwrap());
+        assert_eq!(rules[0].project, "Meetings");
+        assert_eq!(rules[0].description, "Standup");
+        assert!(rules[0].tags.is_empty());
+
+        let config: Config = "[[recur]]\ndays = \"mon\"\nstart = \"09:30\"\nend = \"09:45\"\nproject = \"p\"\ndescription = \"d\"\ntags = [\"a\", \"b\"]"
+            .parse()
+            .unwrap();
+        assert_eq!(get_rules(&config).unwrap()[0].tags, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn parse_weekdays_test() {
+        assert_eq!(parse_weekdays("daily").unwrap().len(), 7);
+        assert_eq!(
+            parse_weekdays("sat-mon").unwrap(),
+            vec![Weekday::Sat, Weekday::Sun, Weekday::Mon]
+        );
+        assert_eq!(
+            parse_weekdays("mon, Wed-thu").unwrap(),
+            vec![Weekday::Mon, Weekday::Wed, Weekday::Thu]
+        );
+        assert_eq!(parse_weekdays("someday"), None);
+    }
+
+    #[test]
+    fn parse_invalid_rules() {
+        assert!(get_rules(&rule_config("mon-fri", "09:45", "09:30")).is_err());
+        assert!(get_rules(&rule_config("mon-fri", "9.30", "09:45")).is_err());
+        assert!(get_rules(&rule_config("someday", "09:30", "09:45")).is_err());
+        assert!(get_rules(&"[[recur]]\ndays = \"mon\"".parse().unwrap()).is_err());
+    }
+
+    #[test]
+    fn activities_between_test() {
+        let rule = get_rules(&rule_config("mon-fri", "09:30", "09:45"))
+            .unwrap()
+            .remove(0);
+
+        // from friday to tuesday
+        let activities = rule.activities_between(
+            NaiveDate::from_ymd_opt(2024, 3, 8).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 3, 12).unwrap(),
+        );
+
+        assert_eq!(activities.len(), 3);
+        assert_eq!(
+            activities[1].start,
+            NaiveDate::from_ymd_opt(2024, 3, 11)
+                .unwrap()
+                .and_hms_opt(9, 30, 0)
+                .unwrap()
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use clap::ValueEnum;
+
+use crate::data::activity::Activity;
+
+// how the descriptions of exported activities are redacted (see `export --redact-descriptions`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum DescriptionRedaction {
+    // replaces each description by a hash, so activities with the same description can still be
+    // told apart from others
+    #[default]
+    Hash,
+    // removes the descriptions
+    Blank,
+}
+
+// anonymizes activities before a log is shared, e.g. with an employer. Times and projects are
+// kept, while notes are always removed as they may contain details of the tasks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Redaction {
+    Descriptions(DescriptionRedaction),
+    // removes the descriptions and the tags, too
+    OnlyProjects,
+}
+
+impl Redaction {
+    #[must_use]
+    pub fn apply(self, activity: &Activity) -> Activity {
+        let description = match self {
+            Self::Descriptions(DescriptionRedaction::Hash) => {
+                hash_description(&activity.description)
+            }
+            Self::Descriptions(DescriptionRedaction::Blank) | Self::OnlyProjects => String::new(),
+        };
+        let tags = match self {
+            Self::Descriptions(_) => activity.tags.clone(),
+            Self::OnlyProjects => Vec::new(),
+        };
+
+        Activity {
+            description,
+            tags,
+            notes: Vec::new(),
+            ..activity.clone()
+        }
+    }
+}
+
+// a stable hash of a text (FNV-1a, as the hashers of the standard library may change between
+// versions)
+#[must_use]
+pub fn hash(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+fn hash_description(description: &str) -> String {
+    if description.is_empty() {
+        return String::new();
+    }
+
+    format!("{:08x}", hash(description) & 0xffff_ffff)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDateTime;
+
+    use super::*;
+
+    #[test]
+    fn redact_activities() {
+        let mut activity = Activity::start(
+            "p".to_string(),
+            "fix the bug of customer X".to_string(),
+            NaiveDateTime::parse_from_str("2024-03-01 09:00", "%F %R").ok(),
+        )
+        .with_tags(vec!["billable".to_string()]);
+        activity.add_note("secret");
+
+        let hashed = Redaction::Descriptions(DescriptionRedaction::Hash).apply(&activity);
+        assert_eq!(hashed.description.len(), 8);
+        assert_ne!(hashed.description, activity.description);
+        assert_eq!(
+            hashed.description,
+            Redaction::Descriptions(DescriptionRedaction::Hash)
+                .apply(&activity)
+                .description
+        );
+        assert_eq!(hashed.tags, vec!["billable".to_string()]);
+        assert!(hashed.notes.is_empty());
+        assert_eq!(hashed.start, activity.start);
+        assert_eq!(hashed.project, "p");
+
+        let blank = Redaction::Descriptions(DescriptionRedaction::Blank).apply(&activity);
+        assert_eq!(blank.description, "");
+        assert_eq!(blank.tags, vec!["billable".to_string()]);
+
+        let only_projects = Redaction::OnlyProjects.apply(&activity);
+        assert_eq!(only_projects.description, "");
+        assert!(only_projects.tags.is_empty());
+        assert_eq!(only_projects.project, "p");
+    }
+}
@@ -0,0 +1,317 @@
+use chrono::{Datelike, Duration, NaiveDateTime, NaiveTime, Weekday};
+
+use crate::data::activity::Activity;
+use crate::data::config::{Config, ConfigError, Value};
+use crate::data::date_parser;
+use crate::data::duration_parser;
+use crate::data::filter::Filters;
+
+// the name of the table configuring the reminders in the configuration file
+pub static CONFIG_TABLE: &str = "remind";
+
+// the hours of a day in which activities are expected to be tracked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorkingHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+// when to remind to start or to stop tracking
+//
+// all settings are optional and may be overridden by command line arguments. In the
+// configuration file they are defined like this:
+//
+//     [remind]
+//     idle = "15m"
+//     running = "4h"
+//     working_hours = "09:00-17:00"
+//     working_days = ["mon", "tue", "wed", "thu", "fri"]
+//     daily_limit = "8h"
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReminderSettings {
+    // remind to start an activity if none has been running for this long during working hours
+    pub idle: Duration,
+    // remind to stop an activity if it has been running for this long
+    pub running: Duration,
+    pub working_hours: WorkingHours,
+    pub working_days: Vec<Weekday>,
+    // remind to stop tracking if more than this has been tracked today
+    pub daily_limit: Option<Duration>,
+}
+
+// the reason to remind the user
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reminder {
+    // no activity has been running for this duration during working hours
+    Idle(Duration),
+    // the activity has been running for longer than expected and may have been forgotten
+    Running(Activity),
+    // more than the daily limit has been tracked today and an activity is still running
+    DailyLimit { tracked: Duration, limit: Duration },
+}
+
+impl Default for ReminderSettings {
+    fn default() -> Self {
+        Self {
+            idle: Duration::minutes(15),
+            running: Duration::hours(4),
+            working_hours: WorkingHours {
+                start: NaiveTime::from_hms_opt(9, 0, 0).unwrap_or_default(),
+                end: NaiveTime::from_hms_opt(17, 0, 0).unwrap_or_default(),
+            },
+            working_days: vec![
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+            ],
+            daily_limit: None,
+        }
+    }
+}
+
+impl ReminderSettings {
+    pub fn from_config(config: &Config) -> Result<Self, ConfigError> {
+        let mut settings = Self::default();
+        let invalid = |key: &str, expected: &str| {
+            ConfigError::InvalidSetting(format!("{CONFIG_TABLE}.{key}"), expected.to_string())
+        };
+        let get_str = |key: &str| match config.get(CONFIG_TABLE, key) {
+            None => Ok(None),
+            Some(Value::String(s)) => Ok(Some(s.as_str())),
+            Some(_) => Err(invalid(key, "expected a string")),
+        };
+
+        if let Some(idle) = get_str("idle")? {
+            settings.idle =
+                duration_parser::parse_duration(idle).map_err(|e| invalid("idle", &e))?;
+        }
+
+        if let Some(running) = get_str("running")? {
+            settings.running =
+                duration_parser::parse_duration(running).map_err(|e| invalid("running", &e))?;
+        }
+
+        if let Some(daily_limit) = get_str("daily_limit")? {
+            settings.daily_limit = Some(
+                duration_parser::parse_duration(daily_limit)
+                    .map_err(|e| invalid("daily_limit", &e))?,
+            );
+        }
+
+        if let Some(working_hours) = get_str("working_hours")? {
+            settings.working_hours =
+                parse_working_hours(working_hours).map_err(|e| invalid("working_hours", &e))?;
+        }
+
+        if let Some(working_days) = config.get(CONFIG_TABLE, "working_days") {
+            let expected = "expected a list of weekdays like [\"mon\", \"tue\"]";
+            let Value::Array(days) = working_days else {
+                return Err(invalid("working_days", expected));
+            };
+
+            settings.working_days = days
+                .iter()
+                .map(|day| day.as_str().and_then(|day| day.parse().ok()))
+                .collect::<Option<_>>()
+                .ok_or_else(|| invalid("working_days", expected))?;
+        }
+
+        Ok(settings)
+    }
+
+    fn is_working_time(&self, now: NaiveDateTime) -> bool {
+        self.working_days.contains(&now.weekday())
+            && self.working_hours.start <= now.time()
+            && now.time() < self.working_hours.end
+    }
+}
+
+// parses working hours like `09:00-17:00` or `9am-5pm`
+pub fn parse_working_hours(working_hours: &str) -> Result<WorkingHours, String> {
+    let error = || format!("invalid working hours '{working_hours}', expected e.g. 09:00-17:00");
+
+    let (start, end) = working_hours.split_once('-').ok_or_else(error)?;
+    let start = date_parser::parse_time_of_day(start).map_err(|_| error())?;
+    let end = date_parser::parse_time_of_day(end).map_err(|_| error())?;
+
+    if start < end {
+        Ok(WorkingHours { start, end })
+    } else {
+        Err(error())
+    }
+}
+
+// whether the user should be reminded of anything
+//
+// a running activity is checked first, then the daily limit. Once the daily limit has been
+// exceeded, there is no reminder of idle time anymore. Otherwise the idle time is measured from
+// the end of the last activity, but not before the working hours of the day have begun.
+#[must_use]
+pub fn check(
+    settings: &ReminderSettings,
+    activities: &[&Activity],
+    now: NaiveDateTime,
+) -> Option<Reminder> {
+    let running = activities.iter().find(|a| !a.is_stopped());
+    if let Some(running) = running
+        && running.get_duration_at(now) >= settings.running
+    {
+        return Some(Reminder::Running((*running).clone()));
+    }
+
+    if let Some(limit) = settings.daily_limit {
+        let tracked: Duration = activities
+            .iter()
+            .copied()
+            .filter(Filters::today(now.date()))
+            .map(|a| a.get_duration_at(now))
+            .sum();
+
+        if tracked > limit {
+            return running.map(|_| Reminder::DailyLimit { tracked, limit });
+        }
+    }
+
+    if running.is_some() || !settings.is_working_time(now) {
+        return None;
+    }
+
+    let start_of_work = now.date().and_time(settings.working_hours.start);
+    let idle_since = activities
+        .iter()
+        .filter_map(|a| a.end)
+        .max()
+        .map_or(start_of_work, |end| end.max(start_of_work));
+    let idle = now - idle_since;
+
+    (idle >= settings.idle).then_some(Reminder::Idle(idle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::activity::activity;
+
+    fn datetime(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn remind_of_idle_time_during_working_hours() {
+        let settings = ReminderSettings::default();
+        let a1 = activity("p", "2024-03-11 09:00", "2024-03-11 10:00");
+        let activities = vec![&a1];
+
+        // 2024-03-11 is a monday
+        assert_eq!(
+            check(&settings, &activities, datetime("2024-03-11 10:10")),
+            None
+        );
+        assert_eq!(
+            check(&settings, &activities, datetime("2024-03-11 10:20")),
+            Some(Reminder::Idle(Duration::minutes(20)))
+        );
+        assert_eq!(
+            check(&settings, &activities, datetime("2024-03-12 09:30")),
+            Some(Reminder::Idle(Duration::minutes(30)))
+        );
+        assert_eq!(
+            check(&settings, &activities, datetime("2024-03-12 08:30")),
+            None
+        );
+        assert_eq!(
+            check(&settings, &activities, datetime("2024-03-11 17:30")),
+            None
+        );
+        assert_eq!(
+            check(&settings, &activities, datetime("2024-03-16 10:30")),
+            None
+        );
+    }
+
+    #[test]
+    fn remind_of_running_activity() {
+        let settings = ReminderSettings::default();
+        let a1 = Activity {
+            end: None,
+            ..activity("p", "2024-03-11 09:00", "2024-03-11 09:00")
+        };
+        let activities = vec![&a1];
+
+        assert_eq!(
+            check(&settings, &activities, datetime("2024-03-11 12:00")),
+            None
+        );
+        assert_eq!(
+            check(&settings, &activities, datetime("2024-03-11 22:00")),
+            Some(Reminder::Running(a1.clone()))
+        );
+    }
+
+    #[test]
+    fn remind_of_daily_limit() {
+        let settings = ReminderSettings {
+            daily_limit: Some(Duration::hours(2)),
+            ..ReminderSettings::default()
+        };
+        let a1 = activity("p", "2024-03-10 20:00", "2024-03-10 23:00");
+        let a2 = activity("p", "2024-03-11 09:00", "2024-03-11 10:30");
+        let a3 = Activity {
+            end: None,
+            ..activity("p", "2024-03-11 11:00", "2024-03-11 11:00")
+        };
+        let a4 = activity("p", "2024-03-11 11:00", "2024-03-11 11:45");
+
+        assert_eq!(
+            check(&settings, &[&a1, &a2, &a3], datetime("2024-03-11 11:20")),
+            None
+        );
+        assert_eq!(
+            check(&settings, &[&a1, &a2, &a3], datetime("2024-03-11 11:40")),
+            Some(Reminder::DailyLimit {
+                tracked: Duration::minutes(130),
+                limit: Duration::hours(2)
+            })
+        );
+        assert_eq!(
+            check(&settings, &[&a1, &a2, &a4], datetime("2024-03-11 13:00")),
+            None,
+            "no reminder of idle time after the daily limit"
+        );
+        assert_eq!(
+            check(&settings, &[&a1, &a3], datetime("2024-03-11 16:00")),
+            Some(Reminder::Running(a3.clone())),
+            "a forgotten activity is reminded of first"
+        );
+    }
+
+    #[test]
+    fn parse_reminder_settings() {
+        let config: Config = "[remind]\nidle = \"30m\"\nworking_hours = \"8am-4pm\"\nworking_days = [\"sat\", \"sunday\"]"
+            .parse()
+            .unwrap();
+        let settings = ReminderSettings::from_config(&config).unwrap();
+
+        assert_eq!(settings.idle, Duration::minutes(30));
+        assert_eq!(settings.running, Duration::hours(4));
+        assert_eq!(
+            settings.working_hours,
+            parse_working_hours("08:00-16:00").unwrap()
+        );
+        assert_eq!(settings.working_days, vec![Weekday::Sat, Weekday::Sun]);
+        assert_eq!(settings.daily_limit, None);
+
+        for setting in [
+            "idle = \"15\"",
+            "daily_limit = 8",
+            "working_hours = \"17:00-09:00\"",
+            "working_days = [\"someday\"]",
+            "working_days = \"mon\"",
+        ] {
+            let config: Config = format!("[remind]\n{setting}").parse().unwrap();
+            assert!(ReminderSettings::from_config(&config).is_err(), "{setting}");
+        }
+    }
+}
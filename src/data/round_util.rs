@@ -1,3 +1,48 @@
+use clap::ValueEnum;
+
+// the direction in which timestamps are rounded
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum RoundingMode {
+    // round to the nearest multiple, halfway values are rounded up
+    #[default]
+    Nearest,
+    // always round to the next multiple
+    Up,
+    // always round to the previous multiple
+    Down,
+}
+
+impl RoundingMode {
+    // the mode given either by its name or by the shorthand flags `--round-up` and `--round-down`
+    #[must_use]
+    pub fn from_flags(mode: Option<Self>, up: bool, down: bool) -> Option<Self> {
+        mode.or(up.then_some(Self::Up))
+            .or(down.then_some(Self::Down))
+    }
+
+    // the opposite direction, e.g. for the start of an activity whose end is rounded up
+    #[must_use]
+    pub fn reversed(self) -> Self {
+        match self {
+            Self::Nearest => Self::Nearest,
+            Self::Up => Self::Down,
+            Self::Down => Self::Up,
+        }
+    }
+}
+
+// the unit whose duration is rounded
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum RoundingScope {
+    // round start and end time of every single activity
+    #[default]
+    Activity,
+    // round the total duration of each day only
+    Day,
+    // round the total duration of each project on each day only
+    ProjectDay,
+}
+
 // Utility functions for rounding datetimes.
 // Limitations:
 // - Cannot handle days properly.
@@ -6,18 +51,39 @@
 pub fn round_datetime(
     datetime: &chrono::NaiveDateTime,
     round: &chrono::Duration,
+    mode: RoundingMode,
 ) -> chrono::NaiveDateTime {
     let timestamp = datetime.and_utc().timestamp();
-    let round_seconds = round.num_seconds();
-
-    let rounded_timestamp =
-        (timestamp as f64 / round_seconds as f64).round() as i64 * round_seconds;
+    let rounded_timestamp = round_seconds(timestamp, round.num_seconds(), mode);
 
     chrono::DateTime::from_timestamp(rounded_timestamp, 0)
         .unwrap()
         .naive_utc()
 }
 
+// rounds a duration to a multiple of `round`
+pub fn round_duration(
+    duration: &chrono::Duration,
+    round: &chrono::Duration,
+    mode: RoundingMode,
+) -> chrono::Duration {
+    chrono::Duration::seconds(round_seconds(
+        duration.num_seconds(),
+        round.num_seconds(),
+        mode,
+    ))
+}
+
+fn round_seconds(seconds: i64, round_seconds: i64, mode: RoundingMode) -> i64 {
+    match mode {
+        RoundingMode::Nearest => {
+            (seconds as f64 / round_seconds as f64).round() as i64 * round_seconds
+        }
+        RoundingMode::Up => (seconds + round_seconds - 1).div_euclid(round_seconds) * round_seconds,
+        RoundingMode::Down => seconds.div_euclid(round_seconds) * round_seconds,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use chrono::{Duration, NaiveDate};
@@ -33,12 +99,20 @@ mod tests {
         let round = Duration::minutes(5);
 
         assert_eq!(
-            round_datetime(&fake_date().and_hms_opt(13, 32, 30).unwrap(), &round),
+            round_datetime(
+                &fake_date().and_hms_opt(13, 32, 30).unwrap(),
+                &round,
+                RoundingMode::Nearest
+            ),
             fake_date().and_hms_opt(13, 35, 0).unwrap()
         );
 
         assert_eq!(
-            round_datetime(&fake_date().and_hms_opt(13, 31, 1).unwrap(), &round),
+            round_datetime(
+                &fake_date().and_hms_opt(13, 31, 1).unwrap(),
+                &round,
+                RoundingMode::Nearest
+            ),
             fake_date().and_hms_opt(13, 30, 0).unwrap()
         );
     }
@@ -48,12 +122,20 @@ mod tests {
         let round = Duration::hours(8);
 
         assert_eq!(
-            round_datetime(&fake_date().and_hms_opt(4, 0, 1).unwrap(), &round),
+            round_datetime(
+                &fake_date().and_hms_opt(4, 0, 1).unwrap(),
+                &round,
+                RoundingMode::Nearest
+            ),
             fake_date().and_hms_opt(8, 0, 0).unwrap()
         );
 
         assert_eq!(
-            round_datetime(&fake_date().and_hms_opt(3, 59, 59).unwrap(), &round),
+            round_datetime(
+                &fake_date().and_hms_opt(3, 59, 59).unwrap(),
+                &round,
+                RoundingMode::Nearest
+            ),
             fake_date().and_hms_opt(0, 0, 0).unwrap()
         );
     }
@@ -63,8 +145,76 @@ mod tests {
         let round = Duration::minutes(10);
 
         assert_eq!(
-            round_datetime(&fake_date().and_hms_opt(13, 5, 0).unwrap(), &round),
+            round_datetime(
+                &fake_date().and_hms_opt(13, 5, 0).unwrap(),
+                &round,
+                RoundingMode::Nearest
+            ),
             fake_date().and_hms_opt(13, 10, 0).unwrap()
         )
     }
+
+    #[test]
+    fn test_round_up() {
+        let round = Duration::minutes(15);
+
+        assert_eq!(
+            round_datetime(
+                &fake_date().and_hms_opt(13, 1, 0).unwrap(),
+                &round,
+                RoundingMode::Up
+            ),
+            fake_date().and_hms_opt(13, 15, 0).unwrap()
+        );
+
+        assert_eq!(
+            round_datetime(
+                &fake_date().and_hms_opt(13, 15, 0).unwrap(),
+                &round,
+                RoundingMode::Up
+            ),
+            fake_date().and_hms_opt(13, 15, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_round_down() {
+        let round = Duration::minutes(15);
+
+        assert_eq!(
+            round_datetime(
+                &fake_date().and_hms_opt(13, 14, 59).unwrap(),
+                &round,
+                RoundingMode::Down
+            ),
+            fake_date().and_hms_opt(13, 0, 0).unwrap()
+        );
+
+        assert_eq!(
+            round_datetime(
+                &fake_date().and_hms_opt(13, 0, 0).unwrap(),
+                &round,
+                RoundingMode::Down
+            ),
+            fake_date().and_hms_opt(13, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_round_duration() {
+        let round = Duration::minutes(15);
+
+        assert_eq!(
+            round_duration(&Duration::minutes(52), &round, RoundingMode::Nearest),
+            Duration::minutes(45)
+        );
+        assert_eq!(
+            round_duration(&Duration::minutes(52), &round, RoundingMode::Up),
+            Duration::minutes(60)
+        );
+        assert_eq!(
+            round_duration(&Duration::minutes(52), &round, RoundingMode::Down),
+            Duration::minutes(45)
+        );
+    }
 }
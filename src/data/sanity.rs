@@ -0,0 +1,275 @@
+use chrono::NaiveDateTime;
+use clap::ValueEnum;
+
+use crate::data::activity::Activity;
+use crate::data::bartib_file::Line;
+use crate::data::timezone;
+
+// how to fix the issues found in the activity log
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum FixStrategy {
+    // ask for each issue
+    #[default]
+    Ask,
+    // fix all issues with the default fix of their kind
+    Auto,
+}
+
+// something unusual about an activity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Issue {
+    // the activity ends before it starts
+    NegativeDuration,
+    // the activity ends on a later day than it starts
+    MultipleDays,
+    // the activity starts before the activity of the line with the given index ends
+    Overlap(usize),
+}
+
+// an issue of the activity of the line with the given index
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Finding {
+    pub index: usize,
+    pub issue: Issue,
+}
+
+// finds all unusual activities in the file content, ordered by the start of the activities
+//
+// running activities last until `now`. An activity only counts as overlapping if it starts before
+// the latest end of all activities that started before it. Activities with a negative duration
+// are not checked for other issues.
+#[must_use]
+pub fn find_issues(file_content: &[Line], now: NaiveDateTime) -> Vec<Finding> {
+    let mut indexes: Vec<usize> = file_content
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.activity.is_ok())
+        .map(|(index, _)| index)
+        .collect();
+    // activities are compared by their UTC times, which are in order across changes of daylight
+    // saving time
+    indexes.sort_by_key(|index| {
+        let activity = get_activity(file_content, *index);
+        timezone::instant(activity.start, activity.start_offset)
+    });
+
+    let mut findings = Vec::new();
+    // the index of the activity that ends last among the activities checked so far
+    let mut latest: Option<(usize, NaiveDateTime)> = None;
+
+    for index in indexes {
+        let activity = get_activity(file_content, index);
+        let start = timezone::instant(activity.start, activity.start_offset);
+        let end = match activity.end {
+            Some(end) => timezone::instant(end, activity.end_offset),
+            None => timezone::instant(now, None),
+        };
+
+        if activity.is_stopped() && end < start {
+            findings.push(Finding {
+                index,
+                issue: Issue::NegativeDuration,
+            });
+            continue;
+        }
+
+        if activity.spans_multiple_days() {
+            findings.push(Finding {
+                index,
+                issue: Issue::MultipleDays,
+            });
+        }
+
+        match latest {
+            Some((latest_index, latest_end)) if latest_end > start => {
+                findings.push(Finding {
+                    index,
+                    issue: Issue::Overlap(latest_index),
+                });
+
+                if end > latest_end {
+                    latest = Some((index, end));
+                }
+            }
+            _ => latest = Some((index, end)),
+        }
+    }
+
+    findings
+}
+
+// fixes the issue of a finding:
+//
+// - the start and the end of activities with a negative duration are swapped
+// - activities spanning multiple days are split at midnight
+// - overlapping activities are resolved by ending the earlier activity when the later one starts
+//
+// the parts of split activities are inserted after the split activity, so the indexes of later
+// lines change.
+pub fn fix(file_content: &mut Vec<Line>, finding: Finding) {
+    let Ok(activity) = &file_content[finding.index].activity else {
+        return;
+    };
+
+    match finding.issue {
+        Issue::NegativeDuration => {
+            let mut activity = activity.clone();
+            if let Some(end) = activity.end {
+                activity.end = Some(activity.start);
+                activity.start = end;
+                (activity.start_offset, activity.end_offset) =
+                    (activity.end_offset, activity.start_offset);
+            }
+            replace_activity(file_content, finding.index, activity);
+        }
+        Issue::MultipleDays => {
+            let mut parts = activity.split_at_midnight().into_iter();
+            if let Some(first) = parts.next() {
+                replace_activity(file_content, finding.index, first);
+            }
+            for (offset, part) in parts.enumerate() {
+                file_content.insert(finding.index + offset + 1, Line::for_activity(part));
+            }
+        }
+        Issue::Overlap(other_index) => {
+            let (start, start_offset) = (activity.start, activity.start_offset);
+            if let Ok(other) = &file_content[other_index].activity {
+                let mut other = other.clone();
+                other.truncate(start);
+                other.end_offset = start_offset.or(other.end_offset);
+                replace_activity(file_content, other_index, other);
+            }
+        }
+    }
+}
+
+fn get_activity(file_content: &[Line], index: usize) -> &Activity {
+    file_content[index]
+        .activity
+        .as_ref()
+        .expect("only lines with activities are indexed")
+}
+
+fn replace_activity(file_content: &mut [Line], index: usize, activity: Activity) {
+    let line = &mut file_content[index];
+    line.activity = Ok(activity);
+    line.set_changed();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time, "%F %R").unwrap()
+    }
+
+    fn now() -> NaiveDateTime {
+        time("2024-03-12 12:00")
+    }
+
+    fn lines(lines: &[&str]) -> Vec<Line> {
+        lines
+            .iter()
+            .enumerate()
+            .map(|(number, line)| Line::new(line, number + 1))
+            .collect()
+    }
+
+    fn activity(file_content: &[Line], index: usize) -> &Activity {
+        file_content[index].activity.as_ref().unwrap()
+    }
+
+    #[test]
+    fn find_issues_test() {
+        let file_content = lines(&[
+            "2024-03-11 09:00 - 2024-03-11 12:00 | p | a",
+            "no activity",
+            "2024-03-11 10:00 - 2024-03-11 11:00 | p | b",
+            "2024-03-11 11:30 - 2024-03-11 13:00 | p | c",
+            "2024-03-11 15:00 - 2024-03-11 14:00 | p | d",
+            "2024-03-11 22:00 - 2024-03-12 02:00 | p | e",
+            "2024-03-12 02:00 - 2024-03-12 03:00 | p | f",
+            "2024-03-12 08:00 | p | g",
+            "2024-03-12 09:00 - 2024-03-12 10:00 | p | h",
+        ]);
+
+        assert_eq!(
+            find_issues(&file_content, now()),
+            vec![
+                Finding {
+                    index: 2,
+                    issue: Issue::Overlap(0)
+                },
+                Finding {
+                    index: 3,
+                    issue: Issue::Overlap(0)
+                },
+                Finding {
+                    index: 4,
+                    issue: Issue::NegativeDuration
+                },
+                Finding {
+                    index: 5,
+                    issue: Issue::MultipleDays
+                },
+                Finding {
+                    index: 8,
+                    issue: Issue::Overlap(7)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn fix_issues() {
+        let mut file_content = lines(&[
+            "2024-03-11 15:00 - 2024-03-11 14:00 | p | a",
+            "2024-03-11 22:00 - 2024-03-12 02:00 | p | b",
+            "2024-03-12 08:00 | p | c | pause 2024-03-12 09:30 - 2024-03-12 09:45",
+            "2024-03-12 09:00 - 2024-03-12 10:00 | p | d",
+        ]);
+
+        fix(
+            &mut file_content,
+            Finding {
+                index: 0,
+                issue: Issue::NegativeDuration,
+            },
+        );
+        assert_eq!(activity(&file_content, 0).start, time("2024-03-11 14:00"));
+        assert_eq!(
+            activity(&file_content, 0).end,
+            Some(time("2024-03-11 15:00"))
+        );
+
+        fix(
+            &mut file_content,
+            Finding {
+                index: 1,
+                issue: Issue::MultipleDays,
+            },
+        );
+        assert_eq!(file_content.len(), 5);
+        assert_eq!(
+            activity(&file_content, 1).end,
+            Some(time("2024-03-12 00:00"))
+        );
+        assert_eq!(activity(&file_content, 2).start, time("2024-03-12 00:00"));
+
+        fix(
+            &mut file_content,
+            Finding {
+                index: 4,
+                issue: Issue::Overlap(3),
+            },
+        );
+        assert_eq!(
+            activity(&file_content, 3).end,
+            Some(time("2024-03-12 09:00"))
+        );
+        assert!(activity(&file_content, 3).pauses.is_empty());
+
+        assert!(find_issues(&file_content, now()).is_empty());
+    }
+}
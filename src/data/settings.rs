@@ -0,0 +1,202 @@
+use chrono::{Duration, Weekday};
+use clap::ValueEnum;
+use std::env;
+
+use crate::conf::{self, Precision};
+use crate::data::config::{Config, ConfigError, Value};
+use crate::data::duration_parser;
+use crate::data::round_util::{RoundingMode, RoundingScope};
+
+// the general settings at the top of the configuration file
+//
+// all settings are optional and are overridden by command line arguments and environment
+// variables:
+//
+//     file = "~/activities.bartib"
+//     output = "json"
+//     color = "never"
+//     editor = "vim"
+//     precision = "seconds"
+//     week_start = "sunday"
+//     round = "15m"
+//     round_mode = "up"
+//     round_scope = "day"
+//     min_duration = "15m"
+//     index = true
+//     split_days = true
+//     max_activity_duration = "12h"
+//     daily_limit = "8h"
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Settings {
+    pub file: Option<String>,
+    pub editor: Option<String>,
+    pub precision: Option<Precision>,
+    pub week_start: Option<Weekday>,
+    pub round: Option<Duration>,
+    pub round_mode: Option<RoundingMode>,
+    pub round_scope: Option<RoundingScope>,
+    pub min_duration: Option<Duration>,
+    pub index: Option<bool>,
+    pub split_days: Option<bool>,
+    pub max_activity_duration: Option<Duration>,
+    pub daily_limit: Option<Duration>,
+}
+
+impl Settings {
+    pub fn from_config(config: &Config) -> Result<Self, ConfigError> {
+        let parse = |key: &str, s: &str, expected: &str| {
+            s.parse()
+                .map_err(|_| ConfigError::InvalidSetting(key.to_string(), expected.to_string()))
+        };
+
+        Ok(Self {
+            file: get_str(config, "file")?.map(expand_home),
+            editor: get_str(config, "editor")?.map(ToString::to_string),
+            precision: get_str(config, "precision")?
+                .map(str::parse)
+                .transpose()
+                .map_err(|e| ConfigError::InvalidSetting("precision".to_string(), e))?,
+            week_start: get_str(config, "week_start")?
+                .map(|s| parse("week_start", s, "expected a weekday like `monday`"))
+                .transpose()?,
+            round: get_str(config, "round")?
+                .map(duration_parser::parse_duration)
+                .transpose()
+                .map_err(|e| ConfigError::InvalidSetting("round".to_string(), e))?,
+            round_mode: get_enum(config, "round_mode")?,
+            round_scope: get_enum(config, "round_scope")?,
+            min_duration: get_str(config, "min_duration")?
+                .map(duration_parser::parse_duration)
+                .transpose()
+                .map_err(|e| ConfigError::InvalidSetting("min_duration".to_string(), e))?,
+            index: get_bool(config, "index")?,
+            split_days: get_bool(config, "split_days")?,
+            max_activity_duration: get_str(config, "max_activity_duration")?
+                .map(duration_parser::parse_duration)
+                .transpose()
+                .map_err(|e| ConfigError::InvalidSetting("max_activity_duration".to_string(), e))?,
+            daily_limit: get_str(config, "daily_limit")?
+                .map(duration_parser::parse_duration)
+                .transpose()
+                .map_err(|e| ConfigError::InvalidSetting("daily_limit".to_string(), e))?,
+        })
+    }
+
+    // applies the settings which affect the whole program, like the precision of timestamps
+    pub fn apply(&self) {
+        if let Some(precision) = self.precision {
+            conf::set_precision(precision);
+        }
+
+        if let Some(week_start) = self.week_start {
+            conf::set_week_start(week_start);
+        }
+
+        if let Some(index) = self.index {
+            conf::set_use_index(index);
+        }
+
+        if let Some(split_days) = self.split_days {
+            conf::set_split_days(split_days);
+        }
+    }
+}
+
+// the string value of a general setting
+pub fn get_str<'a>(config: &'a Config, key: &str) -> Result<Option<&'a str>, ConfigError> {
+    match config.get("", key) {
+        None => Ok(None),
+        Some(Value::String(s)) => Ok(Some(s)),
+        Some(_) => Err(ConfigError::InvalidSetting(
+            key.to_string(),
+            "expected a string".to_string(),
+        )),
+    }
+}
+
+// the boolean value of a general setting
+fn get_bool(config: &Config, key: &str) -> Result<Option<bool>, ConfigError> {
+    config
+        .get("", key)
+        .map(|value| {
+            value.as_bool().ok_or_else(|| {
+                ConfigError::InvalidSetting(
+                    key.to_string(),
+                    "expected `true` or `false`".to_string(),
+                )
+            })
+        })
+        .transpose()
+}
+
+// a general setting with one of the values of a command line argument, e.g. `output = "json"`
+pub fn get_enum<T: ValueEnum>(config: &Config, key: &str) -> Result<Option<T>, ConfigError> {
+    get_str(config, key)?
+        .map(|s| {
+            T::from_str(s, true).map_err(|_| {
+                let values: Vec<String> = T::value_variants()
+                    .iter()
+                    .filter_map(|v| v.to_possible_value())
+                    .map(|v| format!("`{}`", v.get_name()))
+                    .collect();
+                ConfigError::InvalidSetting(
+                    key.to_string(),
+                    format!("expected one of {}", values.join(", ")),
+                )
+            })
+        })
+        .transpose()
+}
+
+// replaces a leading `~` by the home directory of the user
+pub(crate) fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), env::var("HOME")) {
+        (Some(rest), Ok(home)) => format!("{home}/{rest}"),
+        _ => path.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_settings() {
+        let config: Config = "file = \"/tmp/a.bartib\"\neditor = \"vim\"\nweek_start = \"Sunday\"\nround = \"15m\"\nround_mode = \"up\"\nround_scope = \"project-day\"\nmin_duration = \"10m\"\nindex = true\nsplit_days = false\nmax_activity_duration = \"12h\"\ndaily_limit = \"8h\"\n[rates]\nround = 1"
+            .parse()
+            .unwrap();
+        let settings = Settings::from_config(&config).unwrap();
+
+        assert_eq!(settings.file.as_deref(), Some("/tmp/a.bartib"));
+        assert_eq!(settings.editor.as_deref(), Some("vim"));
+        assert_eq!(settings.precision, None);
+        assert_eq!(settings.week_start, Some(Weekday::Sun));
+        assert_eq!(settings.round, Some(Duration::minutes(15)));
+        assert_eq!(settings.round_mode, Some(RoundingMode::Up));
+        assert_eq!(settings.round_scope, Some(RoundingScope::ProjectDay));
+        assert_eq!(settings.min_duration, Some(Duration::minutes(10)));
+        assert_eq!(settings.index, Some(true));
+        assert_eq!(settings.split_days, Some(false));
+        assert_eq!(settings.max_activity_duration, Some(Duration::hours(12)));
+        assert_eq!(settings.daily_limit, Some(Duration::hours(8)));
+    }
+
+    #[test]
+    fn parse_invalid_settings() {
+        for setting in [
+            "week_start = \"someday\"",
+            "round = \"15\"",
+            "round_mode = \"sideways\"",
+            "min_duration = \"short\"",
+            "precision = \"hours\"",
+            "file = 1",
+            "index = \"yes\"",
+            "split_days = 1",
+            "max_activity_duration = \"long\"",
+            "daily_limit = \"8\"",
+        ] {
+            let config: Config = setting.parse().unwrap();
+            assert!(Settings::from_config(&config).is_err(), "{setting}");
+        }
+    }
+}
@@ -0,0 +1,280 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveTime, Weekday};
+use std::collections::BTreeMap;
+
+use crate::conf;
+use crate::data::activity::Activity;
+
+// long-term statistics of the activities of a range of days (see `bartib stats`)
+//
+// the activities are expected to be split at midnight, so each of them belongs to the day it
+// starts on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stats {
+    pub total: Duration,
+    pub tracked_days: usize,
+    // the average time tracked on the days with any time tracked
+    pub average_day: Duration,
+    // the average times of day of the first start and the last end of each day, given as the
+    // time since midnight, so that days ending at midnight count as ending at 24:00
+    pub average_start: Option<Duration>,
+    pub average_end: Option<Duration>,
+    // the days of the week, beginning with the configured first day of the week
+    pub weekdays: Vec<WeekdayStats>,
+    pub longest_streak: Option<Streak>,
+    pub months: Vec<MonthStats>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WeekdayStats {
+    pub weekday: Weekday,
+    pub tracked_days: usize,
+    pub total: Duration,
+}
+
+impl WeekdayStats {
+    // the average time tracked on this weekday if any time has been tracked on it
+    #[must_use]
+    pub fn average(&self) -> Duration {
+        average(self.total, self.tracked_days)
+    }
+}
+
+// consecutive days with time tracked on each of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Streak {
+    pub first: NaiveDate,
+    pub last: NaiveDate,
+}
+
+impl Streak {
+    #[must_use]
+    pub fn days(&self) -> i64 {
+        (self.last - self.first).num_days() + 1
+    }
+}
+
+// the time tracked for each project in a month, ordered by project
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonthStats {
+    // the first day of the month
+    pub month: NaiveDate,
+    pub projects: Vec<(String, Duration)>,
+    pub total: Duration,
+}
+
+// the activities of a single day
+struct Day<'a> {
+    date: NaiveDate,
+    activities: Vec<&'a Activity>,
+}
+
+impl Day<'_> {
+    fn total(&self) -> Duration {
+        self.activities.iter().map(|a| a.get_duration()).sum()
+    }
+
+    fn first_start(&self) -> Option<Duration> {
+        self.activities
+            .iter()
+            .map(|a| a.start - self.midnight())
+            .min()
+    }
+
+    // running activities have not ended yet
+    fn last_end(&self) -> Option<Duration> {
+        self.activities
+            .iter()
+            .filter_map(|a| a.end)
+            .map(|end| end - self.midnight())
+            .max()
+    }
+
+    fn midnight(&self) -> chrono::NaiveDateTime {
+        self.date.and_time(NaiveTime::MIN)
+    }
+}
+
+impl Stats {
+    #[must_use]
+    pub fn new(activities: &[&Activity]) -> Self {
+        let mut days: BTreeMap<NaiveDate, Day> = BTreeMap::new();
+        for activity in activities {
+            let date = activity.start.date();
+            days.entry(date)
+                .or_insert_with(|| Day {
+                    date,
+                    activities: Vec::new(),
+                })
+                .activities
+                .push(activity);
+        }
+
+        let total: Duration = days.values().map(Day::total).sum();
+        let average_of = |times: Vec<Duration>| {
+            (!times.is_empty()).then(|| average(times.iter().copied().sum(), times.len()))
+        };
+
+        Stats {
+            total,
+            tracked_days: days.len(),
+            average_day: average(total, days.len()),
+            average_start: average_of(days.values().filter_map(Day::first_start).collect()),
+            average_end: average_of(days.values().filter_map(Day::last_end).collect()),
+            weekdays: weekday_stats(&days),
+            longest_streak: longest_streak(days.keys().copied()),
+            months: month_stats(activities),
+        }
+    }
+
+    // the weekday with the most time tracked in total
+    #[must_use]
+    pub fn busiest_weekday(&self) -> Option<&WeekdayStats> {
+        self.weekdays
+            .iter()
+            .filter(|weekday| weekday.tracked_days > 0)
+            .fold(
+                None,
+                |busiest: Option<&WeekdayStats>, weekday| match busiest {
+                    Some(busiest) if busiest.total >= weekday.total => Some(busiest),
+                    _ => Some(weekday),
+                },
+            )
+    }
+}
+
+fn average(total: Duration, count: usize) -> Duration {
+    match i32::try_from(count) {
+        Ok(count) if count > 0 => total / count,
+        _ => Duration::zero(),
+    }
+}
+
+fn weekday_stats(days: &BTreeMap<NaiveDate, Day>) -> Vec<WeekdayStats> {
+    let mut weekday = conf::week_start();
+    let mut weekdays = Vec::new();
+
+    for _ in 0..7 {
+        let days_of_weekday: Vec<&Day> = days
+            .values()
+            .filter(|day| day.date.weekday() == weekday)
+            .collect();
+
+        weekdays.push(WeekdayStats {
+            weekday,
+            tracked_days: days_of_weekday.len(),
+            total: days_of_weekday.iter().map(|day| day.total()).sum(),
+        });
+        weekday = weekday.succ();
+    }
+
+    weekdays
+}
+
+// the longest run of consecutive dates. Of several runs of the same length the first one counts
+fn longest_streak(dates: impl Iterator<Item = NaiveDate>) -> Option<Streak> {
+    let mut longest: Option<Streak> = None;
+    let mut current: Option<Streak> = None;
+
+    for date in dates {
+        current = match current {
+            Some(streak) if streak.last.succ_opt() == Some(date) => Some(Streak {
+                last: date,
+                ..streak
+            }),
+            _ => Some(Streak {
+                first: date,
+                last: date,
+            }),
+        };
+
+        if let Some(streak) = current
+            && longest.is_none_or(|longest| streak.days() > longest.days())
+        {
+            longest = Some(streak);
+        }
+    }
+
+    longest
+}
+
+fn month_stats(activities: &[&Activity]) -> Vec<MonthStats> {
+    let mut months: BTreeMap<NaiveDate, BTreeMap<&str, Duration>> = BTreeMap::new();
+
+    for activity in activities {
+        let month = activity
+            .start
+            .date()
+            .with_day(1)
+            .unwrap_or(activity.start.date());
+        *months
+            .entry(month)
+            .or_default()
+            .entry(&activity.project)
+            .or_insert_with(Duration::zero) += activity.get_duration();
+    }
+
+    months
+        .into_iter()
+        .map(|(month, projects)| MonthStats {
+            month,
+            total: projects.values().copied().sum(),
+            projects: projects
+                .into_iter()
+                .map(|(project, duration)| (project.to_string(), duration))
+                .collect(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::activity::activity;
+
+    #[test]
+    fn stats_of_activities() {
+        let a1 = activity("p1", "2024-02-28 09:00", "2024-02-28 12:00");
+        let a2 = activity("p2", "2024-02-28 13:00", "2024-02-28 17:00");
+        let a3 = activity("p1", "2024-02-29 08:00", "2024-02-29 16:00");
+        let a4 = activity("p1", "2024-03-01 10:00", "2024-03-01 15:00");
+        let a5 = activity("p2", "2024-03-04 10:00", "2024-03-04 11:00");
+        let stats = Stats::new(&[&a1, &a2, &a3, &a4, &a5]);
+
+        assert_eq!(stats.total, Duration::hours(21));
+        assert_eq!(stats.tracked_days, 4);
+        assert_eq!(stats.average_day, Duration::minutes(315));
+        assert_eq!(stats.average_start, Some(Duration::minutes(555)));
+        assert_eq!(stats.average_end, Some(Duration::minutes(885)));
+
+        let busiest = stats.busiest_weekday().unwrap();
+        assert_eq!(busiest.weekday, Weekday::Thu);
+        assert_eq!(busiest.average(), Duration::hours(8));
+        assert_eq!(stats.weekdays.len(), 7);
+
+        let streak = stats.longest_streak.unwrap();
+        assert_eq!(streak.first.to_string(), "2024-02-28");
+        assert_eq!(streak.days(), 3);
+
+        assert_eq!(stats.months.len(), 2);
+        assert_eq!(
+            stats.months[0].projects,
+            vec![
+                ("p1".to_string(), Duration::hours(11)),
+                ("p2".to_string(), Duration::hours(4))
+            ]
+        );
+        assert_eq!(stats.months[1].total, Duration::hours(6));
+    }
+
+    #[test]
+    fn stats_without_activities() {
+        let stats = Stats::new(&[]);
+
+        assert_eq!(stats.tracked_days, 0);
+        assert_eq!(stats.average_day, Duration::zero());
+        assert_eq!(stats.average_start, None);
+        assert_eq!(stats.busiest_weekday(), None);
+        assert_eq!(stats.longest_streak, None);
+        assert!(stats.months.is_empty());
+    }
+}
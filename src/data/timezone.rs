@@ -0,0 +1,190 @@
+use chrono::{Duration, FixedOffset, Local, LocalResult, NaiveDateTime, Offset, TimeZone};
+use std::str::FromStr;
+
+// the timezone to show times in (see `--timezone`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Timezone {
+    // the timezone of the system
+    Local,
+    // a fixed offset from UTC like `+02:00`
+    Fixed(FixedOffset),
+}
+
+impl Timezone {
+    // converts a time with its offset from UTC to the local time of this timezone
+    #[must_use]
+    pub fn convert(self, time: NaiveDateTime, offset: FixedOffset) -> (NaiveDateTime, FixedOffset) {
+        let utc = to_utc(time, offset);
+        let target_offset = match self {
+            Self::Local => Local.offset_from_utc_datetime(&utc).fix(),
+            Self::Fixed(offset) => offset,
+        };
+
+        (from_utc(utc, target_offset), target_offset)
+    }
+}
+
+impl FromStr for Timezone {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "invalid timezone '{s}', expected `local`, `UTC` or an offset from UTC like `+02:00` or `-5`"
+            )
+        };
+        let s = s.trim();
+
+        if s.is_empty() {
+            return Err(invalid());
+        }
+        if s.eq_ignore_ascii_case("local") {
+            return Ok(Self::Local);
+        }
+
+        let offset = s
+            .strip_prefix("UTC")
+            .or_else(|| s.strip_prefix("utc"))
+            .unwrap_or(s);
+        if offset.is_empty() || offset == "Z" {
+            return Ok(Self::Fixed(FixedOffset::east_opt(0).ok_or_else(invalid)?));
+        }
+
+        let (sign, offset) = if let Some(offset) = offset.strip_prefix('+') {
+            (1, offset)
+        } else if let Some(offset) = offset.strip_prefix('-') {
+            (-1, offset)
+        } else {
+            return Err(invalid());
+        };
+
+        // offsets are given as hours with optional minutes, e.g. `5`, `05:30` or `0530`
+        let (hours, minutes) = match offset.split_once(':') {
+            Some((hours, minutes)) => (hours, minutes),
+            None if offset.len() > 2 => offset.split_at(offset.len() - 2),
+            None => (offset, "0"),
+        };
+        let hours: i32 = hours.parse().map_err(|_| invalid())?;
+        let minutes: i32 = minutes.parse().map_err(|_| invalid())?;
+        if hours > 14 || minutes >= 60 {
+            return Err(invalid());
+        }
+
+        FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+            .map(Self::Fixed)
+            .ok_or_else(invalid)
+    }
+}
+
+// the current local time with its offset from UTC
+#[must_use]
+pub fn now() -> (NaiveDateTime, FixedOffset) {
+    let now = Local::now();
+    (now.naive_local(), now.offset().fix())
+}
+
+// the offset from UTC of a local time of the system's timezone
+//
+// times repeated at the end of daylight saving time get the earlier offset. Times skipped at its
+// start do not exist and get the offset the clock has been set to.
+#[must_use]
+pub fn local_offset(time: NaiveDateTime) -> FixedOffset {
+    match Local.offset_from_local_datetime(&time) {
+        LocalResult::Single(offset) | LocalResult::Ambiguous(offset, _) => offset.fix(),
+        LocalResult::None => Local.offset_from_utc_datetime(&time).fix(),
+    }
+}
+
+// the time passed between two local times with their offsets from UTC
+//
+// times without offset (e.g. of older activity logs) count as local times of the system's
+// timezone. If both have no offset, their difference is taken as it is.
+#[must_use]
+pub fn elapsed(
+    from: NaiveDateTime,
+    from_offset: Option<FixedOffset>,
+    to: NaiveDateTime,
+    to_offset: Option<FixedOffset>,
+) -> Duration {
+    if from_offset.is_none() && to_offset.is_none() {
+        return to.signed_duration_since(from);
+    }
+
+    instant(to, to_offset).signed_duration_since(instant(from, from_offset))
+}
+
+// the UTC time of a local time, which is a time of the system's timezone if it has no offset
+#[must_use]
+pub fn instant(time: NaiveDateTime, offset: Option<FixedOffset>) -> NaiveDateTime {
+    to_utc(time, offset.unwrap_or_else(|| local_offset(time)))
+}
+
+fn to_utc(time: NaiveDateTime, offset: FixedOffset) -> NaiveDateTime {
+    time - Duration::seconds(offset.local_minus_utc().into())
+}
+
+fn from_utc(utc: NaiveDateTime, offset: FixedOffset) -> NaiveDateTime {
+    utc + Duration::seconds(offset.local_minus_utc().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%F %R").unwrap()
+    }
+
+    fn offset(hours: i32) -> FixedOffset {
+        FixedOffset::east_opt(hours * 3600).unwrap()
+    }
+
+    #[test]
+    fn parse_timezone() {
+        assert_eq!("local".parse(), Ok(Timezone::Local));
+        assert_eq!("UTC".parse(), Ok(Timezone::Fixed(offset(0))));
+        assert_eq!("+02:00".parse(), Ok(Timezone::Fixed(offset(2))));
+        assert_eq!("UTC-5".parse(), Ok(Timezone::Fixed(offset(-5))));
+        assert_eq!(
+            "+0530".parse(),
+            Ok(Timezone::Fixed(
+                FixedOffset::east_opt(5 * 3600 + 30 * 60).unwrap()
+            ))
+        );
+
+        for invalid in ["", "Europe/Berlin", "2", "+25", "+01:60"] {
+            assert!(invalid.parse::<Timezone>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn elapsed_across_daylight_saving_time() {
+        // clocks are set from 02:00 to 03:00 at the start of daylight saving time in Europe
+        assert_eq!(
+            elapsed(
+                time("2024-03-31 01:30"),
+                Some(offset(1)),
+                time("2024-03-31 03:30"),
+                Some(offset(2))
+            ),
+            Duration::hours(1)
+        );
+        assert_eq!(
+            elapsed(
+                time("2024-03-31 01:30"),
+                None,
+                time("2024-03-31 03:30"),
+                None
+            ),
+            Duration::hours(2)
+        );
+    }
+
+    #[test]
+    fn convert_to_timezone() {
+        assert_eq!(
+            Timezone::Fixed(offset(-5)).convert(time("2024-03-11 16:00"), offset(1)),
+            (time("2024-03-11 10:00"), offset(-5))
+        );
+    }
+}
@@ -0,0 +1,116 @@
+use anyhow::{Context, Result, bail};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::data::bartib_file;
+
+// the file that keeps the activity log as it was before the last change, e.g.
+// `activities.bartib.undo`
+#[must_use]
+pub fn get_backup_file_name(file_name: &str) -> String {
+    format!("{file_name}.undo")
+}
+
+// saves a copy of the activity log before it is changed
+//
+// only the last change can be undone, so an older copy is overwritten. A log that does not exist
+// yet is saved as an empty file.
+pub fn save_backup(file_name: &str) -> Result<()> {
+    let backup_file_name = get_backup_file_name(file_name);
+
+    if Path::new(file_name).exists() {
+        fs::copy(file_name, &backup_file_name).map(|_| ())
+    } else {
+        fs::write(&backup_file_name, "")
+    }
+    .context(format!("Could not write to file: {backup_file_name}"))
+}
+
+// removes the copy of the activity log, e.g. after a change that can not be undone
+pub fn discard_backup(file_name: &str) -> Result<()> {
+    let backup_file_name = get_backup_file_name(file_name);
+
+    if Path::new(&backup_file_name).exists() {
+        fs::remove_file(&backup_file_name)
+            .context(format!("Could not remove file: {backup_file_name}"))?;
+    }
+
+    Ok(())
+}
+
+// replaces the activity log by its copy and returns the content of the log before and after
+pub fn restore_backup(file_name: &str) -> Result<(String, String)> {
+    let backup_file_name = get_backup_file_name(file_name);
+
+    if !Path::new(&backup_file_name).exists() {
+        bail!("There is no change to undo");
+    }
+
+    let current = bartib_file::read_text(file_name).unwrap_or_default();
+    let restored = bartib_file::read_text(&backup_file_name)?;
+
+    bartib_file::write_atomically(file_name, &restored)
+        .context(format!("Could not write to file: {file_name}"))?;
+    discard_backup(file_name)?;
+
+    Ok((current, restored))
+}
+
+// the lines that only exist in the current content and the lines that only exist in the restored
+// content, each in the order of their content
+#[must_use]
+pub fn get_changed_lines<'a>(current: &'a str, restored: &'a str) -> (Vec<&'a str>, Vec<&'a str>) {
+    (
+        get_missing_lines(current, restored),
+        get_missing_lines(restored, current),
+    )
+}
+
+// the lines of `content` which are not part of `other`. Lines that occur more often in `content`
+// than in `other` are returned as well
+fn get_missing_lines<'a>(content: &'a str, other: &str) -> Vec<&'a str> {
+    let mut other_lines: HashMap<&str, usize> = HashMap::new();
+    for line in other.lines() {
+        *other_lines.entry(line.trim()).or_default() += 1;
+    }
+
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .filter(|line| match other_lines.get_mut(line) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                false
+            }
+            _ => true,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_changed_lines_test() {
+        let restored = "2024-03-01 09:00 - 2024-03-01 10:00 | p1 | a\n2024-03-01 10:00 | p2 | b\n";
+        let current = "2024-03-01 09:00 - 2024-03-01 10:00 | p1 | a\n2024-03-01 10:00 - 2024-03-01 12:00 | p2 | b\n2024-03-01 12:00 | p3 | c\n";
+
+        let (removed, restored_lines) = get_changed_lines(current, restored);
+
+        assert_eq!(
+            removed,
+            vec![
+                "2024-03-01 10:00 - 2024-03-01 12:00 | p2 | b",
+                "2024-03-01 12:00 | p3 | c"
+            ]
+        );
+        assert_eq!(restored_lines, vec!["2024-03-01 10:00 | p2 | b"]);
+
+        let (removed, restored_lines) = get_changed_lines("a\na\n", "a\n");
+        assert_eq!(removed, vec!["a"]);
+        assert!(restored_lines.is_empty());
+    }
+}
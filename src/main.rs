@@ -1,10 +1,49 @@
-use anyhow::Result;
-use bartib::view::status::StatusReport;
-use chrono::{Duration, Local, NaiveDate, NaiveTime};
-use clap::Parser;
+use anyhow::{Context, Result, bail};
+use bartib::view::chart::{ChartJson, ChartKind, ChartReport};
+use bartib::view::comparison::{ComparisonJson, ComparisonReport};
+use bartib::view::completions::{self, LoggedValue, Shell};
+use bartib::view::earnings::{EarningsJson, EarningsReport};
+use bartib::view::export::ExportFormat;
+use bartib::view::html::HtmlReport;
+use bartib::view::line_editor::LineEditor;
+use bartib::view::list::Grouping;
+use bartib::view::matrix::{MatrixFormat, MatrixReport};
+use bartib::view::output::OutputFormat;
+use bartib::view::report::{GroupedReport, GroupedReportJson, ReportFormat, ReportGrouping};
+use bartib::view::stats::{StatsJson, StatsReport};
+use bartib::view::status::{StatusJson, StatusReport};
+use bartib::view::summary::{SummaryFormat, SummaryJson, SummaryReport};
+use bartib::view::template::{Template, TemplateReport};
+use bartib::view::theme::{self, ColorMode, Theme};
+use bartib::view::timesheet::{TimesheetJson, TimesheetReport};
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime};
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser, Subcommand, ValueHint};
+use std::num::NonZeroUsize;
+use std::process::ExitCode;
 
+use bartib::controller::exit_status::ExitStatus;
+use bartib::controller::init::Encryption;
+use bartib::controller::serve::ServeOptions;
+use bartib::data::aliases::Aliases;
+use bartib::data::config::Config;
+use bartib::data::date_parser::DateRange;
+use bartib::data::encryption::{self, EncryptionSettings};
 use bartib::data::getter::{ActivityFilter, DatePresetArgs};
+use bartib::data::hooks::Hooks;
+use bartib::data::idle;
+use bartib::data::interop::ImportFormat;
+use bartib::data::jira::JiraSettings;
+use bartib::data::lock::FileLock;
+use bartib::data::merge::ConflictStrategy;
 use bartib::data::processor;
+use bartib::data::rates::Rates;
+use bartib::data::redaction::{DescriptionRedaction, Redaction};
+use bartib::data::reminder::{ReminderSettings, WorkingHours};
+use bartib::data::round_util::{RoundingMode, RoundingScope};
+use bartib::data::sanity::FixStrategy;
+use bartib::data::settings::{self, Settings};
+use bartib::data::timezone::Timezone;
 
 #[cfg(windows)]
 use nu_ansi_term::enable_ansi_support;
@@ -22,23 +61,109 @@ struct Cli {
     #[command(subcommand)]
     command: Commands,
     /// the file in which bartib tracks all the activities
-    #[arg(short, long, value_name = "FILE", env = "BARTIB_FILE")]
-    file: String,
+    #[arg(short, long, value_name = "FILE", value_hint = ValueHint::FilePath, env = "BARTIB_FILE")]
+    file: Option<String>,
+    /// the configuration file (default: ~/.config/bartib/config.toml)
+    #[arg(long, value_name = "CONFIG_FILE", value_hint = ValueHint::FilePath, env = "BARTIB_CONFIG")]
+    config: Option<String>,
+    /// the output format of `list`, `report`, `current` and `status` (default: text)
+    #[arg(long, value_enum, env = "BARTIB_OUTPUT", global = true)]
+    output: Option<OutputFormat>,
+    /// print only errors and warnings, e.g. for scripts which check the exit code of `current`, `check` or `sanity`
+    #[arg(short, long, global = true)]
+    quiet: bool,
+    /// when to color the output. `auto` colors it if it is printed to a terminal and the environment variable NO_COLOR is not set (default: auto)
+    #[arg(
+        long,
+        value_enum,
+        value_name = "WHEN",
+        env = "BARTIB_COLOR",
+        global = true
+    )]
+    color: Option<ColorMode>,
+}
+
+// a single line entered in the interactive shell
+#[derive(Parser)]
+#[command(no_binary_name = true, disable_version_flag = true)]
+struct ShellLine {
+    #[command(subcommand)]
+    command: Commands,
 }
 
 #[derive(Parser)]
 enum Commands {
     /// starts a new activity
     Start {
+        /// the project to which the new activity belongs
+        #[arg(short, long, required_unless_present = "interactive")]
+        project: Option<String>,
+        /// the description of the new activity
+        #[arg(short, long, required_unless_present = "interactive")]
+        description: Option<String>,
+        /// a tag for the new activity (may be given several times)
+        #[arg(long = "tag", value_name = "TAG", value_parser = parse_tag)]
+        tags: Vec<String>,
+        /// the key of the issue in a ticket system the new activity is spent on (e.g. PROJ-123)
+        #[arg(long, value_name = "KEY", value_parser = parse_issue)]
+        issue: Option<String>,
+        /// the time for changing the activity status (e.g. 09:00, 09:00:30, 2pm, now or -15m for 15 minutes ago)
+        #[arg(short, long, value_name = "TIME", allow_hyphen_values = true, value_parser = parse_time)]
+        time: Option<NaiveDateTime>,
+        /// do not start the activity again if an activity with the same project and description is already running. Other running activities are stopped nevertheless
+        #[arg(long, env = "BARTIB_IDEMPOTENT")]
+        idempotent: bool,
+        /// pick the project and description from the recently used ones by typing parts of them. A given project or description is kept
+        #[arg(short, long)]
+        interactive: bool,
+        /// stop activities running longer than the setting `max_activity_duration` at their likely end without asking
+        #[arg(long)]
+        auto_stop_stale: bool,
+        /// exit with code 5 if a budget of the project from the configuration file has been exceeded. The activity is started nevertheless
+        #[arg(long)]
+        fail_over_budget: bool,
+    },
+    /// adds an activity that has already been finished
+    Add {
+        /// the project to which the activity belongs
+        #[arg(short, long)]
+        project: String,
+        /// the description of the activity
+        #[arg(short, long)]
+        description: String,
+        /// a tag for the activity (may be given several times)
+        #[arg(long = "tag", value_name = "TAG", value_parser = parse_tag)]
+        tags: Vec<String>,
+        /// the key of the issue in a ticket system the activity has been spent on (e.g. PROJ-123)
+        #[arg(long, value_name = "KEY", value_parser = parse_issue)]
+        issue: Option<String>,
+        /// the start of the activity (e.g. "2024-03-01 09:00", "yesterday 14:30" or "09:00" for today)
+        #[arg(long, value_name = "DATETIME", value_parser = parse_datetime)]
+        start: NaiveDateTime,
+        /// the end of the activity (e.g. "2024-03-01 10:30", "yesterday 15:00" or "10:30" for today)
+        #[arg(long, value_name = "DATETIME", value_parser = parse_datetime)]
+        end: NaiveDateTime,
+    },
+    /// stops all running activities and starts a new one at the very same time
+    Switch {
         /// the project to which the new activity belongs
         #[arg(short, long)]
         project: String,
         /// the description of the new activity
         #[arg(short, long)]
         description: String,
-        /// the time for changing the activity status (HH:MM)
-        #[arg(short, long, value_name = "TIME", value_parser = parse_time)]
-        time: Option<NaiveTime>,
+        /// a tag for the new activity (may be given several times)
+        #[arg(long = "tag", value_name = "TAG", value_parser = parse_tag)]
+        tags: Vec<String>,
+        /// the key of the issue in a ticket system the new activity is spent on (e.g. PROJ-123)
+        #[arg(long, value_name = "KEY", value_parser = parse_issue)]
+        issue: Option<String>,
+        /// the time for changing the activity status (e.g. 09:00, 09:00:30, 2pm, now or -15m for 15 minutes ago)
+        #[arg(short, long, value_name = "TIME", allow_hyphen_values = true, value_parser = parse_time)]
+        time: Option<NaiveDateTime>,
+        /// do not start the activity again if an activity with the same project and description is already running. Other running activities are stopped nevertheless
+        #[arg(long, env = "BARTIB_IDEMPOTENT")]
+        idempotent: bool,
     },
     /// continues a previous activity
     Continue {
@@ -49,11 +174,21 @@ enum Commands {
         #[arg(short, long)]
         project: Option<String>,
         /// the number of the activity to continue (see subcommand `last`)
-        #[arg(value_name = "NUMBER", default_value = "0")]
+        #[arg(
+            value_name = "NUMBER",
+            default_value = "0",
+            conflicts_with = "interactive"
+        )]
         number: usize,
-        /// the time for changing the activity status (HH:MM)
-        #[arg(short, long, value_name = "TIME", value_parser = parse_time)]
-        time: Option<NaiveTime>,
+        /// pick the activity to continue from the recently used ones by typing parts of its description or project
+        #[arg(short, long)]
+        interactive: bool,
+        /// continue the most recent activity of this project (or with NUMBER another one as numbered by `last --project`)
+        #[arg(long, value_name = "PROJECT", conflicts_with = "interactive")]
+        last_of_project: Option<String>,
+        /// the time for changing the activity status (e.g. 09:00, 09:00:30, 2pm, now or -15m for 15 minutes ago)
+        #[arg(short, long, value_name = "TIME", allow_hyphen_values = true, value_parser = parse_time)]
+        time: Option<NaiveDateTime>,
     },
     /// changes the current activity
     Change {
@@ -63,91 +198,475 @@ enum Commands {
         /// the project to which the new activity belongs
         #[arg(short, long)]
         project: Option<String>,
-        /// the time for changing the activity status (HH:MM)
-        #[arg(short, long, value_name = "TIME", value_parser = parse_time)]
-        time: Option<NaiveTime>,
+        /// the time for changing the activity status (e.g. 09:00, 09:00:30, 2pm, now or -15m for 15 minutes ago)
+        #[arg(short, long, value_name = "TIME", allow_hyphen_values = true, value_parser = parse_time)]
+        time: Option<NaiveDateTime>,
     },
     /// stops all currently running activities
     Stop {
-        /// the time for changing the activity status (HH:MM)
-        #[arg(short, long, value_name = "TIME", value_parser = parse_time)]
-        time: Option<NaiveTime>,
+        /// the time for changing the activity status (e.g. 09:00, 09:00:30, 2pm, now or -15m for 15 minutes ago)
+        #[arg(short, long, value_name = "TIME", allow_hyphen_values = true, value_parser = parse_time)]
+        time: Option<NaiveDateTime>,
+        /// stop the activities this long ago because you have been away from the keyboard since then (e.g. 20m)
+        #[arg(long, value_name = "DURATION", conflicts_with = "time", value_parser = parse_duration)]
+        since_idle: Option<Duration>,
+        /// log the idle time for this project (default: idle)
+        #[arg(long, value_name = "PROJECT", requires = "since_idle", num_args = 0..=1, default_missing_value = idle::DEFAULT_IDLE_PROJECT)]
+        idle_project: Option<String>,
+        /// split activities running past midnight into one activity per day (default: the setting `split_days`)
+        #[arg(long)]
+        split_days: bool,
+    },
+    /// marks the begin of time away from the keyboard, e.g. when called by an idle detector
+    IdleStart {
+        /// the time the user became idle (e.g. 09:00, 09:00:30, 2pm, now or -15m for 15 minutes ago)
+        #[arg(short, long, value_name = "TIME", allow_hyphen_values = true, value_parser = parse_time)]
+        time: Option<NaiveDateTime>,
+    },
+    /// marks the end of time away from the keyboard and subtracts it from the running activities as a pause
+    IdleEnd {
+        /// the time the user returned (e.g. 09:00, 09:00:30, 2pm, now or -15m for 15 minutes ago)
+        #[arg(short, long, value_name = "TIME", allow_hyphen_values = true, value_parser = parse_time)]
+        time: Option<NaiveDateTime>,
+        /// split the running activities and log the idle time for this project instead of a pause (default: idle)
+        #[arg(long, value_name = "PROJECT", num_args = 0..=1, default_missing_value = idle::DEFAULT_IDLE_PROJECT)]
+        idle_project: Option<String>,
+    },
+    /// pauses all running activities, e.g. for a break. Pauses do not count towards durations
+    Pause {
+        /// the time for changing the activity status (e.g. 09:00, 09:00:30, 2pm, now or -15m for 15 minutes ago)
+        #[arg(short, long, value_name = "TIME", allow_hyphen_values = true, value_parser = parse_time)]
+        time: Option<NaiveDateTime>,
+    },
+    /// resumes all paused activities
+    Resume {
+        /// the time for changing the activity status (e.g. 09:00, 09:00:30, 2pm, now or -15m for 15 minutes ago)
+        #[arg(short, long, value_name = "TIME", allow_hyphen_values = true, value_parser = parse_time)]
+        time: Option<NaiveDateTime>,
     },
     /// cancels all currently running activities
     Cancel,
+    /// reverts the last change of the activity log, e.g. a stop at a wrong time
+    Undo,
     /// lists all currently running activities
-    Current,
+    Current {
+        /// the maximum duration to be tracked per day (e.g. 8h). Exceeding it will be highlighted (default: the setting `daily_limit`)
+        #[arg(long, value_name = "DURATION", value_parser = parse_duration, env = "BARTIB_DAILY_LIMIT")]
+        daily_limit: Option<Duration>,
+        /// keep running and refresh the durations every second until q is pressed
+        #[arg(short, long)]
+        watch: bool,
+        /// show the running activities in a single line, e.g. for the status bar of tmux or i3
+        #[arg(long)]
+        simple: bool,
+    },
     /// list recent activities
     List {
-        /// begin of date range (inclusive)
-        #[arg(long, value_name = "FROM_DATE", value_parser = parse_date)]
+        /// begin of date range (inclusive). Accepts dates like 2024-03-01, `yesterday`, `last monday`, `3 days ago` or `-3d` and ranges like `2024-03` or `last month`
+        #[arg(long, value_name = "FROM_DATE", allow_hyphen_values = true, value_parser = parse_first_date)]
         from: Option<NaiveDate>,
-        /// end of date range (inclusive)
-        #[arg(long, value_name = "TO_DATE", value_parser = parse_date)]
+        /// end of date range (inclusive). Accepts the same formats as `--from`
+        #[arg(long, value_name = "TO_DATE", allow_hyphen_values = true, value_parser = parse_last_date)]
         to: Option<NaiveDate>,
-        /// show activities of a certain date only
-        #[arg(short, long, value_name = "DATE", conflicts_with_all = &["from", "to", "today", "yesterday", "current_week", "last_week"], value_parser = parse_date)]
-        date: Option<NaiveDate>,
+        /// show activities of a certain date or range of dates only, e.g. `2024-03` or `last month`. Accepts the same formats as `--from`
+        #[arg(short, long, value_name = "DATE", conflicts_with_all = &["from", "to", "today", "yesterday", "current_week", "last_week", "current_month", "last_month", "month"], allow_hyphen_values = true, value_parser = parse_date_range)]
+        date: Option<DateRange>,
         /// show activities of the current day
-        #[arg(long, conflicts_with_all = &["from", "to", "date", "yesterday", "current_week", "last_week"])]
+        #[arg(long, conflicts_with_all = &["from", "to", "date", "yesterday", "current_week", "last_week", "current_month", "last_month", "month"])]
         today: bool,
         /// show yesterdays' activities
-        #[arg(long, conflicts_with_all = &["from", "to", "date", "today", "current_week", "last_week"])]
+        #[arg(long, conflicts_with_all = &["from", "to", "date", "today", "current_week", "last_week", "current_month", "last_month", "month"])]
         yesterday: bool,
         /// show activities of the current week
-        #[arg(long, conflicts_with_all = &["from", "to", "date", "today", "yesterday", "last_week"])]
+        #[arg(long, conflicts_with_all = &["from", "to", "date", "today", "yesterday", "last_week", "current_month", "last_month", "month"])]
         current_week: bool,
         /// show activities of the last week
-        #[arg(long, conflicts_with_all = &["from", "to", "date", "today", "yesterday", "current_week"])]
+        #[arg(long, conflicts_with_all = &["from", "to", "date", "today", "yesterday", "current_week", "current_month", "last_month", "month"])]
         last_week: bool,
-        /// rounds the start and end time to the nearest duration. Durations can be in minutes or hours. E.g. 15m or 4h
+        /// show activities of the current month
+        #[arg(long, conflicts_with_all = &["from", "to", "date", "today", "yesterday", "current_week", "last_week", "last_month", "month"])]
+        current_month: bool,
+        /// show activities of the last month
+        #[arg(long, conflicts_with_all = &["from", "to", "date", "today", "yesterday", "current_week", "last_week", "current_month", "month"])]
+        last_month: bool,
+        /// show activities of a certain month (e.g. 2024-05)
+        #[arg(long, value_name = "MONTH", conflicts_with_all = &["from", "to", "date", "today", "yesterday", "current_week", "last_week", "current_month", "last_month"], value_parser = parse_month)]
+        month: Option<DateRange>,
+        /// rounds the start and end time to a multiple of this duration (see `--round-mode`). Durations can be given in hours, minutes or seconds. E.g. 15m, 4h or 1h30m
         #[arg(long, value_parser = parse_duration)]
         round: Option<Duration>,
+        /// do not round, even if rounding is configured in the configuration file
+        #[arg(long, conflicts_with = "round")]
+        no_round: bool,
+        /// the direction in which durations are rounded. `up` rounds the start down and the end up, so no activity gets shorter, `down` does the reverse (default: nearest)
+        #[arg(long, value_enum)]
+        round_mode: Option<RoundingMode>,
+        /// round so that no activity gets shorter, the same as `--round-mode up`
+        #[arg(long, conflicts_with_all = &["round_mode", "round_down"])]
+        round_up: bool,
+        /// round so that no activity gets longer, the same as `--round-mode down`
+        #[arg(long, conflicts_with_all = &["round_mode", "round_up"])]
+        round_down: bool,
+        /// extends shorter activities to this duration after rounding, e.g. to bill at least 15m for a short call
+        #[arg(long, value_parser = parse_duration, conflicts_with = "no_round")]
+        min_duration: Option<Duration>,
+        /// round every single activity or only the total durations of each day (or of each project per day). Default: activity
+        #[arg(long, value_enum)]
+        round_scope: Option<RoundingScope>,
+        /// show the times in this timezone: `local`, `UTC` or an offset from UTC like `+02:00`. Days begin at midnight of this timezone
+        #[arg(long, value_name = "TIMEZONE")]
+        timezone: Option<Timezone>,
         /// do list activities for this project only
         #[arg(short, long)]
         project: Option<String>,
+        /// do list activities with this tag only (may be given several times)
+        #[arg(long = "tag", value_name = "TAG", value_parser = parse_tag)]
+        tags: Vec<String>,
         /// do not group activities by date in list
         #[arg(long)]
         no_grouping: bool,
+        /// group activities by the day or the ISO week they started
+        #[arg(long, value_enum, default_value_t = Grouping::Day, conflicts_with = "no_grouping")]
+        group_by: Grouping,
         /// maximum number of activities to display
         #[arg(short, long, value_name = "NUMBER")]
         number: Option<usize>,
+        /// show the notes of the activities
+        #[arg(short, long)]
+        verbose: bool,
     },
     /// reports duration of tracked activities
     Report {
-        /// begin of date range (inclusive)
-        #[arg(long, value_name = "FROM_DATE", value_parser = parse_date)]
+        /// begin of date range (inclusive). Accepts dates like 2024-03-01, `yesterday`, `last monday`, `3 days ago` or `-3d` and ranges like `2024-03` or `last month`
+        #[arg(long, value_name = "FROM_DATE", allow_hyphen_values = true, value_parser = parse_first_date)]
         from: Option<NaiveDate>,
-        /// end of date range (inclusive)
-        #[arg(long, value_name = "TO_DATE", value_parser = parse_date)]
+        /// end of date range (inclusive). Accepts the same formats as `--from`
+        #[arg(long, value_name = "TO_DATE", allow_hyphen_values = true, value_parser = parse_last_date)]
         to: Option<NaiveDate>,
-        /// show activities of a certain date only
-        #[arg(short, long, value_name = "DATE", conflicts_with_all = &["from", "to", "today", "yesterday", "current_week", "last_week"], value_parser = parse_date)]
-        date: Option<NaiveDate>,
+        /// show activities of a certain date or range of dates only, e.g. `2024-03` or `last month`. Accepts the same formats as `--from`
+        #[arg(short, long, value_name = "DATE", conflicts_with_all = &["from", "to", "today", "yesterday", "current_week", "last_week", "current_month", "last_month", "month"], allow_hyphen_values = true, value_parser = parse_date_range)]
+        date: Option<DateRange>,
         /// show activities of the current day
-        #[arg(long, conflicts_with_all = &["from", "to", "date", "yesterday", "current_week", "last_week"])]
+        #[arg(long, conflicts_with_all = &["from", "to", "date", "yesterday", "current_week", "last_week", "current_month", "last_month", "month"])]
         today: bool,
         /// show yesterdays' activities
-        #[arg(long, conflicts_with_all = &["from", "to", "date", "today", "current_week", "last_week"])]
+        #[arg(long, conflicts_with_all = &["from", "to", "date", "today", "current_week", "last_week", "current_month", "last_month", "month"])]
         yesterday: bool,
         /// show activities of the current week
-        #[arg(long, conflicts_with_all = &["from", "to", "date", "today", "yesterday", "last_week"])]
+        #[arg(long, conflicts_with_all = &["from", "to", "date", "today", "yesterday", "last_week", "current_month", "last_month", "month"])]
         current_week: bool,
         /// show activities of the last week
-        #[arg(long, conflicts_with_all = &["from", "to", "date", "today", "yesterday", "current_week"])]
+        #[arg(long, conflicts_with_all = &["from", "to", "date", "today", "yesterday", "current_week", "current_month", "last_month", "month"])]
         last_week: bool,
-        /// rounds the start and end time to the nearest duration. Durations can be in minutes or hours. E.g. 15m or 4h
+        /// show activities of the current month
+        #[arg(long, conflicts_with_all = &["from", "to", "date", "today", "yesterday", "current_week", "last_week", "last_month", "month"])]
+        current_month: bool,
+        /// show activities of the last month
+        #[arg(long, conflicts_with_all = &["from", "to", "date", "today", "yesterday", "current_week", "last_week", "current_month", "month"])]
+        last_month: bool,
+        /// show activities of a certain month (e.g. 2024-05)
+        #[arg(long, value_name = "MONTH", conflicts_with_all = &["from", "to", "date", "today", "yesterday", "current_week", "last_week", "current_month", "last_month"], value_parser = parse_month)]
+        month: Option<DateRange>,
+        /// rounds the start and end time to a multiple of this duration (see `--round-mode`). Durations can be given in hours, minutes or seconds. E.g. 15m, 4h or 1h30m
         #[arg(long, value_parser = parse_duration)]
         round: Option<Duration>,
+        /// do not round, even if rounding is configured in the configuration file
+        #[arg(long, conflicts_with = "round")]
+        no_round: bool,
+        /// the direction in which durations are rounded. `up` rounds the start down and the end up, so no activity gets shorter, `down` does the reverse (default: nearest)
+        #[arg(long, value_enum)]
+        round_mode: Option<RoundingMode>,
+        /// round so that no activity gets shorter, the same as `--round-mode up`
+        #[arg(long, conflicts_with_all = &["round_mode", "round_down"])]
+        round_up: bool,
+        /// round so that no activity gets longer, the same as `--round-mode down`
+        #[arg(long, conflicts_with_all = &["round_mode", "round_up"])]
+        round_down: bool,
+        /// extends shorter activities to this duration after rounding, e.g. to bill at least 15m for a short call
+        #[arg(long, value_parser = parse_duration, conflicts_with = "no_round")]
+        min_duration: Option<Duration>,
+        /// round every single activity or only the total durations of each day (or of each project per day). Default: activity
+        #[arg(long, value_enum)]
+        round_scope: Option<RoundingScope>,
+        /// show the times in this timezone: `local`, `UTC` or an offset from UTC like `+02:00`. Days begin at midnight of this timezone
+        #[arg(long, value_name = "TIMEZONE")]
+        timezone: Option<Timezone>,
         /// do report activities for this project only
         #[arg(short, long)]
         project: Option<String>,
+        /// do report activities with this tag only (may be given several times)
+        #[arg(long = "tag", value_name = "TAG", value_parser = parse_tag)]
+        tags: Vec<String>,
+        /// group the report by project, description, day, week or month. Groups are broken down by project and description
+        #[arg(long, value_enum, default_value_t = ReportGrouping::Project, conflicts_with = "matrix")]
+        group_by: ReportGrouping,
+        /// sum up hierarchical projects like `client/backend/api` at a level of the hierarchy, e.g. as `client` with depth 1
+        #[arg(long, value_name = "DEPTH")]
+        depth: Option<NonZeroUsize>,
+        /// show the earnings per project using the hourly rates of the [rates] table in the configuration file
+        #[arg(long, conflicts_with_all = &["matrix", "group_by", "depth"])]
+        earnings: bool,
+        /// show a table with the durations per day (rows) and project (columns)
+        #[arg(long)]
+        matrix: bool,
+        /// print the matrix as CSV with durations in decimal hours
+        #[arg(long, requires = "matrix")]
+        csv: bool,
+        /// the format of the report. `html` creates a page with the totals per project, a chart of the days and all activities
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text, conflicts_with_all = &["earnings", "matrix"])]
+        format: ReportFormat,
+        /// the file to write the html or template report to (default: stdout)
+        #[arg(short, long, value_name = "OUTPUT_FILE", value_hint = ValueHint::FilePath)]
+        output_file: Option<String>,
+        /// compare the durations per project with the period before, e.g. the current week with the last week
+        #[arg(long, conflicts_with_all = &["earnings", "matrix", "group_by", "format", "output_file"])]
+        compare: bool,
+        /// render the report with a template file in a subset of the Handlebars syntax, e.g. for invoices or timesheets (see README)
+        #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath, conflicts_with_all = &["earnings", "matrix", "group_by", "format", "compare"])]
+        template: Option<String>,
+        /// exit with code 5 if a budget from the configuration file has been exceeded
+        #[arg(long)]
+        fail_over_budget: bool,
+    },
+    /// lists the projects and descriptions of a day with their durations, e.g. for standup notes
+    Summary {
+        /// summarize yesterdays' activities
+        #[arg(long, conflicts_with = "date")]
+        yesterday: bool,
+        /// summarize the activities of a certain date or range of dates instead of today, e.g. `friday` or `last week`. Accepts the same formats as `list --date`
+        #[arg(short, long, value_name = "DATE", allow_hyphen_values = true, value_parser = parse_date_range)]
+        date: Option<DateRange>,
+        /// do summarize activities for this project only
+        #[arg(short, long)]
+        project: Option<String>,
+        /// do summarize activities with this tag only (may be given several times)
+        #[arg(long = "tag", value_name = "TAG", value_parser = parse_tag)]
+        tags: Vec<String>,
+        /// do not round, even if rounding is configured in the configuration file
+        #[arg(long)]
+        no_round: bool,
+        /// the format of the summary. `markdown` creates a nested list to paste into notes or chat messages
+        #[arg(long, value_enum, default_value_t = SummaryFormat::Text)]
+        format: SummaryFormat,
+    },
+    /// shows the hours per project (rows) and day (columns) of a week as a timesheet
+    Timesheet {
+        /// the calendar week to show, given as its number in the current year (e.g. 11) or with the year (e.g. 2024-W11). Default: the current week
+        #[arg(short, long, value_name = "WEEK", value_parser = parse_week)]
+        week: Option<DateRange>,
+        /// rounds the start and end time to a multiple of this duration (see `--round-mode`). Durations can be given in hours, minutes or seconds. E.g. 15m, 4h or 1h30m
+        #[arg(long, value_parser = parse_duration)]
+        round: Option<Duration>,
+        /// do not round, even if rounding is configured in the configuration file
+        #[arg(long, conflicts_with = "round")]
+        no_round: bool,
+        /// the direction in which durations are rounded. `up` rounds the start down and the end up, so no activity gets shorter, `down` does the reverse (default: nearest)
+        #[arg(long, value_enum)]
+        round_mode: Option<RoundingMode>,
+        /// round so that no activity gets shorter, the same as `--round-mode up`
+        #[arg(long, conflicts_with_all = &["round_mode", "round_down"])]
+        round_up: bool,
+        /// round so that no activity gets longer, the same as `--round-mode down`
+        #[arg(long, conflicts_with_all = &["round_mode", "round_up"])]
+        round_down: bool,
+        /// extends shorter activities to this duration after rounding, e.g. to bill at least 15m for a short call
+        #[arg(long, value_parser = parse_duration, conflicts_with = "no_round")]
+        min_duration: Option<Duration>,
+        /// round every single activity or only the total durations of each day (or of each project per day). Default: activity
+        #[arg(long, value_enum)]
+        round_scope: Option<RoundingScope>,
+        /// do show activities for this project only
+        #[arg(short, long)]
+        project: Option<String>,
+        /// do show activities with this tag only (may be given several times)
+        #[arg(long = "tag", value_name = "TAG", value_parser = parse_tag)]
+        tags: Vec<String>,
+        /// sum up hierarchical projects like `client/backend/api` at a level of the hierarchy, e.g. as `client` with depth 1
+        #[arg(long, value_name = "DEPTH")]
+        depth: Option<NonZeroUsize>,
+    },
+    /// shows a calendar heatmap of the time tracked per day, like the contributions on a GitHub profile
+    Chart {
+        /// show the days of a year (e.g. 2024). Default: the last 52 weeks
+        #[arg(long, value_name = "YEAR", num_args = 0..=1, conflicts_with = "month")]
+        year: Option<Option<i32>>,
+        /// show the days of a month (e.g. 2024-05). Default: the last 52 weeks
+        #[arg(long, value_name = "MONTH", num_args = 0..=1, value_parser = parse_month)]
+        month: Option<Option<DateRange>>,
+        /// show a bar per week stacked by the time tracked for each project instead of the heatmap
+        #[arg(long)]
+        weeks: bool,
+        /// do show activities for this project only
+        #[arg(short, long)]
+        project: Option<String>,
+        /// do show activities with this tag only (may be given several times)
+        #[arg(long = "tag", value_name = "TAG", value_parser = parse_tag)]
+        tags: Vec<String>,
+        /// sum up hierarchical projects like `client/backend/api` at a level of the hierarchy, e.g. as `client` with depth 1
+        #[arg(long, value_name = "DEPTH", requires = "weeks")]
+        depth: Option<NonZeroUsize>,
+    },
+    /// shows long-term statistics like the average working day, the busiest weekday, the longest streak of tracked days and the time tracked per project in each month
+    Stats {
+        /// begin of date range (inclusive). Accepts the same formats as `report --from`
+        #[arg(long, value_name = "FROM_DATE", allow_hyphen_values = true, value_parser = parse_first_date)]
+        from: Option<NaiveDate>,
+        /// end of date range (inclusive). Accepts the same formats as `report --from`
+        #[arg(long, value_name = "TO_DATE", allow_hyphen_values = true, value_parser = parse_last_date)]
+        to: Option<NaiveDate>,
+        /// show statistics of a certain date or range of dates only, e.g. `2024` or `last month`. Accepts the same formats as `--from`
+        #[arg(short, long, value_name = "DATE", conflicts_with_all = &["from", "to"], allow_hyphen_values = true, value_parser = parse_date_range)]
+        date: Option<DateRange>,
+        /// do show activities for this project only
+        #[arg(short, long)]
+        project: Option<String>,
+        /// do show activities with this tag only (may be given several times)
+        #[arg(long = "tag", value_name = "TAG", value_parser = parse_tag)]
+        tags: Vec<String>,
+    },
+    /// keeps running and reminds with desktop notifications to start tracking during working hours or to stop a long running activity
+    Remind {
+        /// remind if no activity has been running for this duration during working hours (default: 15m or `idle` of the [remind] table in the configuration file)
+        #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+        idle: Option<Duration>,
+        /// remind if an activity has been running for this duration (default: 4h or `running` of the [remind] table in the configuration file)
+        #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+        running: Option<Duration>,
+        /// the hours in which idle time is reminded of, e.g. 08:30-17:00 (default: 09:00-17:00 or `working_hours` of the [remind] table in the configuration file)
+        #[arg(long, value_name = "HOURS", value_parser = parse_working_hours)]
+        working_hours: Option<WorkingHours>,
+        /// remind to stop tracking if more than this duration has been tracked today (default: `daily_limit` of the [remind] table in the configuration file or the setting `daily_limit`)
+        #[arg(long, value_name = "DURATION", value_parser = parse_duration, env = "BARTIB_DAILY_LIMIT")]
+        daily_limit: Option<Duration>,
+        /// remind again after this duration as long as the reason persists
+        #[arg(long, value_name = "DURATION", value_parser = parse_duration, default_value = "15m")]
+        repeat: Duration,
+        /// check the activities only once instead of every minute, e.g. for a cron job
+        #[arg(long)]
+        once: bool,
+    },
+    /// exports activities to a file format for other tools like spreadsheets
+    Export {
+        /// the format of the export
+        #[arg(long, value_enum, default_value_t = ExportFormat::Csv)]
+        format: ExportFormat,
+        /// the file to write the export to (default: stdout)
+        #[arg(short, long, value_name = "OUTPUT_FILE", value_hint = ValueHint::FilePath)]
+        output_file: Option<String>,
+        /// begin of date range (inclusive). Accepts dates like 2024-03-01, `yesterday`, `last monday`, `3 days ago` or `-3d` and ranges like `2024-03` or `last month`
+        #[arg(long, value_name = "FROM_DATE", allow_hyphen_values = true, value_parser = parse_first_date)]
+        from: Option<NaiveDate>,
+        /// end of date range (inclusive). Accepts the same formats as `--from`
+        #[arg(long, value_name = "TO_DATE", allow_hyphen_values = true, value_parser = parse_last_date)]
+        to: Option<NaiveDate>,
+        /// export activities of a certain date or range of dates only, e.g. `2024-03` or `last month`. Accepts the same formats as `--from`
+        #[arg(short, long, value_name = "DATE", conflicts_with_all = &["from", "to", "today", "yesterday", "current_week", "last_week", "current_month", "last_month", "month"], allow_hyphen_values = true, value_parser = parse_date_range)]
+        date: Option<DateRange>,
+        /// export activities of the current day
+        #[arg(long, conflicts_with_all = &["from", "to", "date", "yesterday", "current_week", "last_week", "current_month", "last_month", "month"])]
+        today: bool,
+        /// export yesterdays' activities
+        #[arg(long, conflicts_with_all = &["from", "to", "date", "today", "current_week", "last_week", "current_month", "last_month", "month"])]
+        yesterday: bool,
+        /// export activities of the current week
+        #[arg(long, conflicts_with_all = &["from", "to", "date", "today", "yesterday", "last_week", "current_month", "last_month", "month"])]
+        current_week: bool,
+        /// export activities of the last week
+        #[arg(long, conflicts_with_all = &["from", "to", "date", "today", "yesterday", "current_week", "current_month", "last_month", "month"])]
+        last_week: bool,
+        /// export activities of the current month
+        #[arg(long, conflicts_with_all = &["from", "to", "date", "today", "yesterday", "current_week", "last_week", "last_month", "month"])]
+        current_month: bool,
+        /// export activities of the last month
+        #[arg(long, conflicts_with_all = &["from", "to", "date", "today", "yesterday", "current_week", "last_week", "current_month", "month"])]
+        last_month: bool,
+        /// export activities of a certain month (e.g. 2024-05)
+        #[arg(long, value_name = "MONTH", conflicts_with_all = &["from", "to", "date", "today", "yesterday", "current_week", "last_week", "current_month", "last_month"], value_parser = parse_month)]
+        month: Option<DateRange>,
+        /// rounds the start and end time to a multiple of this duration (see `--round-mode`). Durations can be given in hours, minutes or seconds. E.g. 15m, 4h or 1h30m
+        #[arg(long, value_parser = parse_duration)]
+        round: Option<Duration>,
+        /// do not round, even if rounding is configured in the configuration file
+        #[arg(long, conflicts_with = "round")]
+        no_round: bool,
+        /// the direction in which durations are rounded. `up` rounds the start down and the end up, so no activity gets shorter, `down` does the reverse (default: nearest)
+        #[arg(long, value_enum)]
+        round_mode: Option<RoundingMode>,
+        /// round so that no activity gets shorter, the same as `--round-mode up`
+        #[arg(long, conflicts_with_all = &["round_mode", "round_down"])]
+        round_up: bool,
+        /// round so that no activity gets longer, the same as `--round-mode down`
+        #[arg(long, conflicts_with_all = &["round_mode", "round_up"])]
+        round_down: bool,
+        /// extends shorter activities to this duration after rounding, e.g. to bill at least 15m for a short call
+        #[arg(long, value_parser = parse_duration, conflicts_with = "no_round")]
+        min_duration: Option<Duration>,
+        /// do export activities for this project only
+        #[arg(short, long)]
+        project: Option<String>,
+        /// do export activities with this tag only (may be given several times)
+        #[arg(long = "tag", value_name = "TAG", value_parser = parse_tag)]
+        tags: Vec<String>,
+        /// replace the descriptions by hashes or leave them blank and remove all notes, e.g. to share the export without details of the tasks
+        #[arg(long, value_enum, value_name = "MODE", num_args = 0..=1, default_missing_value = "hash")]
+        redact_descriptions: Option<DescriptionRedaction>,
+        /// export only the times and projects of activities, without descriptions, tags and notes
+        #[arg(long, conflicts_with = "redact_descriptions")]
+        only_projects: bool,
+        /// add the worklogs to the issues in JIRA configured in the `[jira]` table instead of printing them (requires `--format jira`). Pushing activities again logs them twice
+        #[arg(long, conflicts_with_all = &["output_file", "redact_descriptions", "only_projects"])]
+        push: bool,
     },
     /// displays the descriptions and projects of recent activities
     Last {
         /// maximum number of lines to display
         #[arg(short, long, value_name = "NUMBER", default_value = "10")]
         number: usize,
+        /// displays the latest activities with their numbers for `edit-activity` and `delete`
+        #[arg(short, long)]
+        activities: bool,
+        /// displays the descriptions of this project only, numbered for `continue --last-of-project`
+        #[arg(short, long, conflicts_with = "activities")]
+        project: Option<String>,
+    },
+    /// changes a single activity
+    EditActivity {
+        /// the number of the activity to change (see `last --activities`)
+        #[arg(value_name = "NUMBER")]
+        number: usize,
+        /// the new project of the activity
+        #[arg(short, long)]
+        project: Option<String>,
+        /// the new description of the activity
+        #[arg(short, long)]
+        description: Option<String>,
+        /// the new start time of the activity on the same day (e.g. 09:00, 09:00:30 or 2pm)
+        #[arg(long, value_name = "TIME", value_parser = parse_time_of_day)]
+        start: Option<NaiveTime>,
+        /// the new end time of the activity on the same day (e.g. 09:00, 09:00:30 or 2pm)
+        #[arg(long, value_name = "TIME", value_parser = parse_time_of_day)]
+        end: Option<NaiveTime>,
+        /// replaces the tags of the activity (may be given several times)
+        #[arg(long = "tag", value_name = "TAG", value_parser = parse_tag)]
+        tags: Vec<String>,
+        /// the new key of the issue in a ticket system the activity has been spent on (e.g. PROJ-123)
+        #[arg(long, value_name = "KEY", value_parser = parse_issue)]
+        issue: Option<String>,
+    },
+    /// deletes a single activity
+    Delete {
+        /// the number of the activity to delete (see `last --activities`)
+        #[arg(value_name = "NUMBER")]
+        number: usize,
+    },
+    /// adds a note to the currently running activity or to a past one
+    Note {
+        /// the text of the note
+        #[arg(value_name = "NOTE")]
+        note: String,
+        /// the number of the activity to add the note to (see `last --activities`)
+        #[arg(short, long, value_name = "NUMBER")]
+        number: Option<usize>,
     },
     /// list all projects
     Projects {
@@ -158,6 +677,20 @@ enum Commands {
         #[arg(short, long)]
         no_quotes: bool,
     },
+    /// renames or merges projects in all activities of the log
+    Project {
+        #[command(subcommand)]
+        command: ProjectCommands,
+    },
+    /// creates the activity log if it does not exist yet, or encrypts or decrypts it together with its archives
+    Init {
+        /// encrypt the activity log and its archives with gpg, so they may be kept in synced folders. Bartib reads and writes them transparently afterwards (see the `[encryption]` table of the configuration file)
+        #[arg(long, conflicts_with = "decrypt")]
+        encrypt: bool,
+        /// store the activity log and its archives as plain text again
+        #[arg(long)]
+        decrypt: bool,
+    },
     /// opens the activity log in an editor
     Edit {
         /// the command to start your preferred text editor
@@ -167,7 +700,14 @@ enum Commands {
     /// checks file and reports parsing errors
     Check,
     /// checks sanity of bartib log
-    Sanity,
+    Sanity {
+        /// fix overlapping activities, negative durations and activities spanning multiple days
+        #[arg(long)]
+        fix: bool,
+        /// whether to ask for each fix or to apply all of them
+        #[arg(long, value_enum, default_value_t = FixStrategy::Ask, requires = "fix")]
+        strategy: FixStrategy,
+    },
     /// search for existing descriptions and projects
     Search {
         /// the search term
@@ -179,69 +719,653 @@ enum Commands {
         /// show status for this project only
         #[arg(short, long)]
         project: Option<String>,
+        /// show status for activities with this tag only (may be given several times)
+        #[arg(long = "tag", value_name = "TAG", value_parser = parse_tag)]
+        tags: Vec<String>,
+        /// the maximum duration to be tracked per day (e.g. 8h). Exceeding it will be highlighted (default: the setting `daily_limit`)
+        #[arg(long, value_name = "DURATION", value_parser = parse_duration, env = "BARTIB_DAILY_LIMIT")]
+        daily_limit: Option<Duration>,
+        /// exit with code 6 if less than this percentage of any goal from the configuration file has been reached (e.g. 100)
+        #[arg(long, value_name = "PERCENT")]
+        fail_under: Option<i64>,
+        /// exit with code 5 if a budget from the configuration file has been exceeded
+        #[arg(long)]
+        fail_over_budget: bool,
+    },
+    /// starts an interactive shell to enter several subcommands without the `bartib` prefix
+    Shell,
+    /// shows the running activity, today's activities and a report in an interactive terminal ui
+    Ui,
+    /// answers requests to an HTTP API for starting and stopping activities and for reports
+    Serve {
+        /// the port to listen on
+        #[arg(long, default_value = "8080")]
+        port: u16,
+        /// the address to listen on. Use 0.0.0.0 to accept requests from other machines
+        #[arg(long, value_name = "ADDRESS", default_value = "127.0.0.1")]
+        address: String,
+        /// only answer requests with the header `Authorization: Bearer <TOKEN>`. Without it a
+        /// random token is generated
+        #[arg(long, env = "BARTIB_SERVE_TOKEN")]
+        token: Option<String>,
+        /// answer requests without a token. Only allowed on a loopback address
+        #[arg(long, conflicts_with = "token")]
+        no_token: bool,
+        /// allow browsers to send requests from this origin, e.g. `moz-extension://<ID>`
+        #[arg(long, value_name = "ORIGIN")]
+        allow_origin: Option<String>,
+        /// stop activities running longer than the setting `max_activity_duration` at their likely end when another activity is started
+        #[arg(long)]
+        auto_stop_stale: bool,
+    },
+    /// merges the activities of another bartib file (e.g. from another device) into the file
+    Merge {
+        /// the bartib file to merge
+        #[arg(value_name = "OTHER_FILE", value_hint = ValueHint::FilePath)]
+        other_file: String,
+        /// how to resolve activities that overlap with existing ones
+        #[arg(long, value_enum, default_value_t = ConflictStrategy::Ask)]
+        strategy: ConflictStrategy,
+        /// only print what would be merged
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// imports the activities exported by another time tracker into the file
+    Import {
+        /// the time tracker the activities have been exported from
+        #[arg(value_enum)]
+        format: ImportFormat,
+        /// the exported file or the data directory of the time tracker (e.g. `~/.timewarrior/data`)
+        #[arg(value_name = "IMPORT_FILE", value_hint = ValueHint::FilePath)]
+        import_file: String,
+        /// how to resolve activities that overlap with existing ones
+        #[arg(long, value_enum, default_value_t = ConflictStrategy::Ask)]
+        strategy: ConflictStrategy,
+        /// only print what would be imported
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// merges two copies of an activity log as a merge driver for git (see README)
+    MergeDriver {
+        /// the common ancestor of both copies (`%O` in git)
+        #[arg(value_name = "BASE", value_hint = ValueHint::FilePath)]
+        base: String,
+        /// the current copy, which gets the merged activities (`%A` in git)
+        #[arg(value_name = "OURS", value_hint = ValueHint::FilePath)]
+        ours: String,
+        /// the other copy (`%B` in git)
+        #[arg(value_name = "THEIRS", value_hint = ValueHint::FilePath)]
+        theirs: String,
+    },
+    /// moves stopped activities to yearly archive files next to the activity log
+    Archive {
+        /// archive the activities started before this date (default: the first day of the current year)
+        #[arg(long, value_name = "DATE", allow_hyphen_values = true, value_parser = parse_first_date)]
+        before: Option<NaiveDate>,
+        /// only print which activities would be archived
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// shows the configuration file or changes a setting in it
+    Config {
+        #[command(subcommand)]
+        command: Option<ConfigCommands>,
+    },
+    /// manages recurring activities defined in the configuration file
+    Recur {
+        #[command(subcommand)]
+        command: RecurCommands,
+    },
+    /// prints a completion script for a shell, including projects and descriptions of the activity log
+    Completions {
+        /// the shell to generate the completion script for
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+    /// prints the projects, descriptions or tags of the activity log for the completion scripts
+    #[command(name = completions::VALUES_SUBCOMMAND, hide = true)]
+    CompleteValues {
+        #[arg(value_enum)]
+        value: LoggedValue,
     },
 }
 
-fn main() -> Result<()> {
+#[derive(Subcommand)]
+enum ProjectCommands {
+    /// gives a project a new name that is not used yet, in the activity log and its yearly archives
+    Rename {
+        /// the current name of the project
+        #[arg(value_name = "OLD")]
+        old: String,
+        /// the new name of the project
+        #[arg(value_name = "NEW")]
+        new: String,
+        /// only print how many activities would be changed
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// moves all activities of a project to another existing project, in the activity log and its yearly archives
+    Merge {
+        /// the project whose activities are moved
+        #[arg(value_name = "SOURCE")]
+        source: String,
+        /// the project the activities are moved to
+        #[arg(value_name = "TARGET")]
+        target: String,
+        /// only print how many activities would be changed
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// prints the path of the configuration file
+    Path,
+    /// prints the value of a setting, e.g. `week_start` or `aliases.cb` for a key in a table
+    Get {
+        /// the name of the setting
+        #[arg(value_name = "KEY")]
+        key: String,
+    },
+    /// changes a setting, e.g. `bartib config set round 15m` or `bartib config set aliases.cb "Client/Backend"`
+    Set {
+        /// the name of the setting
+        #[arg(value_name = "KEY")]
+        key: String,
+        /// the new value. Numbers, `true` and `false` are written as such, everything else as a string
+        #[arg(value_name = "VALUE")]
+        value: String,
+    },
+    /// removes a setting
+    Unset {
+        /// the name of the setting
+        #[arg(value_name = "KEY")]
+        key: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum RecurCommands {
+    /// lists the rules for recurring activities
+    List,
+    /// adds recurring activities for a range of dates unless they overlap with other activities
+    Apply {
+        /// begin of date range (inclusive, default: today)
+        #[arg(long, value_name = "FROM_DATE", allow_hyphen_values = true, value_parser = parse_first_date)]
+        from: Option<NaiveDate>,
+        /// end of date range (inclusive, default: today)
+        #[arg(long, value_name = "TO_DATE", allow_hyphen_values = true, value_parser = parse_last_date)]
+        to: Option<NaiveDate>,
+        /// only print which activities would be added
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+impl Commands {
+    // whether the command reads and then changes the activity log
+    //
+    // the log is locked while such a command runs so that no other bartib process changes it in
+    // the meantime. Editing the log in an editor is left out as the lock would be held for too long,
+    // and so are commands asking the user how to change the log. They lock it only to write it
+    fn changes_log(&self) -> bool {
+        matches!(
+            self,
+            Commands::Add { .. }
+                | Commands::Switch { .. }
+                | Commands::Continue { .. }
+                | Commands::Change { .. }
+                | Commands::Stop { .. }
+                | Commands::IdleStart { .. }
+                | Commands::IdleEnd { .. }
+                | Commands::Pause { .. }
+                | Commands::Resume { .. }
+                | Commands::Cancel
+                | Commands::Undo
+                | Commands::EditActivity { .. }
+                | Commands::Delete { .. }
+                | Commands::Note { .. }
+                | Commands::Project { .. }
+                | Commands::Sanity {
+                    fix: true,
+                    strategy: FixStrategy::Auto
+                }
+                | Commands::Merge {
+                    strategy: ConflictStrategy::Ours
+                        | ConflictStrategy::Theirs
+                        | ConflictStrategy::Both,
+                    ..
+                }
+                | Commands::Import {
+                    strategy: ConflictStrategy::Ours
+                        | ConflictStrategy::Theirs
+                        | ConflictStrategy::Both,
+                    ..
+                }
+                | Commands::Archive { .. }
+                | Commands::Init { .. }
+                | Commands::Recur {
+                    command: RecurCommands::Apply { .. }
+                }
+        )
+    }
+
+    // replaces `--interactive` by the activity the user picks
+    //
+    // the user picks the activity before the log is locked, as picking may take a while.
+    fn pick_interactively(self, file_name: &str) -> Result<Self> {
+        match self {
+            Commands::Start {
+                project,
+                description,
+                tags,
+                issue,
+                time,
+                idempotent,
+                interactive: true,
+                auto_stop_stale,
+                fail_over_budget,
+            } => {
+                let (_, picked_description, picked_project) =
+                    bartib::controller::manipulation::pick_recent_activity(file_name)?;
+                Ok(Commands::Start {
+                    project: project.or(Some(picked_project)),
+                    description: description.or(Some(picked_description)),
+                    tags,
+                    issue,
+                    time,
+                    idempotent,
+                    interactive: false,
+                    auto_stop_stale,
+                    fail_over_budget,
+                })
+            }
+            Commands::Continue {
+                project,
+                description,
+                time,
+                interactive: true,
+                ..
+            } => {
+                let (number, _, _) =
+                    bartib::controller::manipulation::pick_recent_activity(file_name)?;
+                Ok(Commands::Continue {
+                    project,
+                    description,
+                    time,
+                    number,
+                    interactive: false,
+                    last_of_project: None,
+                })
+            }
+            command => Ok(command),
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => match e.downcast_ref::<ExitStatus>() {
+            Some(status) => ExitCode::from(*status),
+            None => {
+                eprintln!("Error: {e:?}");
+                ExitCode::FAILURE
+            }
+        },
+    }
+}
+
+fn run() -> Result<()> {
     #[cfg(windows)]
     if let Err(e) = enable_ansi_support() {
         println!("Could not enable ansi support! Errorcode: {}", e);
     }
 
     let cli = Cli::parse();
+    bartib::conf::set_quiet(cli.quiet);
+    let config = match cli.command {
+        // the configuration file is created by setting a value
+        Commands::Config { .. } => bartib::data::config::load_optional(cli.config.as_deref())?,
+        _ => bartib::data::config::load(cli.config.as_deref())?,
+    };
+    let settings = Settings::from_config(&config)?;
+    settings.apply();
 
-    run_subcommand(cli)
+    let output = match cli.output {
+        Some(output) => output,
+        None => settings::get_enum(&config, "output")?.unwrap_or_default(),
+    };
+
+    let color = match cli.color {
+        Some(color) => color,
+        None => settings::get_enum(&config, "color")?.unwrap_or_default(),
+    };
+    bartib::conf::set_color(color.use_color());
+    theme::set_theme(Theme::from_config(&config)?);
+    encryption::set_settings(EncryptionSettings::from_config(&config)?);
+
+    let Some(file_name) = cli.file.or_else(|| settings.file.clone()) else {
+        return match cli.command {
+            // completion scripts may be generated and used before an activity log exists
+            Commands::Completions { shell } => {
+                print_completions(shell);
+                Ok(())
+            }
+            Commands::CompleteValues { .. } => Ok(()),
+            // the configuration file may define the activity log
+            Commands::Config { command } => run_config_command(command, &config),
+            Commands::MergeDriver { base, ours, theirs } => {
+                bartib::controller::merge::merge_driver(&base, &ours, &theirs)
+            }
+            _ => Cli::command()
+                .error(
+                    ErrorKind::MissingRequiredArgument,
+                    "the following required arguments were not provided:\n  --file <FILE>",
+                )
+                .exit(),
+        };
+    };
+
+    run_subcommand(cli.command, &file_name, &config, &settings, output)
 }
 
-fn run_subcommand(cli: Cli) -> Result<()> {
-    let file_name = &cli.file;
-    match cli.command {
-        Commands::Start {
-            project,
-            description,
-            time,
+fn run_subcommand(
+    command: Commands,
+    file_name: &str,
+    config: &Config,
+    settings: &Settings,
+    output: OutputFormat,
+) -> Result<()> {
+    let aliases = Aliases::from_config(config)?;
+    bartib::controller::hooks::set_hooks(Hooks::from_config(config)?);
+    let command = command.pick_interactively(file_name)?;
+    let result = run_command(command, file_name, config, settings, output, &aliases);
+
+    // hooks may take a while, e.g. to post to a webhook, so they are run once the log is unlocked
+    bartib::controller::hooks::run_pending();
+    result
+}
+
+fn run_command(
+    command: Commands,
+    file_name: &str,
+    config: &Config,
+    settings: &Settings,
+    output: OutputFormat,
+    aliases: &Aliases,
+) -> Result<()> {
+    let _lock = command
+        .changes_log()
+        .then(|| FileLock::acquire(file_name))
+        .transpose()
+        .context(format!("Could not lock file: {file_name}"))?;
+
+    match command {
+        Commands::Shell => run_shell(file_name, config, settings, output),
+        Commands::Config { command } => run_config_command(command, config),
+        Commands::Ui => bartib::controller::ui::run(file_name),
+        Commands::Serve {
+            port,
+            address,
+            token,
+            no_token,
+            allow_origin,
+            auto_stop_stale,
         } => {
-            let time = time.map(|t| Local::now().date_naive().and_time(t));
+            if auto_stop_stale && settings.max_activity_duration.is_none() {
+                bail!(
+                    "Please set `max_activity_duration` in the configuration file to stop stale activities"
+                );
+            }
+            let token = match token {
+                Some(token) => Some(token),
+                None if no_token => None,
+                None => {
+                    let token = bartib::controller::serve::generate_token();
+                    println!("Token: {token}");
+                    Some(token)
+                }
+            };
+            let rounding = || {
+                let rounding = Rounding::new(settings, None, false, None, None, None);
+                (rounding.processors, rounding.total_rounding)
+            };
+            bartib::controller::serve::serve(
+                file_name,
+                &ServeOptions {
+                    address: &address,
+                    port,
+                    token: token.as_deref(),
+                    allow_origin: allow_origin.as_deref(),
+                    aliases,
+                    budgets: &bartib::data::budget::get_budgets(config)?,
+                    stale_activities: settings.max_activity_duration.map(|max_duration| {
+                        bartib::api::StaleActivities {
+                            max_duration,
+                            auto_stop: auto_stop_stale,
+                        }
+                    }),
+                    rounding: &rounding,
+                },
+            )
+        }
+        Commands::Completions { shell } => {
+            print_completions(shell);
+            Ok(())
+        }
+        Commands::CompleteValues { value } => {
+            bartib::controller::shell::list_logged_values(file_name, value);
+            Ok(())
+        }
+        Commands::Merge {
+            other_file,
+            strategy,
+            dry_run,
+        } => bartib::controller::merge::merge(file_name, &other_file, strategy, dry_run),
+        Commands::Import {
+            format,
+            import_file,
+            strategy,
+            dry_run,
+        } => bartib::controller::import::import(file_name, &import_file, format, strategy, dry_run),
+        Commands::MergeDriver { base, ours, theirs } => {
+            bartib::controller::merge::merge_driver(&base, &ours, &theirs)
+        }
+        Commands::Archive { before, dry_run } => {
+            let today = Local::now().date_naive();
+            let first_day_of_year = NaiveDate::from_ymd_opt(today.year(), 1, 1).unwrap_or(today);
 
-            bartib::controller::manipulation::start(file_name, &project, &description, time)
+            bartib::controller::archive::archive(
+                file_name,
+                before.unwrap_or(first_day_of_year),
+                dry_run,
+            )
         }
-        Commands::Change {
+        Commands::Recur { command } => match command {
+            RecurCommands::List => bartib::controller::recur::list_rules(config),
+            RecurCommands::Apply { from, to, dry_run } => {
+                let today = Local::now().date_naive();
+                bartib::controller::recur::apply(
+                    file_name,
+                    config,
+                    from.unwrap_or(today),
+                    to.unwrap_or(today),
+                    dry_run,
+                )
+            }
+        },
+        Commands::Start {
             project,
             description,
+            tags,
+            issue,
             time,
+            idempotent,
+            interactive: _,
+            auto_stop_stale,
+            fail_over_budget,
         } => {
-            let time = time.map(|t| Local::now().date_naive().and_time(t));
-
-            bartib::controller::manipulation::change(
+            // both have been given or picked
+            let (Some(project), Some(description)) = (project, description) else {
+                bail!("Please specify a project and a description or use --interactive");
+            };
+            if auto_stop_stale && settings.max_activity_duration.is_none() {
+                bail!(
+                    "Please set `max_activity_duration` in the configuration file to stop stale activities"
+                );
+            }
+            let stale_activities = settings.max_activity_duration.map(|max_duration| {
+                bartib::controller::manipulation::StaleActivities {
+                    max_duration,
+                    auto_stop: auto_stop_stale,
+                }
+            });
+            let project = aliases.resolve(&project)?;
+            bartib::controller::manipulation::start(
                 file_name,
-                project.as_deref(),
-                description.as_deref(),
+                project,
+                &description,
+                bartib::controller::manipulation::Labels {
+                    tags: &tags,
+                    issue: issue.as_deref(),
+                },
                 time,
+                idempotent,
+                stale_activities,
+            )?;
+            bartib::controller::budget::check_budgets(
+                file_name,
+                &bartib::data::budget::get_budgets(config)?,
+                Some(project),
+                fail_over_budget,
             )
         }
+        Commands::Add {
+            project,
+            description,
+            tags,
+            issue,
+            start,
+            end,
+        } => bartib::controller::manipulation::add(
+            file_name,
+            aliases.resolve(&project)?,
+            &description,
+            bartib::controller::manipulation::Labels {
+                tags: &tags,
+                issue: issue.as_deref(),
+            },
+            start,
+            end,
+        ),
+        Commands::Switch {
+            project,
+            description,
+            tags,
+            issue,
+            time,
+            idempotent,
+        } => bartib::controller::manipulation::switch(
+            file_name,
+            aliases.resolve(&project)?,
+            &description,
+            bartib::controller::manipulation::Labels {
+                tags: &tags,
+                issue: issue.as_deref(),
+            },
+            time,
+            idempotent,
+        ),
+        Commands::Change {
+            project,
+            description,
+            time,
+        } => bartib::controller::manipulation::change(
+            file_name,
+            project.as_deref().map(|p| aliases.resolve(p)).transpose()?,
+            description.as_deref(),
+            time,
+        ),
         Commands::Continue {
             project,
             description,
             time,
             number,
+            interactive: _,
+            last_of_project,
+        } => bartib::controller::manipulation::continue_last_activity(
+            file_name,
+            project.as_deref().map(|p| aliases.resolve(p)).transpose()?,
+            description.as_deref(),
+            time,
+            number,
+            last_of_project
+                .as_deref()
+                .map(|p| aliases.resolve(p))
+                .transpose()?,
+        ),
+        Commands::Stop {
+            time,
+            since_idle,
+            idle_project,
+            split_days,
         } => {
-            let time = time.map(|t| Local::now().date_naive().and_time(t));
+            // set for each command, so that `--split-days` does not stay on in the shell
+            bartib::conf::set_split_days(split_days || settings.split_days.unwrap_or(false));
 
-            bartib::controller::manipulation::continue_last_activity(
-                file_name,
-                project.as_deref(),
-                description.as_deref(),
-                time,
-                number,
-            )
+            match since_idle {
+                Some(idle) => bartib::controller::manipulation::stop_since_idle(
+                    file_name,
+                    idle,
+                    idle_project
+                        .as_deref()
+                        .map(|p| aliases.resolve(p))
+                        .transpose()?,
+                ),
+                None => bartib::controller::manipulation::stop(file_name, time),
+            }
         }
-        Commands::Stop { time } => {
-            let time = time.map(|t| Local::now().date_naive().and_time(t));
-
-            bartib::controller::manipulation::stop(file_name, time)
+        Commands::IdleStart { time } => {
+            bartib::controller::manipulation::idle_start(file_name, time)
         }
+        Commands::IdleEnd { time, idle_project } => bartib::controller::manipulation::idle_end(
+            file_name,
+            time,
+            idle_project
+                .as_deref()
+                .map(|p| aliases.resolve(p))
+                .transpose()?,
+        ),
+        Commands::Pause { time } => bartib::controller::manipulation::pause(file_name, time),
+        Commands::Resume { time } => bartib::controller::manipulation::resume(file_name, time),
         Commands::Cancel => bartib::controller::manipulation::cancel(file_name),
-        Commands::Current => bartib::controller::list::list_running(file_name),
+        Commands::Undo => bartib::controller::manipulation::undo(file_name),
+        Commands::Current {
+            daily_limit,
+            watch,
+            simple,
+        } => {
+            if watch && output == OutputFormat::Json {
+                bail!("--watch can not be used with the JSON output");
+            }
+
+            if watch {
+                bartib::controller::list::watch_running(
+                    file_name,
+                    daily_limit.or(settings.daily_limit),
+                    simple,
+                )
+            } else {
+                bartib::controller::list::list_running(
+                    file_name,
+                    daily_limit.or(settings.daily_limit),
+                    output,
+                    simple,
+                )
+            }
+        }
         Commands::List {
             from,
             to,
@@ -250,22 +1374,60 @@ fn run_subcommand(cli: Cli) -> Result<()> {
             yesterday,
             current_week,
             last_week,
+            current_month,
+            last_month,
+            month,
             round,
+            no_round,
+            round_mode,
+            round_up,
+            round_down,
+            min_duration,
+            round_scope,
+            timezone,
             project,
+            tags,
             no_grouping,
+            group_by,
             number,
+            verbose,
         } => {
             let date_presets = DatePresetArgs {
                 today,
                 yesterday,
                 current_week,
                 last_week,
+                current_month,
+                last_month,
             };
-            let filter =
-                ActivityFilter::new(number, from, to, date, project.as_deref(), date_presets);
-            let processors = create_processors(round);
-            let do_group_activities = !no_grouping && filter.date.is_none();
-            bartib::controller::list::list(file_name, filter, do_group_activities, processors)
+            let filter = ActivityFilter::new(
+                number,
+                from,
+                to,
+                date.or(month),
+                project.as_deref().map(|p| aliases.resolve(p)).transpose()?,
+                &tags,
+                date_presets,
+            );
+            let rounding = Rounding::new(
+                settings,
+                round,
+                no_round,
+                RoundingMode::from_flags(round_mode, round_up, round_down),
+                round_scope,
+                min_duration,
+            )
+            .in_timezone(timezone);
+            let grouping = (!no_grouping && filter.date.is_none()).then_some(group_by);
+            bartib::controller::list::list(
+                file_name,
+                filter,
+                grouping,
+                rounding.processors,
+                rounding.total_rounding,
+                verbose,
+                output,
+            )
         }
         Commands::Report {
             from,
@@ -275,62 +1437,668 @@ fn run_subcommand(cli: Cli) -> Result<()> {
             yesterday,
             current_week,
             last_week,
+            current_month,
+            last_month,
+            month,
+            round,
+            no_round,
+            round_mode,
+            round_up,
+            round_down,
+            min_duration,
+            round_scope,
+            timezone,
+            project,
+            tags,
+            group_by,
+            depth,
+            earnings,
+            matrix,
+            csv,
+            format,
+            output_file,
+            compare,
+            fail_over_budget,
+            template,
+        } => {
+            let date_presets = DatePresetArgs {
+                today,
+                yesterday,
+                current_week,
+                last_week,
+                current_month,
+                last_month,
+            };
+            let filter = ActivityFilter::new(
+                None,
+                from,
+                to,
+                date.or(month),
+                project.as_deref().map(|p| aliases.resolve(p)).transpose()?,
+                &tags,
+                date_presets,
+            );
+            let rounding = Rounding::new(
+                settings,
+                round,
+                no_round,
+                RoundingMode::from_flags(round_mode, round_up, round_down),
+                round_scope,
+                min_duration,
+            )
+            .in_timezone(timezone);
+            if compare {
+                let writer: Box<dyn processor::ComparisonWriter> = match output {
+                    OutputFormat::Text => Box::new(ComparisonReport {}),
+                    OutputFormat::Json => Box::new(ComparisonJson {}),
+                };
+                bartib::controller::report::show_comparison(
+                    file_name,
+                    filter,
+                    rounding.processors,
+                    rounding.total_rounding,
+                    depth.map(NonZeroUsize::get),
+                    writer.as_ref(),
+                )?;
+                return bartib::controller::budget::check_budgets(
+                    file_name,
+                    &bartib::data::budget::get_budgets(config)?,
+                    None,
+                    fail_over_budget,
+                );
+            }
+            let writer = create_report_writer(
+                &ReportKind {
+                    group_by,
+                    earnings,
+                    matrix,
+                    csv,
+                    format,
+                    template,
+                },
+                output,
+                output_file,
+                config,
+            )?;
+            bartib::controller::report::show_report(
+                file_name,
+                filter,
+                rounding.processors,
+                rounding.total_rounding,
+                depth.map(NonZeroUsize::get),
+                writer.as_ref(),
+            )?;
+            bartib::controller::budget::check_budgets(
+                file_name,
+                &bartib::data::budget::get_budgets(config)?,
+                None,
+                fail_over_budget,
+            )
+        }
+        Commands::Remind {
+            idle,
+            running,
+            working_hours,
+            daily_limit,
+            repeat,
+            once,
+        } => {
+            let mut reminder_settings = ReminderSettings::from_config(config)?;
+            reminder_settings.idle = idle.unwrap_or(reminder_settings.idle);
+            reminder_settings.running = running.unwrap_or(reminder_settings.running);
+            reminder_settings.working_hours =
+                working_hours.unwrap_or(reminder_settings.working_hours);
+            reminder_settings.daily_limit = daily_limit
+                .or(reminder_settings.daily_limit)
+                .or(settings.daily_limit);
+
+            bartib::controller::remind::remind(file_name, &reminder_settings, repeat, once)
+        }
+        Commands::Summary {
+            yesterday,
+            date,
+            project,
+            tags,
+            no_round,
+            format,
+        } => {
+            let filter = ActivityFilter::new(
+                None,
+                None,
+                None,
+                date,
+                project.as_deref().map(|p| aliases.resolve(p)).transpose()?,
+                &tags,
+                DatePresetArgs {
+                    today: !yesterday && date.is_none(),
+                    yesterday,
+                    ..DatePresetArgs::default()
+                },
+            );
+            let rounding = Rounding::new(settings, None, no_round, None, None, None);
+            let writer: Box<dyn processor::ReportWriter> = match output {
+                OutputFormat::Text => Box::new(SummaryReport { format }),
+                OutputFormat::Json => Box::new(SummaryJson {}),
+            };
+            bartib::controller::report::show_report(
+                file_name,
+                filter,
+                rounding.processors,
+                rounding.total_rounding,
+                None,
+                writer.as_ref(),
+            )
+        }
+        Commands::Timesheet {
+            week,
+            round,
+            no_round,
+            round_mode,
+            round_up,
+            round_down,
+            min_duration,
+            round_scope,
+            project,
+            tags,
+            depth,
+        } => {
+            let week = week.unwrap_or_else(|| DateRange::week_of(Local::now().date_naive()));
+            let filter = ActivityFilter::new(
+                None,
+                None,
+                None,
+                Some(week),
+                project.as_deref().map(|p| aliases.resolve(p)).transpose()?,
+                &tags,
+                DatePresetArgs::default(),
+            );
+            let rounding = Rounding::new(
+                settings,
+                round,
+                no_round,
+                RoundingMode::from_flags(round_mode, round_up, round_down),
+                round_scope,
+                min_duration,
+            );
+            let writer: Box<dyn processor::ReportWriter> = match output {
+                OutputFormat::Text => Box::new(TimesheetReport {}),
+                OutputFormat::Json => Box::new(TimesheetJson {}),
+            };
+            bartib::controller::report::show_report(
+                file_name,
+                filter,
+                rounding.processors,
+                rounding.total_rounding,
+                depth.map(NonZeroUsize::get),
+                writer.as_ref(),
+            )
+        }
+        Commands::Chart {
+            year,
+            month,
+            weeks,
+            project,
+            tags,
+            depth,
+        } => {
+            let today = Local::now().date_naive();
+            let range = match (year, month) {
+                (Some(year), _) => {
+                    let year = year.unwrap_or_else(|| today.year());
+                    let first_day = |year| NaiveDate::from_ymd_opt(year, 1, 1);
+                    let (Some(first), Some(next_year)) = (first_day(year), first_day(year + 1))
+                    else {
+                        bail!("Invalid year: {year}");
+                    };
+                    DateRange {
+                        first,
+                        last: next_year - Duration::days(1),
+                    }
+                }
+                (_, Some(month)) => month.unwrap_or_else(|| DateRange::month_of(today)),
+                (None, None) => DateRange {
+                    first: DateRange::week_of(today - Duration::weeks(51)).first,
+                    last: today,
+                },
+            };
+            let filter = ActivityFilter::new(
+                None,
+                Some(range.first),
+                Some(range.last),
+                None,
+                project.as_deref().map(|p| aliases.resolve(p)).transpose()?,
+                &tags,
+                DatePresetArgs::default(),
+            );
+            let rounding = Rounding::new(settings, None, false, None, None, None);
+            let writer: Box<dyn processor::ReportWriter> = match output {
+                OutputFormat::Text => Box::new(ChartReport {
+                    kind: if weeks {
+                        ChartKind::Weeks
+                    } else {
+                        ChartKind::Heatmap
+                    },
+                }),
+                OutputFormat::Json => Box::new(ChartJson {}),
+            };
+            bartib::controller::report::show_report(
+                file_name,
+                filter,
+                rounding.processors,
+                rounding.total_rounding,
+                depth.map(NonZeroUsize::get),
+                writer.as_ref(),
+            )
+        }
+        Commands::Stats {
+            from,
+            to,
+            date,
+            project,
+            tags,
+        } => {
+            let filter = ActivityFilter::new(
+                None,
+                from,
+                to,
+                date,
+                project.as_deref().map(|p| aliases.resolve(p)).transpose()?,
+                &tags,
+                DatePresetArgs::default(),
+            );
+            let rounding = Rounding::new(settings, None, false, None, None, None);
+            let writer: Box<dyn processor::ReportWriter> = match output {
+                OutputFormat::Text => Box::new(StatsReport {}),
+                OutputFormat::Json => Box::new(StatsJson {}),
+            };
+            bartib::controller::report::show_report(
+                file_name,
+                filter,
+                rounding.processors,
+                rounding.total_rounding,
+                None,
+                writer.as_ref(),
+            )
+        }
+        Commands::Export {
+            format,
+            output_file,
+            from,
+            to,
+            date,
+            today,
+            yesterday,
+            current_week,
+            last_week,
+            current_month,
+            last_month,
+            month,
             round,
+            no_round,
+            round_mode,
+            round_up,
+            round_down,
+            min_duration,
             project,
+            tags,
+            redact_descriptions,
+            only_projects,
+            push,
         } => {
+            if push && format != ExportFormat::Jira {
+                bail!("Only worklogs of the format `jira` can be pushed");
+            }
             let date_presets = DatePresetArgs {
                 today,
                 yesterday,
                 current_week,
                 last_week,
+                current_month,
+                last_month,
             };
-            let filter =
-                ActivityFilter::new(None, from, to, date, project.as_deref(), date_presets);
-            let processors = create_processors(round);
-            bartib::controller::report::show_report(file_name, filter, processors)
+            let filter = ActivityFilter::new(
+                None,
+                from,
+                to,
+                date.or(month),
+                project.as_deref().map(|p| aliases.resolve(p)).transpose()?,
+                &tags,
+                date_presets,
+            );
+            let rounding = Rounding::new(
+                settings,
+                round,
+                no_round,
+                RoundingMode::from_flags(round_mode, round_up, round_down),
+                Some(RoundingScope::Activity),
+                min_duration,
+            );
+            if push {
+                return bartib::controller::export::push_to_jira(
+                    file_name,
+                    filter,
+                    rounding.processors,
+                    &JiraSettings::from_config(config)?,
+                );
+            }
+            let redaction = if only_projects {
+                Some(Redaction::OnlyProjects)
+            } else {
+                redact_descriptions.map(Redaction::Descriptions)
+            };
+            bartib::controller::export::export(
+                file_name,
+                filter,
+                rounding.processors,
+                format,
+                redaction,
+                output_file.as_deref(),
+            )
         }
+        Commands::Project { command } => match command {
+            ProjectCommands::Rename { old, new, dry_run } => bartib::controller::project::rename(
+                file_name,
+                aliases.resolve(&old)?,
+                aliases.resolve(&new)?,
+                dry_run,
+            ),
+            ProjectCommands::Merge {
+                source,
+                target,
+                dry_run,
+            } => bartib::controller::project::merge(
+                file_name,
+                aliases.resolve(&source)?,
+                aliases.resolve(&target)?,
+                dry_run,
+            ),
+        },
         Commands::Projects { current, no_quotes } => {
             bartib::controller::list::list_projects(file_name, current, no_quotes)
         }
-        Commands::Last { number } => {
-            bartib::controller::list::list_last_activities(file_name, number)
+        Commands::Last {
+            number,
+            activities,
+            project,
+        } => {
+            if activities {
+                bartib::controller::list::list_numbered_activities(file_name, number)
+            } else {
+                bartib::controller::list::list_last_activities(
+                    file_name,
+                    number,
+                    project.as_deref().map(|p| aliases.resolve(p)).transpose()?,
+                )
+            }
+        }
+        Commands::EditActivity {
+            number,
+            project,
+            description,
+            start,
+            end,
+            tags,
+            issue,
+        } => bartib::controller::manipulation::edit_activity(
+            file_name,
+            number,
+            project.as_deref().map(|p| aliases.resolve(p)).transpose()?,
+            description.as_deref(),
+            start,
+            end,
+            bartib::controller::manipulation::Labels {
+                tags: &tags,
+                issue: issue.as_deref(),
+            },
+        ),
+        Commands::Delete { number } => {
+            bartib::controller::manipulation::delete_activity(file_name, number)
         }
-        Commands::Edit { editor } => {
-            bartib::controller::manipulation::start_editor(file_name, editor.as_deref())
+        Commands::Note { note, number } => {
+            bartib::controller::manipulation::add_note(file_name, number, &note)
         }
+        Commands::Init { encrypt, decrypt } => bartib::controller::init::init(
+            file_name,
+            encrypt
+                .then_some(Encryption::Encrypt)
+                .or(decrypt.then_some(Encryption::Decrypt)),
+        ),
+        Commands::Edit { editor } => bartib::controller::manipulation::start_editor(
+            file_name,
+            editor.as_deref().or(settings.editor.as_deref()),
+        ),
         Commands::Check => bartib::controller::list::check(file_name),
-        Commands::Sanity => bartib::controller::list::sanity_check(file_name),
+        Commands::Sanity { fix, strategy } => {
+            bartib::controller::sanity::sanity_check(file_name, fix.then_some(strategy))
+        }
         Commands::Search { search_term } => {
             bartib::controller::list::search(file_name, Some(&search_term))
         }
-        Commands::Status { project } => {
+        Commands::Status {
+            project,
+            tags,
+            daily_limit,
+            fail_under,
+            fail_over_budget,
+        } => {
             let filter = ActivityFilter {
                 number_of_activities: None,
                 from_date: None,
                 to_date: None,
                 date: None,
-                project: project.as_deref(),
+                project: project.as_deref().map(|p| aliases.resolve(p)).transpose()?,
+                tags: &tags,
             };
-            let processors = create_processors(None);
-            let writer = create_status_writer();
-            bartib::controller::status::show_status(file_name, filter, processors, writer.as_ref())
+            let processors: processor::ProcessorList = Vec::new();
+            let writer = create_status_writer(output);
+            bartib::controller::status::show_status(
+                file_name,
+                filter,
+                processors,
+                writer.as_ref(),
+                daily_limit.or(settings.daily_limit),
+                bartib::controller::status::Targets {
+                    goals: &bartib::data::goal::get_goals(config)?,
+                    fail_under,
+                    budgets: &bartib::data::budget::get_budgets(config)?,
+                    fail_over_budget,
+                },
+            )
         }
     }
 }
 
-fn create_processors(round: Option<Duration>) -> processor::ProcessorList {
-    let mut processors: Vec<Box<dyn processor::ActivityProcessor>> = Vec::new();
+// reads subcommands from the user until the input is closed or the user exits the shell
+fn run_shell(
+    file_name: &str,
+    config: &Config,
+    settings: &Settings,
+    output: OutputFormat,
+) -> Result<()> {
+    bartib::data::bartib_file::keep_in_memory();
+
+    let subcommands: Vec<String> = ShellLine::command()
+        .get_subcommands()
+        .map(|subcommand| subcommand.get_name().to_string())
+        .filter(|name| name != "shell")
+        .chain(["exit".to_string(), "help".to_string()])
+        .collect();
+    let complete = |line: &str| bartib::controller::shell::complete(file_name, line, &subcommands);
+    let mut line_editor = LineEditor::new();
+
+    println!(
+        "Enter bartib subcommands without `bartib`. Use `help` to list them and `exit` to leave."
+    );
 
-    if let Some(round) = round {
-        processors.push(Box::new(processor::RoundProcessor { round }));
+    while let Some(line) = line_editor.read_line("bartib> ", &complete)? {
+        let arguments = match bartib::view::line_editor::split_arguments(&line) {
+            Ok(arguments) => arguments,
+            Err(e) => {
+                eprintln!("Error: {e}");
+                continue;
+            }
+        };
+
+        match arguments.first().map(String::as_str) {
+            None => continue,
+            Some("exit" | "quit") => break,
+            Some("shell") => {
+                eprintln!("Error: the shell is already running");
+                continue;
+            }
+            Some(_) => {}
+        }
+
+        match ShellLine::try_parse_from(arguments) {
+            Ok(shell_line) => {
+                if let Err(e) =
+                    run_subcommand(shell_line.command, file_name, config, settings, output)
+                    && e.downcast_ref::<ExitStatus>().is_none()
+                {
+                    eprintln!("Error: {e:?}");
+                }
+            }
+            Err(e) => e.print()?,
+        }
+    }
+
+    Ok(())
+}
+
+fn run_config_command(command: Option<ConfigCommands>, config: &Config) -> Result<()> {
+    match command {
+        None => bartib::controller::config::show(config),
+        Some(ConfigCommands::Path) => bartib::controller::config::show_path(config),
+        Some(ConfigCommands::Get { key }) => bartib::controller::config::get(config, &key),
+        Some(ConfigCommands::Set { key, value }) => {
+            bartib::controller::config::set(config, &key, Some(&value))
+        }
+        Some(ConfigCommands::Unset { key }) => bartib::controller::config::set(config, &key, None),
     }
+}
 
-    processors
+fn print_completions(shell: Shell) {
+    let mut command = Cli::command();
+    command.build();
+    print!("{}", completions::generate(&command, shell));
 }
 
-fn create_status_writer() -> Box<dyn processor::StatusReportWriter> {
-    let result = StatusReport {};
-    Box::new(result)
+// the rounding of activities or of their totals as requested on the command line
+struct Rounding {
+    processors: processor::ProcessorList,
+    total_rounding: Option<processor::TotalRounding>,
+}
+
+impl Rounding {
+    // rounds as given on the command line or else as configured in the configuration file. A
+    // minimum duration is applied after rounding
+    fn new(
+        settings: &Settings,
+        round: Option<Duration>,
+        no_round: bool,
+        mode: Option<RoundingMode>,
+        scope: Option<RoundingScope>,
+        min_duration: Option<Duration>,
+    ) -> Self {
+        let mut processors: Vec<Box<dyn processor::ActivityProcessor>> = Vec::new();
+        let mut total_rounding = None;
+        let mode = mode.or(settings.round_mode).unwrap_or_default();
+        let scope = scope.or(settings.round_scope).unwrap_or_default();
+
+        if let Some(round) = round.or(settings.round).filter(|_| !no_round) {
+            if scope == RoundingScope::Activity {
+                processors.push(Box::new(processor::RoundProcessor { round, mode }));
+            } else {
+                total_rounding = Some(processor::TotalRounding { round, mode, scope });
+            }
+        }
+
+        if let Some(min_duration) = min_duration.or(settings.min_duration).filter(|_| !no_round) {
+            processors.push(Box::new(processor::MinDurationProcessor { min_duration }));
+        }
+
+        Self {
+            processors,
+            total_rounding,
+        }
+    }
+
+    // converts the activities to another timezone before rounding them
+    fn in_timezone(mut self, timezone: Option<Timezone>) -> Self {
+        if let Some(timezone) = timezone {
+            self.processors
+                .insert(0, Box::new(processor::TimezoneProcessor { timezone }));
+        }
+        self
+    }
+}
+
+// the options of the report subcommand which choose the report writer
+struct ReportKind {
+    group_by: ReportGrouping,
+    earnings: bool,
+    matrix: bool,
+    csv: bool,
+    format: ReportFormat,
+    template: Option<String>,
+}
+
+fn create_report_writer(
+    kind: &ReportKind,
+    output: OutputFormat,
+    output_file: Option<String>,
+    config: &Config,
+) -> Result<Box<dyn processor::ReportWriter>> {
+    if let Some(template) = &kind.template {
+        return Ok(Box::new(TemplateReport {
+            template: Template::load(template)?,
+            output_file,
+        }));
+    }
+
+    if output_file.is_some() && kind.format != ReportFormat::Html {
+        bail!("--output-file can only be used with --format html or --template");
+    }
+
+    if kind.earnings {
+        let rates = Rates::from_config(config)?;
+        if rates.is_empty() {
+            bail!(
+                "No hourly rates have been defined. Please add a [rates] table to the configuration file"
+            );
+        }
+
+        return Ok(match output {
+            OutputFormat::Text => Box::new(EarningsReport { rates }),
+            OutputFormat::Json => Box::new(EarningsJson { rates }),
+        });
+    }
+
+    Ok(match (kind.format, kind.matrix, kind.csv, output) {
+        (ReportFormat::Html, _, _, _) => Box::new(HtmlReport { output_file }),
+        (_, true, true, _) => Box::new(MatrixReport {
+            format: MatrixFormat::Csv,
+        }),
+        (_, true, false, OutputFormat::Json) => Box::new(MatrixReport {
+            format: MatrixFormat::Json,
+        }),
+        (_, true, false, OutputFormat::Text) => Box::new(MatrixReport {
+            format: MatrixFormat::Table,
+        }),
+        (_, false, _, OutputFormat::Text) => Box::new(GroupedReport {
+            grouping: kind.group_by,
+        }),
+        (_, false, _, OutputFormat::Json) => Box::new(GroupedReportJson {
+            grouping: kind.group_by,
+        }),
+    })
+}
+
+fn create_status_writer(output: OutputFormat) -> Box<dyn processor::StatusReportWriter> {
+    match output {
+        OutputFormat::Text => Box::new(StatusReport {}),
+        OutputFormat::Json => Box::new(StatusJson {}),
+    }
 }
 
 // fn apply_date_presets(
@@ -371,24 +2139,52 @@ fn create_status_writer() -> Box<dyn processor::StatusReportWriter> {
 //     }
 // }
 
-fn parse_date(date_string: &str) -> Result<NaiveDate, String> {
-    NaiveDate::parse_from_str(date_string, bartib::conf::FORMAT_DATE).map_err(|e| e.to_string())
+// the first day of a date or a range of dates like `2024-03` or `last week`
+fn parse_first_date(date_string: &str) -> Result<NaiveDate, String> {
+    parse_date_range(date_string).map(|range| range.first)
 }
 
-fn parse_time(time_string: &str) -> Result<NaiveTime, String> {
-    NaiveTime::parse_from_str(time_string, bartib::conf::FORMAT_TIME).map_err(|e| e.to_string())
+// the last day of a date or a range of dates like `2024-03` or `last week`
+fn parse_last_date(date_string: &str) -> Result<NaiveDate, String> {
+    parse_date_range(date_string).map(|range| range.last)
+}
+
+fn parse_date_range(date_string: &str) -> Result<DateRange, String> {
+    bartib::data::date_parser::parse_date_range(date_string, Local::now().date_naive())
+}
+
+fn parse_month(month_string: &str) -> Result<DateRange, String> {
+    bartib::data::date_parser::parse_month(month_string)
+}
+
+fn parse_working_hours(working_hours: &str) -> Result<WorkingHours, String> {
+    bartib::data::reminder::parse_working_hours(working_hours)
+}
+
+fn parse_week(week_string: &str) -> Result<DateRange, String> {
+    bartib::data::date_parser::parse_week(week_string, Local::now().date_naive())
+}
+
+fn parse_datetime(datetime_string: &str) -> Result<NaiveDateTime, String> {
+    bartib::data::date_parser::parse_datetime(datetime_string, Local::now().date_naive())
+}
+
+fn parse_time(time_string: &str) -> Result<NaiveDateTime, String> {
+    bartib::data::date_parser::parse_time(time_string, Local::now().naive_local())
+}
+
+fn parse_time_of_day(time_string: &str) -> Result<NaiveTime, String> {
+    bartib::data::date_parser::parse_time_of_day(time_string)
+}
+
+fn parse_tag(tag: &str) -> Result<String, String> {
+    bartib::data::activity::parse_tag(tag)
+}
+
+fn parse_issue(issue: &str) -> Result<String, String> {
+    bartib::data::activity::parse_issue(issue)
 }
 
 fn parse_duration(duration_string: &str) -> Result<Duration, String> {
-    let (number_string, duration_unit) = duration_string.split_at(duration_string.len() - 1);
-    let number: i64 = number_string
-        .parse()
-        .map_err(|e: std::num::ParseIntError| e.to_string())?;
-    match duration_unit {
-        "m" => Ok(Duration::minutes(number)),
-        "h" => Ok(Duration::hours(number)),
-        _ => Err(format!(
-            "invalid duration unit '{duration_unit}', expected 'm' or 'h'"
-        )),
-    }
+    bartib::data::duration_parser::parse_duration(duration_string)
 }
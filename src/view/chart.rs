@@ -0,0 +1,478 @@
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Duration, NaiveDate};
+use nu_ansi_term::{Color, Style};
+
+use crate::conf;
+use crate::data::activity;
+use crate::data::date_parser::DateRange;
+use crate::data::processor::{ReportData, ReportWriter, TotalRounding};
+use crate::outputln;
+use crate::view::format_util;
+use crate::view::json::{self, Json, ToJson};
+use crate::view::report;
+
+// the kinds of charts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartKind {
+    // a calendar with a cell per day colored by the time tracked on that day
+    Heatmap,
+    // a bar per week stacked by the time tracked for each project
+    Weeks,
+}
+
+// the colors of days with more and more time tracked, from none to the most
+static HEATMAP_COLORS: [Color; 5] = [
+    Color::Fixed(238),
+    Color::Fixed(22),
+    Color::Fixed(28),
+    Color::Fixed(34),
+    Color::Fixed(46),
+];
+
+// the colors of the projects in the bars of weeks, used again for more projects
+static PROJECT_COLORS: [Color; 6] = [
+    Color::Blue,
+    Color::Green,
+    Color::Yellow,
+    Color::Magenta,
+    Color::Cyan,
+    Color::Red,
+];
+
+static HEATMAP_CELL: &str = "■";
+static BAR_CELL: &str = "█";
+
+// the cells of days and projects if the output is not colored (see `--color`)
+static HEATMAP_PLAIN_CELLS: [&str; 5] = ["·", "░", "▒", "▓", "█"];
+static BAR_PLAIN_CELLS: [&str; 4] = ["█", "▓", "▒", "░"];
+
+// the width of the bar of the week with the most time tracked
+const BAR_WIDTH: usize = 40;
+
+// the time tracked per day and per project and week within a range of dates
+struct Chart<'a> {
+    range: DateRange,
+    days: BTreeMap<NaiveDate, Duration>,
+    // the first day of each week with the time tracked per project, in the order of the projects
+    weeks: Vec<(NaiveDate, Vec<Duration>)>,
+    projects: Vec<&'a str>,
+}
+
+impl<'a> Chart<'a> {
+    fn new(
+        activities: &[&'a activity::Activity],
+        total_rounding: Option<&TotalRounding>,
+        range: DateRange,
+    ) -> Chart<'a> {
+        let mut days: BTreeMap<NaiveDate, Vec<&activity::Activity>> = BTreeMap::new();
+        let mut projects: Vec<&str> = Vec::new();
+        for activity in activities {
+            days.entry(activity.start.date())
+                .or_default()
+                .push(activity);
+            if !projects.contains(&activity.project.as_str()) {
+                projects.push(&activity.project);
+            }
+        }
+        projects.sort_unstable();
+
+        // like in the report, the durations of projects are rounded only if the totals of
+        // projects per day are rounded
+        let project_rounding = total_rounding.filter(|r| r.rounds_projects());
+
+        let weeks = weeks_of(range)
+            .into_iter()
+            .map(|week_start| {
+                let week = DateRange::week_of(week_start);
+                let durations = projects
+                    .iter()
+                    .map(|project| {
+                        let project_activities: Vec<&activity::Activity> = activities
+                            .iter()
+                            .filter(|a| {
+                                a.project == *project
+                                    && week.first <= a.start.date()
+                                    && a.start.date() <= week.last
+                            })
+                            .copied()
+                            .collect();
+                        report::sum_duration_with_rounding(&project_activities, project_rounding)
+                    })
+                    .collect();
+                (week_start, durations)
+            })
+            .collect();
+
+        Chart {
+            range,
+            days: days
+                .into_iter()
+                .map(|(date, activities)| {
+                    (
+                        date,
+                        report::sum_duration_with_rounding(&activities, total_rounding),
+                    )
+                })
+                .collect(),
+            weeks,
+            projects,
+        }
+    }
+
+    fn total(&self) -> Duration {
+        self.days.values().copied().sum()
+    }
+
+    // a row per day of the week and a column per week with the names of the months above
+    fn heatmap(&self) -> String {
+        let weeks = weeks_of(self.range);
+        let max = self
+            .days
+            .values()
+            .copied()
+            .max()
+            .unwrap_or_else(Duration::zero);
+        let mut lines = vec![format!("    {}", month_labels(&weeks, self.range))];
+
+        for weekday in 0..7 {
+            let first_day = weeks
+                .first()
+                .map_or(self.range.first, |week| *week + Duration::days(weekday));
+            // like on GitHub, every other day is labeled
+            let label = if weekday % 2 == 0 {
+                first_day.format("%a").to_string()
+            } else {
+                String::new()
+            };
+
+            let cells: Vec<String> = weeks
+                .iter()
+                .map(|week| {
+                    let date = *week + Duration::days(weekday);
+                    if date < self.range.first || date > self.range.last {
+                        return "  ".to_string();
+                    }
+
+                    let duration = self.days.get(&date).copied().unwrap_or_else(Duration::zero);
+                    format!("{} ", heatmap_cell(intensity(duration, max)))
+                })
+                .collect();
+
+            lines.push(format!("{label:<4}{}", cells.concat()));
+        }
+
+        let legend: Vec<String> = (0..HEATMAP_COLORS.len()).map(heatmap_cell).collect();
+        lines.push(String::new());
+        lines.push(format!("    Less {} More", legend.join(" ")));
+
+        match self.days.iter().max_by_key(|(_, duration)| **duration) {
+            Some((date, duration)) => lines.push(format!(
+                "    {} tracked on {} days, most on {} ({})",
+                format_util::format_duration(&self.total()),
+                self.days.len(),
+                date.format(conf::FORMAT_DATE),
+                format_util::format_duration(duration)
+            )),
+            None => lines.push("    No time tracked".to_string()),
+        }
+
+        lines.join("\n")
+    }
+
+    // a bar per week stacked by project, followed by the colors of the projects
+    fn week_bars(&self) -> String {
+        let max = self
+            .weeks
+            .iter()
+            .map(|(_, durations)| durations.iter().copied().sum())
+            .max()
+            .unwrap_or_else(Duration::zero);
+        let mut lines = Vec::new();
+
+        for (week_start, durations) in &self.weeks {
+            let total: Duration = durations.iter().copied().sum();
+            let widths = bar_widths(durations, max);
+            let bar: String = widths
+                .iter()
+                .enumerate()
+                .map(|(index, width)| project_bar(index, *width))
+                .collect();
+            let padding = " ".repeat(BAR_WIDTH - widths.iter().sum::<usize>());
+
+            let total = if total.is_zero() {
+                String::new()
+            } else {
+                format_util::format_duration(&total)
+            };
+
+            lines.push(format!(
+                "{} {bar}{padding} {total}",
+                week_start.format(conf::FORMAT_DATE),
+            ));
+        }
+
+        let legend: Vec<String> = self
+            .projects
+            .iter()
+            .enumerate()
+            .map(|(index, project)| format!("{} {project}", project_bar(index, 1)))
+            .collect();
+        lines.push(String::new());
+        lines.push(legend.join("  "));
+
+        lines.join("\n")
+    }
+}
+
+impl ToJson for Chart<'_> {
+    fn to_json(&self) -> Json {
+        let days = self
+            .days
+            .iter()
+            .map(|(date, duration)| {
+                Json::object([
+                    ("date", Json::date(date)),
+                    ("duration", Json::duration(duration)),
+                ])
+            })
+            .collect();
+
+        let weeks = self
+            .weeks
+            .iter()
+            .map(|(week_start, durations)| {
+                let projects = self
+                    .projects
+                    .iter()
+                    .zip(durations)
+                    .filter(|(_, duration)| !duration.is_zero())
+                    .map(|(project, duration)| {
+                        Json::object([
+                            ("project", Json::string(project)),
+                            ("duration", Json::duration(duration)),
+                        ])
+                    })
+                    .collect();
+
+                Json::object([
+                    ("first_day", Json::date(week_start)),
+                    ("projects", Json::Array(projects)),
+                    ("duration", Json::duration(&durations.iter().copied().sum())),
+                ])
+            })
+            .collect();
+
+        Json::object([
+            ("from", Json::date(&self.range.first)),
+            ("to", Json::date(&self.range.last)),
+            ("days", Json::Array(days)),
+            ("weeks", Json::Array(weeks)),
+            ("duration", Json::duration(&self.total())),
+        ])
+    }
+}
+
+// the first days of all weeks overlapping with the range
+fn weeks_of(range: DateRange) -> Vec<NaiveDate> {
+    let mut weeks = Vec::new();
+    let mut week_start = DateRange::week_of(range.first).first;
+
+    while week_start <= range.last {
+        weeks.push(week_start);
+        week_start += Duration::weeks(1);
+    }
+
+    weeks
+}
+
+// the abbreviated names of the months above the first week they start in. Names which would
+// overlap the previous one are left out
+fn month_labels(weeks: &[NaiveDate], range: DateRange) -> String {
+    let mut labels = String::new();
+
+    for (column, week_start) in weeks.iter().enumerate() {
+        let first_of_month = (0..7)
+            .map(|day| *week_start + Duration::days(day))
+            .find(|date| date.day() == 1 && range.first <= *date && *date <= range.last);
+        let first_of_month = match (column, first_of_month) {
+            (_, Some(date)) => date,
+            // the first column is labeled with the month the range starts in
+            (0, None) => range.first,
+            _ => continue,
+        };
+
+        let position = column * 2;
+        if labels.chars().count() <= position {
+            let padding = position - labels.chars().count();
+            labels.push_str(&" ".repeat(padding));
+            labels.push_str(&first_of_month.format("%b").to_string());
+        }
+    }
+
+    labels
+}
+
+// how intense the color of a day is, from 0 for no time tracked to 4 for the most
+fn intensity(duration: Duration, max: Duration) -> usize {
+    if duration <= Duration::zero() || max <= Duration::zero() {
+        return 0;
+    }
+
+    let levels = (HEATMAP_COLORS.len() - 1) as i64;
+    let level = (duration.num_seconds() * levels + max.num_seconds() - 1) / max.num_seconds();
+    usize::try_from(level.clamp(1, levels)).unwrap_or(1)
+}
+
+// the widths of the parts of a bar for the durations, scaled to the longest bar
+fn bar_widths(durations: &[Duration], max: Duration) -> Vec<usize> {
+    if max <= Duration::zero() {
+        return vec![0; durations.len()];
+    }
+
+    // the parts are rounded so that the whole bar has the width of its rounded total
+    let mut widths = Vec::new();
+    let mut sum = Duration::zero();
+    let mut width_so_far = 0;
+    for duration in durations {
+        sum += *duration;
+        let width = scale(sum, max);
+        widths.push(width - width_so_far);
+        width_so_far = width;
+    }
+
+    widths
+}
+
+fn scale(duration: Duration, max: Duration) -> usize {
+    let width = (duration.num_seconds() as f64 * BAR_WIDTH as f64 / max.num_seconds() as f64)
+        .round() as usize;
+    width.min(BAR_WIDTH)
+}
+
+fn project_color(index: usize) -> Style {
+    PROJECT_COLORS[index % PROJECT_COLORS.len()].normal()
+}
+
+// a cell of the heatmap with the color, or without colors the shade, of a level of intensity
+fn heatmap_cell(level: usize) -> String {
+    if conf::color() {
+        HEATMAP_COLORS[level].paint(HEATMAP_CELL).to_string()
+    } else {
+        HEATMAP_PLAIN_CELLS[level].to_string()
+    }
+}
+
+// the part of a bar of the project with the index
+fn project_bar(index: usize, width: usize) -> String {
+    if conf::color() {
+        project_color(index)
+            .paint(BAR_CELL.repeat(width))
+            .to_string()
+    } else {
+        BAR_PLAIN_CELLS[index % BAR_PLAIN_CELLS.len()].repeat(width)
+    }
+}
+
+// writes a heatmap of the days or the bars of the weeks of the report's range
+pub struct ChartReport {
+    pub kind: ChartKind,
+}
+
+impl ReportWriter for ChartReport {
+    fn process(&self, data: &ReportData) -> anyhow::Result<()> {
+        let chart = Chart::new(data.activities, data.total_rounding, range_of(data));
+
+        match self.kind {
+            ChartKind::Heatmap => outputln!("{}", chart.heatmap()),
+            ChartKind::Weeks => outputln!("{}", chart.week_bars()),
+        }
+        Ok(())
+    }
+}
+
+// writes the durations per day and per project and week as JSON
+pub struct ChartJson {}
+
+impl ReportWriter for ChartJson {
+    fn process(&self, data: &ReportData) -> anyhow::Result<()> {
+        json::print(&Chart::new(
+            data.activities,
+            data.total_rounding,
+            range_of(data),
+        ));
+        Ok(())
+    }
+}
+
+// the range of the chart is the range of the report or, if it is not limited, the range of its
+// activities
+fn range_of(data: &ReportData) -> DateRange {
+    let dates = data.activities.iter().map(|a| a.start.date());
+    let today = chrono::Local::now().date_naive();
+
+    DateRange {
+        first: data
+            .from_date
+            .or_else(|| dates.clone().min())
+            .unwrap_or(today),
+        last: data.to_date.or_else(|| dates.max()).unwrap_or(today),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::activity::activity;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn intensity_test() {
+        let max = Duration::hours(8);
+
+        assert_eq!(intensity(Duration::zero(), max), 0);
+        assert_eq!(intensity(Duration::minutes(1), max), 1);
+        assert_eq!(intensity(Duration::hours(2), max), 1);
+        assert_eq!(intensity(Duration::hours(5), max), 3);
+        assert_eq!(intensity(max, max), 4);
+        assert_eq!(intensity(Duration::hours(1), Duration::zero()), 0);
+    }
+
+    #[test]
+    fn bar_widths_test() {
+        let durations = [Duration::hours(1), Duration::hours(2), Duration::hours(1)];
+
+        assert_eq!(bar_widths(&durations, Duration::hours(4)), vec![10, 20, 10]);
+        assert_eq!(bar_widths(&durations, Duration::hours(8)), vec![5, 10, 5]);
+        assert_eq!(bar_widths(&durations, Duration::zero()), vec![0, 0, 0]);
+    }
+
+    #[test]
+    fn chart_of_a_month() {
+        let a1 = activity("p1", "2024-03-01 09:00", "2024-03-01 17:00");
+        let a2 = activity("p2", "2024-03-12 09:00", "2024-03-12 11:00");
+        let a3 = activity("p1", "2024-03-13 09:00", "2024-03-13 10:00");
+        let activities = vec![&a1, &a2, &a3];
+        let range = DateRange::month_of(date("2024-03-01"));
+
+        let chart = Chart::new(&activities, None, range);
+
+        assert_eq!(chart.total(), Duration::hours(11));
+        assert_eq!(chart.projects, vec!["p1", "p2"]);
+        // the weeks start on monday, the first one on 2024-02-26
+        assert_eq!(chart.weeks.len(), 5);
+        assert_eq!(chart.weeks[0].0, date("2024-02-26"));
+        assert_eq!(
+            chart.weeks[2].1,
+            vec![Duration::hours(1), Duration::hours(2)]
+        );
+
+        let heatmap = chart.heatmap();
+        assert!(heatmap.starts_with("    Mar"));
+        assert_eq!(heatmap.lines().count(), 11);
+        assert!(heatmap.contains("most on 2024-03-01 (8h 00m)"));
+    }
+}
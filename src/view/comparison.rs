@@ -0,0 +1,288 @@
+use std::collections::BTreeMap;
+
+use chrono::{Duration, NaiveDate};
+
+use crate::data::activity;
+use crate::data::processor::{ComparisonData, ComparisonWriter, ReportData};
+use crate::outputln;
+use crate::view::format_util;
+use crate::view::json::{self, Json, ToJson};
+use crate::view::report;
+use crate::view::table;
+use crate::view::theme;
+
+// the durations of each project in two periods
+struct Comparison<'a> {
+    current_range: (Option<NaiveDate>, Option<NaiveDate>),
+    previous_range: (Option<NaiveDate>, Option<NaiveDate>),
+    projects: Vec<ProjectComparison<'a>>,
+    total: ProjectComparison<'a>,
+}
+
+struct ProjectComparison<'a> {
+    project: &'a str,
+    current: Duration,
+    previous: Duration,
+}
+
+impl ProjectComparison<'_> {
+    fn change(&self) -> Duration {
+        self.current - self.previous
+    }
+
+    // the change relative to the previous period in percent, if there has been time tracked then
+    fn percent_change(&self) -> Option<i64> {
+        let previous = self.previous.num_seconds();
+        (previous > 0).then(|| {
+            ((self.current.num_seconds() - previous) as f64 * 100.0 / previous as f64).round()
+                as i64
+        })
+    }
+}
+
+impl<'a> Comparison<'a> {
+    // the projects of both periods, ordered by their names
+    fn new(current: &ReportData<'a>, previous: &ReportData<'a>) -> Comparison<'a> {
+        let group_by_project = |data: &ReportData<'a>| {
+            let mut projects: BTreeMap<&'a str, Vec<&'a activity::Activity>> = BTreeMap::new();
+            for activity in data.activities {
+                projects
+                    .entry(&activity.project)
+                    .or_default()
+                    .push(activity);
+            }
+            projects
+        };
+        let current_projects = group_by_project(current);
+        let previous_projects = group_by_project(previous);
+
+        // like in the report, the durations of projects are rounded only if the totals of
+        // projects per day are rounded
+        let project_rounding = current.total_rounding.filter(|r| r.rounds_projects());
+        let sum = |projects: &BTreeMap<&str, Vec<&activity::Activity>>, project: &str| {
+            projects
+                .get(project)
+                .map_or_else(Duration::zero, |activities| {
+                    report::sum_duration_with_rounding(activities, project_rounding)
+                })
+        };
+
+        let mut project_names: Vec<&str> = current_projects
+            .keys()
+            .chain(previous_projects.keys())
+            .copied()
+            .collect();
+        project_names.sort_unstable();
+        project_names.dedup();
+
+        Comparison {
+            current_range: (current.from_date, current.to_date),
+            previous_range: (previous.from_date, previous.to_date),
+            projects: project_names
+                .into_iter()
+                .map(|project| ProjectComparison {
+                    project,
+                    current: sum(&current_projects, project),
+                    previous: sum(&previous_projects, project),
+                })
+                .collect(),
+            total: ProjectComparison {
+                project: "Total",
+                current: report::sum_duration_with_rounding(
+                    current.activities,
+                    current.total_rounding,
+                ),
+                previous: report::sum_duration_with_rounding(
+                    previous.activities,
+                    previous.total_rounding,
+                ),
+            },
+        }
+    }
+
+    fn to_table(&self) -> table::Table {
+        let column = |label: &str, wrap: table::Wrap| table::Column {
+            label: label.to_string(),
+            wrap,
+        };
+        let mut comparison_table = table::Table::new(vec![
+            column("Project", table::Wrap::Wrap),
+            column(&format_range(self.current_range), table::Wrap::NoWrap),
+            column(&format_range(self.previous_range), table::Wrap::NoWrap),
+            column("Change", table::Wrap::NoWrap),
+            column("%", table::Wrap::NoWrap),
+        ]);
+
+        for project in &self.projects {
+            comparison_table.add_row(comparison_row(project));
+        }
+
+        let mut total_row = comparison_row(&self.total);
+        total_row.set_color(theme::theme().total);
+        comparison_table.add_row(total_row);
+
+        comparison_table
+    }
+}
+
+fn comparison_row(project: &ProjectComparison) -> table::Row {
+    table::Row::new(vec![
+        project.project.to_string(),
+        format_tracked(&project.current),
+        format_tracked(&project.previous),
+        format_change(&project.change()),
+        project
+            .percent_change()
+            .map_or_else(|| "new".to_string(), |percent| format!("{percent:+}%")),
+    ])
+}
+
+// projects without time tracked in a period are marked by a dash
+fn format_tracked(duration: &Duration) -> String {
+    if duration.is_zero() {
+        "-".to_string()
+    } else {
+        format_util::format_duration(duration)
+    }
+}
+
+// a change of a duration with its sign, e.g. `+1h 30m` or `-15m`
+fn format_change(change: &Duration) -> String {
+    if change.is_zero() {
+        return "0m".to_string();
+    }
+
+    let sign = if *change < Duration::zero() { '-' } else { '+' };
+    format!("{sign}{}", format_util::format_duration(&change.abs()))
+}
+
+fn format_range((from_date, to_date): (Option<NaiveDate>, Option<NaiveDate>)) -> String {
+    let format = |date: Option<NaiveDate>| date.map_or_else(String::new, |d| d.to_string());
+
+    if from_date == to_date {
+        format(from_date)
+    } else {
+        format!("{} - {}", format(from_date), format(to_date))
+    }
+}
+
+impl ToJson for ProjectComparison<'_> {
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("project", Json::string(self.project)),
+            ("duration", Json::duration(&self.current)),
+            ("previous_duration", Json::duration(&self.previous)),
+            ("change", Json::duration(&self.change())),
+            (
+                "percent_change",
+                Json::optional(self.percent_change(), Json::Integer),
+            ),
+        ])
+    }
+}
+
+impl ToJson for Comparison<'_> {
+    fn to_json(&self) -> Json {
+        let range = |(from_date, to_date): (Option<NaiveDate>, Option<NaiveDate>)| {
+            Json::object([
+                ("from", Json::optional(from_date.as_ref(), Json::date)),
+                ("to", Json::optional(to_date.as_ref(), Json::date)),
+            ])
+        };
+
+        Json::object([
+            ("period", range(self.current_range)),
+            ("previous_period", range(self.previous_range)),
+            (
+                "projects",
+                Json::Array(self.projects.iter().map(ToJson::to_json).collect()),
+            ),
+            ("duration", Json::duration(&self.total.current)),
+            ("previous_duration", Json::duration(&self.total.previous)),
+            ("change", Json::duration(&self.total.change())),
+            (
+                "percent_change",
+                Json::optional(self.total.percent_change(), Json::Integer),
+            ),
+        ])
+    }
+}
+
+// writes the durations per project of both periods with their changes
+pub struct ComparisonReport {}
+
+impl ComparisonWriter for ComparisonReport {
+    fn process(&self, data: &ComparisonData) -> anyhow::Result<()> {
+        let comparison = Comparison::new(&data.current, &data.previous);
+
+        if comparison.projects.is_empty() {
+            outputln!("No activities have been tracked in either period");
+            return Ok(());
+        }
+
+        outputln!("\n{}", comparison.to_table());
+        Ok(())
+    }
+}
+
+// writes the comparison as JSON
+pub struct ComparisonJson {}
+
+impl ComparisonWriter for ComparisonJson {
+    fn process(&self, data: &ComparisonData) -> anyhow::Result<()> {
+        json::print(&Comparison::new(&data.current, &data.previous));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::activity::activity;
+
+    #[test]
+    fn compare_projects_of_two_periods() {
+        let a1 = activity("p1", "2024-03-11 09:00", "2024-03-11 11:30");
+        let a2 = activity("p2", "2024-03-12 09:00", "2024-03-12 10:00");
+        let a3 = activity("p1", "2024-03-04 09:00", "2024-03-04 11:00");
+        let a4 = activity("p3", "2024-03-05 09:00", "2024-03-05 09:45");
+        let date = |s: &str| Some(NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap());
+        let current_activities = vec![&a1, &a2];
+        let previous_activities = vec![&a3, &a4];
+
+        let comparison = Comparison::new(
+            &ReportData {
+                activities: &current_activities,
+                total_rounding: None,
+                from_date: date("2024-03-11"),
+                to_date: date("2024-03-17"),
+            },
+            &ReportData {
+                activities: &previous_activities,
+                total_rounding: None,
+                from_date: date("2024-03-04"),
+                to_date: date("2024-03-10"),
+            },
+        );
+
+        let rows: Vec<(&str, String, Option<i64>)> = comparison
+            .projects
+            .iter()
+            .map(|p| (p.project, format_change(&p.change()), p.percent_change()))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("p1", "+30m".to_string(), Some(25)),
+                ("p2", "+1h 00m".to_string(), None),
+                ("p3", "-45m".to_string(), Some(-100)),
+            ]
+        );
+        assert_eq!(format_change(&comparison.total.change()), "+45m");
+        assert_eq!(comparison.total.percent_change(), Some(27));
+        assert_eq!(
+            format_range(comparison.previous_range),
+            "2024-03-04 - 2024-03-10"
+        );
+    }
+}
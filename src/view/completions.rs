@@ -0,0 +1,591 @@
+use clap::{Arg, Command, ValueEnum, ValueHint};
+
+// the shells completion scripts can be generated for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+// the values of the activity log the completion scripts look up while completing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LoggedValue {
+    Projects,
+    Descriptions,
+    Tags,
+}
+
+// the hidden subcommand the completion scripts call to look up the logged values
+pub static VALUES_SUBCOMMAND: &str = "complete-values";
+
+impl LoggedValue {
+    // the logged values an option expects, e.g. projects for `--project`
+    fn of(arg: &Arg) -> Option<Self> {
+        match arg.get_long() {
+            Some("project") => Some(Self::Projects),
+            Some("description") => Some(Self::Descriptions),
+            Some("tag") => Some(Self::Tags),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Projects => "projects",
+            Self::Descriptions => "descriptions",
+            Self::Tags => "tags",
+        }
+    }
+}
+
+// how the value of an option or a positional argument is completed
+enum ValueCompletion {
+    Logged(LoggedValue),
+    Files,
+    Choices(Vec<String>),
+    None,
+}
+
+impl ValueCompletion {
+    fn of(arg: &Arg) -> Self {
+        if let Some(logged_value) = LoggedValue::of(arg) {
+            return Self::Logged(logged_value);
+        }
+
+        if matches!(
+            arg.get_value_hint(),
+            ValueHint::FilePath | ValueHint::AnyPath
+        ) {
+            return Self::Files;
+        }
+
+        let choices: Vec<String> = arg
+            .get_possible_values()
+            .iter()
+            .filter(|value| !value.is_hide_set())
+            .map(|value| value.get_name().to_string())
+            .collect();
+
+        if choices.is_empty() {
+            Self::None
+        } else {
+            Self::Choices(choices)
+        }
+    }
+}
+
+// generates the completion script of a command for a shell
+//
+// projects, descriptions and tags are looked up with the hidden subcommand `complete-values`
+// while completing, so the scripts always suggest the values of the current activity log.
+#[must_use]
+pub fn generate(command: &Command, shell: Shell) -> String {
+    match shell {
+        Shell::Bash => generate_bash(command),
+        Shell::Zsh => generate_zsh(command),
+        Shell::Fish => generate_fish(command),
+    }
+}
+
+fn generate_bash(command: &Command) -> String {
+    let name = command.get_name();
+    let commands = collect_commands(command);
+
+    let mut script = format!(
+        "_{name}() {{
+    local cur=\"${{COMP_WORDS[COMP_CWORD]}}\"
+    local prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"
+    local command=\"{name}\"
+    local i
+
+    for ((i = 1; i < COMP_CWORD; i++)); do
+        case \"${{command}}__${{COMP_WORDS[i]}}\" in
+"
+    );
+
+    for (path, _) in commands.iter().skip(1) {
+        let id = path.join("__");
+        script.push_str(&format!("            {id}) command=\"{id}\" ;;\n"));
+    }
+
+    script.push_str(
+        "        esac
+    done
+
+    case \"${command}__${prev}\" in
+",
+    );
+
+    for (path, command) in &commands {
+        let id = path.join("__");
+
+        for arg in options(command).filter(|arg| takes_value(arg)) {
+            let patterns = option_names(arg)
+                .iter()
+                .map(|option| format!("{id}__{option}"))
+                .collect::<Vec<_>>()
+                .join(" | ");
+            let completion = match ValueCompletion::of(arg) {
+                ValueCompletion::Logged(value) => format!("_{name}_values {}", value.name()),
+                ValueCompletion::Files => "COMPREPLY=($(compgen -f -- \"$cur\"))".to_string(),
+                ValueCompletion::Choices(choices) => format!(
+                    "COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))",
+                    choices.join(" ")
+                ),
+                ValueCompletion::None => "COMPREPLY=()".to_string(),
+            };
+            script.push_str(&format!(
+                "        {patterns})\n            {completion}\n            return\n            ;;\n"
+            ));
+        }
+    }
+
+    script.push_str(
+        "    esac
+
+    case \"$command\" in
+",
+    );
+
+    for (path, command) in &commands {
+        let words: Vec<String> = subcommands(command)
+            .map(|subcommand| subcommand.get_name().to_string())
+            .chain(options(command).flat_map(option_names))
+            .collect();
+        script.push_str(&format!(
+            "        {}) COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")) ;;\n",
+            path.join("__"),
+            words.join(" ")
+        ));
+    }
+
+    script.push_str(&format!(
+        "    esac
+}}
+
+_{name}_values() {{
+    local IFS=$'\\n'
+    COMPREPLY=($(compgen -W \"$({name} {VALUES_SUBCOMMAND} \"$1\" 2>/dev/null)\" -- \"$cur\"))
+    if [ ${{#COMPREPLY[@]}} -gt 0 ]; then
+        COMPREPLY=($(printf '%q\\n' \"${{COMPREPLY[@]}}\"))
+    fi
+}}
+
+complete -F _{name} {name}
+"
+    ));
+
+    script
+}
+
+fn generate_zsh(command: &Command) -> String {
+    let name = command.get_name();
+
+    let mut script = format!(
+        "#compdef {name}
+
+_{name}_values() {{
+    local -a values
+    values=(\"${{(@f)$({name} {VALUES_SUBCOMMAND} $1 2>/dev/null)}}\")
+    compadd -a values
+}}
+"
+    );
+
+    for (path, command) in collect_commands(command) {
+        let function = format!("_{}", path.join("__"));
+        let mut specs: Vec<String> = options(command)
+            .flat_map(|arg| zsh_option_specs(name, arg))
+            .collect();
+        specs.extend(positionals(command).map(|arg| zsh_positional_spec(name, arg)));
+
+        if command.has_subcommands() {
+            specs.push("':command:->command'".to_string());
+            specs.push("'*::argument:->argument'".to_string());
+        }
+
+        script.push_str(&format!(
+            "\n{function}() {{
+    local context state state_descr line
+    typeset -A opt_args
+
+    _arguments -C \\\n        {}
+",
+            specs.join(" \\\n        ")
+        ));
+
+        if command.has_subcommands() {
+            let descriptions: Vec<String> = subcommands(command)
+                .map(|subcommand| {
+                    format!(
+                        "                '{}:{}'",
+                        subcommand.get_name(),
+                        escape_single_quotes(&about(subcommand))
+                    )
+                })
+                .collect();
+            let dispatches: Vec<String> = subcommands(command)
+                .map(|subcommand| {
+                    format!(
+                        "                {}) {function}__{} ;;",
+                        subcommand.get_name(),
+                        subcommand.get_name()
+                    )
+                })
+                .collect();
+
+            script.push_str(&format!(
+                "
+    case $state in
+        command)
+            local -a commands
+            commands=(
+{}
+            )
+            _describe -t commands '{name} command' commands
+            ;;
+        argument)
+            case $line[1] in
+{}
+            esac
+            ;;
+    esac
+",
+                descriptions.join("\n"),
+                dispatches.join("\n")
+            ));
+        }
+
+        script.push_str("}\n");
+    }
+
+    script.push_str(&format!("\n_{name} \"$@\"\n"));
+    script
+}
+
+fn zsh_option_specs(name: &str, arg: &Arg) -> Vec<String> {
+    let repeat = if is_repeatable(arg) { "*" } else { "" };
+    let description = escape_single_quotes(&help(arg))
+        .replace('[', "\\[")
+        .replace(']', "\\]");
+
+    let value = if takes_value(arg) {
+        format!(
+            ":{}:{}",
+            value_name(arg),
+            zsh_action(name, &ValueCompletion::of(arg))
+        )
+    } else {
+        String::new()
+    };
+
+    let mut specs = Vec::new();
+
+    if let Some(short) = arg.get_short() {
+        let separator = if takes_value(arg) { "+" } else { "" };
+        specs.push(format!(
+            "'{repeat}-{short}{separator}[{description}]{value}'"
+        ));
+    }
+
+    if let Some(long) = arg.get_long() {
+        let separator = if takes_value(arg) { "=" } else { "" };
+        specs.push(format!(
+            "'{repeat}--{long}{separator}[{description}]{value}'"
+        ));
+    }
+
+    specs
+}
+
+fn zsh_positional_spec(name: &str, arg: &Arg) -> String {
+    let repeat = if is_repeatable(arg) { "*" } else { "" };
+
+    format!(
+        "'{repeat}:{}:{}'",
+        value_name(arg),
+        zsh_action(name, &ValueCompletion::of(arg))
+    )
+}
+
+fn zsh_action(name: &str, completion: &ValueCompletion) -> String {
+    match completion {
+        ValueCompletion::Logged(value) => format!("_{name}_values {}", value.name()),
+        ValueCompletion::Files => "_files".to_string(),
+        ValueCompletion::Choices(choices) => format!("({})", choices.join(" ")),
+        ValueCompletion::None => " ".to_string(),
+    }
+}
+
+fn generate_fish(command: &Command) -> String {
+    let name = command.get_name();
+    let mut script = format!("complete -c {name} -f\n");
+
+    for (path, command) in collect_commands(command) {
+        let subcommand_names: Vec<&str> = subcommands(command).map(Command::get_name).collect();
+        let condition = fish_condition(&path[1..], &subcommand_names);
+
+        script.push('\n');
+
+        for subcommand in subcommands(command) {
+            script.push_str(&format!(
+                "complete -c {name} -n \"{condition}\" -a {} -d '{}'\n",
+                subcommand.get_name(),
+                escape_fish(&about(subcommand))
+            ));
+        }
+
+        for arg in options(command) {
+            let mut line = format!("complete -c {name} -n \"{condition}\"");
+
+            if let Some(short) = arg.get_short() {
+                line.push_str(&format!(" -s {short}"));
+            }
+            if let Some(long) = arg.get_long() {
+                line.push_str(&format!(" -l {long}"));
+            }
+            if !help(arg).is_empty() {
+                line.push_str(&format!(" -d '{}'", escape_fish(&help(arg))));
+            }
+
+            if takes_value(arg) {
+                line.push_str(&fish_value(name, &ValueCompletion::of(arg)));
+            }
+
+            script.push_str(&line);
+            script.push('\n');
+        }
+
+        for arg in positionals(command) {
+            let value = fish_value(name, &ValueCompletion::of(arg));
+
+            if !matches!(ValueCompletion::of(arg), ValueCompletion::None) {
+                script.push_str(&format!("complete -c {name} -n \"{condition}\"{value}\n"));
+            }
+        }
+    }
+
+    script
+}
+
+// the condition that the subcommands of the given path have been entered, but none of its
+// own subcommands
+fn fish_condition(path: &[String], subcommand_names: &[&str]) -> String {
+    let mut conditions: Vec<String> = path
+        .iter()
+        .map(|name| format!("__fish_seen_subcommand_from {name}"))
+        .collect();
+
+    if !subcommand_names.is_empty() {
+        conditions.push(format!(
+            "not __fish_seen_subcommand_from {}",
+            subcommand_names.join(" ")
+        ));
+    }
+
+    if conditions.is_empty() {
+        "true".to_string()
+    } else {
+        conditions.join("; and ")
+    }
+}
+
+fn fish_value(name: &str, completion: &ValueCompletion) -> String {
+    match completion {
+        ValueCompletion::Logged(value) => format!(
+            " -x -a \"({name} {VALUES_SUBCOMMAND} {} 2>/dev/null)\"",
+            value.name()
+        ),
+        ValueCompletion::Files => " -r -F".to_string(),
+        ValueCompletion::Choices(choices) => format!(" -x -a \"{}\"", choices.join(" ")),
+        ValueCompletion::None => " -x".to_string(),
+    }
+}
+
+// the command and all its visible subcommands together with their paths of subcommand names
+fn collect_commands(command: &Command) -> Vec<(Vec<String>, &Command)> {
+    let mut commands = vec![(vec![command.get_name().to_string()], command)];
+    let mut i = 0;
+
+    while i < commands.len() {
+        let (path, command) = commands[i].clone();
+
+        for subcommand in subcommands(command) {
+            let mut subcommand_path = path.clone();
+            subcommand_path.push(subcommand.get_name().to_string());
+            commands.push((subcommand_path, subcommand));
+        }
+
+        i += 1;
+    }
+
+    commands
+}
+
+fn subcommands(command: &Command) -> impl Iterator<Item = &Command> {
+    command
+        .get_subcommands()
+        .filter(|subcommand| !subcommand.is_hide_set())
+}
+
+fn options(command: &Command) -> impl Iterator<Item = &Arg> {
+    command
+        .get_arguments()
+        .filter(|arg| !arg.is_positional() && !arg.is_hide_set())
+}
+
+fn positionals(command: &Command) -> impl Iterator<Item = &Arg> {
+    command
+        .get_arguments()
+        .filter(|arg| arg.is_positional() && !arg.is_hide_set())
+}
+
+fn option_names(arg: &Arg) -> Vec<String> {
+    arg.get_short()
+        .map(|short| format!("-{short}"))
+        .into_iter()
+        .chain(arg.get_long().map(|long| format!("--{long}")))
+        .collect()
+}
+
+fn takes_value(arg: &Arg) -> bool {
+    arg.get_action().takes_values()
+}
+
+fn is_repeatable(arg: &Arg) -> bool {
+    matches!(arg.get_action(), clap::ArgAction::Append)
+}
+
+fn value_name(arg: &Arg) -> String {
+    arg.get_value_names()
+        .and_then(|names| names.first())
+        .map_or_else(
+            || arg.get_id().to_string().to_uppercase(),
+            ToString::to_string,
+        )
+}
+
+// the first line of the help of an argument
+fn help(arg: &Arg) -> String {
+    arg.get_help()
+        .map(|help| {
+            help.to_string()
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string()
+        })
+        .unwrap_or_default()
+}
+
+// the first line of the description of a subcommand
+fn about(command: &Command) -> String {
+    command
+        .get_about()
+        .map(|about| {
+            about
+                .to_string()
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string()
+        })
+        .unwrap_or_default()
+}
+
+fn escape_single_quotes(s: &str) -> String {
+    s.replace('\'', "'\\''")
+}
+
+fn escape_fish(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('\'', "\\'")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::ArgAction;
+
+    fn command() -> Command {
+        Command::new("bartib")
+            .arg(
+                Arg::new("file")
+                    .short('f')
+                    .long("file")
+                    .value_hint(ValueHint::FilePath),
+            )
+            .subcommand(
+                Command::new("start")
+                    .about("starts a new activity")
+                    .arg(Arg::new("project").short('p').long("project"))
+                    .arg(
+                        Arg::new("tags")
+                            .long("tag")
+                            .help("a tag of the activity")
+                            .action(ArgAction::Append),
+                    ),
+            )
+            .subcommand(
+                Command::new("recur")
+                    .about("what's recurring")
+                    .subcommand(Command::new("list"))
+                    .subcommand(
+                        Command::new("apply").arg(
+                            Arg::new("dry_run")
+                                .long("dry-run")
+                                .action(ArgAction::SetTrue),
+                        ),
+                    ),
+            )
+            .subcommand(Command::new(VALUES_SUBCOMMAND).hide(true))
+    }
+
+    #[test]
+    fn generate_bash_test() {
+        let script = generate(&command(), Shell::Bash);
+
+        assert!(script.contains("bartib__recur__apply) command=\"bartib__recur__apply\" ;;"));
+        assert!(script.contains(
+            "bartib__start__-p | bartib__start__--project)\n            _bartib_values projects\n"
+        ));
+        assert!(
+            script.contains("bartib__-f | bartib__--file)\n            COMPREPLY=($(compgen -f")
+        );
+        assert!(script.contains("bartib) COMPREPLY=($(compgen -W \"start recur -f --file\""));
+        assert!(script.contains("bartib__recur__apply) COMPREPLY=($(compgen -W \"--dry-run\""));
+        assert!(!script.contains("bartib__complete-values"));
+    }
+
+    #[test]
+    fn generate_zsh_test() {
+        let script = generate(&command(), Shell::Zsh);
+
+        assert!(script.starts_with("#compdef bartib\n"));
+        assert!(script.contains("'-p+[]:PROJECT:_bartib_values projects'"));
+        assert!(script.contains("'*--tag=[a tag of the activity]:TAGS:_bartib_values tags'"));
+        assert!(script.contains("'--file=[]:FILE:_files'"));
+        assert!(script.contains("'recur:what'\\''s recurring'"));
+        assert!(script.contains("apply) _bartib__recur__apply ;;"));
+        assert!(script.contains("_bartib__recur__apply() {"));
+        assert!(!script.contains("complete-values:"));
+    }
+
+    #[test]
+    fn generate_fish_test() {
+        let script = generate(&command(), Shell::Fish);
+
+        assert!(script.contains(
+            "complete -c bartib -n \"not __fish_seen_subcommand_from start recur\" -a recur -d 'what\\'s recurring'"
+        ));
+        assert!(script.contains(
+            "complete -c bartib -n \"__fish_seen_subcommand_from start\" -s p -l project -x -a \"(bartib complete-values projects 2>/dev/null)\""
+        ));
+        assert!(script.contains(
+            "complete -c bartib -n \"__fish_seen_subcommand_from recur; and __fish_seen_subcommand_from apply\" -l dry-run\n"
+        ));
+        assert!(script.contains(
+            "complete -c bartib -n \"__fish_seen_subcommand_from recur; and not __fish_seen_subcommand_from list apply\" -a list"
+        ));
+    }
+}
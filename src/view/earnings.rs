@@ -0,0 +1,218 @@
+use std::collections::BTreeMap;
+
+use chrono::Duration;
+
+use crate::data::activity;
+use crate::data::processor::{ReportData, ReportWriter, TotalRounding};
+use crate::data::rates::{self, Rates};
+use crate::outputln;
+use crate::view::format_util;
+use crate::view::json::{self, Json, ToJson};
+use crate::view::report;
+use crate::view::table;
+use crate::view::theme;
+
+// the tracked time and the earnings of each project
+struct Earnings<'a> {
+    projects: Vec<ProjectEarnings<'a>>,
+    total_duration: Duration,
+    total_earnings: f64,
+    currency: Option<&'a str>,
+}
+
+struct ProjectEarnings<'a> {
+    project: &'a str,
+    duration: Duration,
+    rate: Option<f64>,
+    earnings: Option<f64>,
+}
+
+impl<'a> Earnings<'a> {
+    fn new(
+        activities: &[&'a activity::Activity],
+        rates: &'a Rates,
+        total_rounding: Option<&TotalRounding>,
+    ) -> Earnings<'a> {
+        let mut project_map: BTreeMap<&str, Vec<&activity::Activity>> = BTreeMap::new();
+        for a in activities {
+            project_map.entry(&a.project).or_default().push(a);
+        }
+
+        // like in the report, the durations of projects are rounded only if the totals of
+        // projects per day are rounded
+        let project_rounding = total_rounding.filter(|r| r.rounds_projects());
+
+        let projects: Vec<ProjectEarnings> = project_map
+            .into_iter()
+            .map(|(project, activities)| {
+                let duration = report::sum_duration_with_rounding(&activities, project_rounding);
+                let rate = rates.get_rate(project);
+
+                ProjectEarnings {
+                    project,
+                    duration,
+                    rate,
+                    earnings: rate.map(|rate| rates::calculate_earnings(&duration, rate)),
+                }
+            })
+            .collect();
+
+        Earnings {
+            total_earnings: projects.iter().filter_map(|p| p.earnings).sum(),
+            projects,
+            total_duration: report::sum_duration_with_rounding(activities, total_rounding),
+            currency: rates.currency.as_deref(),
+        }
+    }
+
+    fn to_table(&self) -> table::Table {
+        let mut earnings_table = table::Table::new(vec![
+            table::Column {
+                label: "Project".to_string(),
+                wrap: table::Wrap::Wrap,
+            },
+            table::Column {
+                label: "Duration".to_string(),
+                wrap: table::Wrap::NoWrap,
+            },
+            table::Column {
+                label: "Rate".to_string(),
+                wrap: table::Wrap::NoWrap,
+            },
+            table::Column {
+                label: "Earnings".to_string(),
+                wrap: table::Wrap::NoWrap,
+            },
+        ]);
+
+        for project in &self.projects {
+            earnings_table.add_row(table::Row::new(vec![
+                project.project.to_string(),
+                format_util::format_duration(&project.duration),
+                project
+                    .rate
+                    .map_or_else(|| "-".to_string(), |r| self.format_amount(r)),
+                project
+                    .earnings
+                    .map_or_else(|| "-".to_string(), |e| self.format_amount(e)),
+            ]));
+        }
+
+        let mut total_row = table::Row::new(vec![
+            "Total".to_string(),
+            format_util::format_duration(&self.total_duration),
+            String::new(),
+            self.format_amount(self.total_earnings),
+        ]);
+        total_row.set_color(theme::theme().total);
+        earnings_table.add_row(total_row);
+
+        earnings_table
+    }
+
+    fn format_amount(&self, amount: f64) -> String {
+        match self.currency {
+            Some(currency) => format!("{amount:.2} {currency}"),
+            None => format!("{amount:.2}"),
+        }
+    }
+}
+
+impl ToJson for Earnings<'_> {
+    fn to_json(&self) -> Json {
+        let projects = self
+            .projects
+            .iter()
+            .map(|project| {
+                Json::object([
+                    ("project", Json::string(project.project)),
+                    ("duration", Json::duration(&project.duration)),
+                    ("rate", Json::optional(project.rate, Json::Float)),
+                    ("earnings", Json::optional(project.earnings, Json::Float)),
+                ])
+            })
+            .collect();
+
+        Json::object([
+            ("projects", Json::Array(projects)),
+            ("duration", Json::duration(&self.total_duration)),
+            ("earnings", Json::Float(self.total_earnings)),
+            ("currency", Json::optional(self.currency, Json::string)),
+        ])
+    }
+}
+
+// writes the earnings per project
+pub struct EarningsReport {
+    pub rates: Rates,
+}
+
+impl ReportWriter for EarningsReport {
+    fn process(&self, data: &ReportData) -> anyhow::Result<()> {
+        show_earnings(data.activities, &self.rates, data.total_rounding);
+        Ok(())
+    }
+}
+
+pub struct EarningsJson {
+    pub rates: Rates,
+}
+
+impl ReportWriter for EarningsJson {
+    fn process(&self, data: &ReportData) -> anyhow::Result<()> {
+        show_earnings_as_json(data.activities, &self.rates, data.total_rounding);
+        Ok(())
+    }
+}
+
+// shows the tracked time of each project multiplied by its hourly rate
+//
+// projects without a rate are listed but do not add to the total earnings.
+pub fn show_earnings(
+    activities: &[&activity::Activity],
+    rates: &Rates,
+    total_rounding: Option<&TotalRounding>,
+) {
+    if activities.is_empty() {
+        outputln!("No activity to display");
+        return;
+    }
+
+    outputln!(
+        "\n{}",
+        Earnings::new(activities, rates, total_rounding).to_table()
+    );
+}
+
+pub fn show_earnings_as_json(
+    activities: &[&activity::Activity],
+    rates: &Rates,
+    total_rounding: Option<&TotalRounding>,
+) {
+    json::print(&Earnings::new(activities, rates, total_rounding));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::activity::activity;
+    use crate::data::config::Config;
+
+    #[test]
+    fn earnings_json() {
+        let config: Config = "[rates]\np1 = 80".parse().unwrap();
+        let rates = Rates::from_config(&config).unwrap();
+
+        let a1 = activity("p1", "2024-03-01 09:00", "2024-03-01 10:30");
+        let a2 = activity("p2", "2024-03-01 11:00", "2024-03-01 12:00");
+        let a3 = activity("p1", "2024-03-02 09:00", "2024-03-02 09:15");
+        let activities = vec![&a1, &a2, &a3];
+
+        assert_eq!(
+            Earnings::new(&activities, &rates, None)
+                .to_json()
+                .to_string(),
+            r#"{"projects":[{"project":"p1","duration":6300,"rate":80,"earnings":140},{"project":"p2","duration":3600,"rate":null,"earnings":null}],"duration":9900,"earnings":140,"currency":null}"#
+        );
+    }
+}
@@ -0,0 +1,359 @@
+use chrono::{NaiveDateTime, Utc};
+use clap::ValueEnum;
+
+use crate::conf;
+use crate::data::activity;
+use crate::data::jira;
+use crate::data::redaction;
+use crate::data::timezone;
+use crate::view::format_util;
+use crate::view::json::Json;
+
+static ICS_DATETIME: &str = "%Y%m%dT%H%M%S";
+
+// the file formats activities can be exported to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    // comma separated values with one activity per line
+    #[default]
+    Csv,
+    // iCalendar with one event per activity
+    Ics,
+    // the CSV format of Toggl Track's detailed report, which Toggl can import
+    Toggl,
+    // JSON with the worklogs of each issue in the format of JIRA, which `--push` adds to JIRA
+    Jira,
+}
+
+// creates the content of an export file
+#[must_use]
+pub fn export_activities(activities: &[&activity::Activity], format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Csv => to_csv(activities),
+        ExportFormat::Ics => to_ics(activities, Utc::now().naive_utc()),
+        ExportFormat::Toggl => to_toggl_csv(activities),
+        ExportFormat::Jira => to_jira_json(activities),
+    }
+}
+
+// one line per activity with the duration in decimal hours. Running activities have no end
+fn to_csv(activities: &[&activity::Activity]) -> String {
+    let mut lines = vec!["Start,End,Duration (h),Project,Description,Tags,Notes".to_string()];
+
+    for activity in activities {
+        let cells = [
+            activity.start.format(conf::format_datetime()).to_string(),
+            activity
+                .end
+                .map(|end| end.format(conf::format_datetime()).to_string())
+                .unwrap_or_default(),
+            format_util::format_hours(&activity.get_duration()),
+            activity.project.clone(),
+            activity.description.clone(),
+            activity.tags.join(", "),
+            activity.notes.join("; "),
+        ];
+
+        lines.push(
+            cells
+                .iter()
+                .map(|cell| format_util::escape_csv_field(cell))
+                .collect::<Vec<_>>()
+                .join(","),
+        );
+    }
+
+    lines.join("\n") + "\n"
+}
+
+// one line per activity in the columns of Toggl's detailed report. Times always have seconds and
+// running activities have no end
+fn to_toggl_csv(activities: &[&activity::Activity]) -> String {
+    let mut lines = vec![
+        "Project,Description,Start date,Start time,End date,End time,Duration,Tags".to_string(),
+    ];
+
+    for activity in activities {
+        let duration = activity.get_duration().num_seconds();
+        let cells = [
+            activity.project.clone(),
+            activity.description.clone(),
+            activity.start.format(conf::FORMAT_DATE).to_string(),
+            activity.start.format("%T").to_string(),
+            activity
+                .end
+                .map(|end| end.format(conf::FORMAT_DATE).to_string())
+                .unwrap_or_default(),
+            activity
+                .end
+                .map(|end| end.format("%T").to_string())
+                .unwrap_or_default(),
+            format!(
+                "{:02}:{:02}:{:02}",
+                duration / 3600,
+                duration % 3600 / 60,
+                duration % 60
+            ),
+            activity.tags.join(", "),
+        ];
+
+        lines.push(
+            cells
+                .iter()
+                .map(|cell| format_util::escape_csv_field(cell))
+                .collect::<Vec<_>>()
+                .join(","),
+        );
+    }
+
+    lines.join("\n") + "\n"
+}
+
+// the worklogs grouped by issue with their durations in JIRA's format. Activities without issue and
+// running ones are left out
+fn to_jira_json(activities: &[&activity::Activity]) -> String {
+    let issues = jira::group_by_issue(activities)
+        .iter()
+        .map(|worklogs| {
+            Json::object([
+                ("issue", Json::string(worklogs.issue)),
+                (
+                    "timeSpent",
+                    Json::string(&jira::format_duration(&worklogs.total())),
+                ),
+                ("timeSpentSeconds", Json::duration(&worklogs.total())),
+                (
+                    "worklogs",
+                    Json::Array(
+                        worklogs
+                            .activities
+                            .iter()
+                            .map(|a| jira_worklog(a))
+                            .collect(),
+                    ),
+                ),
+            ])
+        })
+        .collect();
+
+    format!("{}\n", Json::Array(issues))
+}
+
+// a single worklog as the REST API of JIRA expects it to add it to an issue
+#[must_use]
+pub fn jira_worklog(activity: &activity::Activity) -> Json {
+    let duration = jira::logged_duration(activity);
+
+    Json::object([
+        ("started", Json::string(&jira::format_started(activity))),
+        ("timeSpent", Json::string(&jira::format_duration(&duration))),
+        ("timeSpentSeconds", Json::duration(&duration)),
+        ("comment", Json::string(&activity.description)),
+    ])
+}
+
+// one event per activity with the project as summary and the description, followed by the notes,
+// as description
+//
+// times are written in UTC. Running activities end at the time of the export, which is given in
+// UTC, too.
+fn to_ics(activities: &[&activity::Activity], timestamp: NaiveDateTime) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//bartib//bartib//EN".to_string(),
+    ];
+
+    for activity in activities {
+        let start = timezone::instant(activity.start, activity.start_offset);
+        let end = activity
+            .end
+            .map_or(timestamp, |end| timezone::instant(end, activity.end_offset));
+
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}", get_uid(activity)));
+        lines.push(format!("DTSTAMP:{}Z", timestamp.format(ICS_DATETIME)));
+        lines.push(format!("DTSTART:{}Z", start.format(ICS_DATETIME)));
+        lines.push(format!("DTEND:{}Z", end.format(ICS_DATETIME)));
+        lines.push(format!("SUMMARY:{}", escape_ics_text(&activity.project)));
+
+        let description: Vec<&str> = std::iter::once(activity.description.as_str())
+            .chain(activity.notes.iter().map(String::as_str))
+            .filter(|text| !text.is_empty())
+            .collect();
+
+        if !description.is_empty() {
+            lines.push(format!(
+                "DESCRIPTION:{}",
+                escape_ics_text(&description.join("\n"))
+            ));
+        }
+
+        if !activity.tags.is_empty() {
+            let tags: Vec<String> = activity.tags.iter().map(|t| escape_ics_text(t)).collect();
+            lines.push(format!("CATEGORIES:{}", tags.join(",")));
+        }
+
+        lines.push("END:VEVENT".to_string());
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    lines
+        .iter()
+        .map(|line| fold_ics_line(line) + "\r\n")
+        .collect()
+}
+
+// a stable id for an activity, so calendars recognize events when the export is imported again
+fn get_uid(activity: &activity::Activity) -> String {
+    let hash = redaction::hash(&format!(
+        "{}|{}|{}",
+        activity.start, activity.project, activity.description
+    ));
+
+    format!(
+        "{}-{:016x}@bartib",
+        activity.start.format(ICS_DATETIME),
+        hash
+    )
+}
+
+fn escape_ics_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+// lines longer than 75 bytes are continued in lines starting with a space
+fn fold_ics_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut line_length = 0;
+
+    for c in line.chars() {
+        if line_length + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            line_length = 1;
+        }
+
+        folded.push(c);
+        line_length += c.len_utf8();
+    }
+
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDateTime;
+
+    use super::*;
+
+    #[test]
+    fn export_csv() {
+        conf::set_precision(conf::Precision::Minutes);
+        let mut a1 = activity::Activity::start(
+            "p1".to_string(),
+            "say \"hi\", then leave".to_string(),
+            NaiveDateTime::parse_from_str("2024-03-01 09:00", "%F %R").ok(),
+        )
+        .with_tags(vec!["a".to_string(), "b".to_string()]);
+        a1.end = NaiveDateTime::parse_from_str("2024-03-01 10:30", "%F %R").ok();
+        a1.add_note("first");
+        a1.add_note("second");
+
+        assert_eq!(
+            export_activities(&[&a1], ExportFormat::Csv),
+            "Start,End,Duration (h),Project,Description,Tags,Notes\n\
+             2024-03-01 09:00,2024-03-01 10:30,1.50,p1,\"say \"\"hi\"\", then leave\",\"a, b\",first; second\n"
+        );
+    }
+
+    #[test]
+    fn export_toggl() {
+        let mut a1 = activity::Activity::start(
+            "p1".to_string(),
+            "a, b".to_string(),
+            NaiveDateTime::parse_from_str("2024-03-01 09:00", "%F %R").ok(),
+        )
+        .with_tags(vec!["billable".to_string()]);
+        a1.end = NaiveDateTime::parse_from_str("2024-03-01 10:30", "%F %R").ok();
+
+        assert_eq!(
+            export_activities(&[&a1], ExportFormat::Toggl),
+            "Project,Description,Start date,Start time,End date,End time,Duration,Tags\n\
+             p1,\"a, b\",2024-03-01,09:00:00,2024-03-01,10:30:00,01:30:00,billable\n"
+        );
+    }
+
+    #[test]
+    fn export_jira() {
+        let mut a1 = activity::Activity::start(
+            "p1".to_string(),
+            "review".to_string(),
+            NaiveDateTime::parse_from_str("2024-03-01 09:00", "%F %R").ok(),
+        )
+        .with_issue(Some("PROJ-1".to_string()));
+        a1.end = NaiveDateTime::parse_from_str("2024-03-01 10:30", "%F %R").ok();
+        a1.start_offset = chrono::FixedOffset::east_opt(3600);
+        a1.end_offset = a1.start_offset;
+        let without_issue = activity::Activity::start("p2".to_string(), "d".to_string(), None);
+
+        assert_eq!(
+            export_activities(&[&a1, &without_issue], ExportFormat::Jira),
+            "[{\"issue\":\"PROJ-1\",\"timeSpent\":\"1h 30m\",\"timeSpentSeconds\":5400,\"worklogs\":\
+             [{\"started\":\"2024-03-01T09:00:00.000+0100\",\"timeSpent\":\"1h 30m\",\
+             \"timeSpentSeconds\":5400,\"comment\":\"review\"}]}]\n"
+        );
+    }
+
+    #[test]
+    fn export_ics() {
+        let mut a1 = activity::Activity::start(
+            "p1".to_string(),
+            "a; b, c".to_string(),
+            NaiveDateTime::parse_from_str("2024-03-01 09:00", "%F %R").ok(),
+        );
+        a1.end = NaiveDateTime::parse_from_str("2024-03-01 10:30", "%F %R").ok();
+        a1.start_offset = "+01:00".parse().ok();
+        a1.end_offset = a1.start_offset;
+        a1.pause(NaiveDateTime::parse_from_str("2024-03-01 10:00", "%F %R").ok());
+        a1.resume(NaiveDateTime::parse_from_str("2024-03-01 10:15", "%F %R").ok());
+        a1.add_note("n");
+        let timestamp = NaiveDateTime::parse_from_str("2024-03-02 12:00", "%F %R").unwrap();
+
+        let ics = to_ics(&[&a1], timestamp);
+        let lines: Vec<&str> = ics.split("\r\n").collect();
+
+        assert_eq!(lines[0], "BEGIN:VCALENDAR");
+        assert!(lines[4].starts_with("UID:20240301T090000-"));
+        assert_eq!(
+            lines[5..10],
+            [
+                "DTSTAMP:20240302T120000Z",
+                "DTSTART:20240301T080000Z",
+                "DTEND:20240301T093000Z",
+                "SUMMARY:p1",
+                "DESCRIPTION:a\\; b\\, c\\nn",
+            ]
+        );
+        assert_eq!(lines[11], "END:VCALENDAR");
+
+        // running activities end at the time of the export
+        let mut running = a1.clone();
+        running.end = None;
+        running.end_offset = None;
+        let ics = to_ics(&[&running], timestamp);
+        assert!(ics.contains("\r\nDTEND:20240302T120000Z\r\n"));
+    }
+
+    #[test]
+    fn fold_long_ics_lines() {
+        let line = format!("SUMMARY:{}", "ä".repeat(40));
+        let folded = fold_ics_line(&line);
+
+        assert!(folded.split("\r\n").all(|l| l.len() <= 75));
+        assert_eq!(folded.replace("\r\n ", ""), line);
+    }
+}
@@ -1,4 +1,7 @@
-use chrono::Duration;
+use chrono::{Duration, IsoWeek};
+
+use crate::conf;
+use crate::data::activity;
 
 pub fn format_duration(duration: &Duration) -> String {
     let mut duration_string = String::new();
@@ -10,11 +13,60 @@ pub fn format_duration(duration: &Duration) -> String {
     if duration.num_minutes() > 0 {
         duration_string.push_str(&format!("{:0>2}m", duration.num_minutes() % 60));
     } else {
-        #[cfg(not(feature = "second-precision"))]
-        duration_string.push_str("<1m");
-        #[cfg(feature = "second-precision")]
-        duration_string.push_str(&format!("{:0>2}s", duration.num_seconds() % 60));
+        match conf::precision() {
+            conf::Precision::Minutes => duration_string.push_str("<1m"),
+            conf::Precision::Seconds => {
+                duration_string.push_str(&format!("{:0>2}s", duration.num_seconds() % 60));
+            }
+        }
     }
 
     duration_string
 }
+
+// formats a duration like a stopwatch, e.g. `1:05:12`
+pub fn format_elapsed(duration: &Duration) -> String {
+    let seconds = duration.num_seconds().max(0);
+    format!(
+        "{}:{:0>2}:{:0>2}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+// formats a duration as decimal hours with two decimal places, e.g. `1.50`
+pub fn format_hours(duration: &Duration) -> String {
+    format!("{:.2}", duration.num_seconds() as f64 / 3600.0)
+}
+
+// quotes a field of a CSV file if it contains delimiters, quotes or line breaks
+pub fn escape_csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// formats an ISO week like `2024-W11`
+pub fn format_week(week: IsoWeek) -> String {
+    format!("{}-W{:0>2}", week.year(), week.week())
+}
+
+// formats tags like `#billable #remote`
+pub fn format_tags(tags: &[String]) -> String {
+    tags.iter()
+        .map(|tag| format!("#{tag}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// the description of an activity followed by its tags
+pub fn format_description_with_tags(activity: &activity::Activity) -> String {
+    if activity.tags.is_empty() {
+        activity.description.clone()
+    } else {
+        format!("{} {}", activity.description, format_tags(&activity.tags))
+    }
+}
@@ -0,0 +1,263 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+
+use anyhow::{Context, Result};
+use chrono::{Duration, NaiveDate};
+
+use crate::conf;
+use crate::data::activity;
+use crate::data::processor::{ReportData, ReportWriter};
+use crate::view::format_util;
+use crate::view::report;
+use crate::{output, outputln};
+
+static STYLE: &str = "
+body { font-family: sans-serif; color: #222; max-width: 60em; margin: 2em auto; padding: 0 1em; }
+h1 { margin-bottom: 0.2em; }
+.period { color: #666; margin-top: 0; }
+table { border-collapse: collapse; width: 100%; margin-bottom: 2em; }
+th, td { text-align: left; padding: 0.3em 0.6em; border-bottom: 1px solid #ddd; vertical-align: top; }
+td.duration, th.duration { text-align: right; white-space: nowrap; }
+tr.total td { font-weight: bold; border-top: 2px solid #222; }
+.bar { background: #4a7ebb; height: 1em; min-width: 1px; }
+td.chart { width: 60%; }
+.tags { color: #666; }
+";
+
+// writes the report as a self-contained HTML page with the totals per project, a bar chart of
+// the days and a table of all activities
+pub struct HtmlReport {
+    pub output_file: Option<String>,
+}
+
+impl ReportWriter for HtmlReport {
+    fn process(&self, data: &ReportData) -> Result<()> {
+        let html = render_report(data);
+
+        match &self.output_file {
+            Some(output_file) => {
+                fs::write(output_file, html)
+                    .context(format!("Could not write to file: {output_file}"))?;
+                outputln!(
+                    "Wrote the report of {} activities to {}",
+                    data.activities.len(),
+                    output_file
+                );
+            }
+            None => output!("{html}"),
+        }
+
+        Ok(())
+    }
+}
+
+fn render_report(data: &ReportData) -> String {
+    let mut html = String::new();
+    let period = format_period(data);
+
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    let _ = writeln!(html, "<title>Report {}</title>", escape_html(&period));
+    let _ = writeln!(html, "<style>{STYLE}</style>\n</head>\n<body>");
+    html.push_str("<h1>Report</h1>\n");
+    let _ = writeln!(html, "<p class=\"period\">{}</p>", escape_html(&period));
+
+    if data.activities.is_empty() {
+        html.push_str("<p>No activity to display</p>\n");
+    } else {
+        write_project_totals(&mut html, data);
+        write_daily_chart(&mut html, data);
+        write_activities(&mut html, data.activities);
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+// the dates the report is restricted to or otherwise the days of the first and last activity
+fn format_period(data: &ReportData) -> String {
+    let first = data
+        .from_date
+        .or_else(|| data.activities.first().map(|a| a.start.date()));
+    let last = data
+        .to_date
+        .or_else(|| data.activities.last().map(|a| a.start.date()));
+
+    match (first, last) {
+        (Some(first), Some(last)) if first == last => first.format(conf::FORMAT_DATE).to_string(),
+        (Some(first), Some(last)) => format!(
+            "{} – {}",
+            first.format(conf::FORMAT_DATE),
+            last.format(conf::FORMAT_DATE)
+        ),
+        _ => String::new(),
+    }
+}
+
+// the duration and the share of the total of each project. Like in the text report, the
+// durations of projects are rounded only if the totals of projects per day are rounded
+fn write_project_totals(html: &mut String, data: &ReportData) {
+    let mut projects: BTreeMap<&str, Vec<&activity::Activity>> = BTreeMap::new();
+    for activity in data.activities {
+        projects
+            .entry(&activity.project)
+            .or_default()
+            .push(activity);
+    }
+
+    let project_rounding = data.total_rounding.filter(|r| r.rounds_projects());
+    let total = report::sum_duration_with_rounding(data.activities, data.total_rounding);
+
+    html.push_str("<h2>Projects</h2>\n<table>\n");
+    html.push_str("<tr><th>Project</th><th class=\"duration\">Duration</th><th class=\"duration\">Share</th></tr>\n");
+
+    for (project, activities) in projects {
+        let duration = report::sum_duration_with_rounding(&activities, project_rounding);
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td class=\"duration\">{}</td><td class=\"duration\">{}%</td></tr>",
+            escape_html(project),
+            format_util::format_duration(&duration),
+            duration.num_seconds() * 100 / total.num_seconds().max(1)
+        );
+    }
+
+    let _ = writeln!(
+        html,
+        "<tr class=\"total\"><td>Total</td><td class=\"duration\">{}</td><td></td></tr>\n</table>",
+        format_util::format_duration(&total)
+    );
+}
+
+// a horizontal bar for the total duration of each day
+fn write_daily_chart(html: &mut String, data: &ReportData) {
+    let mut days: BTreeMap<NaiveDate, Vec<&activity::Activity>> = BTreeMap::new();
+    for activity in data.activities {
+        days.entry(activity.start.date())
+            .or_default()
+            .push(activity);
+    }
+
+    let totals: Vec<(NaiveDate, Duration)> = days
+        .into_iter()
+        .map(|(day, activities)| {
+            (
+                day,
+                report::sum_duration_with_rounding(&activities, data.total_rounding),
+            )
+        })
+        .collect();
+    let longest = totals
+        .iter()
+        .map(|(_, duration)| duration.num_seconds())
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    html.push_str("<h2>Days</h2>\n<table>\n");
+
+    for (day, duration) in totals {
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td class=\"chart\"><div class=\"bar\" style=\"width: {:.1}%\"></div></td><td class=\"duration\">{}</td></tr>",
+            day.format("%a, %F"),
+            duration.num_seconds().max(0) as f64 * 100.0 / longest as f64,
+            format_util::format_duration(&duration)
+        );
+    }
+
+    html.push_str("</table>\n");
+}
+
+fn write_activities(html: &mut String, activities: &[&activity::Activity]) {
+    let time_format = conf::precision().format_time();
+
+    html.push_str("<h2>Activities</h2>\n<table>\n");
+    html.push_str("<tr><th>Date</th><th>Start</th><th>End</th><th class=\"duration\">Duration</th><th>Project</th><th>Description</th></tr>\n");
+
+    for activity in activities {
+        let end = activity.end.map_or_else(
+            || "running".to_string(),
+            |end| end.format(time_format).to_string(),
+        );
+        let tags = if activity.tags.is_empty() {
+            String::new()
+        } else {
+            format!(
+                " <span class=\"tags\">{}</span>",
+                escape_html(&format_util::format_tags(&activity.tags))
+            )
+        };
+
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td class=\"duration\">{}</td><td>{}</td><td>{}{}</td></tr>",
+            activity.start.format(conf::FORMAT_DATE),
+            activity.start.format(time_format),
+            end,
+            format_util::format_duration(&activity.get_duration()),
+            escape_html(&activity.project),
+            escape_html(&activity.description),
+            tags
+        );
+    }
+
+    html.push_str("</table>\n");
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::activity::activity;
+
+    #[test]
+    fn render_html_report() {
+        conf::set_precision(conf::Precision::Minutes);
+        let a1 = activity::Activity {
+            description: "Fix \"header\"".to_string(),
+            ..activity("<Client>", "2024-03-01 09:00", "2024-03-01 12:00")
+                .with_tags(vec!["billable".to_string()])
+        };
+        let a2 = activity::Activity {
+            description: "Mails".to_string(),
+            ..activity("Internal", "2024-03-02 09:00", "2024-03-02 10:00")
+                .with_tags(vec!["billable".to_string()])
+        };
+        let activities = vec![&a1, &a2];
+
+        let html = render_report(&ReportData {
+            activities: &activities,
+            total_rounding: None,
+            from_date: None,
+            to_date: None,
+        });
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<p class=\"period\">2024-03-01 – 2024-03-02</p>"));
+        assert!(html.contains(
+            "<tr><td>&lt;Client&gt;</td><td class=\"duration\">3h 00m</td><td class=\"duration\">75%</td></tr>"
+        ));
+        assert!(html.contains("<td class=\"duration\">4h 00m</td>"));
+        assert!(html.contains("<div class=\"bar\" style=\"width: 33.3%\"></div>"));
+        assert!(
+            html.contains("<td>Fix &quot;header&quot; <span class=\"tags\">#billable</span></td>")
+        );
+        assert!(!html.contains("http"));
+    }
+
+    #[test]
+    fn escape_html_test() {
+        assert_eq!(
+            escape_html("<a href=\"x\">Tom & Jerry's</a>"),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;"
+        );
+    }
+}
@@ -0,0 +1,244 @@
+use std::fmt;
+
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+
+use crate::api;
+use crate::data::activity;
+use crate::outputln;
+
+// a JSON value that can be written to the output
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+// types that can be written as JSON
+pub trait ToJson {
+    fn to_json(&self) -> Json;
+}
+
+impl Json {
+    // creates an object from key value pairs, keeping the order of the keys
+    #[must_use]
+    pub fn object<const N: usize>(entries: [(&str, Json); N]) -> Self {
+        Self::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+
+    #[must_use]
+    pub fn string(s: &str) -> Self {
+        Self::String(s.to_string())
+    }
+
+    // durations are written as number of seconds
+    #[must_use]
+    pub fn duration(duration: &Duration) -> Self {
+        Self::Integer(duration.num_seconds())
+    }
+
+    // timestamps are written in ISO 8601 format without a timezone
+    #[must_use]
+    pub fn datetime(datetime: &NaiveDateTime) -> Self {
+        Self::String(datetime.format("%Y-%m-%dT%H:%M:%S").to_string())
+    }
+
+    #[must_use]
+    pub fn date(date: &NaiveDate) -> Self {
+        Self::String(date.format("%Y-%m-%d").to_string())
+    }
+
+    #[must_use]
+    pub fn optional<T>(value: Option<T>, to_json: impl FnOnce(T) -> Self) -> Self {
+        value.map_or(Self::Null, to_json)
+    }
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Null => write!(f, "null"),
+            Self::Bool(b) => write!(f, "{b}"),
+            Self::Integer(i) => write!(f, "{i}"),
+            Self::Float(x) if x.is_finite() => write!(f, "{x}"),
+            Self::Float(_) => write!(f, "null"),
+            Self::String(s) => write_string(f, s),
+            Self::Array(values) => {
+                write!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                write!(f, "]")
+            }
+            Self::Object(entries) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{value}")?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+impl ToJson for activity::Activity {
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("start", Json::datetime(&self.start)),
+            ("end", Json::optional(self.end.as_ref(), Json::datetime)),
+            ("project", Json::string(&self.project)),
+            ("description", Json::string(&self.description)),
+            (
+                "tags",
+                Json::Array(self.tags.iter().map(|tag| Json::string(tag)).collect()),
+            ),
+            ("issue", Json::optional(self.issue.as_deref(), Json::string)),
+            (
+                "pauses",
+                Json::Array(
+                    self.pauses
+                        .iter()
+                        .map(|pause| {
+                            Json::object([
+                                ("start", Json::datetime(&pause.start)),
+                                ("end", Json::optional(pause.end.as_ref(), Json::datetime)),
+                            ])
+                        })
+                        .collect(),
+                ),
+            ),
+            (
+                "notes",
+                Json::Array(self.notes.iter().map(|note| Json::string(note)).collect()),
+            ),
+            ("duration", Json::duration(&self.get_duration())),
+        ])
+    }
+}
+
+// the report of the library interface is written like the JSON of `bartib report`
+impl ToJson for api::Report {
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("projects", self.projects.to_json()),
+            ("total", Json::duration(&self.total)),
+        ])
+    }
+}
+
+impl ToJson for api::ProjectReport {
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("project", Json::string(&self.project)),
+            ("duration", Json::duration(&self.total)),
+            ("descriptions", self.descriptions.to_json()),
+        ])
+    }
+}
+
+impl ToJson for api::DescriptionReport {
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("description", Json::string(&self.description)),
+            ("duration", Json::duration(&self.total)),
+        ])
+    }
+}
+
+impl ToJson for Json {
+    fn to_json(&self) -> Json {
+        self.clone()
+    }
+}
+
+impl<T: ToJson> ToJson for [T] {
+    fn to_json(&self) -> Json {
+        Json::Array(self.iter().map(ToJson::to_json).collect())
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for &T {
+    fn to_json(&self) -> Json {
+        (*self).to_json()
+    }
+}
+
+// writes a value as a single line of JSON to stdout
+pub fn print(value: &(impl ToJson + ?Sized)) {
+    outputln!("{}", value.to_json());
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", u32::from(c))?,
+            c => write!(f, "{c}")?,
+        }
+    }
+
+    write!(f, "\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_json() {
+        let value = Json::object([
+            ("null", Json::Null),
+            ("bool", Json::Bool(true)),
+            (
+                "numbers",
+                Json::Array(vec![Json::Integer(-3), Json::Float(1.5)]),
+            ),
+            ("string", Json::string("say \"hi\"\n\\ \u{1}")),
+            ("empty", Json::Object(Vec::new())),
+        ]);
+
+        assert_eq!(
+            value.to_string(),
+            r#"{"null":null,"bool":true,"numbers":[-3,1.5],"string":"say \"hi\"\n\\ \u0001","empty":{}}"#
+        );
+    }
+
+    #[test]
+    fn activity_to_json() {
+        let mut a = activity::Activity::start(
+            "p".to_string(),
+            "d".to_string(),
+            NaiveDateTime::parse_from_str("2024-03-01 09:00", "%F %R").ok(),
+        )
+        .with_tags(vec!["billable".to_string()]);
+        a.end = NaiveDateTime::parse_from_str("2024-03-01 09:30", "%F %R").ok();
+        a.add_note("n");
+
+        assert_eq!(
+            a.to_json().to_string(),
+            r#"{"start":"2024-03-01T09:00:00","end":"2024-03-01T09:30:00","project":"p","description":"d","tags":["billable"],"issue":null,"pauses":[],"notes":["n"],"duration":1800}"#
+        );
+    }
+}
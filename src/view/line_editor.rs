@@ -0,0 +1,374 @@
+use std::io;
+use std::io::{BufRead, IsTerminal, Write};
+
+use crate::view::terminal;
+
+// the part of a line that should be completed and the possible replacements for it
+pub struct Completion {
+    // the byte position where the word to complete starts
+    pub start: usize,
+    // all candidates the word may be replaced with
+    pub candidates: Vec<String>,
+}
+
+// a minimal line editor for interactive prompts
+//
+// on unix terminals the editor switches the terminal to raw mode to support completions with the
+// tab key and a history of previous lines with the arrow keys. In all other cases it falls back to
+// reading plain lines from stdin.
+pub struct LineEditor {
+    history: Vec<String>,
+}
+
+impl Default for LineEditor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LineEditor {
+    pub fn new() -> Self {
+        Self {
+            history: Vec::new(),
+        }
+    }
+
+    // reads a line from the user. Returns `None` if the input has been closed
+    pub fn read_line(
+        &mut self,
+        prompt: &str,
+        complete: &dyn Fn(&str) -> Completion,
+    ) -> io::Result<Option<String>> {
+        let line = if io::stdin().is_terminal() && io::stdout().is_terminal() {
+            match terminal::RawMode::enable() {
+                Ok(_raw_mode) => self.read_line_interactive(prompt, complete)?,
+                Err(_) => read_line_plain(prompt)?,
+            }
+        } else {
+            read_line_plain(prompt)?
+        };
+
+        if let Some(line) = &line
+            && !line.trim().is_empty()
+            && self.history.last() != Some(line)
+        {
+            self.history.push(line.clone());
+        }
+
+        Ok(line)
+    }
+
+    fn read_line_interactive(
+        &self,
+        prompt: &str,
+        complete: &dyn Fn(&str) -> Completion,
+    ) -> io::Result<Option<String>> {
+        let mut stdin = io::stdin().lock();
+        let mut stdout = io::stdout();
+        let mut line = String::new();
+        let mut history_index = self.history.len();
+
+        redraw(&mut stdout, prompt, &line)?;
+
+        loop {
+            let byte = match terminal::read_byte(&mut stdin)? {
+                Some(byte) => byte,
+                None => return Ok(None),
+            };
+
+            match byte {
+                b'\r' | b'\n' => {
+                    write!(stdout, "\r\n")?;
+                    stdout.flush()?;
+                    return Ok(Some(line));
+                }
+                // Ctrl-D
+                4 => {
+                    if line.is_empty() {
+                        write!(stdout, "\r\n")?;
+                        stdout.flush()?;
+                        return Ok(None);
+                    }
+                }
+                // Ctrl-C
+                3 => {
+                    write!(stdout, "^C\r\n")?;
+                    line.clear();
+                    history_index = self.history.len();
+                }
+                // Backspace
+                8 | 127 => {
+                    line.pop();
+                }
+                b'\t' => {
+                    complete_line(&mut stdout, &mut line, complete)?;
+                }
+                // escape sequences (e.g. arrow keys)
+                27 => {
+                    if terminal::read_byte(&mut stdin)? != Some(b'[') {
+                        continue;
+                    }
+
+                    match terminal::read_byte(&mut stdin)? {
+                        Some(b'A') if history_index > 0 => {
+                            history_index -= 1;
+                            line = self.history[history_index].clone();
+                        }
+                        Some(b'B') if history_index < self.history.len() => {
+                            history_index += 1;
+                            line = self.history.get(history_index).cloned().unwrap_or_default();
+                        }
+                        _ => {}
+                    }
+                }
+                byte if byte < 32 => {}
+                byte => {
+                    let mut bytes = vec![byte];
+                    for _ in 1..terminal::utf8_length(byte) {
+                        if let Some(next) = terminal::read_byte(&mut stdin)? {
+                            bytes.push(next);
+                        }
+                    }
+                    line.push_str(&String::from_utf8_lossy(&bytes));
+                }
+            }
+
+            redraw(&mut stdout, prompt, &line)?;
+        }
+    }
+}
+
+// splits a line into arguments like a shell would do
+//
+// arguments are separated by whitespace. Single and double quotes group words into one argument
+// and a backslash escapes the following character outside of single quotes.
+pub fn split_arguments(line: &str) -> Result<Vec<String>, String> {
+    let scanned = scan_arguments(line);
+
+    if scanned.open_quote {
+        return Err("missing closing quote".to_string());
+    }
+
+    if scanned.open_escape {
+        return Err("line ends with an escape character".to_string());
+    }
+
+    Ok(scanned
+        .arguments
+        .into_iter()
+        .map(|(_, argument)| argument)
+        .collect())
+}
+
+// splits a partial line into the complete arguments and the word that is currently typed
+//
+// returns the complete arguments, the byte position where the current word starts and the current
+// word without quotes.
+pub fn split_current_word(line: &str) -> (Vec<String>, usize, String) {
+    let mut scanned = scan_arguments(line);
+    let ends_with_separator = !scanned.open_quote
+        && !scanned.open_escape
+        && line.chars().last().is_none_or(char::is_whitespace);
+
+    let (start, word) = if ends_with_separator {
+        (line.len(), String::new())
+    } else {
+        scanned
+            .arguments
+            .pop()
+            .unwrap_or((line.len(), String::new()))
+    };
+
+    let arguments = scanned
+        .arguments
+        .into_iter()
+        .map(|(_, argument)| argument)
+        .collect();
+
+    (arguments, start, word)
+}
+
+struct ScannedArguments {
+    // the arguments together with their start positions in the line
+    arguments: Vec<(usize, String)>,
+    open_quote: bool,
+    open_escape: bool,
+}
+
+fn scan_arguments(line: &str) -> ScannedArguments {
+    let mut arguments = Vec::new();
+    let mut current: Option<(usize, String)> = None;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+
+    for (i, c) in line.char_indices() {
+        if escaped {
+            current.get_or_insert_with(|| (i, String::new())).1.push(c);
+            escaped = false;
+            continue;
+        }
+
+        match (quote, c) {
+            (Some('\''), '\'') | (Some('"'), '"') => quote = None,
+            (Some('\''), c) => current.get_or_insert_with(|| (i, String::new())).1.push(c),
+            (_, '\\') => {
+                escaped = true;
+                current.get_or_insert_with(|| (i, String::new()));
+            }
+            (Some(_), c) => current.get_or_insert_with(|| (i, String::new())).1.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                current.get_or_insert_with(|| (i, String::new()));
+            }
+            (None, c) if c.is_whitespace() => {
+                if let Some(argument) = current.take() {
+                    arguments.push(argument);
+                }
+            }
+            (None, c) => current.get_or_insert_with(|| (i, String::new())).1.push(c),
+        }
+    }
+
+    arguments.extend(current);
+
+    ScannedArguments {
+        arguments,
+        open_quote: quote.is_some(),
+        open_escape: escaped,
+    }
+}
+
+// quotes an argument if necessary, so that `split_arguments` returns it as a single argument
+pub fn quote_argument(argument: &str) -> String {
+    let needs_quotes = argument.is_empty()
+        || argument
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, '"' | '\'' | '\\'));
+
+    if needs_quotes {
+        format!(
+            "\"{}\"",
+            argument.replace('\\', "\\\\").replace('"', "\\\"")
+        )
+    } else {
+        argument.to_string()
+    }
+}
+
+fn read_line_plain(prompt: &str) -> io::Result<Option<String>> {
+    print!("{prompt}");
+    io::stdout().flush()?;
+
+    let mut line = String::new();
+    if io::stdin().lock().read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+
+    Ok(Some(line.trim_end_matches(['\r', '\n']).to_string()))
+}
+
+fn complete_line(
+    stdout: &mut io::Stdout,
+    line: &mut String,
+    complete: &dyn Fn(&str) -> Completion,
+) -> io::Result<()> {
+    let completion = complete(line);
+    let word = &line[completion.start..];
+
+    match completion.candidates.as_slice() {
+        [] => write!(stdout, "\x07")?,
+        [candidate] => {
+            line.truncate(completion.start);
+            line.push_str(candidate);
+            line.push(' ');
+        }
+        candidates => {
+            let prefix = common_prefix(candidates);
+
+            if prefix.len() > word.len() {
+                line.truncate(completion.start);
+                line.push_str(&prefix);
+            } else {
+                write!(stdout, "\r\n{}\r\n", candidates.join("  "))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn common_prefix(candidates: &[String]) -> String {
+    let mut prefix = candidates.first().cloned().unwrap_or_default();
+
+    for candidate in candidates {
+        while !candidate.starts_with(&prefix) {
+            prefix.pop();
+        }
+    }
+
+    prefix
+}
+
+fn redraw(stdout: &mut io::Stdout, prompt: &str, line: &str) -> io::Result<()> {
+    write!(stdout, "\r\x1b[K{prompt}{line}")?;
+    stdout.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_arguments_test() {
+        assert_eq!(
+            split_arguments("start -p \"a project\" -d 'it''s' -t 12:00").unwrap(),
+            vec!["start", "-p", "a project", "-d", "its", "-t", "12:00"]
+        );
+        assert_eq!(
+            split_arguments("  search a\\ b \"\" ").unwrap(),
+            vec!["search", "a b", ""]
+        );
+        assert!(split_arguments("start -p \"a project").is_err());
+    }
+
+    #[test]
+    fn split_current_word_test() {
+        assert_eq!(
+            split_current_word("start -p \"a pro"),
+            (
+                vec!["start".to_string(), "-p".to_string()],
+                9,
+                "a pro".to_string()
+            )
+        );
+        assert_eq!(
+            split_current_word("start -p "),
+            (
+                vec!["start".to_string(), "-p".to_string()],
+                9,
+                String::new()
+            )
+        );
+        assert_eq!(split_current_word(""), (vec![], 0, String::new()));
+    }
+
+    #[test]
+    fn quote_argument_test() {
+        assert_eq!(quote_argument("project"), "project");
+        assert_eq!(quote_argument("a project"), "\"a project\"");
+        assert_eq!(quote_argument("say \"hi\""), "\"say \\\"hi\\\"\"");
+
+        let quoted = quote_argument("a \"quoted\\\" project");
+        assert_eq!(
+            split_arguments(&quoted).unwrap(),
+            vec!["a \"quoted\\\" project"]
+        );
+    }
+
+    #[test]
+    fn common_prefix_test() {
+        let candidates = vec!["project a".to_string(), "project b".to_string()];
+        assert_eq!(common_prefix(&candidates), "project ");
+        assert_eq!(common_prefix(&[]), "");
+    }
+}
@@ -1,17 +1,22 @@
-use chrono::NaiveDate;
-use nu_ansi_term::Color;
+use chrono::{Datelike, Duration, IsoWeek, Local, NaiveDate};
+use clap::ValueEnum;
 use std::collections::BTreeMap;
 
 use crate::conf;
 use crate::data::activity;
+use crate::data::processor::TotalRounding;
+use crate::data::recurrence::RecurrenceRule;
+use crate::outputln;
 use crate::view::format_util;
+use crate::view::json::{self, Json, ToJson};
 use crate::view::report;
 use crate::view::table;
+use crate::view::theme;
 
-// displays a table with activities
-pub fn list_activities(activities: &[&activity::Activity], with_start_dates: bool) {
+// displays a table with activities, with their notes if `verbose` is set
+pub fn list_activities(activities: &[&activity::Activity], with_start_dates: bool, verbose: bool) {
     if activities.is_empty() {
-        println!("No activity to display");
+        outputln!("No activity to display");
         return;
     }
 
@@ -19,33 +24,106 @@ pub fn list_activities(activities: &[&activity::Activity], with_start_dates: boo
 
     activities
         .iter()
-        .map(|t| get_activity_table_row(t, with_start_dates))
+        .flat_map(|t| get_activity_table_rows(t, with_start_dates, verbose))
         .for_each(|row| activity_table.add_row(row));
 
-    println!("\n{activity_table}");
+    outputln!("\n{activity_table}");
 }
 
-// list activities grouped by the dates of their start time
-pub fn list_activities_grouped_by_date(activities: &[&activity::Activity]) {
+// displays a table with activities and their numbers for `edit-activity` and `delete`
+pub fn list_numbered_activities(activities: &[(usize, &activity::Activity)]) {
     if activities.is_empty() {
-        println!("No activity to display");
+        outputln!("No activities have been tracked yet");
+        return;
+    }
+
+    let mut columns = vec![table::Column {
+        label: " # ".to_string(),
+        wrap: table::Wrap::NoWrap,
+    }];
+    columns.extend(create_activity_columns());
+    let mut activity_table = table::Table::new(columns);
+
+    for (number, activity) in activities {
+        let mut row = get_activity_table_row(activity, true);
+        row.prepend(format!("[{number}]"));
+        activity_table.add_row(row);
+    }
+
+    outputln!("\n{activity_table}");
+}
+
+// the periods by which activities may be grouped
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Grouping {
+    #[default]
+    Day,
+    Week,
+}
+
+// list activities grouped by the dates (or weeks) of their start time
+//
+// if the activities are grouped by day and span more than one week, the headers show the ISO week
+// number of each day, too.
+pub fn list_activities_grouped(
+    activities: &[&activity::Activity],
+    grouping: Grouping,
+    total_rounding: Option<&TotalRounding>,
+    verbose: bool,
+) {
+    if activities.is_empty() {
+        outputln!("No activity to display");
         return;
     }
 
     let mut activity_table = create_activity_table();
 
-    group_activities_by_date(activities)
-        .iter()
-        .map(|(date, activity_list)| {
-            create_activities_group(&format!("{date}"), activity_list.as_slice())
-        })
-        .for_each(|g| activity_table.add_group(g));
+    match grouping {
+        Grouping::Day => {
+            let activities_by_date = group_activities_by_date(activities);
+            let with_week = spans_multiple_weeks(activities_by_date.keys());
 
-    println!("\n{activity_table}");
+            activities_by_date
+                .iter()
+                .map(|(date, activity_list)| {
+                    let title = if with_week {
+                        format!("{date} ({})", format_util::format_week(date.iso_week()))
+                    } else {
+                        format!("{date}")
+                    };
+                    create_activities_group(
+                        &title,
+                        activity_list.as_slice(),
+                        false,
+                        total_rounding,
+                        verbose,
+                    )
+                })
+                .for_each(|g| activity_table.add_group(g));
+        }
+        Grouping::Week => group_activities_by_week(activities)
+            .iter()
+            .map(|(week, activity_list)| {
+                create_activities_group(
+                    &format_week_title(*week),
+                    activity_list.as_slice(),
+                    true,
+                    total_rounding,
+                    verbose,
+                )
+            })
+            .for_each(|g| activity_table.add_group(g)),
+    }
+
+    outputln!("\n{activity_table}");
 }
 
 fn create_activity_table() -> table::Table {
-    table::Table::new(vec![
+    table::Table::new(create_activity_columns())
+}
+
+fn create_activity_columns() -> Vec<table::Column> {
+    vec![
         table::Column {
             label: "Started".to_string(),
             wrap: table::Wrap::NoWrap,
@@ -66,16 +144,22 @@ fn create_activity_table() -> table::Table {
             label: "Duration".to_string(),
             wrap: table::Wrap::NoWrap,
         },
-    ])
+    ]
 }
 
-fn create_activities_group(title: &str, activities: &[&activity::Activity]) -> table::Group {
+fn create_activities_group(
+    title: &str,
+    activities: &[&activity::Activity],
+    with_start_dates: bool,
+    total_rounding: Option<&TotalRounding>,
+    verbose: bool,
+) -> table::Group {
     let rows = activities
         .iter()
-        .map(|a| get_activity_table_row(a, false))
+        .flat_map(|a| get_activity_table_rows(a, with_start_dates, verbose))
         .collect();
 
-    let total_duration = report::sum_duration(activities);
+    let total_duration = report::sum_duration_with_rounding(activities, total_rounding);
 
     table::Group::new(
         Some(format!(
@@ -89,8 +173,21 @@ fn create_activities_group(title: &str, activities: &[&activity::Activity]) -> t
 
 // displays a table with running activities (no end time)
 pub fn list_running_activities(activities: &[&activity::Activity]) {
+    show_running_activities(activities, format_util::format_duration);
+}
+
+// displays the running activities with their durations to the second, e.g. for a table which is
+// refreshed every second
+pub fn list_running_activities_elapsed(activities: &[&activity::Activity]) {
+    show_running_activities(activities, format_util::format_elapsed);
+}
+
+fn show_running_activities(
+    activities: &[&activity::Activity],
+    format_duration: fn(&Duration) -> String,
+) {
     if activities.is_empty() {
-        println!("No Activity is currently running");
+        outputln!("No Activity is currently running");
     } else {
         let mut activity_table = table::Table::new(vec![
             table::Column {
@@ -114,19 +211,147 @@ pub fn list_running_activities(activities: &[&activity::Activity]) {
         activities
             .iter()
             .map(|activity| {
-                table::Row::new(vec![
-                    activity.start.format(conf::FORMAT_DATETIME).to_string(),
-                    activity.description.clone(),
+                let mut row = table::Row::new(vec![
+                    activity.start.format(conf::format_datetime()).to_string(),
+                    format_util::format_description_with_tags(activity),
                     activity.project.clone(),
-                    format_util::format_duration(&activity.get_duration()),
-                ])
+                    format_running_duration(activity, format_duration),
+                ]);
+                if let Some(style) = theme::theme().project_style(&activity.project) {
+                    row.set_cell_color(2, style);
+                }
+                row
             })
             .for_each(|row| activity_table.add_row(row));
 
-        println!("\n{activity_table}");
+        outputln!("\n{activity_table}");
+    }
+}
+
+// the duration of a running activity, marked if the activity is paused
+fn format_running_duration(
+    activity: &activity::Activity,
+    format_duration: fn(&Duration) -> String,
+) -> String {
+    let duration = format_duration(&activity.get_duration());
+
+    if activity.is_paused() {
+        format!("{duration} (paused)")
+    } else {
+        duration
+    }
+}
+
+// a single line with the running activities for status bars like the ones of tmux or i3, e.g.
+// `Client A: Feature 1:05:12`. The tracked time of today is appended if a daily limit is given
+pub fn format_running_compact(
+    activities: &[&activity::Activity],
+    today_and_limit: Option<(&Duration, &Duration)>,
+) -> String {
+    let mut line = if activities.is_empty() {
+        "No activity".to_string()
+    } else {
+        activities
+            .iter()
+            .map(|activity| {
+                format!(
+                    "{}: {} {}",
+                    activity.project,
+                    activity.description,
+                    format_running_duration(activity, format_util::format_elapsed)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" | ")
+    };
+
+    if let Some((today, daily_limit)) = today_and_limit {
+        line.push_str(&format!(
+            " | today: {} of {}",
+            format_util::format_duration(today),
+            format_util::format_duration(daily_limit)
+        ));
+    }
+
+    line
+}
+
+// prints the running activities and the tracked time of today as JSON
+pub fn print_running_activities_as_json(
+    activities: &[&activity::Activity],
+    today: &Duration,
+    daily_limit: Option<&Duration>,
+) {
+    json::print(&Json::object([
+        ("activities", activities.to_json()),
+        ("today", Json::duration(today)),
+        ("daily_limit", Json::optional(daily_limit, Json::duration)),
+    ]));
+}
+
+// displays how the tracked time of today relates to the daily limit
+pub fn show_daily_limit(today: &Duration, daily_limit: &Duration) {
+    if today > daily_limit {
+        outputln!(
+            "\n{}",
+            theme::style(theme::theme().error.bold()).paint(format!(
+                "Daily limit of {} exceeded by {} (today: {})",
+                format_util::format_duration(daily_limit),
+                format_util::format_duration(&(*today - *daily_limit)),
+                format_util::format_duration(today)
+            ))
+        );
+    } else {
+        outputln!(
+            "\nTracked today: {} of {}",
+            format_util::format_duration(today),
+            format_util::format_duration(daily_limit)
+        );
     }
 }
 
+// displays a table with the rules for recurring activities
+pub fn list_recurrence_rules(rules: &[RecurrenceRule]) {
+    if rules.is_empty() {
+        outputln!("No recurring activities have been configured");
+        return;
+    }
+
+    let mut rules_table = table::Table::new(vec![
+        table::Column {
+            label: "Days".to_string(),
+            wrap: table::Wrap::Wrap,
+        },
+        table::Column {
+            label: "Time".to_string(),
+            wrap: table::Wrap::NoWrap,
+        },
+        table::Column {
+            label: "Description".to_string(),
+            wrap: table::Wrap::Wrap,
+        },
+        table::Column {
+            label: "Project".to_string(),
+            wrap: table::Wrap::Wrap,
+        },
+    ]);
+
+    for rule in rules {
+        rules_table.add_row(table::Row::new(vec![
+            rule.format_weekdays(),
+            format!(
+                "{} - {}",
+                rule.start.format(conf::format_time()),
+                rule.end.format(conf::format_time())
+            ),
+            rule.description.clone(),
+            rule.project.clone(),
+        ]));
+    }
+
+    outputln!("\n{rules_table}");
+}
+
 // display a list of projects and descriptions with generated index number
 pub fn list_descriptions_and_projects(descriptions_and_projects: &[(&String, &String)]) {
     list_descriptions_and_projects_with_index(
@@ -146,7 +371,7 @@ pub fn list_descriptions_and_projects_with_index(
     zero_length_error: &str,
 ) {
     if descriptions_and_projects.is_empty() {
-        println!("{zero_length_error}");
+        outputln!("{zero_length_error}");
         return;
     }
     let mut descriptions_and_projects_table = table::Table::new(vec![
@@ -172,7 +397,7 @@ pub fn list_descriptions_and_projects_with_index(
         ]));
     }
 
-    println!("\n{descriptions_and_projects_table}");
+    outputln!("\n{descriptions_and_projects_table}");
 }
 
 // create a row for a activity
@@ -187,36 +412,67 @@ fn get_activity_table_row(activity: &activity::Activity, with_start_dates: bool)
         || "-".to_string(),
         |end| {
             if more_then_one_day {
-                end.format(conf::FORMAT_DATETIME).to_string()
+                end.format(conf::format_datetime()).to_string()
             } else {
-                end.format(conf::FORMAT_TIME).to_string()
+                end.format(conf::format_time()).to_string()
             }
         },
     );
 
     let start_format = if with_start_dates {
-        conf::FORMAT_DATETIME
+        conf::format_datetime()
     } else {
-        conf::FORMAT_TIME
+        conf::format_time()
     };
 
     let mut new_row = table::Row::new(vec![
         activity.start.format(start_format).to_string(),
         display_end,
-        activity.description.clone(),
+        format_util::format_description_with_tags(activity),
         activity.project.clone(),
         format_util::format_duration(&activity.get_duration()),
     ]);
 
+    let theme = theme::theme();
     if !activity.is_stopped() {
-        new_row.set_color(Color::Green.normal());
+        new_row.set_color(theme.running);
     } else if more_then_one_day {
-        new_row.set_color(Color::Yellow.normal());
+        new_row.set_color(theme.multiple_days);
+    } else if theme.is_old(activity, Local::now().date_naive()) {
+        new_row.set_color(theme.old);
+    }
+    if let Some(style) = theme.project_style(&activity.project) {
+        new_row.set_cell_color(3, style);
     }
 
     new_row
 }
 
+// the row of an activity followed by a dimmed row for each of its notes if `verbose` is set
+fn get_activity_table_rows(
+    activity: &activity::Activity,
+    with_start_dates: bool,
+    verbose: bool,
+) -> Vec<table::Row> {
+    let mut rows = vec![get_activity_table_row(activity, with_start_dates)];
+
+    if verbose {
+        rows.extend(activity.notes.iter().map(|note| {
+            let mut row = table::Row::new(vec![
+                String::new(),
+                String::new(),
+                format!("- {note}"),
+                String::new(),
+                String::new(),
+            ]);
+            row.set_color(theme::theme().notes);
+            row
+        }));
+    }
+
+    rows
+}
+
 // groups activities in vectors of activities that started at the same day
 fn group_activities_by_date<'a>(
     activities: &[&'a activity::Activity],
@@ -236,3 +492,112 @@ fn group_activities_by_date<'a>(
 
     activities_by_date
 }
+
+// groups activities in vectors of activities that started in the same ISO week
+fn group_activities_by_week<'a>(
+    activities: &[&'a activity::Activity],
+) -> BTreeMap<IsoWeek, Vec<&'a activity::Activity>> {
+    let mut activities_by_week: BTreeMap<IsoWeek, Vec<&'a activity::Activity>> = BTreeMap::new();
+
+    for &activity in activities {
+        activities_by_week
+            .entry(activity.start.date().iso_week())
+            .or_default()
+            .push(activity);
+    }
+
+    for activity_list in activities_by_week.values_mut() {
+        activity_list.sort_by_key(|activity| activity.start);
+    }
+
+    activities_by_week
+}
+
+// the title of a group of activities of a week, e.g. "2024-W11 (2024-03-11 - 2024-03-17)"
+fn format_week_title(week: IsoWeek) -> String {
+    let monday = NaiveDate::from_isoywd_opt(week.year(), week.week(), chrono::Weekday::Mon);
+    let sunday = NaiveDate::from_isoywd_opt(week.year(), week.week(), chrono::Weekday::Sun);
+
+    match (monday, sunday) {
+        (Some(monday), Some(sunday)) => format!(
+            "{} ({} - {})",
+            format_util::format_week(week),
+            monday.format(conf::FORMAT_DATE),
+            sunday.format(conf::FORMAT_DATE)
+        ),
+        _ => format_util::format_week(week),
+    }
+}
+
+// whether the dates belong to more than one ISO week
+pub fn spans_multiple_weeks<'a>(mut dates: impl Iterator<Item = &'a NaiveDate>) -> bool {
+    let Some(first_week) = dates.next().map(NaiveDate::iso_week) else {
+        return false;
+    };
+
+    dates.any(|date| date.iso_week() != first_week)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDateTime;
+
+    use super::*;
+    use crate::data::activity::activity;
+
+    #[test]
+    fn group_activities_by_week_test() {
+        // sunday, monday and tuesday of the following week
+        let a1 = activity("p", "2024-03-10 09:00", "2024-03-10 10:00");
+        let a2 = activity("p", "2024-03-12 09:00", "2024-03-12 10:00");
+        let a3 = activity("p", "2024-03-11 09:00", "2024-03-11 10:00");
+        let activities = vec![&a1, &a2, &a3];
+
+        let groups = group_activities_by_week(&activities);
+        let weeks: Vec<String> = groups.keys().map(|w| format_week_title(*w)).collect();
+
+        assert_eq!(
+            weeks,
+            vec![
+                "2024-W10 (2024-03-04 - 2024-03-10)",
+                "2024-W11 (2024-03-11 - 2024-03-17)"
+            ]
+        );
+        let last_week: Vec<NaiveDateTime> = groups
+            .values()
+            .last()
+            .unwrap()
+            .iter()
+            .map(|a| a.start)
+            .collect();
+        assert_eq!(last_week, vec![a3.start, a2.start]);
+    }
+
+    #[test]
+    fn spans_multiple_weeks_test() {
+        let monday = NaiveDate::from_ymd_opt(2024, 3, 11).unwrap();
+        let sunday = NaiveDate::from_ymd_opt(2024, 3, 17).unwrap();
+        let next_monday = NaiveDate::from_ymd_opt(2024, 3, 18).unwrap();
+
+        assert!(!spans_multiple_weeks([].iter()));
+        assert!(!spans_multiple_weeks([monday, sunday].iter()));
+        assert!(spans_multiple_weeks([monday, next_monday].iter()));
+    }
+
+    #[test]
+    fn format_running_compact_test() {
+        let a1 = activity("p", "2024-03-11 09:00", "2024-03-11 10:05");
+        let mut a2 = a1.clone();
+        a2.project = "q".to_string();
+
+        assert_eq!(format_running_compact(&[], None), "No activity");
+        assert_eq!(format_running_compact(&[&a1], None), "p: d 1:05:00");
+        assert_eq!(
+            format_running_compact(
+                &[&a1, &a2],
+                Some((&Duration::minutes(130), &Duration::hours(8)))
+            ),
+            "p: d 1:05:00 | q: d 1:05:00 | today: 2h 10m of 8h 00m"
+        );
+    }
+}
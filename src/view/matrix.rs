@@ -0,0 +1,300 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::{Datelike, Duration, NaiveDate};
+
+use crate::conf;
+use crate::data::activity;
+use crate::data::processor::{ReportData, ReportWriter, TotalRounding};
+use crate::outputln;
+use crate::view::format_util;
+use crate::view::json::{self, Json, ToJson};
+use crate::view::list;
+use crate::view::report;
+use crate::view::table;
+use crate::view::theme;
+
+// the output formats of the matrix
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatrixFormat {
+    Table,
+    Csv,
+    Json,
+}
+
+// the durations of a range of activities with dates as rows and projects as columns
+struct Matrix<'a> {
+    projects: Vec<&'a str>,
+    rows: Vec<MatrixRow>,
+    project_totals: Vec<Duration>,
+    total: Duration,
+}
+
+struct MatrixRow {
+    date: NaiveDate,
+    durations: Vec<Duration>,
+    total: Duration,
+}
+
+impl<'a> Matrix<'a> {
+    fn new(
+        activities: &'a [&'a activity::Activity],
+        total_rounding: Option<&TotalRounding>,
+    ) -> Matrix<'a> {
+        let projects: Vec<&str> = activities
+            .iter()
+            .map(|a| a.project.as_str())
+            .collect::<BTreeSet<&str>>()
+            .into_iter()
+            .collect();
+
+        let mut date_map: BTreeMap<NaiveDate, Vec<&activity::Activity>> = BTreeMap::new();
+        for a in activities {
+            date_map.entry(a.start.date()).or_default().push(a);
+        }
+
+        // the cells are rounded only if the totals of projects per day are rounded
+        let cell_rounding = total_rounding.filter(|r| r.rounds_projects());
+
+        let rows: Vec<MatrixRow> = date_map
+            .into_iter()
+            .map(|(date, activities)| MatrixRow {
+                date,
+                durations: projects
+                    .iter()
+                    .map(|project| {
+                        let project_activities: Vec<&activity::Activity> = activities
+                            .iter()
+                            .filter(|a| a.project == *project)
+                            .copied()
+                            .collect();
+                        report::sum_duration_with_rounding(&project_activities, cell_rounding)
+                    })
+                    .collect(),
+                total: report::sum_duration_with_rounding(&activities, total_rounding),
+            })
+            .collect();
+
+        let project_totals = (0..projects.len())
+            .map(|i| rows.iter().map(|row| row.durations[i]).sum())
+            .collect();
+
+        Matrix {
+            projects,
+            rows,
+            project_totals,
+            total: report::sum_duration_with_rounding(activities, total_rounding),
+        }
+    }
+
+    fn to_table(&self) -> table::Table {
+        let mut columns = vec![table::Column {
+            label: "Date".to_string(),
+            wrap: table::Wrap::NoWrap,
+        }];
+        columns.extend(self.projects.iter().map(|project| table::Column {
+            label: (*project).to_string(),
+            wrap: table::Wrap::Wrap,
+        }));
+        columns.push(table::Column {
+            label: "Total".to_string(),
+            wrap: table::Wrap::NoWrap,
+        });
+
+        let mut matrix_table = table::Table::new(columns);
+        let with_week = list::spans_multiple_weeks(self.rows.iter().map(|row| &row.date));
+
+        for row in &self.rows {
+            let label = if with_week {
+                format!(
+                    "{} ({})",
+                    row.date.format(conf::FORMAT_DATE),
+                    format_util::format_week(row.date.iso_week())
+                )
+            } else {
+                row.date.format(conf::FORMAT_DATE).to_string()
+            };
+
+            matrix_table.add_row(table::Row::new(create_cells(
+                label,
+                &row.durations,
+                row.total,
+                format_cell,
+            )));
+        }
+
+        let mut total_row = table::Row::new(create_cells(
+            "Total".to_string(),
+            &self.project_totals,
+            self.total,
+            format_cell,
+        ));
+        total_row.set_color(theme::theme().total);
+        matrix_table.add_row(total_row);
+
+        matrix_table
+    }
+
+    fn to_csv(&self) -> String {
+        let mut header = vec!["Date".to_string()];
+        header.extend(self.projects.iter().map(|p| (*p).to_string()));
+        header.push("Total".to_string());
+
+        let mut lines = vec![header];
+
+        for row in &self.rows {
+            lines.push(create_cells(
+                row.date.format(conf::FORMAT_DATE).to_string(),
+                &row.durations,
+                row.total,
+                format_util::format_hours,
+            ));
+        }
+
+        lines.push(create_cells(
+            "Total".to_string(),
+            &self.project_totals,
+            self.total,
+            format_util::format_hours,
+        ));
+
+        lines
+            .iter()
+            .map(|cells| {
+                cells
+                    .iter()
+                    .map(|cell| format_util::escape_csv_field(cell))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl ToJson for Matrix<'_> {
+    fn to_json(&self) -> Json {
+        let durations_by_project = |durations: &[Duration]| {
+            Json::Object(
+                self.projects
+                    .iter()
+                    .zip(durations)
+                    .map(|(project, duration)| ((*project).to_string(), Json::duration(duration)))
+                    .collect(),
+            )
+        };
+
+        let days = self
+            .rows
+            .iter()
+            .map(|row| {
+                Json::object([
+                    ("date", Json::date(&row.date)),
+                    ("projects", durations_by_project(&row.durations)),
+                    ("total", Json::duration(&row.total)),
+                ])
+            })
+            .collect();
+
+        Json::object([
+            ("days", Json::Array(days)),
+            ("projects", durations_by_project(&self.project_totals)),
+            ("total", Json::duration(&self.total)),
+        ])
+    }
+}
+
+// writes the durations per day and project
+pub struct MatrixReport {
+    pub format: MatrixFormat,
+}
+
+impl ReportWriter for MatrixReport {
+    fn process(&self, data: &ReportData) -> anyhow::Result<()> {
+        show_activities(data.activities, data.total_rounding, self.format);
+        Ok(())
+    }
+}
+
+// shows the durations of the activities per day and project
+pub fn show_activities<'a>(
+    activities: &'a [&'a activity::Activity],
+    total_rounding: Option<&TotalRounding>,
+    format: MatrixFormat,
+) {
+    let matrix = Matrix::new(activities, total_rounding);
+
+    match format {
+        MatrixFormat::Table => outputln!("\n{}", matrix.to_table()),
+        MatrixFormat::Csv => outputln!("{}", matrix.to_csv()),
+        MatrixFormat::Json => json::print(&matrix),
+    }
+}
+
+fn create_cells(
+    label: String,
+    durations: &[Duration],
+    total: Duration,
+    format: fn(&Duration) -> String,
+) -> Vec<String> {
+    let mut cells = vec![label];
+    cells.extend(durations.iter().map(format));
+    cells.push(format(&total));
+    cells
+}
+
+// empty cells are left blank in the table to make it easier to read
+fn format_cell(duration: &Duration) -> String {
+    if duration.is_zero() {
+        String::new()
+    } else {
+        format_util::format_duration(duration)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::activity::activity;
+    use crate::data::round_util::{RoundingMode, RoundingScope};
+
+    #[test]
+    fn matrix_csv() {
+        let a1 = activity("p1", "2024-03-01 09:00", "2024-03-01 10:30");
+        let a2 = activity("p, 2", "2024-03-01 11:00", "2024-03-01 11:15");
+        let a3 = activity("p1", "2024-03-02 09:00", "2024-03-02 09:45");
+        let activities = vec![&a1, &a2, &a3];
+
+        let matrix = Matrix::new(&activities, None);
+
+        assert_eq!(
+            matrix.to_csv(),
+            "Date,\"p, 2\",p1,Total\n\
+             2024-03-01,0.25,1.50,1.75\n\
+             2024-03-02,0.00,0.75,0.75\n\
+             Total,0.25,2.25,2.50"
+        );
+    }
+
+    #[test]
+    fn matrix_with_rounding_per_project_and_day() {
+        let a1 = activity("p1", "2024-03-01 09:00", "2024-03-01 09:05");
+        let a2 = activity("p2", "2024-03-01 10:00", "2024-03-01 10:05");
+        let a3 = activity("p1", "2024-03-02 09:00", "2024-03-02 09:20");
+        let activities = vec![&a1, &a2, &a3];
+
+        let rounding = TotalRounding {
+            round: Duration::minutes(15),
+            mode: RoundingMode::Up,
+            scope: RoundingScope::ProjectDay,
+        };
+        let matrix = Matrix::new(&activities, Some(&rounding));
+
+        assert_eq!(matrix.rows[0].durations, vec![Duration::minutes(15); 2]);
+        assert_eq!(matrix.rows[0].total, Duration::minutes(30));
+        assert_eq!(
+            matrix.project_totals,
+            vec![Duration::minutes(45), Duration::minutes(15)]
+        );
+        assert_eq!(matrix.total, Duration::minutes(60));
+    }
+}
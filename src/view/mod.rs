@@ -1,5 +1,24 @@
+pub mod chart;
+pub mod comparison;
+pub mod completions;
+pub mod earnings;
+pub mod export;
 pub mod format_util;
+pub mod html;
+pub mod json;
+pub mod line_editor;
 pub mod list;
+pub mod matrix;
+pub mod notification;
+pub mod output;
+pub mod picker;
 pub mod report;
 pub mod status;
+pub mod stats;
+pub mod summary;
 pub mod table;
+pub mod template;
+pub mod theme;
+pub mod timesheet;
+pub mod terminal;
+pub mod ui;
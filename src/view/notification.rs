@@ -0,0 +1,87 @@
+use chrono::Local;
+use std::io;
+use std::io::Write;
+#[cfg(unix)]
+use std::process::Command;
+
+use crate::conf;
+use crate::data::reminder::Reminder;
+use crate::view::format_util;
+
+// reminds the user with a desktop notification
+//
+// the reminder is printed, too, e.g. for the log of a service running `bartib remind`. If no
+// notification can be shown, the terminal bell rings instead.
+pub fn show_reminder(reminder: &Reminder) {
+    let message = format_reminder(reminder);
+
+    println!(
+        "{} {}",
+        Local::now().naive_local().format(conf::format_datetime()),
+        message
+    );
+
+    if send_notification("bartib", &message).is_err() {
+        print!("\x07");
+        let _ = io::stdout().flush();
+    }
+}
+
+fn format_reminder(reminder: &Reminder) -> String {
+    match reminder {
+        Reminder::Idle(idle) => format!(
+            "No activity has been tracked for {}",
+            format_util::format_duration(idle)
+        ),
+        Reminder::Running(activity) => format!(
+            "\"{}\" ({}) has been running for {}. Did you forget to stop it?",
+            activity.description,
+            activity.project,
+            format_util::format_duration(&activity.get_duration())
+        ),
+        Reminder::DailyLimit { tracked, limit } => format!(
+            "{} have been tracked today, more than the daily limit of {}",
+            format_util::format_duration(tracked),
+            format_util::format_duration(limit)
+        ),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn send_notification(title: &str, message: &str) -> io::Result<()> {
+    let quote = |s: &str| format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""));
+    let script = format!(
+        "display notification {} with title {}",
+        quote(message),
+        quote(title)
+    );
+
+    run(Command::new("osascript").arg("-e").arg(script))
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn send_notification(title: &str, message: &str) -> io::Result<()> {
+    run(Command::new("notify-send")
+        .arg("--app-name=bartib")
+        .arg(title)
+        .arg(message))
+}
+
+#[cfg(not(unix))]
+fn send_notification(_title: &str, _message: &str) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "desktop notifications are only supported on linux and macos",
+    ))
+}
+
+#[cfg(unix)]
+fn run(command: &mut Command) -> io::Result<()> {
+    let status = command.status()?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("notification failed: {status}")))
+    }
+}
@@ -0,0 +1,32 @@
+use clap::ValueEnum;
+
+// the format in which commands print their results
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    // tables and reports for the terminal
+    #[default]
+    Text,
+    // machine readable JSON on a single line
+    Json,
+}
+
+// prints the normal output of a command like `print!`, unless it is suppressed by `--quiet`.
+// Errors and warnings are always printed
+#[macro_export]
+macro_rules! output {
+    ($($arg:tt)*) => {
+        if !$crate::conf::quiet() {
+            print!($($arg)*);
+        }
+    };
+}
+
+// prints the normal output of a command like `println!`, unless it is suppressed by `--quiet`
+#[macro_export]
+macro_rules! outputln {
+    ($($arg:tt)*) => {
+        if !$crate::conf::quiet() {
+            println!($($arg)*);
+        }
+    };
+}
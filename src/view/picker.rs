@@ -0,0 +1,272 @@
+use std::io;
+use std::io::{BufRead, IsTerminal, Write};
+use std::time::Duration;
+
+use nu_ansi_term::Style;
+
+use crate::view::terminal;
+use crate::view::theme;
+
+// the number of items shown below the search term
+const VISIBLE_ITEMS: usize = 10;
+
+// how long to wait for the rest of an escape sequence before an escape is taken as a key of its own
+const ESCAPE_TIMEOUT: Duration = Duration::from_millis(100);
+
+// lets the user pick an item by typing parts of it, like fzf. Returns the index of the item or
+// `None` if nothing has been picked
+//
+// on unix terminals the items matching the search term are shown while typing and are selected
+// with the arrow keys. In all other cases a search term or the number of an item is read from a
+// plain line of input.
+pub fn pick(prompt: &str, items: &[String]) -> io::Result<Option<usize>> {
+    if items.is_empty() {
+        return Ok(None);
+    }
+
+    if io::stdin().is_terminal()
+        && io::stdout().is_terminal()
+        && let Ok(_raw_mode) = terminal::RawMode::enable_with_timeout(ESCAPE_TIMEOUT)
+    {
+        return pick_interactive(prompt, items);
+    }
+
+    pick_plain(prompt, items)
+}
+
+// the indexes of the items matching the search term, the best matches first. Items matching
+// equally well keep their order
+#[must_use]
+pub fn filter_items(search_term: &str, items: &[String]) -> Vec<usize> {
+    let mut matches: Vec<(usize, usize)> = items
+        .iter()
+        .enumerate()
+        .filter_map(|(index, item)| fuzzy_score(search_term, item).map(|score| (index, score)))
+        .collect();
+
+    matches.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+    matches.into_iter().map(|(index, _)| index).collect()
+}
+
+// how well an item matches a search term whose characters must all appear in the item in the
+// same order, ignoring case and whitespace. Characters following each other and characters at
+// the beginning of words score higher. Returns `None` if the item does not match
+#[must_use]
+pub fn fuzzy_score(search_term: &str, item: &str) -> Option<usize> {
+    let search_chars: Vec<char> = search_term
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    let item_chars: Vec<char> = item.chars().flat_map(char::to_lowercase).collect();
+
+    let Some(first_search_char) = search_chars.first() else {
+        return Some(0);
+    };
+
+    // the characters are matched as early as possible after each occurrence of the first one
+    item_chars
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| *c == first_search_char)
+        .filter_map(|(start, _)| score_from(&search_chars, &item_chars, start))
+        .max()
+}
+
+fn score_from(search_chars: &[char], item_chars: &[char], start: usize) -> Option<usize> {
+    let mut remaining = search_chars.iter().peekable();
+    let mut score = 0;
+    let mut previous_matched = false;
+
+    for (position, c) in item_chars.iter().enumerate().skip(start) {
+        let Some(search_char) = remaining.peek() else {
+            break;
+        };
+
+        let is_match = c == *search_char;
+
+        if is_match {
+            score += 1;
+
+            if previous_matched {
+                score += 2;
+            } else if position == 0 || !item_chars[position - 1].is_alphanumeric() {
+                score += 3;
+            }
+
+            remaining.next();
+        }
+
+        previous_matched = is_match;
+    }
+
+    remaining.peek().is_none().then_some(score)
+}
+
+fn pick_interactive(prompt: &str, items: &[String]) -> io::Result<Option<usize>> {
+    let mut stdin = io::stdin().lock();
+    let mut stdout = io::stdout();
+    let mut search_term = String::new();
+    let mut selected = 0;
+    let mut matches = filter_items(&search_term, items);
+
+    redraw(&mut stdout, prompt, &search_term, items, &matches, selected)?;
+
+    let picked = loop {
+        let Some(byte) = terminal::read_byte(&mut stdin)? else {
+            continue;
+        };
+
+        match byte {
+            b'\r' | b'\n' => break matches.get(selected).copied(),
+            // Ctrl-C, Ctrl-D and Ctrl-G
+            3 | 4 | 7 => break None,
+            // Ctrl-P and Ctrl-N
+            16 => selected = selected.saturating_sub(1),
+            14 | b'\t' => selected = (selected + 1).min(matches.len().saturating_sub(1)),
+            // Backspace
+            8 | 127 => {
+                search_term.pop();
+                matches = filter_items(&search_term, items);
+                selected = 0;
+            }
+            // escape sequences (e.g. arrow keys) or the escape key itself
+            27 => match terminal::read_byte(&mut stdin)? {
+                Some(b'[') => match terminal::read_byte(&mut stdin)? {
+                    Some(b'A') => selected = selected.saturating_sub(1),
+                    Some(b'B') => selected = (selected + 1).min(matches.len().saturating_sub(1)),
+                    _ => {}
+                },
+                _ => break None,
+            },
+            byte if byte < 32 => {}
+            byte => {
+                let mut bytes = vec![byte];
+                for _ in 1..terminal::utf8_length(byte) {
+                    if let Some(next) = terminal::read_byte(&mut stdin)? {
+                        bytes.push(next);
+                    }
+                }
+                search_term.push_str(&String::from_utf8_lossy(&bytes));
+                matches = filter_items(&search_term, items);
+                selected = 0;
+            }
+        }
+
+        redraw(&mut stdout, prompt, &search_term, items, &matches, selected)?;
+    };
+
+    write!(stdout, "\r\x1b[J")?;
+    stdout.flush()?;
+
+    Ok(picked)
+}
+
+// shows the search term with the best matching items below it and leaves the cursor behind the
+// search term
+fn redraw(
+    stdout: &mut io::Stdout,
+    prompt: &str,
+    search_term: &str,
+    items: &[String],
+    matches: &[usize],
+    selected: usize,
+) -> io::Result<()> {
+    let width = term_size::dimensions_stdout().map_or(80, |(width, _)| width);
+    let first_visible = selected.saturating_sub(VISIBLE_ITEMS - 1);
+    let visible_matches = matches.iter().skip(first_visible).take(VISIBLE_ITEMS);
+
+    write!(stdout, "\r\x1b[J{prompt}{search_term}")?;
+
+    let mut lines = 0;
+    for (position, index) in visible_matches.enumerate() {
+        let item: String = items[*index]
+            .chars()
+            .take(width.saturating_sub(3))
+            .collect();
+
+        if first_visible + position == selected {
+            write!(
+                stdout,
+                "\r\n{}",
+                theme::style(Style::new().reverse()).paint(format!("> {item}"))
+            )?;
+        } else {
+            write!(stdout, "\r\n  {item}")?;
+        }
+        lines += 1;
+    }
+
+    if matches.is_empty() {
+        write!(
+            stdout,
+            "\r\n  {}",
+            theme::style(Style::new().dimmed()).paint("no match")
+        )?;
+        lines += 1;
+    }
+
+    let column = prompt.chars().count() + search_term.chars().count();
+    write!(stdout, "\x1b[{lines}A\r")?;
+    if column > 0 {
+        write!(stdout, "\x1b[{column}C")?;
+    }
+
+    stdout.flush()
+}
+
+// shows the first items with their numbers and reads a number or a search term. The best match
+// of the search term is picked
+fn pick_plain(prompt: &str, items: &[String]) -> io::Result<Option<usize>> {
+    for (index, item) in items.iter().enumerate().take(VISIBLE_ITEMS).rev() {
+        println!("[{index}] {item}");
+    }
+
+    print!("{prompt}");
+    io::stdout().flush()?;
+
+    let mut line = String::new();
+    if io::stdin().lock().read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    let line = line.trim();
+
+    if let Ok(number) = line.parse::<usize>()
+        && number < items.len()
+    {
+        return Ok(Some(number));
+    }
+
+    Ok(filter_items(line, items).first().copied())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_score_test() {
+        assert!(fuzzy_score("", "anything").is_some());
+        assert!(fuzzy_score("fxt", "Fix the tests").is_some());
+        assert!(fuzzy_score("FIX", "fix").is_some());
+        assert_eq!(fuzzy_score("tf", "fix the tests"), None);
+        assert!(
+            fuzzy_score("test", "fix the tests") > fuzzy_score("test", "the east side"),
+            "consecutive characters score higher"
+        );
+    }
+
+    #[test]
+    fn filter_items_test() {
+        let items = vec![
+            "review pull request  web".to_string(),
+            "write release notes  web".to_string(),
+            "deploy  api".to_string(),
+        ];
+
+        assert_eq!(filter_items("", &items), vec![0, 1, 2]);
+        assert_eq!(filter_items("rel", &items), vec![1, 0]);
+        assert_eq!(filter_items("api", &items), vec![2]);
+        assert_eq!(filter_items("xyz", &items), Vec::<usize>::new());
+    }
+}
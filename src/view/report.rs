@@ -1,35 +1,137 @@
 use std::collections::BTreeMap;
 use std::fmt;
-use std::fmt::Formatter;
 use std::ops::Add;
 
-use chrono::Duration;
+use chrono::{Datelike, Duration};
+use clap::ValueEnum;
 use nu_ansi_term::Style;
 use textwrap;
 
 use crate::conf;
 use crate::data::activity;
+use crate::data::processor::{ReportData, ReportWriter, TotalRounding};
+use crate::outputln;
 use crate::view::format_util;
+use crate::view::json::{self, Json, ToJson};
+use crate::view::theme;
+
+// the groups of the first level of a report. Each group is broken down by the following levels
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ReportGrouping {
+    #[default]
+    Project,
+    Description,
+    Day,
+    Week,
+    Month,
+}
+
+// the formats of a report besides JSON
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    // a table in the terminal
+    #[default]
+    Text,
+    // a self-contained HTML page, e.g. for clients
+    Html,
+}
+
+impl ReportGrouping {
+    // the levels of the report, from the groups of the first level down to the sublines
+    fn levels(self) -> &'static [Self] {
+        match self {
+            Self::Project => &[Self::Project, Self::Description],
+            Self::Description => &[Self::Description, Self::Project],
+            Self::Day => &[Self::Day, Self::Project, Self::Description],
+            Self::Week => &[Self::Week, Self::Project, Self::Description],
+            Self::Month => &[Self::Month, Self::Project, Self::Description],
+        }
+    }
+
+    // the name of the group an activity belongs to. Days, weeks and months are named so that
+    // their names sort chronologically
+    fn group_name(self, activity: &activity::Activity) -> String {
+        match self {
+            Self::Project => activity.project.clone(),
+            Self::Description => activity.description.clone(),
+            Self::Day => activity.start.format("%F").to_string(),
+            Self::Week => format_util::format_week(activity.start.date().iso_week()),
+            Self::Month => activity.start.format("%Y-%m").to_string(),
+        }
+    }
+
+    // the total of a group. Days, weeks and months are always rounded like the total of the
+    // report, projects only if the rounding is done per project
+    pub(crate) fn sum_duration(
+        self,
+        activities: &[&activity::Activity],
+        total_rounding: Option<&TotalRounding>,
+    ) -> Duration {
+        match self {
+            Self::Description => sum_duration(activities),
+            Self::Project => {
+                let project_rounding = total_rounding.filter(|r| r.rounds_projects());
+                sum_duration_with_rounding(activities, project_rounding)
+            }
+            Self::Day | Self::Week | Self::Month => {
+                sum_duration_with_rounding(activities, total_rounding)
+            }
+        }
+    }
+
+    fn json_key(self) -> &'static str {
+        match self {
+            Self::Project => "project",
+            Self::Description => "description",
+            Self::Day => "day",
+            Self::Week => "week",
+            Self::Month => "month",
+        }
+    }
+
+    fn json_list_key(self) -> &'static str {
+        match self {
+            Self::Project => "projects",
+            Self::Description => "descriptions",
+            Self::Day => "days",
+            Self::Week => "weeks",
+            Self::Month => "months",
+        }
+    }
+}
 
-type ProjectMap<'a> = BTreeMap<&'a str, (Vec<&'a activity::Activity>, Duration)>;
+// a line of the report with the total of its activities, broken down into the groups of the
+// next level
+struct Group {
+    grouping: ReportGrouping,
+    name: String,
+    duration: Duration,
+    subgroups: Vec<Group>,
+}
 
-struct Report<'a> {
-    project_map: ProjectMap<'a>,
+struct Report {
+    grouping: ReportGrouping,
+    groups: Vec<Group>,
     total_duration: Duration,
 }
 
-impl<'a> Report<'a> {
-    fn new(activities: &'a [&'a activity::Activity]) -> Report<'a> {
+impl Report {
+    fn new(
+        activities: &[&activity::Activity],
+        grouping: ReportGrouping,
+        total_rounding: Option<&TotalRounding>,
+    ) -> Report {
         Report {
-            project_map: create_project_map(activities),
-            total_duration: sum_duration(activities),
+            grouping,
+            groups: create_groups(activities, grouping.levels(), total_rounding),
+            total_duration: sum_duration_with_rounding(activities, total_rounding),
         }
     }
 }
 
-impl fmt::Display for Report<'_> {
+impl fmt::Display for Report {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut longest_line = get_longest_line(&self.project_map).unwrap_or(0);
+        let mut longest_line = get_longest_line(&self.groups, 0).unwrap_or(0);
         let longest_duration_string = get_longest_duration_string(self).unwrap_or(0);
 
         let terminal_width = term_size::dimensions_stdout().map_or(conf::DEFAULT_WIDTH, |d| d.0);
@@ -38,15 +140,8 @@ impl fmt::Display for Report<'_> {
             longest_line = terminal_width - longest_duration_string - 1;
         }
 
-        for (project, (activities, duration)) in &self.project_map {
-            print_project_heading(f, project, duration, longest_line, longest_duration_string)?;
-
-            print_descriptions_with_durations(
-                f,
-                activities,
-                longest_line,
-                longest_duration_string,
-            )?;
+        for group in &self.groups {
+            print_group(f, group, 0, longest_line, longest_duration_string)?;
             writeln!(f)?;
         }
 
@@ -56,27 +151,112 @@ impl fmt::Display for Report<'_> {
     }
 }
 
-pub fn show_activities<'a>(activities: &'a [&'a activity::Activity]) {
-    let report = Report::new(activities);
-    println!("\n{report}");
+impl ToJson for Group {
+    fn to_json(&self) -> Json {
+        match self.subgroups.first() {
+            Some(subgroup) => Json::object([
+                (self.grouping.json_key(), Json::string(&self.name)),
+                ("duration", Json::duration(&self.duration)),
+                (
+                    subgroup.grouping.json_list_key(),
+                    Json::Array(self.subgroups.iter().map(ToJson::to_json).collect()),
+                ),
+            ]),
+            None => Json::object([
+                (self.grouping.json_key(), Json::string(&self.name)),
+                ("duration", Json::duration(&self.duration)),
+            ]),
+        }
+    }
+}
+
+impl ToJson for Report {
+    fn to_json(&self) -> Json {
+        Json::object([
+            (
+                self.grouping.json_list_key(),
+                Json::Array(self.groups.iter().map(ToJson::to_json).collect()),
+            ),
+            ("total", Json::duration(&self.total_duration)),
+        ])
+    }
+}
+
+pub fn show_activities(
+    activities: &[&activity::Activity],
+    grouping: ReportGrouping,
+    total_rounding: Option<&TotalRounding>,
+) {
+    let report = Report::new(activities, grouping, total_rounding);
+    outputln!("\n{report}");
 }
 
-fn create_project_map<'a>(activities: &'a [&'a activity::Activity]) -> ProjectMap<'a> {
-    let mut project_map: ProjectMap = BTreeMap::new();
+pub fn show_activities_as_json(
+    activities: &[&activity::Activity],
+    grouping: ReportGrouping,
+    total_rounding: Option<&TotalRounding>,
+) {
+    json::print(&Report::new(activities, grouping, total_rounding));
+}
 
-    for a in activities {
-        project_map
-            .entry(&a.project)
-            .or_insert_with(|| (Vec::<&'a activity::Activity>::new(), Duration::seconds(0)))
-            .0
-            .push(a);
+// writes the durations per group as text
+pub struct GroupedReport {
+    pub grouping: ReportGrouping,
+}
+
+impl ReportWriter for GroupedReport {
+    fn process(&self, data: &ReportData) -> anyhow::Result<()> {
+        show_activities(data.activities, self.grouping, data.total_rounding);
+        Ok(())
+    }
+}
+
+pub struct GroupedReportJson {
+    pub grouping: ReportGrouping,
+}
+
+impl ReportWriter for GroupedReportJson {
+    fn process(&self, data: &ReportData) -> anyhow::Result<()> {
+        show_activities_as_json(data.activities, self.grouping, data.total_rounding);
+        Ok(())
     }
+}
+
+// groups the activities by the first level and each group recursively by the remaining levels
+fn create_groups(
+    activities: &[&activity::Activity],
+    levels: &[ReportGrouping],
+    total_rounding: Option<&TotalRounding>,
+) -> Vec<Group> {
+    let Some((grouping, sublevels)) = levels.split_first() else {
+        return Vec::new();
+    };
+
+    group_activities(activities, *grouping)
+        .into_iter()
+        .map(|(name, activities)| Group {
+            grouping: *grouping,
+            name,
+            duration: grouping.sum_duration(&activities, total_rounding),
+            subgroups: create_groups(&activities, sublevels, total_rounding),
+        })
+        .collect()
+}
 
-    for (activities, duration) in project_map.values_mut() {
-        *duration = sum_duration(activities);
+pub(crate) fn group_activities<'a>(
+    activities: &[&'a activity::Activity],
+    grouping: ReportGrouping,
+) -> BTreeMap<String, Vec<&'a activity::Activity>> {
+    let mut activity_map: BTreeMap<String, Vec<&'a activity::Activity>> = BTreeMap::new();
+
+    for a in activities {
+        activity_map
+            .entry(grouping.group_name(a))
+            .or_default()
+            .push(a);
     }
 
-    project_map
+    activity_map
 }
 
 pub fn sum_duration(activities: &[&activity::Activity]) -> Duration {
@@ -89,64 +269,57 @@ pub fn sum_duration(activities: &[&activity::Activity]) -> Duration {
     duration
 }
 
-fn print_project_heading(
-    f: &mut Formatter,
-    project: &&str,
-    duration: &Duration,
-    longest_line: usize,
+// sums up the durations and rounds the totals if requested
+pub fn sum_duration_with_rounding(
+    activities: &[&activity::Activity],
+    total_rounding: Option<&TotalRounding>,
+) -> Duration {
+    total_rounding.map_or_else(
+        || sum_duration(activities),
+        |rounding| rounding.sum_duration(activities),
+    )
+}
+
+// prints a group and its subgroups. Groups with subgroups are printed as bold headings
+fn print_group(
+    f: &mut fmt::Formatter<'_>,
+    group: &Group,
+    depth: usize,
+    line_width: usize,
     duration_width: usize,
 ) -> fmt::Result {
-    write!(f, "{}", Style::new().bold().prefix())?;
-    let project_lines = textwrap::wrap(project, textwrap::Options::new(longest_line));
+    let style = if group.subgroups.is_empty() {
+        Style::new()
+    } else {
+        theme::style(theme::theme().heading)
+    };
+    let indent_string = " ".repeat(conf::REPORT_INDENTATION * depth);
+    let wrapping_options = textwrap::Options::new(line_width)
+        .initial_indent(&indent_string)
+        .subsequent_indent(&indent_string);
 
-    for (i, line) in project_lines.iter().enumerate() {
-        if i + 1 < project_lines.len() {
+    write!(f, "{}", style.prefix())?;
+    let name_lines = textwrap::wrap(&group.name, &wrapping_options);
+
+    for (i, line) in name_lines.iter().enumerate() {
+        if i + 1 < name_lines.len() {
             writeln!(f, "{line}")?;
         } else {
             write!(
                 f,
                 "{line:.<width$} {duration:>duration_width$}",
                 line = line,
-                width = longest_line,
-                duration = format_util::format_duration(duration),
+                width = line_width,
+                duration = format_util::format_duration(&group.duration),
                 duration_width = duration_width
             )?;
         }
     }
 
-    writeln!(f, "{}", Style::new().bold().infix(Style::new()))
-}
-
-fn print_descriptions_with_durations<'a>(
-    f: &mut fmt::Formatter<'_>,
-    activities: &'a [&'a activity::Activity],
-    line_width: usize,
-    duration_width: usize,
-) -> fmt::Result {
-    let description_map = group_activities_by_description(activities);
-    let indent_string = " ".repeat(conf::REPORT_INDENTATION);
-    let wrapping_options = textwrap::Options::new(line_width)
-        .initial_indent(&indent_string)
-        .subsequent_indent(&indent_string);
-
-    for (description, activities) in &description_map {
-        let description_duration = sum_duration(activities);
-        let description_lines = textwrap::wrap(description, &wrapping_options);
+    writeln!(f, "{}", style.infix(Style::new()))?;
 
-        for (i, line) in description_lines.iter().enumerate() {
-            if i + 1 < description_lines.len() {
-                writeln!(f, "{line}")?;
-            } else {
-                writeln!(
-                    f,
-                    "{line:.<width$} {duration:>duration_width$}",
-                    line = line,
-                    width = line_width,
-                    duration = format_util::format_duration(&description_duration),
-                    duration_width = duration_width
-                )?;
-            }
-        }
+    for subgroup in &group.subgroups {
+        print_group(f, subgroup, depth + 1, line_width, duration_width)?;
     }
 
     Ok(())
@@ -157,63 +330,54 @@ fn print_total_duration(
     total_duration: Duration,
     line_width: usize,
 ) -> fmt::Result {
+    let style = theme::style(theme::theme().total);
     writeln!(
         f,
         "{prefix}{total:.<width$} {duration}{suffix}",
-        prefix = Style::new().bold().prefix(),
+        prefix = style.prefix(),
         total = "Total",
         width = line_width,
         duration = format_util::format_duration(&total_duration),
-        suffix = Style::new().bold().infix(Style::new())
+        suffix = style.infix(Style::new())
     )?;
 
     Ok(())
 }
 
-fn group_activities_by_description<'a>(
-    activities: &'a [&'a activity::Activity],
-) -> BTreeMap<&'a str, Vec<&'a activity::Activity>> {
-    let mut activity_map: BTreeMap<&'a str, Vec<&'a activity::Activity>> = BTreeMap::new();
-
-    for a in activities {
-        activity_map.entry(&a.description).or_default().push(a);
-    }
-
-    activity_map
-}
-
-fn get_longest_line(project_map: &ProjectMap) -> Option<usize> {
-    let longest_project_line = project_map.keys().map(|p| p.chars().count()).max();
-    let longest_activity_line = project_map
-        .values()
-        .flat_map(|(a, _d)| a)
-        .map(|a| a.description.chars().count() + conf::REPORT_INDENTATION)
+fn get_longest_line(groups: &[Group], depth: usize) -> Option<usize> {
+    let longest_group_line = groups
+        .iter()
+        .map(|g| g.name.chars().count() + conf::REPORT_INDENTATION * depth)
         .max();
-    get_max_option(longest_project_line, longest_activity_line)
+    let longest_subgroup_line = groups
+        .iter()
+        .filter_map(|g| get_longest_line(&g.subgroups, depth + 1))
+        .max();
+    get_max_option(longest_group_line, longest_subgroup_line)
 }
 
-fn get_longest_duration_string(report: &Report) -> Option<usize> {
-    let longest_project_duration = report
-        .project_map
-        .values()
-        .map(|(_a, d)| format_util::format_duration(d))
+fn get_longest_duration_in_groups(groups: &[Group]) -> Option<usize> {
+    let longest_group_duration = groups
+        .iter()
+        .map(|g| format_util::format_duration(&g.duration))
         .map(|s| s.chars().count())
         .max();
-    let longest_activity_duration = report
-        .project_map
-        .values()
-        .flat_map(|(a, _d)| a)
-        .map(|a| format_util::format_duration(&a.get_duration()))
-        .map(|s| s.chars().count())
+    let longest_subgroup_duration = groups
+        .iter()
+        .filter_map(|g| get_longest_duration_in_groups(&g.subgroups))
         .max();
+    get_max_option(longest_group_duration, longest_subgroup_duration)
+}
 
-    let longest_single_duration =
-        get_max_option(longest_project_duration, longest_activity_duration);
+fn get_longest_duration_string(report: &Report) -> Option<usize> {
     let length_of_total_duration = format_util::format_duration(&report.total_duration)
         .chars()
         .count();
 
-    get_max_option(longest_single_duration, Some(length_of_total_duration))
+    get_max_option(
+        get_longest_duration_in_groups(&report.groups),
+        Some(length_of_total_duration),
+    )
 }
 
 fn get_max_option(o1: Option<usize>, o2: Option<usize>) -> Option<usize> {
@@ -237,6 +401,7 @@ mod tests {
     use chrono::NaiveDateTime;
 
     use super::*;
+    use crate::data::activity::activity;
 
     #[test]
     fn sum_duration_test() {
@@ -288,11 +453,11 @@ mod tests {
         let a3 = activity::Activity::start("p2".to_string(), "d1".to_string(), None);
 
         let activities = vec![&a1, &a2, &a3];
-        let m = create_project_map(&activities);
+        let m = group_activities(&activities, ReportGrouping::Project);
 
         assert_eq!(m.len(), 2);
-        assert_eq!(m.get("p1").unwrap().0.len(), 2);
-        assert_eq!(m.get("p2").unwrap().0.len(), 1);
+        assert_eq!(m.get("p1").unwrap().len(), 2);
+        assert_eq!(m.get("p2").unwrap().len(), 1);
     }
 
     #[test]
@@ -303,7 +468,7 @@ mod tests {
         let a4 = activity::Activity::start("p2".to_string(), "d1".to_string(), None);
 
         let activities = vec![&a1, &a2, &a3, &a4];
-        let m = group_activities_by_description(&activities);
+        let m = group_activities(&activities, ReportGrouping::Description);
 
         assert_eq!(m.len(), 2);
         assert_eq!(m.get("d1").unwrap().len(), 3);
@@ -313,10 +478,10 @@ mod tests {
     #[test]
     fn get_longest_line_test() {
         let mut activities: Vec<&activity::Activity> = Vec::new();
-        let project_map1 = create_project_map(&activities);
+        let groups1 = create_groups(&activities, ReportGrouping::Project.levels(), None);
 
         // keine Einträge -> keine Längste Zeile
-        assert_eq!(get_longest_line(&project_map1), None);
+        assert_eq!(get_longest_line(&groups1, 0), None);
 
         let a1 = activity::Activity::start("p1".to_string(), "d1".to_string(), None);
         let a2 = activity::Activity::start("p1".to_string(), "d2".to_string(), None);
@@ -331,14 +496,72 @@ mod tests {
         activities.push(&a5);
 
         // längste Zeile ist Description + 4
-        let project_map2 = create_project_map(&activities);
-        assert_eq!(get_longest_line(&project_map2).unwrap(), 6);
+        let groups2 = create_groups(&activities, ReportGrouping::Project.levels(), None);
+        assert_eq!(get_longest_line(&groups2, 0).unwrap(), 6);
 
         // längste Zeile ist Projektname mit 8 Zeichen
         let a6 = activity::Activity::start("p1234567".to_string(), "d1".to_string(), None);
         activities.push(&a6);
-        let project_map3 = create_project_map(&activities);
-        assert_eq!(get_longest_line(&project_map3).unwrap(), 8);
+        let groups3 = create_groups(&activities, ReportGrouping::Project.levels(), None);
+        assert_eq!(get_longest_line(&groups3, 0).unwrap(), 8);
+    }
+
+    #[test]
+    fn group_by_month_test() {
+        let a1 = activity::Activity {
+            description: "d1".to_string(),
+            ..activity("p1", "2024-02-28 09:00", "2024-02-28 10:00")
+        };
+        let a2 = activity::Activity {
+            description: "d2".to_string(),
+            ..activity("p1", "2024-03-01 09:00", "2024-03-01 09:30")
+        };
+        let a3 = activity::Activity {
+            description: "d1".to_string(),
+            ..activity("p2", "2024-03-02 09:00", "2024-03-02 11:00")
+        };
+        let a4 = activity::Activity {
+            description: "d2".to_string(),
+            ..activity("p1", "2024-03-03 09:00", "2024-03-03 09:15")
+        };
+
+        let activities = vec![&a1, &a2, &a3, &a4];
+        let groups = create_groups(&activities, ReportGrouping::Month.levels(), None);
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].name, "2024-02");
+        assert_eq!(groups[1].name, "2024-03");
+        assert_eq!(groups[1].duration, Duration::minutes(165));
+
+        let projects = &groups[1].subgroups;
+        assert_eq!(projects.len(), 2);
+        assert_eq!(projects[0].name, "p1");
+        assert_eq!(projects[0].duration, Duration::minutes(45));
+        assert_eq!(projects[0].subgroups.len(), 1);
+        assert_eq!(projects[0].subgroups[0].name, "d2");
+        assert!(projects[0].subgroups[0].subgroups.is_empty());
+
+        // lines are indented by their depth
+        assert_eq!(
+            get_longest_line(&groups, 0).unwrap(),
+            2 + 2 * conf::REPORT_INDENTATION
+        );
+    }
+
+    #[test]
+    fn group_by_week_json_test() {
+        let a1 = activity::Activity {
+            description: "d1".to_string(),
+            ..activity("p1", "2024-03-01 09:00", "2024-03-01 10:00")
+        };
+        let activities = vec![&a1];
+
+        assert_eq!(
+            Report::new(&activities, ReportGrouping::Week, None)
+                .to_json()
+                .to_string(),
+            r#"{"weeks":[{"week":"2024-W09","duration":3600,"projects":[{"project":"p1","duration":3600,"descriptions":[{"description":"d1","duration":3600}]}]}],"total":3600}"#
+        );
     }
 
     #[test]
@@ -0,0 +1,293 @@
+use chrono::{Duration, NaiveDate};
+use nu_ansi_term::Style;
+
+use crate::conf;
+use crate::data::processor::{ReportData, ReportWriter};
+use crate::data::stats::{MonthStats, Stats, Streak, WeekdayStats};
+use crate::view::format_util;
+use crate::view::json::{self, Json, ToJson};
+use crate::view::table;
+use crate::view::theme;
+use crate::{output, outputln};
+
+// the width of the names of the figures in the summary
+const NAME_WIDTH: usize = 20;
+
+pub struct StatsReport {}
+
+impl ReportWriter for StatsReport {
+    fn process(&self, data: &ReportData) -> anyhow::Result<()> {
+        let stats = Stats::new(data.activities);
+        outputln!("{}", title(data));
+
+        if stats.tracked_days == 0 {
+            outputln!("\nNo time tracked");
+            return Ok(());
+        }
+
+        outputln!("\n{}", summary(&stats));
+        // tables end with a line break
+        output!("\n{}", weekday_table(&stats));
+        output!("\n{}", month_table(&stats));
+        Ok(())
+    }
+}
+
+// writes the statistics as JSON
+pub struct StatsJson {}
+
+impl ReportWriter for StatsJson {
+    fn process(&self, data: &ReportData) -> anyhow::Result<()> {
+        json::print(&Stats::new(data.activities));
+        Ok(())
+    }
+}
+
+impl ToJson for Stats {
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("duration", Json::duration(&self.total)),
+            ("tracked_days", Json::Integer(count(self.tracked_days))),
+            ("average_day", Json::duration(&self.average_day)),
+            (
+                "average_start",
+                Json::optional(self.average_start, |time| {
+                    Json::string(&format_time_of_day(time))
+                }),
+            ),
+            (
+                "average_end",
+                Json::optional(self.average_end, |time| {
+                    Json::string(&format_time_of_day(time))
+                }),
+            ),
+            (
+                "busiest_weekday",
+                Json::optional(self.busiest_weekday(), |weekday| {
+                    Json::string(&weekday.weekday.to_string())
+                }),
+            ),
+            (
+                "longest_streak",
+                Json::optional(self.longest_streak.as_ref(), ToJson::to_json),
+            ),
+            (
+                "weekdays",
+                Json::Array(self.weekdays.iter().map(ToJson::to_json).collect()),
+            ),
+            (
+                "months",
+                Json::Array(self.months.iter().map(ToJson::to_json).collect()),
+            ),
+        ])
+    }
+}
+
+impl ToJson for WeekdayStats {
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("weekday", Json::string(&self.weekday.to_string())),
+            ("tracked_days", Json::Integer(count(self.tracked_days))),
+            ("duration", Json::duration(&self.total)),
+            ("average", Json::duration(&self.average())),
+        ])
+    }
+}
+
+impl ToJson for Streak {
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("first_day", Json::date(&self.first)),
+            ("last_day", Json::date(&self.last)),
+            ("days", Json::Integer(self.days())),
+        ])
+    }
+}
+
+impl ToJson for MonthStats {
+    fn to_json(&self) -> Json {
+        let projects = self
+            .projects
+            .iter()
+            .map(|(project, duration)| {
+                Json::object([
+                    ("project", Json::string(project)),
+                    ("duration", Json::duration(duration)),
+                ])
+            })
+            .collect();
+
+        Json::object([
+            ("month", Json::string(&format_month(self.month))),
+            ("projects", Json::Array(projects)),
+            ("duration", Json::duration(&self.total)),
+        ])
+    }
+}
+
+fn count(count: usize) -> i64 {
+    i64::try_from(count).unwrap_or(i64::MAX)
+}
+
+// the range of the statistics as far as it has been restricted
+fn title(data: &ReportData) -> String {
+    let title = match (data.from_date, data.to_date) {
+        (Some(from), Some(to)) => format!(
+            "Statistics from {} to {}",
+            from.format(conf::FORMAT_DATE),
+            to.format(conf::FORMAT_DATE)
+        ),
+        (Some(from), None) => format!("Statistics since {}", from.format(conf::FORMAT_DATE)),
+        (None, Some(to)) => format!("Statistics until {}", to.format(conf::FORMAT_DATE)),
+        (None, None) => "Statistics of all activities".to_string(),
+    };
+
+    theme::style(theme::theme().heading)
+        .paint(title)
+        .to_string()
+}
+
+fn summary(stats: &Stats) -> String {
+    let mut lines = vec![
+        line("Total", format_util::format_duration(&stats.total)),
+        line("Days tracked", stats.tracked_days.to_string()),
+        line(
+            "Average day",
+            format_util::format_duration(&stats.average_day),
+        ),
+    ];
+
+    if let Some(start) = stats.average_start {
+        lines.push(line("Average start", format_time_of_day(start)));
+    }
+    if let Some(end) = stats.average_end {
+        lines.push(line("Average end", format_time_of_day(end)));
+    }
+    if let Some(busiest) = stats.busiest_weekday() {
+        lines.push(line(
+            "Busiest weekday",
+            format!(
+                "{} ({} on average)",
+                busiest.weekday,
+                format_util::format_duration(&busiest.average())
+            ),
+        ));
+    }
+    if let Some(streak) = stats.longest_streak {
+        let days = match streak.days() {
+            1 => "1 day".to_string(),
+            days => format!("{days} days"),
+        };
+        lines.push(line(
+            "Longest streak",
+            format!(
+                "{days} ({} - {})",
+                streak.first.format(conf::FORMAT_DATE),
+                streak.last.format(conf::FORMAT_DATE)
+            ),
+        ));
+    }
+
+    lines.join("\n")
+}
+
+fn line(name: &str, value: String) -> String {
+    format!(
+        " {} {}",
+        theme::style(Style::new().italic().dimmed()).paint(format!("{name:.<NAME_WIDTH$}")),
+        theme::style(Style::new().bold()).paint(value)
+    )
+}
+
+// a row per day of the week with the busiest one highlighted
+fn weekday_table(stats: &Stats) -> table::Table {
+    let mut weekday_table = table::Table::new(vec![
+        column("Weekday"),
+        column("Days"),
+        column("Total"),
+        column("Average"),
+    ]);
+    let busiest = stats.busiest_weekday().map(|busiest| busiest.weekday);
+
+    for weekday in &stats.weekdays {
+        // days of the week without any time tracked are left blank
+        let format_duration = |duration: Duration| {
+            if weekday.tracked_days == 0 {
+                String::new()
+            } else {
+                format_util::format_duration(&duration)
+            }
+        };
+        let mut row = table::Row::new(vec![
+            weekday.weekday.to_string(),
+            weekday.tracked_days.to_string(),
+            format_duration(weekday.total),
+            format_duration(weekday.average()),
+        ]);
+        if Some(weekday.weekday) == busiest {
+            row.set_color(Style::new().bold());
+        }
+        weekday_table.add_row(row);
+    }
+
+    weekday_table
+}
+
+// the time tracked per project in each month
+fn month_table(stats: &Stats) -> table::Table {
+    let mut month_table = table::Table::new(vec![
+        column("Month"),
+        table::Column {
+            label: "Project".to_string(),
+            wrap: table::Wrap::Wrap,
+        },
+        column("Duration"),
+    ]);
+
+    for month in &stats.months {
+        for (project, duration) in &month.projects {
+            month_table.add_row(table::Row::new(vec![
+                format_month(month.month),
+                project.clone(),
+                format_util::format_duration(duration),
+            ]));
+        }
+
+        let mut total = table::Row::new(vec![
+            format_month(month.month),
+            "Total".to_string(),
+            format_util::format_duration(&month.total),
+        ]);
+        total.set_color(theme::theme().total);
+        month_table.add_row(total);
+    }
+
+    month_table
+}
+
+fn column(label: &str) -> table::Column {
+    table::Column {
+        label: label.to_string(),
+        wrap: table::Wrap::NoWrap,
+    }
+}
+
+fn format_month(month: NaiveDate) -> String {
+    month.format("%Y-%m").to_string()
+}
+
+// formats a duration since midnight as a time of day like `08:45`
+fn format_time_of_day(time: Duration) -> String {
+    format!("{:02}:{:02}", time.num_hours(), time.num_minutes() % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_times_of_day() {
+        assert_eq!(format_time_of_day(Duration::minutes(8 * 60 + 5)), "08:05");
+        assert_eq!(format_time_of_day(Duration::hours(24)), "24:00");
+    }
+}
@@ -1,30 +1,129 @@
 use std::fmt;
 
 use chrono::Duration;
-use nu_ansi_term::{Color, Style};
+use nu_ansi_term::Style;
 
 use crate::data::activity;
+use crate::data::budget::{BudgetPeriod, BudgetState, BudgetUsage};
+use crate::data::goal::{GoalPeriod, GoalProgress};
 use crate::data::processor::{StatusReportData, StatusReportWriter};
+use crate::outputln;
 use crate::view::format_util;
+use crate::view::json::{self, Json, ToJson};
+use crate::view::theme;
 
 pub struct StatusReport {}
 
 impl StatusReportWriter for StatusReport {
     fn process(&self, data: &StatusReportData) -> anyhow::Result<()> {
-        println!("{data}");
+        outputln!("{data}");
         Ok(())
     }
 }
 
+// writes the status as JSON, e.g. for status bars
+pub struct StatusJson {}
+
+impl StatusReportWriter for StatusJson {
+    fn process(&self, data: &StatusReportData) -> anyhow::Result<()> {
+        json::print(data);
+        Ok(())
+    }
+}
+
+impl ToJson for StatusReportData<'_> {
+    fn to_json(&self) -> Json {
+        Json::object([
+            ("project", Json::optional(self.project, Json::string)),
+            ("activity", Json::optional(self.activity, ToJson::to_json)),
+            ("today", Json::duration(&self.today)),
+            ("current_week", Json::duration(&self.current_week)),
+            ("current_month", Json::duration(&self.current_month)),
+            (
+                "daily_limit",
+                Json::optional(self.daily_limit.as_ref(), Json::duration),
+            ),
+            (
+                "goals",
+                Json::Array(self.goals.iter().map(ToJson::to_json).collect()),
+            ),
+            (
+                "budgets",
+                Json::Array(self.budgets.iter().map(ToJson::to_json).collect()),
+            ),
+        ])
+    }
+}
+
+impl ToJson for GoalProgress<'_> {
+    fn to_json(&self) -> Json {
+        let period = match self.goal.period {
+            GoalPeriod::Day => "day",
+            GoalPeriod::Week => "week",
+        };
+
+        Json::object([
+            ("period", Json::string(period)),
+            (
+                "project",
+                Json::optional(self.goal.project.as_deref(), Json::string),
+            ),
+            ("target", Json::duration(&self.goal.target)),
+            ("tracked", Json::duration(&self.tracked)),
+            ("remaining", Json::duration(&self.remaining())),
+            ("percent", Json::Integer(self.percent())),
+        ])
+    }
+}
+
+impl ToJson for BudgetUsage<'_> {
+    fn to_json(&self) -> Json {
+        let period = match self.budget.period {
+            BudgetPeriod::Week => "week",
+            BudgetPeriod::Month => "month",
+            BudgetPeriod::Year => "year",
+        };
+        let state = match self.state() {
+            BudgetState::Within => "within",
+            BudgetState::Near => "near",
+            BudgetState::Over => "over",
+        };
+
+        Json::object([
+            ("project", Json::string(&self.budget.project)),
+            ("period", Json::string(period)),
+            ("limit", Json::duration(&self.budget.limit)),
+            ("used", Json::duration(&self.used)),
+            ("remaining", Json::duration(&self.remaining())),
+            ("percent", Json::Integer(self.percent())),
+            ("state", Json::string(state)),
+        ])
+    }
+}
+
 impl fmt::Display for StatusReportData<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let longest_line = 30;
         print_title(f, self.project)?;
         print_activity(f, self.activity, self.project)?;
-        print_duration(f, "Today", self.today, longest_line)?;
+        print_today(f, self.today, self.daily_limit, longest_line)?;
         print_duration(f, "Current week", self.current_week, longest_line)?;
         print_duration(f, "Current month", self.current_month, longest_line)?;
 
+        if !self.goals.is_empty() {
+            writeln!(f)?;
+        }
+        for goal in &self.goals {
+            print_goal(f, goal, longest_line)?;
+        }
+
+        if !self.budgets.is_empty() {
+            writeln!(f)?;
+        }
+        for budget in &self.budgets {
+            print_budget(f, budget, longest_line)?;
+        }
+
         Ok(())
     }
 }
@@ -34,18 +133,221 @@ fn print_duration(
     name: &str,
     total_duration: Duration,
     line_width: usize,
+) -> fmt::Result {
+    print_duration_with_style(f, name, total_duration, line_width, Style::new().bold())?;
+    write(f, "\n", Style::new().italic())?;
+    Ok(())
+}
+
+fn print_duration_with_style(
+    f: &mut fmt::Formatter<'_>,
+    name: &str,
+    total_duration: Duration,
+    line_width: usize,
+    style: Style,
 ) -> fmt::Result {
     write(f, " ", Style::new().italic())?;
     write_period(f, name, line_width, Style::new().italic().dimmed())?;
     write(
         f,
         format_util::format_duration(&total_duration).as_str(),
-        Style::new().bold(),
+        style,
+    )?;
+    Ok(())
+}
+
+// prints the duration of today and, if a daily limit is set, how it relates to this limit
+fn print_today(
+    f: &mut fmt::Formatter<'_>,
+    today: Duration,
+    daily_limit: Option<Duration>,
+    line_width: usize,
+) -> fmt::Result {
+    match daily_limit {
+        Some(limit) if today > limit => {
+            print_duration_with_style(f, "Today", today, line_width, theme::theme().error.bold())?;
+            write(
+                f,
+                &format!(
+                    " (limit of {} exceeded by {})",
+                    format_util::format_duration(&limit),
+                    format_util::format_duration(&(today - limit))
+                ),
+                theme::theme().error,
+            )?;
+        }
+        Some(limit) => {
+            print_duration_with_style(f, "Today", today, line_width, Style::new().bold())?;
+            write(
+                f,
+                &format!(
+                    " ({} left of {})",
+                    format_util::format_duration(&(limit - today)),
+                    format_util::format_duration(&limit)
+                ),
+                Style::new().dimmed(),
+            )?;
+        }
+        None => {
+            print_duration_with_style(f, "Today", today, line_width, Style::new().bold())?;
+        }
+    }
+    write(f, "\n", Style::new().italic())?;
+    Ok(())
+}
+
+// prints the tracked time of a goal with a progress bar and the time left to reach it
+fn print_goal(
+    f: &mut fmt::Formatter<'_>,
+    progress: &GoalProgress,
+    line_width: usize,
+) -> fmt::Result {
+    let name = match &progress.goal.project {
+        Some(project) => format!("Goal {} ({project})", format_period(progress.goal.period)),
+        None => format!("Goal {}", format_period(progress.goal.period)),
+    };
+    let reached = progress.remaining().is_zero();
+
+    print_duration_with_style(f, &name, progress.tracked, line_width, Style::new().bold())?;
+    write(
+        f,
+        &format!(
+            " of {} ",
+            format_util::format_duration(&progress.goal.target)
+        ),
+        Style::new().dimmed(),
     )?;
+    write(
+        f,
+        &format_progress_bar(progress.percent()),
+        if reached {
+            theme::theme().success
+        } else {
+            Style::new()
+        },
+    )?;
+
+    if reached {
+        write(f, " reached", theme::theme().success)?;
+    } else {
+        write(
+            f,
+            &format!(
+                " {}% ({} left)",
+                progress.percent(),
+                format_util::format_duration(&progress.remaining())
+            ),
+            Style::new().dimmed(),
+        )?;
+    }
+    write(f, "\n", Style::new().italic())?;
+    Ok(())
+}
+
+// prints the time spent on the projects of a budget with a progress bar, which turns yellow when
+// the budget is nearly used up and red when it has been exceeded
+fn print_budget(f: &mut fmt::Formatter<'_>, usage: &BudgetUsage, line_width: usize) -> fmt::Result {
+    let name = format!(
+        "Budget {} ({})",
+        format_budget_period(usage.budget.period),
+        usage.budget.project
+    );
+    let color = match usage.state() {
+        BudgetState::Within => Style::new(),
+        BudgetState::Near => theme::theme().warning,
+        BudgetState::Over => theme::theme().error,
+    };
+
+    print_duration_with_style(f, &name, usage.used, line_width, Style::new().bold())?;
+    write(
+        f,
+        &format!(" of {} ", format_util::format_duration(&usage.budget.limit)),
+        Style::new().dimmed(),
+    )?;
+    write(f, &format_progress_bar(usage.percent()), color)?;
+
+    if usage.state() == BudgetState::Over {
+        write(
+            f,
+            &format!(
+                " exceeded by {}",
+                format_util::format_duration(&(usage.used - usage.budget.limit))
+            ),
+            color,
+        )?;
+    } else {
+        write(
+            f,
+            &format!(
+                " {}% ({} left)",
+                usage.percent(),
+                format_util::format_duration(&usage.remaining())
+            ),
+            color.dimmed(),
+        )?;
+    }
     write(f, "\n", Style::new().italic())?;
     Ok(())
 }
 
+// a colored warning for a budget which is nearly used up or has been exceeded
+#[must_use]
+pub fn format_budget_warning(usage: &BudgetUsage) -> Option<String> {
+    let style = match usage.state() {
+        BudgetState::Within => return None,
+        BudgetState::Near => theme::theme().warning,
+        BudgetState::Over => theme::theme().error.bold(),
+    };
+
+    budget_warning(usage).map(|warning| theme::style(style).paint(warning).to_string())
+}
+
+// the warning for a budget which is nearly used up or has been exceeded, without colors
+#[must_use]
+pub fn budget_warning(usage: &BudgetUsage) -> Option<String> {
+    let budget = format!(
+        "the budget of {} for {} {}",
+        format_util::format_duration(&usage.budget.limit),
+        usage.budget.project,
+        format_budget_period(usage.budget.period)
+    );
+
+    match usage.state() {
+        BudgetState::Within => None,
+        BudgetState::Near => Some(format!(
+            "Warning: {} of {budget} have been used ({}%)",
+            format_util::format_duration(&usage.used),
+            usage.percent()
+        )),
+        BudgetState::Over => Some(format!(
+            "Warning: {budget} has been exceeded by {} ({}%)",
+            format_util::format_duration(&(usage.used - usage.budget.limit)),
+            usage.percent()
+        )),
+    }
+}
+
+// a bar like `[#####-----]` showing how much of a goal has been reached
+fn format_progress_bar(percent: i64) -> String {
+    let filled = usize::try_from(percent.clamp(0, 100) / 5).unwrap_or(0);
+    format!("[{}{}]", "#".repeat(filled), "-".repeat(20 - filled))
+}
+
+fn format_period(period: GoalPeriod) -> &'static str {
+    match period {
+        GoalPeriod::Day => "today",
+        GoalPeriod::Week => "this week",
+    }
+}
+
+fn format_budget_period(period: BudgetPeriod) -> &'static str {
+    match period {
+        BudgetPeriod::Week => "this week",
+        BudgetPeriod::Month => "this month",
+        BudgetPeriod::Year => "this year",
+    }
+}
+
 fn print_activity(
     f: &mut fmt::Formatter<'_>,
     activity: Option<&activity::Activity>,
@@ -54,17 +356,32 @@ fn print_activity(
     match activity {
         Some(activity) => {
             write(f, "\n  NOW: ", Style::new().italic().dimmed())?;
-            write(f, activity.description.as_str(), Color::Green.bold())?;
+            write(
+                f,
+                activity.description.as_str(),
+                theme::theme().running.bold(),
+            )?;
             if project.is_none() {
                 write(f, " on ", Style::new().italic().dimmed())?;
                 write(f, &activity.project, Style::new().italic())?;
             };
+            if !activity.tags.is_empty() {
+                write(f, " ", Style::new())?;
+                write(
+                    f,
+                    &format_util::format_tags(&activity.tags),
+                    Style::new().dimmed(),
+                )?;
+            }
             write(f, " ...... ", Style::new().dimmed())?;
             write(
                 f,
                 format_util::format_duration(&activity.get_duration()).as_str(),
                 Style::new().bold(),
             )?;
+            if activity.is_paused() {
+                write(f, " (paused)", Style::new().italic().dimmed())?;
+            }
             write(f, "\n\n", Style::new().dimmed())?;
         }
         None => {
@@ -94,6 +411,7 @@ fn print_title(f: &mut fmt::Formatter<'_>, project: Option<&str>) -> fmt::Result
 }
 
 fn write(f: &mut fmt::Formatter<'_>, text: &str, style: Style) -> fmt::Result {
+    let style = theme::style(style);
     write!(
         f,
         "{prefix}{text}{suffix}",
@@ -109,6 +427,7 @@ fn write_period(
     line_width: usize,
     style: Style,
 ) -> fmt::Result {
+    let style = theme::style(style);
     write!(
         f,
         "{prefix} {text:.<line_width$} {suffix}",
@@ -123,6 +442,8 @@ mod tests {
     use chrono::Local;
 
     use super::*;
+    use crate::data::budget::Budget;
+    use crate::data::goal::Goal;
 
     #[test]
     fn report_test() {
@@ -132,6 +453,9 @@ mod tests {
             current_month: Duration::hours(10),
             current_week: Duration::hours(5),
             today: Duration::minutes(30),
+            daily_limit: None,
+            goals: Vec::new(),
+            budgets: Vec::new(),
         };
         let expected = "\u{1b}[2m
  =======<>[3m Status for <>[1mALL<>[3m projects <>[2m ======= 
@@ -156,6 +480,9 @@ mod tests {
             current_month: Duration::hours(10),
             current_week: Duration::hours(5),
             today: Duration::minutes(30),
+            daily_limit: None,
+            goals: Vec::new(),
+            budgets: Vec::new(),
         };
         let expected = "\u{1b}[2m
  =======<>[3m Status for project: <>[1mproject<>[2m ======= 
@@ -178,8 +505,14 @@ mod tests {
         let act = activity::Activity {
             start: now - Duration::minutes(10),
             end: None,
+            start_offset: None,
+            end_offset: None,
             project: "project".to_string(),
             description: "olia".to_string(),
+            tags: Vec::new(),
+            issue: None,
+            pauses: Vec::new(),
+            notes: Vec::new(),
         };
         let data = StatusReportData {
             activity: Some(&act),
@@ -187,6 +520,9 @@ mod tests {
             current_month: Duration::hours(10),
             current_week: Duration::hours(5),
             today: Duration::minutes(30),
+            daily_limit: None,
+            goals: Vec::new(),
+            budgets: Vec::new(),
         };
         let expected = "\u{1b}[2m
  =======<>[3m Status for project: <>[1mproject<>[2m ======= 
@@ -209,8 +545,14 @@ mod tests {
         let act = activity::Activity {
             start: now - Duration::minutes(10),
             end: None,
+            start_offset: None,
+            end_offset: None,
             project: "project".to_string(),
             description: "olia".to_string(),
+            tags: Vec::new(),
+            issue: None,
+            pauses: Vec::new(),
+            notes: Vec::new(),
         };
         let data = StatusReportData {
             activity: Some(&act),
@@ -218,6 +560,9 @@ mod tests {
             current_month: Duration::hours(10),
             current_week: Duration::hours(5),
             today: Duration::minutes(30),
+            daily_limit: None,
+            goals: Vec::new(),
+            budgets: Vec::new(),
         };
         let expected = "\u{1b}[2m
  =======<>[3m Status for <>[1mALL<>[3m projects <>[2m ======= 
@@ -234,6 +579,73 @@ mod tests {
         assert_eq!(clean(res.as_str()), clean(expected));
     }
 
+    #[test]
+    fn report_daily_limit_exceeded_test() {
+        let data = StatusReportData {
+            activity: None,
+            project: Some("project"),
+            current_month: Duration::hours(10),
+            current_week: Duration::hours(9),
+            today: Duration::hours(9),
+            daily_limit: Some(Duration::hours(8)),
+            goals: Vec::new(),
+            budgets: Vec::new(),
+        };
+        let expected = "\u{1b}[2m
+ =======<>[3m Status for project: <>[1mproject<>[2m ======= 
+<>[2;3m
+  NOW: <>[1m NO Activity
+
+<>[3m <>[2;3m Today......................... <>[1;31m9h 00m<>[31m (limit of 8h 00m exceeded by 1h 00m)<>[3m
+<>[3m <>[2;3m Current week.................. <>[1m9h 00m<>[3m
+<>[3m <>[2;3m Current month................. <>[1m10h 00m<>[3m
+\u{1b}[0m";
+
+        let res = data.to_string();
+
+        assert_eq!(clean(res.as_str()), clean(expected));
+    }
+
+    #[test]
+    fn goals_test() {
+        let goal = Goal {
+            period: GoalPeriod::Week,
+            target: Duration::hours(40),
+            project: Some("Client *".to_string()),
+        };
+        let progress = GoalProgress {
+            goal: &goal,
+            tracked: Duration::hours(30),
+        };
+
+        assert_eq!(
+            progress.to_json().to_string(),
+            r#"{"period":"week","project":"Client *","target":144000,"tracked":108000,"remaining":36000,"percent":75}"#
+        );
+        assert_eq!(format_progress_bar(75), "[###############-----]");
+        assert_eq!(format_progress_bar(0), "[--------------------]");
+        assert_eq!(format_progress_bar(150), "[####################]");
+    }
+
+    #[test]
+    fn budgets_test() {
+        let budget = Budget {
+            project: "Client X".to_string(),
+            period: BudgetPeriod::Month,
+            limit: Duration::hours(20),
+            warn_at: 80,
+        };
+        let usage = BudgetUsage {
+            budget: &budget,
+            used: Duration::hours(17),
+        };
+
+        assert_eq!(
+            usage.to_json().to_string(),
+            r#"{"project":"Client X","period":"month","limit":72000,"used":61200,"remaining":10800,"percent":85,"state":"near"}"#
+        );
+    }
+
     fn clean(a: &str) -> String {
         let st_f = "\u{1b}[0m\u{1b}";
 
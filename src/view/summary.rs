@@ -0,0 +1,235 @@
+use chrono::Duration;
+use clap::ValueEnum;
+
+use crate::data::activity;
+use crate::data::processor::{ReportData, ReportWriter, TotalRounding};
+use crate::outputln;
+use crate::view::format_util;
+use crate::view::json::{self, Json, ToJson};
+use crate::view::report;
+
+// the formats of a summary besides JSON
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum SummaryFormat {
+    // an indented list for the terminal
+    #[default]
+    Text,
+    // a nested markdown list, e.g. for standup notes or chat messages
+    Markdown,
+}
+
+// the projects of a range of days with the distinct descriptions of their activities, e.g. to
+// tell in a standup what has been done
+struct Summary<'a> {
+    projects: Vec<ProjectSummary<'a>>,
+    total: Duration,
+}
+
+struct ProjectSummary<'a> {
+    project: &'a str,
+    duration: Duration,
+    // the descriptions in the order they have been worked on first
+    descriptions: Vec<(&'a str, Duration)>,
+}
+
+impl<'a> Summary<'a> {
+    // the projects with the most time spent on them come first
+    fn new(
+        activities: &[&'a activity::Activity],
+        total_rounding: Option<&TotalRounding>,
+    ) -> Summary<'a> {
+        let mut projects: Vec<&str> = Vec::new();
+        for activity in activities {
+            if !projects.contains(&activity.project.as_str()) {
+                projects.push(&activity.project);
+            }
+        }
+
+        let mut project_summaries: Vec<ProjectSummary> = projects
+            .into_iter()
+            .map(|project| {
+                let activities_of_project: Vec<&activity::Activity> = activities
+                    .iter()
+                    .filter(|a| a.project == project)
+                    .copied()
+                    .collect();
+
+                let mut descriptions: Vec<&str> = Vec::new();
+                for activity in &activities_of_project {
+                    if !descriptions.contains(&activity.description.trim()) {
+                        descriptions.push(activity.description.trim());
+                    }
+                }
+
+                ProjectSummary {
+                    project,
+                    duration: report::sum_duration_with_rounding(
+                        &activities_of_project,
+                        total_rounding,
+                    ),
+                    descriptions: descriptions
+                        .into_iter()
+                        .map(|description| {
+                            let activities_with_description: Vec<&activity::Activity> =
+                                activities_of_project
+                                    .iter()
+                                    .filter(|a| a.description.trim() == description)
+                                    .copied()
+                                    .collect();
+                            let duration = report::sum_duration_with_rounding(
+                                &activities_with_description,
+                                total_rounding,
+                            );
+                            (description, duration)
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+
+        // the sort is stable, so projects with the same duration keep their order
+        project_summaries.sort_by_key(|project| std::cmp::Reverse(project.duration));
+
+        Summary {
+            projects: project_summaries,
+            total: report::sum_duration_with_rounding(activities, total_rounding),
+        }
+    }
+
+    fn to_text(&self) -> String {
+        self.format(|project, duration| format!("{project} ({duration})"))
+    }
+
+    fn to_markdown(&self) -> String {
+        self.format(|project, duration| format!("- **{project}** ({duration})"))
+    }
+
+    // a line for each project followed by an indented list of its descriptions
+    fn format(&self, format_project: impl Fn(&str, &str) -> String) -> String {
+        let mut lines = Vec::new();
+
+        for project in &self.projects {
+            lines.push(format_project(
+                project.project,
+                &format_util::format_duration(&project.duration),
+            ));
+
+            for (description, duration) in &project.descriptions {
+                let description = if description.is_empty() {
+                    "(no description)"
+                } else {
+                    description
+                };
+                lines.push(format!(
+                    "  - {description} ({})",
+                    format_util::format_duration(duration)
+                ));
+            }
+        }
+
+        lines.join("\n")
+    }
+}
+
+impl ToJson for Summary<'_> {
+    fn to_json(&self) -> Json {
+        let projects = self
+            .projects
+            .iter()
+            .map(|project| {
+                let descriptions = project
+                    .descriptions
+                    .iter()
+                    .map(|(description, duration)| {
+                        Json::object([
+                            ("description", Json::string(description)),
+                            ("duration", Json::duration(duration)),
+                        ])
+                    })
+                    .collect();
+
+                Json::object([
+                    ("project", Json::string(project.project)),
+                    ("descriptions", Json::Array(descriptions)),
+                    ("duration", Json::duration(&project.duration)),
+                ])
+            })
+            .collect();
+
+        Json::object([
+            ("projects", Json::Array(projects)),
+            ("total", Json::duration(&self.total)),
+        ])
+    }
+}
+
+// writes the projects and descriptions of the report's activities as a list
+pub struct SummaryReport {
+    pub format: SummaryFormat,
+}
+
+impl ReportWriter for SummaryReport {
+    fn process(&self, data: &ReportData) -> anyhow::Result<()> {
+        if data.activities.is_empty() {
+            outputln!("No activity to summarize");
+            return Ok(());
+        }
+
+        let summary = Summary::new(data.activities, data.total_rounding);
+
+        match self.format {
+            SummaryFormat::Text => outputln!("{}", summary.to_text()),
+            SummaryFormat::Markdown => outputln!("{}", summary.to_markdown()),
+        }
+        Ok(())
+    }
+}
+
+// writes the summary as JSON
+pub struct SummaryJson {}
+
+impl ReportWriter for SummaryJson {
+    fn process(&self, data: &ReportData) -> anyhow::Result<()> {
+        json::print(&Summary::new(data.activities, data.total_rounding));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::activity::activity;
+
+    #[test]
+    fn summary_merges_repeated_descriptions() {
+        let a1 = activity::Activity {
+            description: "review".to_string(),
+            ..activity("p1", "2024-03-11 09:00", "2024-03-11 09:30")
+        };
+        let a2 = activity::Activity {
+            description: "deploy".to_string(),
+            ..activity("p2", "2024-03-11 10:00", "2024-03-11 12:00")
+        };
+        let a3 = activity::Activity {
+            description: "fix tests".to_string(),
+            ..activity("p1", "2024-03-11 13:00", "2024-03-11 14:00")
+        };
+        let a4 = activity::Activity {
+            description: "review ".to_string(),
+            ..activity("p1", "2024-03-11 15:00", "2024-03-11 16:00")
+        };
+        let activities = vec![&a1, &a2, &a3, &a4];
+
+        let summary = Summary::new(&activities, None);
+
+        assert_eq!(summary.total, Duration::minutes(270));
+        assert_eq!(
+            summary.to_text(),
+            "p1 (2h 30m)\n  - review (1h 30m)\n  - fix tests (1h 00m)\np2 (2h 00m)\n  - deploy (2h 00m)"
+        );
+        assert_eq!(
+            summary.to_markdown(),
+            "- **p1** (2h 30m)\n  - review (1h 30m)\n  - fix tests (1h 00m)\n- **p2** (2h 00m)\n  - deploy (2h 00m)"
+        );
+    }
+}
@@ -7,6 +7,7 @@ use nu_ansi_term::Style;
 use textwrap;
 
 use crate::conf;
+use crate::view::theme;
 
 pub enum Wrap {
     Wrap,
@@ -21,6 +22,8 @@ pub struct Column {
 pub struct Row {
     content: Vec<String>,
     style: Option<Style>,
+    // styles of single cells, which take precedence over the style of the row
+    cell_styles: Vec<Option<Style>>,
 }
 
 pub struct Group {
@@ -39,12 +42,28 @@ impl Row {
         Self {
             content,
             style: None,
+            cell_styles: Vec::new(),
         }
     }
 
     pub fn set_color(&mut self, style: Style) {
         self.style = Some(style);
     }
+
+    pub fn set_cell_color(&mut self, index: usize, style: Style) {
+        if self.cell_styles.len() <= index {
+            self.cell_styles.resize(index + 1, None);
+        }
+        self.cell_styles[index] = Some(style);
+    }
+
+    // inserts a cell before all other cells of the row
+    pub fn prepend(&mut self, cell: String) {
+        self.content.insert(0, cell);
+        if !self.cell_styles.is_empty() {
+            self.cell_styles.insert(0, None);
+        }
+    }
 }
 
 impl Group {
@@ -196,7 +215,7 @@ impl fmt::Display for Table {
 
         let labels: Vec<&String> = self.columns.iter().map(|c| &c.label).collect();
 
-        write_cells(f, &labels, &column_width, Some(Style::new().underline()))?;
+        write_cells(f, &labels, &column_width, Some(theme::theme().header), &[])?;
         writeln!(f)?;
 
         for row in &self.rows {
@@ -216,7 +235,7 @@ fn write_group(f: &mut fmt::Formatter<'_>, group: &Group, column_width: &[usize]
     let title = group.title.as_ref().unwrap_or(&empty_string);
 
     writeln!(f)?;
-    writeln!(f, "{}", Style::new().bold().paint(title))?;
+    writeln!(f, "{}", theme::style(theme::theme().heading).paint(title))?;
 
     for row in &group.rows {
         write_row(f, row, column_width)?;
@@ -226,7 +245,7 @@ fn write_group(f: &mut fmt::Formatter<'_>, group: &Group, column_width: &[usize]
 }
 
 fn write_row(f: &mut fmt::Formatter<'_>, row: &Row, column_width: &[usize]) -> fmt::Result {
-    write_cells(f, &row.content, column_width, row.style)?;
+    write_cells(f, &row.content, column_width, row.style, &row.cell_styles)?;
     writeln!(f)?;
     Ok(())
 }
@@ -236,6 +255,7 @@ fn write_cells<T: AsRef<str> + std::fmt::Display>(
     cells: &[T],
     column_width: &[usize],
     style: Option<Style>,
+    cell_styles: &[Option<Style>],
 ) -> fmt::Result {
     let wrapped_cells: Vec<Vec<Cow<str>>> = cells
         .iter()
@@ -255,9 +275,12 @@ fn write_cells<T: AsRef<str> + std::fmt::Display>(
         .unwrap_or(1);
 
     for line in 0..most_lines {
-        for (width, wrapped_cell) in column_width.iter().zip(wrapped_cells.iter()) {
+        for (index, (width, wrapped_cell)) in
+            column_width.iter().zip(wrapped_cells.iter()).enumerate()
+        {
+            let cell_style = cell_styles.get(index).copied().flatten().or(style);
             match wrapped_cell.get(line) {
-                Some(c) => write_with_width_and_style(f, c, width, style)?,
+                Some(c) => write_with_width_and_style(f, c, width, cell_style)?,
                 None => write!(f, "{} ", "\u{a0}".repeat(*width))?, // pad with non breaking space
             }
         }
@@ -277,6 +300,7 @@ fn write_with_width_and_style(
     width: &usize,
     opt_style: Option<Style>,
 ) -> fmt::Result {
+    let opt_style = opt_style.map(theme::style);
     let style_prefix = opt_style.map_or(String::new(), |style| style.prefix().to_string());
     let style_suffix = opt_style.map_or(String::new(), |style| style.suffix().to_string());
 
@@ -0,0 +1,688 @@
+use std::borrow::Cow;
+use std::fmt::Write;
+use std::fs;
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use chrono::Duration;
+use thiserror::Error;
+
+use crate::conf;
+use crate::data::activity;
+use crate::data::processor::{ReportData, ReportWriter};
+use crate::view::format_util;
+use crate::view::json::Json;
+use crate::view::report::{self, ReportGrouping};
+use crate::{output, outputln};
+
+// errors in the syntax of a report template
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum TemplateError {
+    #[error("the tag in line {0} is not closed with `}}}}`")]
+    UnclosedTag(usize),
+    #[error("unknown tag `{{{{{0}}}}}` in line {1}")]
+    UnknownTag(String, usize),
+    #[error("`{{{{/{0}}}}}` in line {1} does not close an open block")]
+    UnexpectedClose(String, usize),
+    #[error("the block `{{{{#{0}}}}}` in line {1} is not closed")]
+    UnclosedBlock(String, usize),
+}
+
+// a template in a small subset of the syntax of Handlebars:
+//
+// - `{{name}}` writes a value, `{{project.name}}` a value of an object. Lists are joined by commas
+// - `{{#each projects}}...{{/each}}` repeats its content for each element of a list. Within the
+//   loop, names are looked up in the element first, `{{this}}` is the element itself and
+//   `{{@index}}`, `{{@number}}`, `{{@first}}` and `{{@last}}` tell its position
+// - `{{#if name}}...{{else}}...{{/if}}` and `{{#unless name}}...{{/unless}}` test whether a
+//   value is neither missing, empty, zero nor false. `{{#each}}` may have an `{{else}}` as well
+// - `{{! comment }}` is left out
+//
+// block tags and comments which stand alone in a line are removed with their line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Value(String),
+    Each {
+        path: String,
+        body: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    If {
+        path: String,
+        negate: bool,
+        body: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+}
+
+// a tag between `{{` and `}}` with the line it starts in
+struct Tag {
+    content: String,
+    line: usize,
+}
+
+// a block which has been opened but not yet closed while parsing
+struct OpenBlock {
+    kind: String,
+    path: String,
+    line: usize,
+    body: Vec<Node>,
+    otherwise: Option<Vec<Node>>,
+}
+
+impl OpenBlock {
+    fn nodes(&mut self) -> &mut Vec<Node> {
+        self.otherwise.as_mut().unwrap_or(&mut self.body)
+    }
+
+    fn close(self) -> Node {
+        let otherwise = self.otherwise.unwrap_or_default();
+
+        match self.kind.as_str() {
+            "each" => Node::Each {
+                path: self.path,
+                body: self.body,
+                otherwise,
+            },
+            kind => Node::If {
+                path: self.path,
+                negate: kind == "unless",
+                body: self.body,
+                otherwise,
+            },
+        }
+    }
+}
+
+impl FromStr for Template {
+    type Err = TemplateError;
+
+    fn from_str(template: &str) -> Result<Self, Self::Err> {
+        let (mut texts, tags) = tokenize(template)?;
+        trim_standalone_tags(&mut texts, &tags);
+
+        let mut root = Vec::new();
+        let mut open_blocks: Vec<OpenBlock> = Vec::new();
+
+        for (text, tag) in texts.iter().zip(&tags) {
+            let nodes = open_blocks.last_mut().map_or(&mut root, OpenBlock::nodes);
+            if !text.is_empty() {
+                nodes.push(Node::Text(text.clone()));
+            }
+
+            let unknown_tag = || TemplateError::UnknownTag(tag.content.clone(), tag.line);
+
+            if tag.content.starts_with('!') {
+                continue;
+            } else if let Some(block) = tag.content.strip_prefix('#') {
+                let (kind, path) = block
+                    .split_once(char::is_whitespace)
+                    .ok_or_else(unknown_tag)?;
+                if !["each", "if", "unless"].contains(&kind) || !is_path(path.trim()) {
+                    return Err(unknown_tag());
+                }
+
+                open_blocks.push(OpenBlock {
+                    kind: kind.to_string(),
+                    path: path.trim().to_string(),
+                    line: tag.line,
+                    body: Vec::new(),
+                    otherwise: None,
+                });
+            } else if let Some(kind) = tag.content.strip_prefix('/') {
+                let kind = kind.trim();
+                let block = open_blocks
+                    .pop_if(|block| block.kind == kind)
+                    .ok_or_else(|| TemplateError::UnexpectedClose(kind.to_string(), tag.line))?;
+                let node = block.close();
+                open_blocks
+                    .last_mut()
+                    .map_or(&mut root, OpenBlock::nodes)
+                    .push(node);
+            } else if tag.content == "else" {
+                match open_blocks.last_mut() {
+                    Some(block) if block.otherwise.is_none() => block.otherwise = Some(Vec::new()),
+                    _ => return Err(unknown_tag()),
+                }
+            } else if is_path(&tag.content) {
+                nodes.push(Node::Value(tag.content.clone()));
+            } else {
+                return Err(unknown_tag());
+            }
+        }
+
+        if let Some(block) = open_blocks.pop() {
+            return Err(TemplateError::UnclosedBlock(block.kind, block.line));
+        }
+
+        if let Some(text) = texts.last().filter(|text| !text.is_empty()) {
+            root.push(Node::Text(text.clone()));
+        }
+
+        Ok(Self { nodes: root })
+    }
+}
+
+impl Template {
+    // reads and parses a template file
+    pub fn load(file_name: &str) -> Result<Self> {
+        let template = fs::read_to_string(file_name)
+            .context(format!("Could not read the template: {file_name}"))?;
+
+        template
+            .parse()
+            .context(format!("Invalid template: {file_name}"))
+    }
+
+    #[must_use]
+    pub fn render(&self, context: &Json) -> String {
+        let mut output = String::new();
+        render_nodes(&mut output, &self.nodes, &Scope::new(context));
+        output
+    }
+}
+
+// splits the template into texts and the tags between them. There is always one more text than
+// tags, so each tag is surrounded by texts, which may be empty
+fn tokenize(template: &str) -> Result<(Vec<String>, Vec<Tag>), TemplateError> {
+    let mut texts = Vec::new();
+    let mut tags = Vec::new();
+    let mut rest = template;
+    let mut line = 1;
+
+    while let Some(start) = rest.find("{{") {
+        line += rest[..start].matches('\n').count();
+        let content_start = start + 2;
+        let Some(length) = rest[content_start..].find("}}") else {
+            return Err(TemplateError::UnclosedTag(line));
+        };
+        let content = &rest[content_start..content_start + length];
+
+        texts.push(rest[..start].to_string());
+        tags.push(Tag {
+            content: content.trim().to_string(),
+            line,
+        });
+
+        line += content.matches('\n').count();
+        rest = &rest[content_start + length + 2..];
+    }
+
+    texts.push(rest.to_string());
+    Ok((texts, tags))
+}
+
+// removes the lines of block tags and comments which stand alone in their line, so they do not
+// leave empty lines in the output. Whether a tag stands alone is decided before any line is
+// removed
+fn trim_standalone_tags(texts: &mut [String], tags: &[Tag]) {
+    let standalone: Vec<bool> = tags
+        .iter()
+        .enumerate()
+        .map(|(i, tag)| {
+            let (before, after) = (&texts[i], &texts[i + 1]);
+            let starts_line = match before.rfind('\n') {
+                Some(position) => before[position + 1..].trim().is_empty(),
+                None => i == 0 && before.trim().is_empty(),
+            };
+            let ends_line = match after.find('\n') {
+                Some(position) => after[..position].trim().is_empty(),
+                None => i + 1 == tags.len() && after.trim().is_empty(),
+            };
+
+            is_block_tag(&tag.content) && starts_line && ends_line
+        })
+        .collect();
+
+    for (i, standalone) in standalone.into_iter().enumerate() {
+        if standalone {
+            let line_start = texts[i].rfind('\n').map_or(0, |position| position + 1);
+            texts[i].truncate(line_start);
+
+            let line_end = texts[i + 1]
+                .find('\n')
+                .map_or(texts[i + 1].len(), |position| position + 1);
+            texts[i + 1].drain(..line_end);
+        }
+    }
+}
+
+fn is_block_tag(content: &str) -> bool {
+    content.starts_with(['#', '/', '!']) || content == "else"
+}
+
+fn is_path(path: &str) -> bool {
+    !path.is_empty()
+        && path
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '@'))
+}
+
+// the values visible at a point of the template, from the element of the innermost loop outwards
+struct Scope<'a> {
+    value: &'a Json,
+    // the index of the element and the length of the list in a loop
+    position: Option<(usize, usize)>,
+    parent: Option<&'a Scope<'a>>,
+}
+
+impl<'a> Scope<'a> {
+    fn new(value: &'a Json) -> Self {
+        Self {
+            value,
+            position: None,
+            parent: None,
+        }
+    }
+
+    fn lookup(&self, path: &str) -> Option<Cow<'a, Json>> {
+        if let Some(variable) = path.strip_prefix('@') {
+            return self.lookup_position(variable).map(Cow::Owned);
+        }
+
+        let mut keys = path.split('.');
+        let first = keys.next()?;
+
+        let mut value = if first == "this" {
+            self.value
+        } else {
+            let mut scope = Some(self);
+            loop {
+                let current = scope?;
+                if let Some(value) = get_key(current.value, first) {
+                    break value;
+                }
+                scope = current.parent;
+            }
+        };
+
+        for key in keys {
+            value = get_key(value, key)?;
+        }
+
+        Some(Cow::Borrowed(value))
+    }
+
+    fn lookup_position(&self, variable: &str) -> Option<Json> {
+        let (index, length) = match self.position {
+            Some(position) => position,
+            None => self.parent?.position?,
+        };
+
+        match variable {
+            "index" => i64::try_from(index).ok().map(Json::Integer),
+            "number" => i64::try_from(index + 1).ok().map(Json::Integer),
+            "first" => Some(Json::Bool(index == 0)),
+            "last" => Some(Json::Bool(index + 1 == length)),
+            _ => None,
+        }
+    }
+}
+
+fn get_key<'a>(value: &'a Json, key: &str) -> Option<&'a Json> {
+    match value {
+        Json::Object(entries) => entries
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value),
+        _ => None,
+    }
+}
+
+fn render_nodes(output: &mut String, nodes: &[Node], scope: &Scope) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => output.push_str(text),
+            Node::Value(path) => {
+                if let Some(value) = scope.lookup(path) {
+                    write_value(output, &value);
+                }
+            }
+            Node::Each {
+                path,
+                body,
+                otherwise,
+            } => match scope.lookup(path).as_deref() {
+                Some(Json::Array(elements)) if !elements.is_empty() => {
+                    for (index, element) in elements.iter().enumerate() {
+                        let element_scope = Scope {
+                            value: element,
+                            position: Some((index, elements.len())),
+                            parent: Some(scope),
+                        };
+                        render_nodes(output, body, &element_scope);
+                    }
+                }
+                _ => render_nodes(output, otherwise, scope),
+            },
+            Node::If {
+                path,
+                negate,
+                body,
+                otherwise,
+            } => {
+                if is_truthy(scope.lookup(path).as_deref()) != *negate {
+                    render_nodes(output, body, scope);
+                } else {
+                    render_nodes(output, otherwise, scope);
+                }
+            }
+        }
+    }
+}
+
+fn write_value(output: &mut String, value: &Json) {
+    match value {
+        Json::Null => {}
+        Json::String(s) => output.push_str(s),
+        Json::Array(values) => {
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    output.push_str(", ");
+                }
+                write_value(output, value);
+            }
+        }
+        value => {
+            let _ = write!(output, "{value}");
+        }
+    }
+}
+
+fn is_truthy(value: Option<&Json>) -> bool {
+    match value {
+        None | Some(Json::Null | Json::Bool(false) | Json::Integer(0)) => false,
+        Some(Json::Float(x)) => *x != 0.0,
+        Some(Json::String(s)) => !s.is_empty(),
+        Some(Json::Array(values)) => !values.is_empty(),
+        Some(_) => true,
+    }
+}
+
+// renders the report with a template given by the user and writes it to stdout or a file
+pub struct TemplateReport {
+    pub template: Template,
+    pub output_file: Option<String>,
+}
+
+impl ReportWriter for TemplateReport {
+    fn process(&self, data: &ReportData) -> Result<()> {
+        let content = self.template.render(&report_context(data));
+
+        match &self.output_file {
+            Some(output_file) => {
+                fs::write(output_file, content)
+                    .context(format!("Could not write to file: {output_file}"))?;
+                outputln!(
+                    "Wrote the report of {} activities to {}",
+                    data.activities.len(),
+                    output_file
+                );
+            }
+            None => output!("{content}"),
+        }
+
+        Ok(())
+    }
+}
+
+// the values of the report a template may use:
+//
+// - `from` and `to`: the dates the report is restricted to, if any
+// - `total`: the total duration of the report
+// - `projects`: the projects with their `name`, their `descriptions` and `activities`
+// - `days`: the days with their `date` and `activities`
+// - `activities`: all activities with their `date`, `start`, `end`, `project`, `description`,
+//   `tags`, `issue` and `notes`
+//
+// the total and all groups and activities have a `duration` like `1h 30m`, `hours` like `1.50`
+// and `seconds`. Totals are rounded like in the text report.
+fn report_context(data: &ReportData) -> Json {
+    let total = report::sum_duration_with_rounding(data.activities, data.total_rounding);
+
+    let projects = report::group_activities(data.activities, ReportGrouping::Project)
+        .into_iter()
+        .map(|(name, activities)| {
+            let duration = ReportGrouping::Project.sum_duration(&activities, data.total_rounding);
+            let descriptions = report::group_activities(&activities, ReportGrouping::Description)
+                .into_iter()
+                .map(|(name, activities)| {
+                    let duration = report::sum_duration(&activities);
+                    Json::object([
+                        ("name", Json::string(&name)),
+                        ("duration", format_duration(&duration)),
+                        ("hours", format_hours(&duration)),
+                        ("seconds", Json::duration(&duration)),
+                        ("activities", activities_context(&activities)),
+                    ])
+                })
+                .collect();
+
+            Json::object([
+                ("name", Json::string(&name)),
+                ("duration", format_duration(&duration)),
+                ("hours", format_hours(&duration)),
+                ("seconds", Json::duration(&duration)),
+                ("descriptions", Json::Array(descriptions)),
+                ("activities", activities_context(&activities)),
+            ])
+        })
+        .collect();
+
+    let days = report::group_activities(data.activities, ReportGrouping::Day)
+        .into_iter()
+        .map(|(date, activities)| {
+            let duration = ReportGrouping::Day.sum_duration(&activities, data.total_rounding);
+            Json::object([
+                ("date", Json::String(date)),
+                ("duration", format_duration(&duration)),
+                ("hours", format_hours(&duration)),
+                ("seconds", Json::duration(&duration)),
+                ("activities", activities_context(&activities)),
+            ])
+        })
+        .collect();
+
+    Json::object([
+        ("from", Json::optional(data.from_date.as_ref(), Json::date)),
+        ("to", Json::optional(data.to_date.as_ref(), Json::date)),
+        (
+            "total",
+            Json::object([
+                ("duration", format_duration(&total)),
+                ("hours", format_hours(&total)),
+                ("seconds", Json::duration(&total)),
+            ]),
+        ),
+        ("projects", Json::Array(projects)),
+        ("days", Json::Array(days)),
+        ("activities", activities_context(data.activities)),
+    ])
+}
+
+fn activities_context(activities: &[&activity::Activity]) -> Json {
+    Json::Array(
+        activities
+            .iter()
+            .map(|activity| {
+                let duration = activity.get_duration();
+                Json::object([
+                    ("date", Json::date(&activity.start.date())),
+                    (
+                        "start",
+                        Json::String(activity.start.format(conf::format_time()).to_string()),
+                    ),
+                    (
+                        "end",
+                        Json::optional(activity.end.as_ref(), |end| {
+                            Json::String(end.format(conf::format_time()).to_string())
+                        }),
+                    ),
+                    ("project", Json::string(&activity.project)),
+                    ("description", Json::string(&activity.description)),
+                    (
+                        "tags",
+                        Json::Array(activity.tags.iter().map(|tag| Json::string(tag)).collect()),
+                    ),
+                    (
+                        "issue",
+                        Json::optional(activity.issue.as_deref(), Json::string),
+                    ),
+                    (
+                        "notes",
+                        Json::Array(
+                            activity
+                                .notes
+                                .iter()
+                                .map(|note| Json::string(note))
+                                .collect(),
+                        ),
+                    ),
+                    ("duration", format_duration(&duration)),
+                    ("hours", format_hours(&duration)),
+                    ("seconds", Json::duration(&duration)),
+                ])
+            })
+            .collect(),
+    )
+}
+
+fn format_duration(duration: &Duration) -> Json {
+    Json::String(format_util::format_duration(duration))
+}
+
+fn format_hours(duration: &Duration) -> Json {
+    Json::String(format_util::format_hours(duration))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDateTime;
+
+    use super::*;
+
+    fn render(template: &str, context: &Json) -> String {
+        template.parse::<Template>().unwrap().render(context)
+    }
+
+    #[test]
+    fn render_values_and_blocks() {
+        let context = Json::object([
+            ("title", Json::string("Report")),
+            ("empty", Json::Array(Vec::new())),
+            (
+                "items",
+                Json::Array(vec![
+                    Json::object([
+                        ("name", Json::string("a")),
+                        (
+                            "tags",
+                            Json::Array(vec![Json::string("x"), Json::string("y")]),
+                        ),
+                    ]),
+                    Json::object([
+                        ("name", Json::string("b")),
+                        ("tags", Json::Array(Vec::new())),
+                    ]),
+                ]),
+            ),
+        ]);
+
+        assert_eq!(
+            render(
+                "{{title}}: {{#each items}}{{@number}}. {{name}} of {{title}}{{#if tags}} ({{tags}}){{/if}}{{#unless @last}}, {{/unless}}{{/each}}",
+                &context
+            ),
+            "Report: 1. a of Report (x, y), 2. b of Report"
+        );
+        assert_eq!(
+            render(
+                "{{#each empty}}{{this}}{{else}}none{{/each}} {{missing}}{{! comment }}.",
+                &context
+            ),
+            "none ."
+        );
+        assert_eq!(
+            render("{{#if empty}}yes{{else}}no{{/if}} {{items.x}}", &context),
+            "no "
+        );
+    }
+
+    #[test]
+    fn remove_standalone_lines() {
+        let context = Json::object([(
+            "items",
+            Json::Array(vec![Json::string("a"), Json::string("b")]),
+        )]);
+
+        assert_eq!(
+            render(
+                "List:\n  {{#each items}}\n  - {{this}}\n  {{/each}}\n{{! end }}\nDone\n",
+                &context
+            ),
+            "List:\n  - a\n  - b\nDone\n"
+        );
+    }
+
+    #[test]
+    fn parse_invalid_templates() {
+        assert_eq!(
+            "a\n{{name".parse::<Template>(),
+            Err(TemplateError::UnclosedTag(2))
+        );
+        assert_eq!(
+            "{{#each items}}\n{{/if}}".parse::<Template>(),
+            Err(TemplateError::UnexpectedClose("if".to_string(), 2))
+        );
+        assert_eq!(
+            "{{#if a}}".parse::<Template>(),
+            Err(TemplateError::UnclosedBlock("if".to_string(), 1))
+        );
+        assert_eq!(
+            "{{#with a}}{{/with}}".parse::<Template>(),
+            Err(TemplateError::UnknownTag("#with a".to_string(), 1))
+        );
+        assert_eq!(
+            "{{else}}".parse::<Template>(),
+            Err(TemplateError::UnknownTag("else".to_string(), 1))
+        );
+    }
+
+    #[test]
+    fn render_report() {
+        conf::set_precision(conf::Precision::Minutes);
+        let activity = |project: &str, start: &str, end: &str| {
+            let mut activity = activity::Activity::start(
+                project.to_string(),
+                "d".to_string(),
+                NaiveDateTime::parse_from_str(start, "%F %R").ok(),
+            );
+            activity.end = NaiveDateTime::parse_from_str(end, "%F %R").ok();
+            activity
+        };
+        let a1 = activity("p1", "2024-03-01 09:00", "2024-03-01 10:30");
+        let a2 = activity("p2", "2024-03-01 11:00", "2024-03-01 11:15");
+        let a3 = activity("p1", "2024-03-02 09:00", "2024-03-02 10:00");
+        let activities = vec![&a1, &a2, &a3];
+        let data = ReportData {
+            activities: &activities,
+            total_rounding: None,
+            from_date: None,
+            to_date: None,
+        };
+        let template: Template = "{{#each projects}}\n| {{name}} | {{hours}} |\n{{/each}}\n| Total | {{total.hours}} |\n{{#each days}}\n{{date}}: {{#each activities}}{{start}}-{{end}} {{project}}{{#unless @last}}, {{/unless}}{{/each}}\n{{/each}}\n"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            template.render(&report_context(&data)),
+            "| p1 | 2.50 |\n| p2 | 0.25 |\n| Total | 2.75 |\n2024-03-01: 09:00-10:30 p1, 11:00-11:15 p2\n2024-03-02: 09:00-10:00 p1\n"
+        );
+    }
+}
@@ -0,0 +1,140 @@
+use std::io;
+use std::io::{Read, Write};
+
+pub use raw_mode::RawMode;
+
+// switches to the alternate screen of the terminal and back to the normal screen when dropped
+pub struct AlternateScreen {}
+
+impl AlternateScreen {
+    pub fn enter() -> io::Result<Self> {
+        let mut stdout = io::stdout();
+        write!(stdout, "\x1b[?1049h")?;
+        stdout.flush()?;
+
+        Ok(Self {})
+    }
+}
+
+impl Drop for AlternateScreen {
+    fn drop(&mut self) {
+        let mut stdout = io::stdout();
+        let _ = write!(stdout, "\x1b[?1049l");
+        let _ = stdout.flush();
+    }
+}
+
+// clears the screen and moves the cursor to the top left corner
+pub fn clear_screen() -> io::Result<()> {
+    let mut stdout = io::stdout();
+    write!(stdout, "\x1b[H\x1b[2J")?;
+    stdout.flush()
+}
+
+// reads a single byte from stdin. Returns `None` at the end of the input or if nothing has been
+// typed within the timeout of the raw mode
+pub fn read_byte(stdin: &mut io::StdinLock) -> io::Result<Option<u8>> {
+    let mut buffer = [0u8; 1];
+
+    match stdin.read(&mut buffer)? {
+        0 => Ok(None),
+        _ => Ok(Some(buffer[0])),
+    }
+}
+
+// the number of bytes of an utf-8 encoded character given its first byte
+#[must_use]
+pub fn utf8_length(first_byte: u8) -> usize {
+    match first_byte {
+        0xF0..=0xFF => 4,
+        0xE0..=0xEF => 3,
+        0xC0..=0xDF => 2,
+        _ => 1,
+    }
+}
+
+#[cfg(unix)]
+mod raw_mode {
+    use std::io;
+    use std::mem::MaybeUninit;
+    use std::time::Duration;
+
+    // puts the terminal into raw mode and restores the original settings when dropped
+    pub struct RawMode {
+        original: libc::termios,
+    }
+
+    impl RawMode {
+        // reading from stdin blocks until at least one byte is available
+        pub fn enable() -> io::Result<Self> {
+            Self::enable_with(1, 0)
+        }
+
+        // reading from stdin returns no bytes if nothing has been typed within the timeout
+        //
+        // the timeout has a resolution of a tenth of a second and may be 25.5 seconds at most.
+        pub fn enable_with_timeout(timeout: Duration) -> io::Result<Self> {
+            let tenths = (timeout.as_millis() / 100).clamp(1, u128::from(u8::MAX));
+            Self::enable_with(0, u8::try_from(tenths).unwrap_or(u8::MAX))
+        }
+
+        fn enable_with(min_bytes: u8, timeout_tenths: u8) -> io::Result<Self> {
+            let mut termios = MaybeUninit::<libc::termios>::uninit();
+
+            // SAFETY: tcgetattr initializes the termios struct if it returns successfully
+            let original = unsafe {
+                if libc::tcgetattr(libc::STDIN_FILENO, termios.as_mut_ptr()) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                termios.assume_init()
+            };
+
+            let mut raw = original;
+            raw.c_lflag &= !(libc::ICANON | libc::ECHO | libc::ISIG | libc::IEXTEN);
+            raw.c_iflag &= !(libc::IXON | libc::ICRNL);
+            raw.c_cc[libc::VMIN] = min_bytes;
+            raw.c_cc[libc::VTIME] = timeout_tenths;
+
+            // SAFETY: raw is a valid termios struct derived from the current settings
+            if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSAFLUSH, &raw) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            Ok(Self { original })
+        }
+    }
+
+    impl Drop for RawMode {
+        fn drop(&mut self) {
+            // SAFETY: original holds the settings read by tcgetattr
+            unsafe {
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSAFLUSH, &self.original);
+            }
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod raw_mode {
+    use std::io;
+    use std::time::Duration;
+
+    pub struct RawMode {}
+
+    impl RawMode {
+        pub fn enable() -> io::Result<Self> {
+            Err(unsupported())
+        }
+
+        pub fn enable_with_timeout(_timeout: Duration) -> io::Result<Self> {
+            Err(unsupported())
+        }
+    }
+
+    fn unsupported() -> io::Error {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            "raw mode is only supported on unix terminals",
+        )
+    }
+}
@@ -0,0 +1,341 @@
+use std::cell::Cell;
+use std::io::IsTerminal;
+
+use chrono::{Duration, NaiveDate};
+use clap::ValueEnum;
+use nu_ansi_term::{Color, Style};
+
+use crate::conf;
+use crate::data::activity::Activity;
+use crate::data::config::{Config, ConfigError};
+use crate::data::redaction;
+
+// the name of the table defining the theme in the configuration file
+pub static CONFIG_TABLE: &str = "theme";
+
+// the colors of projects if they are colored (see `project_colors`). Each project gets one of them
+// by a hash of its name, so it has the same color in every list
+static PROJECT_COLORS: [Color; 8] = [
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::Green,
+    Color::Red,
+    Color::Yellow,
+    Color::LightBlue,
+    Color::LightMagenta,
+];
+
+// when the output is colored (see `--color`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ColorMode {
+    // if the output is a terminal and the environment variable `NO_COLOR` is not set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    #[must_use]
+    pub fn use_color(self) -> bool {
+        match self {
+            Self::Auto => {
+                std::env::var_os("NO_COLOR").is_none_or(|no_color| no_color.is_empty())
+                    && std::io::stdout().is_terminal()
+            }
+            Self::Always => true,
+            Self::Never => false,
+        }
+    }
+}
+
+// the styles of the tables, reports and the status
+//
+// in the configuration file each style is a list of modifiers (`bold`, `dimmed`, `italic`,
+// `underline` and `reverse`) and colors. A color is a name like `red` or `bright-red`, a number of
+// the 256 colors of the terminal or a hex value like `#ff8800`. A color after `on` is used as
+// background, `none` removes the style:
+//
+//     [theme]
+//     running = "bold blue"
+//     header = "black on white"
+//     project_colors = true
+//     dim_after_days = 7
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    // the header of tables
+    pub header: Style,
+    // the titles of groups in tables and reports
+    pub heading: Style,
+    // the rows of totals
+    pub total: Style,
+    // running activities in lists
+    pub running: Style,
+    // activities which span more than one day in lists
+    pub multiple_days: Style,
+    // activities which started before `dim_after_days` days in lists
+    pub old: Style,
+    // the notes of activities in lists
+    pub notes: Style,
+    // goals which have been reached
+    pub success: Style,
+    // budgets which are nearly used up
+    pub warning: Style,
+    // exceeded limits and budgets
+    pub error: Style,
+    // colors the projects in lists by a hash of their names
+    pub project_colors: bool,
+    pub dim_after_days: Option<i64>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            header: Style::new().underline(),
+            heading: Style::new().bold(),
+            total: Style::new().bold(),
+            running: Color::Green.normal(),
+            multiple_days: Color::Yellow.normal(),
+            old: Style::new().dimmed(),
+            notes: Style::new().dimmed(),
+            success: Color::Green.normal(),
+            warning: Color::Yellow.normal(),
+            error: Color::Red.normal(),
+            project_colors: false,
+            dim_after_days: None,
+        }
+    }
+}
+
+thread_local! {
+    static THEME: Cell<Theme> = Cell::new(Theme::default());
+}
+
+impl Theme {
+    pub fn from_config(config: &Config) -> Result<Self, ConfigError> {
+        let default = Self::default();
+        let get_style = |key: &str, default: Style| match config.get(CONFIG_TABLE, key) {
+            None => Ok(default),
+            Some(value) => value
+                .as_str()
+                .ok_or_else(|| "expected a style like `bold red` or `#ff8800 on black`".to_string())
+                .and_then(parse_style)
+                .map_err(|e| ConfigError::InvalidSetting(format!("{CONFIG_TABLE}.{key}"), e)),
+        };
+
+        let project_colors = match config.get(CONFIG_TABLE, "project_colors") {
+            None => default.project_colors,
+            Some(value) => value.as_bool().ok_or_else(|| {
+                ConfigError::InvalidSetting(
+                    format!("{CONFIG_TABLE}.project_colors"),
+                    "expected `true` or `false`".to_string(),
+                )
+            })?,
+        };
+
+        let dim_after_days = match config.get(CONFIG_TABLE, "dim_after_days") {
+            None => default.dim_after_days,
+            Some(value) => Some(
+                value
+                    .as_f64()
+                    .filter(|days| *days >= 0.0 && days.fract() == 0.0)
+                    .map(|days| days as i64)
+                    .ok_or_else(|| {
+                        ConfigError::InvalidSetting(
+                            format!("{CONFIG_TABLE}.dim_after_days"),
+                            "expected a number of days like 7".to_string(),
+                        )
+                    })?,
+            ),
+        };
+
+        Ok(Self {
+            header: get_style("header", default.header)?,
+            heading: get_style("heading", default.heading)?,
+            total: get_style("total", default.total)?,
+            running: get_style("running", default.running)?,
+            multiple_days: get_style("multiple_days", default.multiple_days)?,
+            old: get_style("old", default.old)?,
+            notes: get_style("notes", default.notes)?,
+            success: get_style("success", default.success)?,
+            warning: get_style("warning", default.warning)?,
+            error: get_style("error", default.error)?,
+            project_colors,
+            dim_after_days,
+        })
+    }
+
+    // the color of a project if projects are colored
+    #[must_use]
+    pub fn project_style(&self, project: &str) -> Option<Style> {
+        if !self.project_colors {
+            return None;
+        }
+
+        let index = redaction::hash(project) % PROJECT_COLORS.len() as u64;
+        Some(PROJECT_COLORS[index as usize].normal())
+    }
+
+    // whether an activity is old enough to be dimmed
+    #[must_use]
+    pub fn is_old(&self, activity: &Activity, today: NaiveDate) -> bool {
+        self.dim_after_days.is_some_and(|days| {
+            activity.is_stopped() && activity.start.date() < today - Duration::days(days)
+        })
+    }
+}
+
+// the theme of the current process
+#[must_use]
+pub fn theme() -> Theme {
+    THEME.with(Cell::get)
+}
+
+pub fn set_theme(theme: Theme) {
+    THEME.with(|t| t.set(theme));
+}
+
+// the style itself if the output is colored, otherwise no style at all
+#[must_use]
+pub fn style(style: Style) -> Style {
+    if conf::color() { style } else { Style::new() }
+}
+
+// parses a style like `bold red`, `208 on black` or `none`
+pub fn parse_style(s: &str) -> Result<Style, String> {
+    let mut style = Style::new();
+    let mut words = s.split_whitespace();
+
+    while let Some(word) = words.next() {
+        match word {
+            "none" => style = Style::new(),
+            "bold" => style = style.bold(),
+            "dimmed" => style = style.dimmed(),
+            "italic" => style = style.italic(),
+            "underline" => style = style.underline(),
+            "reverse" => style = style.reverse(),
+            "on" => {
+                let background = words
+                    .next()
+                    .ok_or_else(|| "missing color after `on`".to_string())?;
+                style = style.on(parse_color(background)?);
+            }
+            color => style.foreground = Some(parse_color(color)?),
+        }
+    }
+
+    Ok(style)
+}
+
+fn parse_color(s: &str) -> Result<Color, String> {
+    let color = match s {
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" | "purple" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "white" => Color::White,
+        "bright-black" | "gray" | "grey" => Color::DarkGray,
+        "bright-red" => Color::LightRed,
+        "bright-green" => Color::LightGreen,
+        "bright-yellow" => Color::LightYellow,
+        "bright-blue" => Color::LightBlue,
+        "bright-magenta" | "bright-purple" => Color::LightMagenta,
+        "bright-cyan" => Color::LightCyan,
+        "bright-white" => Color::LightGray,
+        _ => {
+            if let Some(hex) = s.strip_prefix('#') {
+                let channel = |i: usize| {
+                    hex.get(i..i + 2)
+                        .and_then(|c| u8::from_str_radix(c, 16).ok())
+                };
+                match (hex.len(), channel(0), channel(2), channel(4)) {
+                    (6, Some(r), Some(g), Some(b)) => Color::Rgb(r, g, b),
+                    _ => {
+                        return Err(format!(
+                            "invalid color `{s}`, expected a value like #ff8800"
+                        ));
+                    }
+                }
+            } else if let Ok(number) = s.parse::<u8>() {
+                Color::Fixed(number)
+            } else {
+                return Err(format!("unknown color or modifier `{s}`"));
+            }
+        }
+    };
+
+    Ok(color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_styles() {
+        assert_eq!(parse_style("bold red"), Ok(Color::Red.bold()));
+        assert_eq!(
+            parse_style("italic 208 on #ff8800"),
+            Ok(Color::Fixed(208).italic().on(Color::Rgb(255, 136, 0)))
+        );
+        assert_eq!(parse_style("bright-blue"), Ok(Color::LightBlue.normal()));
+        assert_eq!(parse_style("none"), Ok(Style::new()));
+        assert_eq!(parse_style(""), Ok(Style::new()));
+        assert!(parse_style("blinking").is_err());
+        assert!(parse_style("red on").is_err());
+        assert!(parse_style("#ff88").is_err());
+        assert!(parse_style("256").is_err());
+    }
+
+    #[test]
+    fn theme_from_config() {
+        let config: Config =
+            "[theme]\nrunning = \"bold blue\"\nproject_colors = true\ndim_after_days = 7"
+                .parse()
+                .unwrap();
+        let theme = Theme::from_config(&config).unwrap();
+
+        assert_eq!(theme.running, Color::Blue.bold());
+        assert_eq!(theme.header, Style::new().underline());
+        assert!(theme.project_colors);
+        assert_eq!(theme.dim_after_days, Some(7));
+        assert_eq!(theme.project_style("p"), theme.project_style("p"));
+        assert_eq!(Theme::default().project_style("p"), None);
+
+        let invalid: Config = "[theme]\nerror = \"very red\"".parse().unwrap();
+        assert!(Theme::from_config(&invalid).is_err());
+    }
+
+    #[test]
+    fn old_activities() {
+        let activity = |start: &str, end: Option<&str>| {
+            let mut activity =
+                Activity::start("p".to_string(), "d".to_string(), start.parse().ok());
+            activity.end = end.and_then(|end| end.parse().ok());
+            activity
+        };
+        let today = NaiveDate::from_ymd_opt(2024, 3, 19).unwrap();
+        let theme = Theme {
+            dim_after_days: Some(7),
+            ..Theme::default()
+        };
+
+        assert!(theme.is_old(
+            &activity("2024-03-11T09:00:00", Some("2024-03-11T10:00:00")),
+            today
+        ));
+        assert!(!theme.is_old(
+            &activity("2024-03-12T09:00:00", Some("2024-03-12T10:00:00")),
+            today
+        ));
+        assert!(!theme.is_old(&activity("2024-03-11T09:00:00", None), today));
+        assert!(!Theme::default().is_old(
+            &activity("2024-03-01T09:00:00", Some("2024-03-01T10:00:00")),
+            today
+        ));
+    }
+}
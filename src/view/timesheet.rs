@@ -0,0 +1,270 @@
+use std::collections::BTreeSet;
+
+use chrono::{Duration, NaiveDate};
+
+use crate::data::activity;
+use crate::data::processor::{ReportData, ReportWriter, TotalRounding};
+use crate::outputln;
+use crate::view::format_util;
+use crate::view::json::{self, Json, ToJson};
+use crate::view::report;
+use crate::view::table;
+use crate::view::theme;
+
+// the hours of a range of days with projects as rows and days as columns, the layout of a
+// classic timesheet
+struct Timesheet<'a> {
+    days: Vec<NaiveDate>,
+    rows: Vec<TimesheetRow<'a>>,
+    day_totals: Vec<Duration>,
+    total: Duration,
+}
+
+struct TimesheetRow<'a> {
+    project: &'a str,
+    durations: Vec<Duration>,
+    total: Duration,
+}
+
+impl<'a> Timesheet<'a> {
+    fn new(
+        activities: &[&'a activity::Activity],
+        total_rounding: Option<&TotalRounding>,
+        days: Vec<NaiveDate>,
+    ) -> Timesheet<'a> {
+        let projects: BTreeSet<&str> = activities.iter().map(|a| a.project.as_str()).collect();
+
+        // like in the matrix, the cells are rounded only if the totals of projects per day are
+        // rounded
+        let cell_rounding = total_rounding.filter(|r| r.rounds_projects());
+
+        let activities_of = |day: NaiveDate, project: Option<&str>| -> Vec<&activity::Activity> {
+            activities
+                .iter()
+                .filter(|a| a.start.date() == day && project.is_none_or(|p| a.project == p))
+                .copied()
+                .collect()
+        };
+
+        let rows: Vec<TimesheetRow> = projects
+            .into_iter()
+            .map(|project| {
+                let durations: Vec<Duration> = days
+                    .iter()
+                    .map(|day| {
+                        report::sum_duration_with_rounding(
+                            &activities_of(*day, Some(project)),
+                            cell_rounding,
+                        )
+                    })
+                    .collect();
+
+                TimesheetRow {
+                    project,
+                    total: durations.iter().copied().sum(),
+                    durations,
+                }
+            })
+            .collect();
+
+        let day_totals = days
+            .iter()
+            .map(|day| {
+                report::sum_duration_with_rounding(&activities_of(*day, None), total_rounding)
+            })
+            .collect();
+
+        Timesheet {
+            days,
+            rows,
+            day_totals,
+            total: report::sum_duration_with_rounding(activities, total_rounding),
+        }
+    }
+
+    fn to_table(&self) -> table::Table {
+        let mut columns = vec![table::Column {
+            label: "Project".to_string(),
+            wrap: table::Wrap::Wrap,
+        }];
+        columns.extend(self.days.iter().map(|day| table::Column {
+            label: day.format("%a %m-%d").to_string(),
+            wrap: table::Wrap::NoWrap,
+        }));
+        columns.push(table::Column {
+            label: "Total".to_string(),
+            wrap: table::Wrap::NoWrap,
+        });
+
+        let mut timesheet_table = table::Table::new(columns);
+
+        for row in &self.rows {
+            timesheet_table.add_row(table::Row::new(create_cells(
+                row.project.to_string(),
+                &row.durations,
+                row.total,
+            )));
+        }
+
+        let mut total_row = table::Row::new(create_cells(
+            "Total".to_string(),
+            &self.day_totals,
+            self.total,
+        ));
+        total_row.set_color(theme::theme().total);
+        timesheet_table.add_row(total_row);
+
+        timesheet_table
+    }
+}
+
+impl ToJson for Timesheet<'_> {
+    fn to_json(&self) -> Json {
+        let durations_by_day = |durations: &[Duration]| {
+            Json::Object(
+                self.days
+                    .iter()
+                    .zip(durations)
+                    .map(|(day, duration)| {
+                        (day.format("%Y-%m-%d").to_string(), Json::duration(duration))
+                    })
+                    .collect(),
+            )
+        };
+
+        let projects = self
+            .rows
+            .iter()
+            .map(|row| {
+                Json::object([
+                    ("project", Json::String(row.project.to_string())),
+                    ("days", durations_by_day(&row.durations)),
+                    ("total", Json::duration(&row.total)),
+                ])
+            })
+            .collect();
+
+        Json::object([
+            ("projects", Json::Array(projects)),
+            ("days", durations_by_day(&self.day_totals)),
+            ("total", Json::duration(&self.total)),
+        ])
+    }
+}
+
+// writes the hours per project and day of the report's date range
+pub struct TimesheetReport {}
+
+impl ReportWriter for TimesheetReport {
+    fn process(&self, data: &ReportData) -> anyhow::Result<()> {
+        outputln!("\n{}", create_timesheet(data).to_table());
+        Ok(())
+    }
+}
+
+// writes the timesheet as JSON
+pub struct TimesheetJson {}
+
+impl ReportWriter for TimesheetJson {
+    fn process(&self, data: &ReportData) -> anyhow::Result<()> {
+        json::print(&create_timesheet(data));
+        Ok(())
+    }
+}
+
+// every day of the report's date range gets a column, even if nothing has been tracked on it. If
+// the range is open, it ends at the days of the first and last activity
+fn create_timesheet<'a>(data: &ReportData<'a>) -> Timesheet<'a> {
+    let first_activity = data.activities.first().map(|a| a.start.date());
+    let last_activity = data.activities.last().map(|a| a.start.date());
+
+    let days = match (
+        data.from_date.or(first_activity),
+        data.to_date.or(last_activity),
+    ) {
+        (Some(first_day), Some(last_day)) => first_day
+            .iter_days()
+            .take_while(|day| *day <= last_day)
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    Timesheet::new(data.activities, data.total_rounding, days)
+}
+
+fn create_cells(label: String, durations: &[Duration], total: Duration) -> Vec<String> {
+    let mut cells = vec![label];
+    cells.extend(durations.iter().map(format_cell));
+    cells.push(format_util::format_hours(&total));
+    cells
+}
+
+// empty cells are left blank in the table to make it easier to read
+fn format_cell(duration: &Duration) -> String {
+    if duration.is_zero() {
+        String::new()
+    } else {
+        format_util::format_hours(duration)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::activity::activity;
+    use crate::data::round_util::{RoundingMode, RoundingScope};
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, day).unwrap()
+    }
+
+    #[test]
+    fn timesheet_has_a_column_per_day() {
+        let a1 = activity("p1", "2024-03-11 09:00", "2024-03-11 10:30");
+        let a2 = activity("p2", "2024-03-11 11:00", "2024-03-11 11:15");
+        let a3 = activity("p1", "2024-03-13 09:00", "2024-03-13 09:45");
+        let activities = vec![&a1, &a2, &a3];
+
+        let timesheet = Timesheet::new(&activities, None, date(11).iter_days().take(7).collect());
+
+        assert_eq!(timesheet.days.len(), 7);
+        assert_eq!(timesheet.rows.len(), 2);
+        assert_eq!(timesheet.rows[0].project, "p1");
+        assert_eq!(
+            create_cells(
+                "p1".to_string(),
+                &timesheet.rows[0].durations,
+                timesheet.rows[0].total
+            ),
+            vec!["p1", "1.50", "", "0.75", "", "", "", "", "2.25"]
+        );
+        assert_eq!(timesheet.day_totals[0], Duration::minutes(105));
+        assert_eq!(timesheet.total, Duration::minutes(150));
+    }
+
+    #[test]
+    fn timesheet_with_rounding_per_project_and_day() {
+        let a1 = activity("p1", "2024-03-11 09:00", "2024-03-11 09:05");
+        let a2 = activity("p2", "2024-03-11 10:00", "2024-03-11 10:05");
+        let a3 = activity("p1", "2024-03-12 09:00", "2024-03-12 09:20");
+        let activities = vec![&a1, &a2, &a3];
+
+        let rounding = TotalRounding {
+            round: Duration::minutes(15),
+            mode: RoundingMode::Up,
+            scope: RoundingScope::ProjectDay,
+        };
+        let timesheet = Timesheet::new(&activities, Some(&rounding), vec![date(11), date(12)]);
+
+        assert_eq!(
+            timesheet.rows[0].durations,
+            vec![Duration::minutes(15), Duration::minutes(30)]
+        );
+        assert_eq!(timesheet.rows[0].total, Duration::minutes(45));
+        assert_eq!(
+            timesheet.day_totals,
+            vec![Duration::minutes(30), Duration::minutes(30)]
+        );
+        assert_eq!(timesheet.total, Duration::minutes(60));
+    }
+}
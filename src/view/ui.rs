@@ -0,0 +1,145 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use nu_ansi_term::Style;
+
+use crate::conf;
+use crate::data::activity;
+use crate::data::date_parser::DateRange;
+use crate::view::list;
+use crate::view::report;
+use crate::view::theme;
+
+// the period summarized in the report pane of the ui
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReportRange {
+    #[default]
+    Today,
+    Week,
+}
+
+impl ReportRange {
+    #[must_use]
+    pub fn toggle(self) -> Self {
+        match self {
+            Self::Today => Self::Week,
+            Self::Week => Self::Today,
+        }
+    }
+
+    // the first day included in the report
+    #[must_use]
+    pub fn first_day(self, today: NaiveDate) -> NaiveDate {
+        match self {
+            Self::Today => today,
+            Self::Week => DateRange::week_of(today).first,
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            Self::Today => "Report of today",
+            Self::Week => "Report of this week",
+        }
+    }
+}
+
+// everything the ui shows at once
+pub struct Screen<'a> {
+    pub file_name: &'a str,
+    pub now: NaiveDateTime,
+    pub running: &'a [&'a activity::Activity],
+    pub today: &'a [&'a activity::Activity],
+    pub report: &'a [&'a activity::Activity],
+    pub report_range: ReportRange,
+    pub recent: &'a [(&'a String, &'a String)],
+    // the result of the last action, e.g. an error message
+    pub message: &'a str,
+}
+
+// the keys the ui reacts to and what they do
+static KEY_BINDINGS: [(&str, &str); 8] = [
+    ("n", "new"),
+    ("s", "stop"),
+    ("x", "cancel"),
+    ("c", "continue last"),
+    ("0-9", "continue recent"),
+    ("w", "today/week"),
+    ("r", "refresh"),
+    ("q", "quit"),
+];
+
+// prints all panes of the ui
+//
+// the screen should have been cleared before.
+pub fn show_screen(screen: &Screen) {
+    println!(
+        "{}  {}  {}",
+        theme::style(Style::new().bold().reverse()).paint(" bartib "),
+        screen.file_name,
+        theme::style(Style::new().dimmed())
+            .paint(screen.now.format(conf::format_datetime()).to_string())
+    );
+
+    print_pane_title("Running");
+    list::list_running_activities(screen.running);
+
+    print_pane_title("Today");
+    list::list_activities(screen.today, false, false);
+
+    print_pane_title(screen.report_range.title());
+    if screen.report.is_empty() {
+        println!("No activity to display");
+    } else {
+        report::show_activities(screen.report, report::ReportGrouping::Project, None);
+    }
+
+    print_pane_title("Recent");
+    list::list_descriptions_and_projects(screen.recent);
+
+    println!("\n{}", format_key_bindings());
+
+    if !screen.message.is_empty() {
+        println!("{}", screen.message);
+    }
+}
+
+// formats an error that occurred while handling a key
+#[must_use]
+pub fn format_error(error: &anyhow::Error) -> String {
+    theme::style(theme::theme().error)
+        .paint(format!("Error: {error}"))
+        .to_string()
+}
+
+fn print_pane_title(title: &str) {
+    println!(
+        "\n{}",
+        theme::style(Style::new().bold().underline()).paint(title)
+    );
+}
+
+fn format_key_bindings() -> String {
+    KEY_BINDINGS
+        .iter()
+        .map(|(key, action)| format!("{} {action}", theme::style(Style::new().bold()).paint(*key)))
+        .collect::<Vec<_>>()
+        .join("  ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_range() {
+        // a wednesday
+        let today = NaiveDate::from_ymd_opt(2024, 3, 13).unwrap();
+
+        assert_eq!(ReportRange::Today.first_day(today), today);
+        assert_eq!(
+            ReportRange::Week.first_day(today),
+            NaiveDate::from_ymd_opt(2024, 3, 11).unwrap()
+        );
+        assert_eq!(ReportRange::Today.toggle(), ReportRange::Week);
+        assert_eq!(ReportRange::Week.toggle(), ReportRange::Today);
+    }
+}
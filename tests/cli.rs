@@ -0,0 +1,422 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use bartib::controller::exit_status::ExitStatus;
+
+// a directory of its own for the empty activity log and the configuration file of each test
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("bartib-cli-{}-{name}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("config.toml"), "").unwrap();
+    fs::write(dir.join("activities.bartib"), "").unwrap();
+    dir
+}
+
+fn command(dir: &Path, args: &[&str]) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_bartib"));
+    command
+        .args(args)
+        .env("BARTIB_FILE", dir.join("activities.bartib"))
+        .env("BARTIB_CONFIG", dir.join("config.toml"))
+        .env("NO_COLOR", "1");
+    command
+}
+
+fn bartib(dir: &Path, args: &[&str]) -> Output {
+    command(dir, args).output().unwrap()
+}
+
+#[test]
+fn exit_codes() {
+    let dir = test_dir("exit-codes");
+
+    let output = bartib(&dir, &["current"]);
+    assert_eq!(
+        output.status.code(),
+        Some(i32::from(ExitStatus::NotRunning.code()))
+    );
+
+    fs::write(dir.join("activities.bartib"), "no activity\n").unwrap();
+    let output = bartib(&dir, &["check"]);
+    assert_eq!(
+        output.status.code(),
+        Some(i32::from(ExitStatus::ProblemsFound.code()))
+    );
+
+    let missing_file = dir.join("missing.bartib");
+    let output = bartib(&dir, &["--file", missing_file.to_str().unwrap(), "current"]);
+    assert_eq!(output.status.code(), Some(1), "errors exit with code 1");
+    assert!(!output.stderr.is_empty());
+
+    let output = bartib(&dir, &["no-such-command"]);
+    assert_eq!(
+        output.status.code(),
+        Some(2),
+        "invalid arguments exit with code 2"
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn missed_goals() {
+    let dir = test_dir("missed-goals");
+    fs::write(
+        dir.join("config.toml"),
+        "[[goal]]\nperiod = \"day\"\nduration = \"8h\"",
+    )
+    .unwrap();
+
+    let output = bartib(&dir, &["status", "--fail-under", "100"]);
+    assert_eq!(
+        output.status.code(),
+        Some(i32::from(ExitStatus::GoalMissed.code()))
+    );
+    assert!(!output.stderr.is_empty(), "the missed goals are reported");
+
+    let output = bartib(&dir, &["status", "--fail-under", "0"]);
+    assert!(output.status.success());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn quiet_output() {
+    let dir = test_dir("quiet");
+
+    let output = bartib(&dir, &["--quiet", "start", "-p", "p", "-d", "d"]);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty(), "nothing is printed");
+
+    let output = bartib(&dir, &["--quiet", "current"]);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty(), "nothing is printed");
+
+    let output = bartib(&dir, &["current"]);
+    assert!(output.status.success());
+    assert!(
+        !output.stdout.is_empty(),
+        "without `--quiet` the activity is printed"
+    );
+
+    for args in [["report", "--quiet"], ["current", "-q"]] {
+        let output = bartib(&dir, &args);
+        assert!(output.status.success(), "{args:?}");
+        assert!(output.stdout.is_empty(), "`--quiet` follows {args:?}");
+    }
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn configured_daily_limit() {
+    let dir = test_dir("daily-limit");
+    fs::write(dir.join("config.toml"), "daily_limit = \"1m\"").unwrap();
+    let output = bartib(&dir, &["start", "-p", "p", "-d", "d", "-t", "-2h"]);
+    assert!(output.status.success());
+
+    for args in [
+        ["status", "--output", "json"],
+        ["current", "--output", "json"],
+    ] {
+        let output = bartib(&dir, &args);
+        assert!(output.status.success(), "{args:?}");
+        assert!(
+            String::from_utf8_lossy(&output.stdout).contains("\"daily_limit\":60"),
+            "{args:?} uses the configured limit"
+        );
+    }
+
+    let output = bartib(&dir, &["status"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("exceeded by"));
+
+    let output = bartib(&dir, &["status", "--daily-limit", "8h", "--output", "json"]);
+    assert!(
+        String::from_utf8_lossy(&output.stdout).contains("\"daily_limit\":28800"),
+        "the argument overrides the setting"
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn rename_and_merge_projects() {
+    let dir = test_dir("projects");
+    let file_name = dir.join("activities.bartib");
+    let archive_file_name = dir.join("activities.bartib.2023");
+    fs::write(
+        &archive_file_name,
+        "2023-03-01 09:00 - 2023-03-01 10:00 | old | archived\n",
+    )
+    .unwrap();
+    fs::write(
+        &file_name,
+        "2024-03-01 09:00 - 2024-03-01 10:00 | old | current\n\
+         2024-03-01 10:00 - 2024-03-01 11:00 | other | other\n",
+    )
+    .unwrap();
+    let read = |file_name: &PathBuf| fs::read_to_string(file_name).unwrap();
+
+    let output = bartib(&dir, &["project", "rename", "old", "new", "--dry-run"]);
+    assert!(output.status.success());
+    assert!(
+        read(&file_name).contains("| old | current"),
+        "nothing is changed"
+    );
+    assert!(read(&archive_file_name).contains("| old | archived"));
+
+    let output = bartib(&dir, &["project", "rename", "old", "other"]);
+    assert!(!output.status.success(), "the new name is used already");
+
+    let output = bartib(&dir, &["project", "rename", "old", "new"]);
+    assert!(output.status.success());
+    assert!(read(&file_name).contains("| new | current"));
+    assert!(read(&archive_file_name).contains("| new | archived"));
+
+    let output = bartib(&dir, &["project", "merge", "new", "missing"]);
+    assert!(!output.status.success(), "the target does not exist");
+
+    let output = bartib(&dir, &["project", "merge", "new", "other", "--dry-run"]);
+    assert!(output.status.success());
+    assert!(
+        read(&archive_file_name).contains("| new | archived"),
+        "nothing is changed"
+    );
+
+    let output = bartib(&dir, &["project", "merge", "new", "other"]);
+    assert!(output.status.success());
+    assert!(!read(&file_name).contains("| new |"));
+    assert!(read(&archive_file_name).contains("| other | archived"));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn output_after_subcommand() {
+    let dir = test_dir("output");
+
+    let output = bartib(&dir, &["list", "--today", "--output", "json"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with('['));
+
+    let output = command(&dir, &["list", "--today", "--color", "always"])
+        .env_remove("NO_COLOR")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let output = bartib(&dir, &["list", "--today", "--color", "never"]);
+    assert!(output.status.success());
+    assert!(!String::from_utf8_lossy(&output.stdout).contains('\u{1b}'));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+// the lines of the activity log as (project and description, whether the activity is running)
+fn activities(dir: &Path) -> Vec<(String, bool)> {
+    fs::read_to_string(dir.join("activities.bartib"))
+        .unwrap()
+        .lines()
+        .map(|line| {
+            let (time, activity) = line.split_once(" | ").unwrap();
+            (activity.to_string(), !time.contains(" - "))
+        })
+        .collect()
+}
+
+fn running(activity: &str) -> (String, bool) {
+    (activity.to_string(), true)
+}
+
+fn stopped(activity: &str) -> (String, bool) {
+    (activity.to_string(), false)
+}
+
+#[test]
+fn idempotent_start() {
+    let dir = test_dir("idempotent-start");
+
+    let output = bartib(&dir, &["start", "-p", "p", "-d", "d", "-t", "-2h"]);
+    assert!(output.status.success());
+    assert_eq!(activities(&dir), vec![running("p | d")]);
+
+    let output = bartib(&dir, &["start", "-p", "p", "-d", "d", "--idempotent"]);
+    assert!(output.status.success());
+    assert_eq!(
+        activities(&dir),
+        vec![running("p | d")],
+        "the running activity is kept"
+    );
+
+    let output = bartib(&dir, &["start", "-p", "q", "-d", "e", "--idempotent"]);
+    assert!(output.status.success());
+    assert_eq!(
+        activities(&dir),
+        vec![stopped("p | d"), running("q | e")],
+        "another running activity is stopped"
+    );
+
+    let output = bartib(&dir, &["start", "-p", "q", "-d", "e"]);
+    assert!(output.status.success());
+    assert_eq!(
+        activities(&dir),
+        vec![stopped("p | d"), stopped("q | e"), running("q | e")],
+        "without `--idempotent` the activity is started again"
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn switch_activities() {
+    let dir = test_dir("switch");
+
+    let output = bartib(&dir, &["switch", "-p", "p", "-d", "d", "-t", "-2h"]);
+    assert!(output.status.success(), "nothing has to be running");
+    assert_eq!(activities(&dir), vec![running("p | d")]);
+
+    let output = bartib(&dir, &["switch", "-p", "p", "-d", "d", "--idempotent"]);
+    assert!(output.status.success());
+    assert_eq!(
+        activities(&dir),
+        vec![running("p | d")],
+        "switching to the running activity changes nothing"
+    );
+
+    let output = bartib(&dir, &["switch", "-p", "q", "-d", "e", "-t", "-1h"]);
+    assert!(output.status.success());
+    assert_eq!(activities(&dir), vec![stopped("p | d"), running("q | e")]);
+    let log = fs::read_to_string(dir.join("activities.bartib")).unwrap();
+    let lines: Vec<&str> = log.lines().collect();
+    let end_of_first = lines[0].split(" | ").next().unwrap().split(" - ").nth(1);
+    let start_of_second = lines[1].split(" | ").next();
+    assert_eq!(
+        end_of_first, start_of_second,
+        "the new activity starts when the other one ends"
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn warn_of_overlapping_activities() {
+    let dir = test_dir("overlapping");
+    fs::write(
+        dir.join("activities.bartib"),
+        "2024-03-01 09:00 - 2024-03-01 10:00 | p | d\n",
+    )
+    .unwrap();
+
+    let output = bartib(
+        &dir,
+        &[
+            "--quiet",
+            "add",
+            "-p",
+            "p",
+            "-d",
+            "e",
+            "--start",
+            "2024-03-01 09:30",
+            "--end",
+            "2024-03-01 10:30",
+        ],
+    );
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty(), "warnings are not output");
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("Warning: "));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn failed_decryption_keeps_the_log() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = test_dir("failed-decryption");
+    let gpg = dir.join("gpg");
+    // a gpg which fails to decrypt, e.g. with a wrong passphrase, but "encrypts" anything
+    fs::write(
+        &gpg,
+        "#!/bin/sh\nfor arg; do [ \"$arg\" = --decrypt ] && exit 2; done\ncat\n",
+    )
+    .unwrap();
+    fs::set_permissions(&gpg, fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!(
+        "{}:{}",
+        dir.display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+
+    let log = "-----BEGIN PGP MESSAGE-----\n\njA0ECQMI\n-----END PGP MESSAGE-----\n";
+    fs::write(dir.join("activities.bartib"), log).unwrap();
+
+    for args in [
+        ["start", "-p", "p", "-d", "d"].as_slice(),
+        &[
+            "add", "-p", "p", "-d", "d", "--start", "09:00", "--end", "10:00",
+        ],
+    ] {
+        let output = command(&dir, args).env("PATH", &path).output().unwrap();
+        assert_eq!(output.status.code(), Some(1), "{args:?} fails");
+        assert_eq!(
+            fs::read_to_string(dir.join("activities.bartib")).unwrap(),
+            log,
+            "{args:?} keeps the log"
+        );
+    }
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+// the durations in seconds of the activities listed as JSON
+fn listed_durations(output: &Output) -> Vec<i64> {
+    String::from_utf8_lossy(&output.stdout)
+        .split("\"duration\":")
+        .skip(1)
+        .map(|rest| {
+            rest.chars()
+                .take_while(char::is_ascii_digit)
+                .collect::<String>()
+                .parse()
+                .unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn rounding_up_and_down() {
+    let dir = test_dir("rounding");
+    fs::write(
+        dir.join("activities.bartib"),
+        "2024-03-01 09:01 - 2024-03-01 09:14 | p | short\n\
+         2024-03-01 09:14 - 2024-03-01 09:46 | p | long\n\
+         2024-03-01 10:00 - 2024-03-01 10:15 | p | exact\n",
+    )
+    .unwrap();
+    let list = |args: &[&str]| {
+        let output = bartib(
+            &dir,
+            &[&["list", "--date", "2024-03-01", "--output", "json"], args].concat(),
+        );
+        assert!(output.status.success(), "{args:?}");
+        listed_durations(&output)
+    };
+
+    let exact = list(&[]);
+    let rounded_up = list(&["--round", "15m", "--round-up"]);
+    let rounded_down = list(&["--round", "15m", "--round-down"]);
+
+    assert_eq!(exact, vec![13 * 60, 32 * 60, 15 * 60]);
+    assert_eq!(rounded_up, vec![15 * 60, 60 * 60, 15 * 60]);
+    assert_eq!(rounded_down, vec![0, 30 * 60, 15 * 60]);
+    for ((exact, up), down) in exact.iter().zip(&rounded_up).zip(&rounded_down) {
+        assert!(up >= exact, "rounding up never shortens an activity");
+        assert!(down <= exact, "rounding down never lengthens an activity");
+    }
+
+    fs::remove_dir_all(&dir).unwrap();
+}